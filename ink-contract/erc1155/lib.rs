@@ -0,0 +1,337 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+#[ink::contract]
+mod erc1155 {
+    use ink_prelude::vec::Vec;
+    use ink_storage::collections::HashMap as StorageHashMap;
+    use scale::{Encode, Decode};
+
+    pub type TokenId = u128;
+
+    #[ink(storage)]
+    pub struct Erc1155 {
+        balances: StorageHashMap<(AccountId, TokenId), Balance>,
+        supplies: StorageHashMap<TokenId, Balance>,
+        approvals: StorageHashMap<(AccountId, AccountId), ()>,
+        next_id: TokenId,
+    }
+
+    #[ink(event)]
+    pub struct TransferSingle {
+        #[ink(topic)]
+        operator: AccountId,
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        id: TokenId,
+        value: Balance,
+    }
+
+    #[ink(event)]
+    pub struct TransferBatch {
+        #[ink(topic)]
+        operator: AccountId,
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        ids: Vec<TokenId>,
+        values: Vec<Balance>,
+    }
+
+    #[ink(event)]
+    pub struct ApprovalForAll {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        operator: AccountId,
+        approved: bool,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        InsufficientBalance,
+        NotApproved,
+        LengthMismatch,
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    impl Erc1155 {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                balances: StorageHashMap::new(),
+                supplies: StorageHashMap::new(),
+                approvals: StorageHashMap::new(),
+                next_id: 0,
+            }
+        }
+
+        /// Create a new token id and give its whole `supply` to the caller.
+        #[ink(message)]
+        pub fn create(&mut self, supply: Balance) -> TokenId {
+            let caller = self.env().caller();
+            let id = self.next_id;
+            self.next_id += 1;
+
+            self.balances.insert((caller, id), supply);
+            self.supplies.insert(id, supply);
+            self.env().emit_event( TransferSingle {
+                operator: caller,
+                from: None,
+                to: Some(caller),
+                id,
+                value: supply,
+            });
+            id
+        }
+
+        #[ink(message)]
+        pub fn total_supply(&self, id: TokenId) -> Balance {
+            self.supplies.get(&id).copied().unwrap_or(0)
+        }
+
+        #[ink(message)]
+        pub fn balance_of(&self, owner: AccountId, id: TokenId) -> Balance {
+            self.balances.get(&(owner, id)).copied().unwrap_or(0)
+        }
+
+        #[ink(message)]
+        pub fn balance_of_batch(&self, owners: Vec<AccountId>, ids: Vec<TokenId>) -> Result<Vec<Balance>> {
+            if owners.len() != ids.len() {
+                return Err(Error::LengthMismatch);
+            }
+            Ok(owners.into_iter().zip(ids).map(|(owner, id)| self.balance_of(owner, id)).collect())
+        }
+
+        /// Let `operator` move all of the caller's tokens, or stop it from doing so.
+        #[ink(message)]
+        pub fn set_approval_for_all(&mut self, operator: AccountId, approved: bool) -> Result<()> {
+            let owner = self.env().caller();
+            if approved {
+                self.approvals.insert((owner, operator), ());
+            } else {
+                self.approvals.take(&(owner, operator));
+            }
+            self.env().emit_event( ApprovalForAll {
+                owner,
+                operator,
+                approved,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_approved_for_all(&self, owner: AccountId, operator: AccountId) -> bool {
+            self.approvals.contains_key(&(owner, operator))
+        }
+
+        /// Move `value` of token `id` from `from` to `to`. The caller must be `from` or
+        /// approved by it.
+        ///
+        /// `data` is accepted for interface compatibility; receiving contracts are not
+        /// called back.
+        #[ink(message)]
+        pub fn safe_transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            id: TokenId,
+            value: Balance,
+            _data: Vec<u8>,
+        ) -> Result<()> {
+            let operator = self.ensure_approved(from)?;
+            if self.balance_of(from, id) < value {
+                return Err(Error::InsufficientBalance);
+            }
+
+            self.move_tokens(from, to, id, value);
+            self.env().emit_event( TransferSingle {
+                operator,
+                from: Some(from),
+                to: Some(to),
+                id,
+                value,
+            });
+            Ok(())
+        }
+
+        /// Move `values[i]` of token `ids[i]` from `from` to `to` for every `i`, all or
+        /// nothing.
+        #[ink(message)]
+        pub fn safe_batch_transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            ids: Vec<TokenId>,
+            values: Vec<Balance>,
+            _data: Vec<u8>,
+        ) -> Result<()> {
+            let operator = self.ensure_approved(from)?;
+            if ids.len() != values.len() {
+                return Err(Error::LengthMismatch);
+            }
+
+            // Check every balance before touching any, counting repeated ids together.
+            let mut needed: Vec<(TokenId, Balance)> = Vec::new();
+            for (&id, &value) in ids.iter().zip(&values) {
+                match needed.iter_mut().find(|(needed_id, _)| *needed_id == id) {
+                    Some((_, total)) => {
+                        *total = total.checked_add(value).ok_or(Error::InsufficientBalance)?
+                    }
+                    None => needed.push((id, value)),
+                }
+            }
+            if needed.iter().any(|&(id, total)| self.balance_of(from, id) < total) {
+                return Err(Error::InsufficientBalance);
+            }
+
+            for (&id, &value) in ids.iter().zip(&values) {
+                self.move_tokens(from, to, id, value);
+            }
+            self.env().emit_event( TransferBatch {
+                operator,
+                from: Some(from),
+                to: Some(to),
+                ids,
+                values,
+            });
+            Ok(())
+        }
+
+        fn ensure_approved(&self, from: AccountId) -> Result<AccountId> {
+            let caller = self.env().caller();
+            if caller != from && !self.is_approved_for_all(from, caller) {
+                return Err(Error::NotApproved);
+            }
+            Ok(caller)
+        }
+
+        fn move_tokens(&mut self, from: AccountId, to: AccountId, id: TokenId, value: Balance) {
+            let from_balance = self.balance_of(from, id);
+            self.balances.insert((from, id), from_balance - value);
+            let to_balance = self.balance_of(to, id);
+            self.balances.insert((to, id), to_balance + value);
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink_lang as ink;
+
+        type Env = ink_env::DefaultEnvironment;
+
+        fn accounts() -> ink_env::test::DefaultAccounts<Env> {
+            ink_env::test::default_accounts::<Env>().expect("off-chain environment is set up")
+        }
+
+        fn set_caller(caller: AccountId) {
+            let callee = ink_env::account_id::<Env>().unwrap_or([0x0; 32].into());
+            let data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            ink_env::test::push_execution_context::<Env>(caller, callee, 1_000_000, 1_000_000, data);
+        }
+
+        #[ink::test]
+        fn create_works() {
+            let accounts = accounts();
+            let mut erc1155 = Erc1155::new();
+            assert_eq!(erc1155.create(100), 0);
+            assert_eq!(erc1155.create(5), 1);
+            assert_eq!(erc1155.total_supply(0), 100);
+            assert_eq!(erc1155.total_supply(1), 5);
+            assert_eq!(erc1155.total_supply(2), 0);
+            assert_eq!(
+                erc1155.balance_of_batch(vec![accounts.alice, accounts.alice, accounts.bob], vec![0, 1, 0]),
+                Ok(vec![100, 5, 0]),
+            );
+            assert_eq!(ink_env::test::recorded_events().count(), 2);
+        }
+
+        #[ink::test]
+        fn safe_transfer_from_works() {
+            let accounts = accounts();
+            let mut erc1155 = Erc1155::new();
+            erc1155.create(100);
+            assert_eq!(erc1155.safe_transfer_from(accounts.alice, accounts.bob, 0, 30, vec![]), Ok(()));
+            assert_eq!(erc1155.balance_of(accounts.alice, 0), 70);
+            assert_eq!(erc1155.balance_of(accounts.bob, 0), 30);
+            assert_eq!(ink_env::test::recorded_events().count(), 2);
+        }
+
+        #[ink::test]
+        fn safe_transfer_from_failed() {
+            let accounts = accounts();
+            let mut erc1155 = Erc1155::new();
+            erc1155.create(100);
+            assert_eq!(
+                erc1155.safe_transfer_from(accounts.alice, accounts.bob, 0, 101, vec![]),
+                Err(Error::InsufficientBalance),
+            );
+            set_caller(accounts.bob);
+            assert_eq!(
+                erc1155.safe_transfer_from(accounts.alice, accounts.bob, 0, 1, vec![]),
+                Err(Error::NotApproved),
+            );
+        }
+
+        #[ink::test]
+        fn approved_operator_can_transfer() {
+            let accounts = accounts();
+            let mut erc1155 = Erc1155::new();
+            erc1155.create(100);
+            assert_eq!(erc1155.set_approval_for_all(accounts.bob, true), Ok(()));
+            assert!(erc1155.is_approved_for_all(accounts.alice, accounts.bob));
+
+            set_caller(accounts.bob);
+            assert_eq!(erc1155.safe_transfer_from(accounts.alice, accounts.charlie, 0, 10, vec![]), Ok(()));
+            assert_eq!(erc1155.balance_of(accounts.charlie, 0), 10);
+
+            set_caller(accounts.alice);
+            assert_eq!(erc1155.set_approval_for_all(accounts.bob, false), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(
+                erc1155.safe_transfer_from(accounts.alice, accounts.charlie, 0, 10, vec![]),
+                Err(Error::NotApproved),
+            );
+        }
+
+        #[ink::test]
+        fn safe_batch_transfer_from_works() {
+            let accounts = accounts();
+            let mut erc1155 = Erc1155::new();
+            erc1155.create(100);
+            erc1155.create(5);
+            assert_eq!(
+                erc1155.safe_batch_transfer_from(accounts.alice, accounts.bob, vec![0, 1, 0], vec![10, 5, 20], vec![]),
+                Ok(()),
+            );
+            assert_eq!(
+                erc1155.balance_of_batch(vec![accounts.alice, accounts.bob, accounts.bob], vec![0, 0, 1]),
+                Ok(vec![70, 30, 5]),
+            );
+        }
+
+        #[ink::test]
+        fn safe_batch_transfer_from_failed() {
+            let accounts = accounts();
+            let mut erc1155 = Erc1155::new();
+            erc1155.create(100);
+            assert_eq!(
+                erc1155.safe_batch_transfer_from(accounts.alice, accounts.bob, vec![0], vec![], vec![]),
+                Err(Error::LengthMismatch),
+            );
+            // each amount alone is covered, together they are not, so nothing moves
+            assert_eq!(
+                erc1155.safe_batch_transfer_from(accounts.alice, accounts.bob, vec![0, 0], vec![60, 60], vec![]),
+                Err(Error::InsufficientBalance),
+            );
+            assert_eq!(erc1155.balance_of(accounts.alice, 0), 100);
+        }
+    }
+}
@@ -1,47 +1,250 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use ink_env::{AccountId, DefaultEnvironment, Environment};
 use ink_lang as ink;
 
-#[ink::contract]
+type Balance = <DefaultEnvironment as Environment>::Balance;
+
+/// The kitties-node runtime's chain extension, see `kitties_extension.rs` in its runtime.
+#[ink::chain_extension]
+pub trait KittiesExtension {
+    type ErrorCode = KittiesError;
+
+    /// The native balance the next kitty reserves as its deposit.
+    #[ink(extension = 0x1000, returns_result = false)]
+    fn current_deposit() -> Balance;
+
+    /// Mint a kitty to `owner`, paying its deposit from this contract's native balance to the
+    /// caller of the message, who reserves it.
+    #[ink(extension = 0x1001)]
+    fn mint_kitty(owner: AccountId) -> Result<u32, KittiesError>;
+}
+
+/// Why the runtime refused to mint a kitty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum KittiesError {
+    /// The contract couldn't reserve the deposit.
+    MintFailed,
+}
+
+impl ink_env::chain_extension::FromStatusCode for KittiesError {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            _ => Err(Self::MintFailed),
+        }
+    }
+}
+
+/// The default environment, plus the kitties chain extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum KittiesEnvironment {}
+
+impl Environment for KittiesEnvironment {
+    const MAX_EVENT_TOPICS: usize = <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+    type AccountId = <DefaultEnvironment as Environment>::AccountId;
+    type Balance = <DefaultEnvironment as Environment>::Balance;
+    type Hash = <DefaultEnvironment as Environment>::Hash;
+    type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+    type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+
+    type ChainExtension = KittiesExtension;
+}
+
+#[ink::contract(env = crate::KittiesEnvironment)]
 mod erc20 {
+    use ink_env::call::{build_call, utils::ReturnType, ExecutionInput, Selector};
+    use ink_prelude::{string::String, vec::Vec};
     use ink_storage::{
         lazy::Lazy,
         collections::HashMap as StorageHashMap,
     };
     use scale::{Encode, Decode};
 
+    /// Selector of the PSP22 `transfer(to, value, data)` message.
+    const PSP22_TRANSFER: [u8; 4] = [0xdb, 0x20, 0xf9, 0xf5];
+
+    /// `dividend_per_token` is scaled up by this, so small dividends on a large supply still
+    /// register.
+    const DIVIDEND_PRECISION: Balance = 1_000_000_000_000;
+
+    /// The most reads a single `multicall` answers.
+    const MAX_CALL_REQUESTS: usize = 256;
+
     #[ink(storage)]
     pub struct Erc20 {
         total_supply: Lazy<Balance>,
         balances: StorageHashMap<AccountId, Balance>,
         allowances: StorageHashMap<(AccountId, AccountId), Balance>,
+        /// Whether tokens are minted and burned 1:1 against the chain's native currency.
+        wrapped: bool,
+        /// The account each holder delegates its voting power to.
+        delegates: StorageHashMap<AccountId, AccountId>,
+        /// `(delegate, i)` => the `i`-th change of the delegate's voting power, as the block it
+        /// happened in and the votes from then on, oldest first.
+        checkpoints: StorageHashMap<(AccountId, u32), (BlockNumber, Balance)>,
+        num_checkpoints: StorageHashMap<AccountId, u32>,
+        /// The account allowed to sweep tokens sent to this contract by mistake.
+        owner: AccountId,
+        /// Native balance distributed per token since deployment, times `DIVIDEND_PRECISION`.
+        dividend_per_token: Balance,
+        /// `dividend_per_token` when each holder's dividends were last settled into
+        /// `dividends_owed`.
+        dividend_checkpoints: StorageHashMap<AccountId, Balance>,
+        dividends_owed: StorageHashMap<AccountId, Balance>,
+        /// Native balance distributed and not yet claimed.
+        unclaimed_dividends: Balance,
+        /// The most a single transfer may move, if limited.
+        max_transfer: Option<Balance>,
+        /// The most an account may transfer within one block, if limited.
+        max_transfer_per_block: Option<Balance>,
+        /// What each account has transferred so far in the block it last transferred in.
+        transferred_in_block: StorageHashMap<AccountId, (BlockNumber, Balance)>,
+        /// Accounts whose transfers aren't limited.
+        limit_exempt: StorageHashMap<AccountId, ()>,
     }
 
+    /// Only the parties are topics, so indexers can filter transfers by `from` or by `to`.
     #[ink(event)]
     pub struct Transfer {
         #[ink(topic)]
         from: Option<AccountId>,
         #[ink(topic)]
         to: Option<AccountId>,
-        #[ink(topic)]
         value: Balance,
     }
 
+    /// Emitted next to `AllowanceChanged`, for indexers that predate it, unless the
+    /// `no-approval-event` feature is enabled.
     #[ink(event)]
     pub struct Approval {
         #[ink(topic)]
         owner: AccountId,
         #[ink(topic)]
         spender: AccountId,
+        value: Balance,
+    }
+
+    /// The allowance `spender` has left from `owner` after an `approve` or a `transfer_from`,
+    /// so wallets can show it without reading it back.
+    #[ink(event)]
+    pub struct AllowanceChanged {
         #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        allowance: Balance,
+    }
+
+    /// A transfer, `transfer_from` or `withdraw` that was refused, and why.
+    #[ink(event)]
+    pub struct TransferFailed {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
         value: Balance,
+        reason: Error,
     }
 
-    #[derive(Debug, PartialEq, Eq, Encode, Decode)]
+    /// Tokens sent to this contract by mistake were recovered by the owner. `token` is `None`
+    /// for the native currency.
+    #[ink(event)]
+    pub struct Swept {
+        #[ink(topic)]
+        token: Option<AccountId>,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    /// `amount` of native balance was shared among holders, `per_token` more for each token
+    /// held, times `DIVIDEND_PRECISION`.
+    #[ink(event)]
+    pub struct DividendDistributed {
+        amount: Balance,
+        per_token: Balance,
+    }
+
+    #[ink(event)]
+    pub struct DividendClaimed {
+        #[ink(topic)]
+        holder: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct DelegateChanged {
+        #[ink(topic)]
+        delegator: AccountId,
+        from_delegate: Option<AccountId>,
+        #[ink(topic)]
+        to_delegate: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct DelegateVotesChanged {
+        #[ink(topic)]
+        delegate: AccountId,
+        previous_votes: Balance,
+        new_votes: Balance,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum Error {
         InsufficientBalance,
         InsufficientAllowance,
+        /// `deposit` and `withdraw` are only available on a wrapped-native token.
+        NotWrapped,
+        /// Paying out the native currency failed.
+        TransferFailed,
+        /// Past votes can only be looked up for blocks that have ended.
+        BlockNotYetMined,
+        /// Only the contract owner may do this.
+        NotOwner,
+        /// This token's own balances are moved with `transfer`, not swept.
+        NotForeign,
+        /// The foreign token refused the transfer, or could not be called.
+        SweepFailed,
+        /// `distribute` must be sent exactly the amount it distributes, and there must be
+        /// tokens to distribute over.
+        InvalidDividend,
+        /// The caller has no dividends to claim.
+        NoDividend,
+        /// The transfer is larger than `max_transfer`, or would take the sender past
+        /// `max_transfer_per_block` in this block.
+        TransferLimitExceeded,
+        /// `multicall` was asked more than `MAX_CALL_REQUESTS` reads.
+        TooManyCallRequests,
+        /// The runtime refused to mint the kitty.
+        KittyMintFailed,
+    }
+
+    /// A read `multicall` answers.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum CallRequest {
+        TotalSupply,
+        /// `balance_of(who)`.
+        BalanceOf(AccountId),
+        /// `allowance(owner, spender)`.
+        Allowance(AccountId, AccountId),
+    }
+
+    /// The errors a PSP22 token's `transfer` returns, mirrored to decode its result.
+    #[derive(Debug, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PSP22Error {
+        Custom(String),
+        InsufficientBalance,
+        InsufficientAllowance,
+        ZeroRecipientAddress,
+        ZeroSenderAddress,
+        SafeTransferCheckFailed(String),
     }
 
     pub type Result<T> = core::result::Result<T, Error>;
@@ -63,6 +266,43 @@ mod erc20 {
                 total_supply: Lazy::new(supply),
                 balances,
                 allowances: StorageHashMap::new(),
+                wrapped: false,
+                delegates: StorageHashMap::new(),
+                checkpoints: StorageHashMap::new(),
+                num_checkpoints: StorageHashMap::new(),
+                owner: caller,
+                dividend_per_token: 0,
+                dividend_checkpoints: StorageHashMap::new(),
+                dividends_owed: StorageHashMap::new(),
+                unclaimed_dividends: 0,
+                max_transfer: None,
+                max_transfer_per_block: None,
+                transferred_in_block: StorageHashMap::new(),
+                limit_exempt: StorageHashMap::new(),
+            }
+        }
+
+        /// A wrapped-native token: it starts empty and is only minted by `deposit`, fully
+        /// backed by the native balance the contract holds.
+        #[ink(constructor)]
+        pub fn new_wrapped() -> Self {
+            Self {
+                total_supply: Lazy::new(0),
+                balances: StorageHashMap::new(),
+                allowances: StorageHashMap::new(),
+                wrapped: true,
+                delegates: StorageHashMap::new(),
+                checkpoints: StorageHashMap::new(),
+                num_checkpoints: StorageHashMap::new(),
+                owner: Self::env().caller(),
+                dividend_per_token: 0,
+                dividend_checkpoints: StorageHashMap::new(),
+                dividends_owed: StorageHashMap::new(),
+                unclaimed_dividends: 0,
+                max_transfer: None,
+                max_transfer_per_block: None,
+                transferred_in_block: StorageHashMap::new(),
+                limit_exempt: StorageHashMap::new(),
             }
         }
 
@@ -81,6 +321,23 @@ mod erc20 {
             self.allowances.get(&(owner, spender)).copied().unwrap_or(0)
         }
 
+        /// Answer many reads in one call, in the order asked, so a wallet can fetch every
+        /// balance and allowance it shows in a single round trip.
+        #[ink(message)]
+        pub fn multicall(&self, requests: Vec<CallRequest>) -> Result<Vec<Balance>> {
+            if requests.len() > MAX_CALL_REQUESTS {
+                return Err(Error::TooManyCallRequests);
+            }
+            Ok(requests
+                .into_iter()
+                .map(|request| match request {
+                    CallRequest::TotalSupply => self.total_supply(),
+                    CallRequest::BalanceOf(who) => self.balance_of(who),
+                    CallRequest::Allowance(owner, spender) => self.allowance(owner, spender),
+                })
+                .collect())
+        }
+
         #[ink(message)]
         pub fn transfer(&mut self, to: AccountId, value: Balance) -> Result<()> {
             let from = self.env().caller();
@@ -97,10 +354,17 @@ mod erc20 {
             let caller = self.env().caller();
             let allowance = self.allowance(from, caller);
             if allowance < value {
-                return Err(Error::InsufficientAllowance);
+                return self.fail(Some(from), Some(to), value, Error::InsufficientAllowance);
             }
-            self.allowances.insert((from, caller), allowance - value);
+            // spend the allowance only once the transfer went through, so a refused one
+            // leaves it intact
             self.inner_transfer(from, to, value)?;
+            self.allowances.insert((from, caller), allowance - value);
+            self.env().emit_event( AllowanceChanged {
+                owner: from,
+                spender: caller,
+                allowance: allowance - value,
+            });
             Ok(())
         }
 
@@ -108,14 +372,367 @@ mod erc20 {
         pub fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
             let owner = self.env().caller();
             self.allowances.insert((owner, spender), value);
-            self.env().emit_event( Approval {
-                owner, 
+            if !cfg!(feature = "no-approval-event") {
+                self.env().emit_event( Approval {
+                    owner,
+                    spender,
+                    value
+                });
+            }
+            self.env().emit_event( AllowanceChanged {
+                owner,
                 spender,
-                value
+                allowance: value,
+            });
+            Ok(())
+        }
+
+        /// Mint tokens to the caller 1:1 for the native balance sent with the call. Traps on
+        /// failure, so the balance sent goes back.
+        #[ink(message, payable)]
+        pub fn deposit(&mut self) -> Result<()> {
+            if !self.wrapped {
+                trap(Error::NotWrapped);
+            }
+            let caller = self.env().caller();
+            let value = self.env().transferred_balance();
+
+            self.settle_dividends(caller);
+            let balance = self.balance_of(caller);
+            self.balances.insert(caller, balance + value);
+            *self.total_supply += value;
+            self.move_voting_power(None, self.delegates(caller), value);
+            self.env().emit_event( Transfer {
+                from: None,
+                to: Some(caller),
+                value,
+            });
+            Ok(())
+        }
+
+        /// Burn `value` of the caller's tokens and pay out the same amount of native balance.
+        /// Withdrawals count against the caller's transfer limits like transfers do.
+        #[ink(message)]
+        pub fn withdraw(&mut self, value: Balance) -> Result<()> {
+            if !self.wrapped {
+                return Err(Error::NotWrapped);
+            }
+            let caller = self.env().caller();
+            let balance = self.balance_of(caller);
+            if balance < value {
+                return self.fail(Some(caller), None, value, Error::InsufficientBalance);
+            }
+            if !self.within_transfer_limits(caller, value) {
+                return self.fail(Some(caller), None, value, Error::TransferLimitExceeded);
+            }
+
+            // Pay out first: a failed message doesn't revert, so nothing may be burned before.
+            if self.env().transfer(caller, value).is_err() {
+                return self.fail(Some(caller), None, value, Error::TransferFailed);
+            }
+            self.settle_dividends(caller);
+            self.balances.insert(caller, balance - value);
+            *self.total_supply -= value;
+            self.move_voting_power(self.delegates(caller), None, value);
+            self.env().emit_event( Transfer {
+                from: Some(caller),
+                to: None,
+                value,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Recover `amount` of the PSP22 token `token` sent to this contract by mistake, paying
+        /// it to `to`. Only the owner may sweep.
+        #[ink(message)]
+        pub fn sweep_foreign(&mut self, token: AccountId, to: AccountId, amount: Balance) -> Result<()> {
+            self.ensure_owner()?;
+            if token == self.env().account_id() {
+                return Err(Error::NotForeign);
+            }
+
+            let result = build_call::<Environment>()
+                .callee(token)
+                .gas_limit(0)
+                .transferred_value(0)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(PSP22_TRANSFER))
+                        .push_arg(to)
+                        .push_arg(amount)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<ReturnType<core::result::Result<(), PSP22Error>>>()
+                .fire();
+            if !matches!(result, Ok(Ok(()))) {
+                return Err(Error::SweepFailed);
+            }
+            self.env().emit_event( Swept {
+                token: Some(token),
+                to,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Pay `amount` of native balance sent to this contract by mistake to `to`. A wrapped
+        /// token only sweeps what exceeds the balance backing its supply. Only the owner may
+        /// sweep.
+        #[ink(message)]
+        pub fn sweep_native(&mut self, to: AccountId, amount: Balance) -> Result<()> {
+            self.ensure_owner()?;
+            // Dividends not yet claimed belong to the holders too.
+            let supply_backing = if self.wrapped { *self.total_supply } else { 0 };
+            let backing = supply_backing + self.unclaimed_dividends;
+            if self.env().balance().saturating_sub(backing) < amount {
+                return Err(Error::InsufficientBalance);
+            }
+            if self.env().transfer(to, amount).is_err() {
+                return Err(Error::TransferFailed);
+            }
+            self.env().emit_event( Swept {
+                token: None,
+                to,
+                amount,
+            });
+            Ok(())
+        }
+
+        /// Mint a kitty to `owner`, paying its deposit with the caller's tokens. The native
+        /// balance backing them is paid out to the caller and reserved as the deposit, so the
+        /// tokens are burned 1:1 for it, and only a wrapped-native token can pay. The deposit is
+        /// returned to the caller when the kitty is burned. Returns the new kitty's id.
+        #[ink(message)]
+        pub fn mint_kitty(&mut self, owner: AccountId) -> Result<u32> {
+            if !self.wrapped {
+                return Err(Error::NotWrapped);
+            }
+            let caller = self.env().caller();
+            let deposit = self.env().extension().current_deposit();
+            let balance = self.balance_of(caller);
+            if balance < deposit {
+                self.fail(Some(caller), None, deposit, Error::InsufficientBalance)?;
+            }
+
+            // Mint first: a failed message doesn't revert, so nothing may be burned before.
+            let kitty_id = self.env().extension().mint_kitty(owner).map_err(|_| Error::KittyMintFailed)?;
+            self.settle_dividends(caller);
+            self.balances.insert(caller, balance - deposit);
+            *self.total_supply -= deposit;
+            self.move_voting_power(self.delegates(caller), None, deposit);
+            self.env().emit_event( Transfer {
+                from: Some(caller),
+                to: None,
+                value: deposit,
+            });
+            Ok(kitty_id)
+        }
+
+        /// Share `total_amount` of native balance, sent with the call, among the current holders
+        /// in proportion to their balances. Holders collect their share with `claim_dividend`.
+        /// Only the owner may distribute. Traps on failure, so the balance sent goes back.
+        #[ink(message, payable)]
+        pub fn distribute(&mut self, total_amount: Balance) -> Result<()> {
+            if let Err(error) = self.ensure_owner() {
+                trap(error);
+            }
+            let supply = *self.total_supply;
+            if total_amount == 0 || self.env().transferred_balance() != total_amount || supply == 0 {
+                trap(Error::InvalidDividend);
+            }
+
+            // Rounding leaves some dust unclaimable; it stays with the contract.
+            let per_token = total_amount * DIVIDEND_PRECISION / supply;
+            self.dividend_per_token += per_token;
+            self.unclaimed_dividends += total_amount;
+            self.env().emit_event( DividendDistributed {
+                amount: total_amount,
+                per_token,
+            });
+            Ok(())
+        }
+
+        /// Pay the caller the dividends owed on the tokens they have held.
+        #[ink(message)]
+        pub fn claim_dividend(&mut self) -> Result<()> {
+            let holder = self.env().caller();
+            let amount = self.dividend_of(holder);
+            if amount == 0 {
+                return Err(Error::NoDividend);
+            }
+
+            // Pay out first: a failed message doesn't revert, so nothing may be settled before.
+            if self.env().transfer(holder, amount).is_err() {
+                return Err(Error::TransferFailed);
+            }
+            self.dividend_checkpoints.insert(holder, self.dividend_per_token);
+            self.dividends_owed.take(&holder);
+            self.unclaimed_dividends -= amount;
+            self.env().emit_event( DividendClaimed {
+                holder,
+                amount,
             });
             Ok(())
         }
 
+        /// The dividends `holder` can claim.
+        #[ink(message)]
+        pub fn dividend_of(&self, holder: AccountId) -> Balance {
+            self.dividends_owed.get(&holder).copied().unwrap_or(0) + self.pending_dividend(holder)
+        }
+
+        /// Limit how much a single transfer may move, and how much an account may transfer
+        /// within one block, `None` lifting a limit. Meant to contain the damage a compromised
+        /// key can do until the token can be paused. Only the owner may set limits.
+        #[ink(message)]
+        pub fn set_transfer_limits(
+            &mut self,
+            max_transfer: Option<Balance>,
+            max_transfer_per_block: Option<Balance>,
+        ) -> Result<()> {
+            self.ensure_owner()?;
+            self.max_transfer = max_transfer;
+            self.max_transfer_per_block = max_transfer_per_block;
+            Ok(())
+        }
+
+        /// The limits set by `set_transfer_limits`: per transfer and per block.
+        #[ink(message)]
+        pub fn transfer_limits(&self) -> (Option<Balance>, Option<Balance>) {
+            (self.max_transfer, self.max_transfer_per_block)
+        }
+
+        /// Exempt `account` from the transfer limits, or end its exemption, e.g. for exchanges
+        /// and the treasury. Only the owner may exempt accounts.
+        #[ink(message)]
+        pub fn set_limit_exempt(&mut self, account: AccountId, exempt: bool) -> Result<()> {
+            self.ensure_owner()?;
+            if exempt {
+                self.limit_exempt.insert(account, ());
+            } else {
+                self.limit_exempt.take(&account);
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_limit_exempt(&self, account: AccountId) -> bool {
+            self.limit_exempt.contains_key(&account)
+        }
+
+        /// Give the caller's voting power, its whole balance now and later, to `to`. Tokens
+        /// only count as votes once delegated, possibly to their holder itself.
+        #[ink(message)]
+        pub fn delegate(&mut self, to: AccountId) -> Result<()> {
+            let delegator = self.env().caller();
+            let from_delegate = self.delegates.insert(delegator, to);
+            self.env().emit_event( DelegateChanged {
+                delegator,
+                from_delegate,
+                to_delegate: to,
+            });
+            self.move_voting_power(from_delegate, Some(to), self.balance_of(delegator));
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn delegates(&self, account: AccountId) -> Option<AccountId> {
+            self.delegates.get(&account).copied()
+        }
+
+        /// The voting power currently delegated to `account`.
+        #[ink(message)]
+        pub fn get_votes(&self, account: AccountId) -> Balance {
+            match self.num_checkpoints(account) {
+                0 => 0,
+                n => self.checkpoint(account, n - 1).1,
+            }
+        }
+
+        /// The voting power delegated to `account` at the end of `block`, which must have ended.
+        #[ink(message)]
+        pub fn get_past_votes(&self, account: AccountId, block: BlockNumber) -> Result<Balance> {
+            if block >= self.env().block_number() {
+                return Err(Error::BlockNotYetMined);
+            }
+
+            // Find the first checkpoint after `block`; the one before it was in force then.
+            let (mut low, mut high) = (0, self.num_checkpoints(account));
+            while low < high {
+                let mid = low + (high - low) / 2;
+                if self.checkpoint(account, mid).0 > block {
+                    high = mid;
+                } else {
+                    low = mid + 1;
+                }
+            }
+            Ok(match high {
+                0 => 0,
+                n => self.checkpoint(account, n - 1).1,
+            })
+        }
+
+        /// Dividends distributed on `holder`'s current balance since it was last settled.
+        fn pending_dividend(&self, holder: AccountId) -> Balance {
+            let checkpoint = self.dividend_checkpoints.get(&holder).copied().unwrap_or(0);
+            self.balance_of(holder) * (self.dividend_per_token - checkpoint) / DIVIDEND_PRECISION
+        }
+
+        /// Move `holder`'s pending dividends into what they are owed, before their balance
+        /// changes.
+        fn settle_dividends(&mut self, holder: AccountId) {
+            let pending = self.pending_dividend(holder);
+            if pending > 0 {
+                let owed = self.dividends_owed.get(&holder).copied().unwrap_or(0);
+                self.dividends_owed.insert(holder, owed + pending);
+            }
+            self.dividend_checkpoints.insert(holder, self.dividend_per_token);
+        }
+
+        fn num_checkpoints(&self, account: AccountId) -> u32 {
+            self.num_checkpoints.get(&account).copied().unwrap_or(0)
+        }
+
+        fn checkpoint(&self, account: AccountId, index: u32) -> (BlockNumber, Balance) {
+            self.checkpoints.get(&(account, index)).copied().unwrap_or((0, 0))
+        }
+
+        /// Move `value` votes from delegate `from` to delegate `to`, `None` being nobody.
+        fn move_voting_power(&mut self, from: Option<AccountId>, to: Option<AccountId>, value: Balance) {
+            if from == to || value == 0 {
+                return;
+            }
+            if let Some(from) = from {
+                let votes = self.get_votes(from);
+                self.write_checkpoint(from, votes, votes - value);
+            }
+            if let Some(to) = to {
+                let votes = self.get_votes(to);
+                self.write_checkpoint(to, votes, votes + value);
+            }
+        }
+
+        /// Record that `delegate` has `new_votes` from the current block on.
+        fn write_checkpoint(&mut self, delegate: AccountId, previous_votes: Balance, new_votes: Balance) {
+            let block = self.env().block_number();
+            let n = self.num_checkpoints(delegate);
+            if n > 0 && self.checkpoint(delegate, n - 1).0 == block {
+                self.checkpoints.insert((delegate, n - 1), (block, new_votes));
+            } else {
+                self.checkpoints.insert((delegate, n), (block, new_votes));
+                self.num_checkpoints.insert(delegate, n + 1);
+            }
+            self.env().emit_event( DelegateVotesChanged {
+                delegate,
+                previous_votes,
+                new_votes,
+            });
+        }
+
         fn inner_transfer(
             &mut self, 
             from: AccountId, 
@@ -124,12 +741,18 @@ mod erc20 {
         ) -> Result<()> {
             let from_balance = self.balance_of(from);
             if from_balance < value {
-                return Err(Error::InsufficientBalance);
+                return self.fail(Some(from), Some(to), value, Error::InsufficientBalance);
+            }
+            if !self.within_transfer_limits(from, value) {
+                return self.fail(Some(from), Some(to), value, Error::TransferLimitExceeded);
             }
 
+            self.settle_dividends(from);
+            self.settle_dividends(to);
             self.balances.insert(from, from_balance - value);
             let to_balance = self.balance_of(to);
             self.balances.insert(to, to_balance + value);
+            self.move_voting_power(self.delegates(from), self.delegates(to), value);
             self.env().emit_event( Transfer {
                 from: Some(from),
                 to: Some(to),
@@ -137,5 +760,742 @@ mod erc20 {
             });
             Ok(())
         }
+
+        /// Whether `from` may transfer `value` now, recording it against their limit for the
+        /// block if so.
+        fn within_transfer_limits(&mut self, from: AccountId, value: Balance) -> bool {
+            if self.is_limit_exempt(from) {
+                return true;
+            }
+            if self.max_transfer.map_or(false, |max| value > max) {
+                return false;
+            }
+            let block = self.env().block_number();
+            let transferred = match self.transferred_in_block.get(&from) {
+                Some(&(last, transferred)) if last == block => transferred,
+                _ => 0,
+            };
+            let transferred = transferred.saturating_add(value);
+            if self.max_transfer_per_block.map_or(false, |max| transferred > max) {
+                return false;
+            }
+            self.transferred_in_block.insert(from, (block, transferred));
+            true
+        }
+
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            Ok(())
+        }
+
+        /// Report a refused transfer and fail with `reason`.
+        fn fail(
+            &self,
+            from: Option<AccountId>,
+            to: Option<AccountId>,
+            value: Balance,
+            reason: Error,
+        ) -> Result<()> {
+            self.env().emit_event( TransferFailed {
+                from,
+                to,
+                value,
+                reason,
+            });
+            Err(reason)
+        }
+    }
+
+    /// Fail a payable message. Returning an error doesn't revert a message in this ink!
+    /// version, so the contract would keep the balance sent with it; trapping reverts it all.
+    fn trap(error: Error) -> ! {
+        panic!("{:?}", error)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink_env::topics::PrefixedValue;
+        use ink_lang as ink;
+
+        type Env = ink_env::DefaultEnvironment;
+        type Event = <Erc20 as ::ink_lang::BaseEvent>::Type;
+
+        fn accounts() -> ink_env::test::DefaultAccounts<Env> {
+            ink_env::test::default_accounts::<Env>().expect("off-chain environment is set up")
+        }
+
+        fn set_caller(caller: AccountId) {
+            set_caller_with_value(caller, 1_000_000);
+        }
+
+        /// Make `caller` the caller from now on, sending `value` with each call.
+        fn set_caller_with_value(caller: AccountId, value: Balance) {
+            let callee = ink_env::account_id::<Env>().unwrap_or([0x0; 32].into());
+            let data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            ink_env::test::push_execution_context::<Env>(caller, callee, 1_000_000, value, data);
+        }
+
+        /// The topic ink! emits for a field: its prefixed encoding, hashed if longer than a hash.
+        fn topic<T: Encode>(prefix: &[u8], value: &T) -> Hash {
+            use ink_env::{
+                hash::{Blake2x256, CryptoHash, HashOutput},
+                Clear,
+            };
+            let encoded = PrefixedValue { prefix, value }.encode();
+            let mut result = Hash::clear();
+            if encoded.len() <= result.as_ref().len() {
+                result.as_mut()[..encoded.len()].copy_from_slice(&encoded);
+            } else {
+                let mut hash = <<Blake2x256 as HashOutput>::Type as Default>::default();
+                <Blake2x256 as CryptoHash>::hash(&encoded, &mut hash);
+                result.as_mut().copy_from_slice(&hash);
+            }
+            result
+        }
+
+        fn assert_topics(event: &ink_env::test::EmittedEvent, expected: Vec<Hash>) {
+            let topics: Vec<Hash> = event.topics
+                .iter()
+                .map(|topic| Hash::decode(&mut &topic[..]).expect("topics are hashes"))
+                .collect();
+            assert_eq!(topics, expected);
+        }
+
+        #[ink::test]
+        fn transfer_topics_are_the_parties() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.transfer(accounts.bob, 10), Ok(()));
+
+            let events: Vec<_> = ink_env::test::recorded_events().collect();
+            assert_eq!(events.len(), 2);
+            assert_topics(&events[1], vec![
+                topic(b"", &b"Erc20::Transfer"),
+                topic(b"Erc20::Transfer::from", &Some(accounts.alice)),
+                topic(b"Erc20::Transfer::to", &Some(accounts.bob)),
+            ]);
+            match Event::decode(&mut &events[1].data[..]) {
+                Ok(Event::Transfer(Transfer { value, .. })) => assert_eq!(value, 10),
+                _ => panic!("expected a Transfer event"),
+            }
+        }
+
+        #[ink::test]
+        fn refused_transfer_emits_transfer_failed() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.transfer(accounts.bob, 101), Err(Error::InsufficientBalance));
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                erc20.transfer_from(accounts.alice, accounts.charlie, 1),
+                Err(Error::InsufficientAllowance),
+            );
+
+            let events: Vec<_> = ink_env::test::recorded_events().collect();
+            assert_eq!(events.len(), 3);
+            assert_topics(&events[2], vec![
+                topic(b"", &b"Erc20::TransferFailed"),
+                topic(b"Erc20::TransferFailed::from", &Some(accounts.alice)),
+                topic(b"Erc20::TransferFailed::to", &Some(accounts.charlie)),
+            ]);
+            match Event::decode(&mut &events[1].data[..]) {
+                Ok(Event::TransferFailed(TransferFailed { value, reason, .. })) => {
+                    assert_eq!((value, reason), (101, Error::InsufficientBalance))
+                }
+                _ => panic!("expected a TransferFailed event"),
+            }
+        }
+
+        #[ink::test]
+        fn delegated_votes_follow_transfers() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new(100);
+            // tokens count as votes only once delegated
+            assert_eq!(erc20.get_votes(accounts.alice), 0);
+            assert_eq!(erc20.delegate(accounts.alice), Ok(()));
+            assert_eq!(erc20.get_votes(accounts.alice), 100);
+
+            assert_eq!(erc20.transfer(accounts.bob, 30), Ok(()));
+            assert_eq!(erc20.get_votes(accounts.alice), 70);
+
+            set_caller(accounts.bob);
+            assert_eq!(erc20.delegate(accounts.charlie), Ok(()));
+            assert_eq!(erc20.delegates(accounts.bob), Some(accounts.charlie));
+            assert_eq!(erc20.get_votes(accounts.charlie), 30);
+            assert_eq!(erc20.transfer(accounts.alice, 10), Ok(()));
+            assert_eq!(erc20.get_votes(accounts.charlie), 20);
+            assert_eq!(erc20.get_votes(accounts.alice), 80);
+
+            // redelegating moves the whole balance
+            assert_eq!(erc20.delegate(accounts.alice), Ok(()));
+            assert_eq!(erc20.get_votes(accounts.charlie), 0);
+            assert_eq!(erc20.get_votes(accounts.alice), 100);
+        }
+
+        #[ink::test]
+        fn past_votes_are_checkpointed() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.delegate(accounts.alice), Ok(()));
+            ink_env::test::advance_block::<Env>().unwrap();
+            assert_eq!(erc20.transfer(accounts.bob, 40), Ok(()));
+            ink_env::test::advance_block::<Env>().unwrap();
+            // changes within a block share one checkpoint
+            assert_eq!(erc20.transfer(accounts.bob, 10), Ok(()));
+            assert_eq!(erc20.transfer(accounts.bob, 5), Ok(()));
+            assert_eq!(erc20.get_past_votes(accounts.alice, 2), Err(Error::BlockNotYetMined));
+
+            ink_env::test::advance_block::<Env>().unwrap();
+            assert_eq!(erc20.get_past_votes(accounts.alice, 0), Ok(100));
+            assert_eq!(erc20.get_past_votes(accounts.alice, 1), Ok(60));
+            assert_eq!(erc20.get_past_votes(accounts.alice, 2), Ok(45));
+            assert_eq!(erc20.get_past_votes(accounts.bob, 2), Ok(0));
+            assert_eq!(erc20.num_checkpoints(accounts.alice), 3);
+        }
+
+        #[ink::test]
+        fn sweep_native_works() {
+            let accounts = accounts();
+            let contract = ink_env::account_id::<Env>().expect("contract account is set");
+            let mut erc20 = Erc20::new_wrapped();
+            ink_env::test::set_account_balance::<Env>(contract, 150).unwrap();
+            let bob_balance = ink_env::test::get_account_balance::<Env>(accounts.bob).unwrap();
+
+            // the balance backing the wrapped supply stays put
+            *erc20.total_supply = 100;
+            assert_eq!(erc20.sweep_native(accounts.bob, 60), Err(Error::InsufficientBalance));
+            assert_eq!(erc20.sweep_native(accounts.bob, 50), Ok(()));
+            assert_eq!(ink_env::test::get_account_balance::<Env>(accounts.bob), Ok(bob_balance + 50));
+            assert_eq!(ink_env::test::get_account_balance::<Env>(contract), Ok(100));
+
+            let events: Vec<_> = ink_env::test::recorded_events().collect();
+            match Event::decode(&mut &events[0].data[..]) {
+                Ok(Event::Swept(Swept { token, to, amount })) => {
+                    assert_eq!((token, to, amount), (None, accounts.bob, 50))
+                }
+                _ => panic!("expected a Swept event"),
+            }
+        }
+
+        #[ink::test]
+        fn sweep_failed() {
+            let accounts = accounts();
+            let contract = ink_env::account_id::<Env>().expect("contract account is set");
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.owner(), accounts.alice);
+            assert_eq!(erc20.sweep_foreign(contract, accounts.alice, 10), Err(Error::NotForeign));
+
+            set_caller(accounts.bob);
+            assert_eq!(erc20.sweep_native(accounts.bob, 1), Err(Error::NotOwner));
+            assert_eq!(erc20.sweep_foreign(accounts.django, accounts.bob, 1), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn claim_dividend_works() {
+            let accounts = accounts();
+            let contract = ink_env::account_id::<Env>().expect("contract account is set");
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.transfer(accounts.bob, 25), Ok(()));
+
+            set_caller_with_value(accounts.alice, 1_000);
+            assert_eq!(erc20.distribute(1_000), Ok(()));
+            ink_env::test::set_account_balance::<Env>(contract, 1_000).unwrap();
+            assert_eq!(erc20.dividend_of(accounts.alice), 750);
+            assert_eq!(erc20.dividend_of(accounts.bob), 250);
+
+            // dividends stay with who held the tokens when they were distributed
+            assert_eq!(erc20.transfer(accounts.bob, 25), Ok(()));
+            assert_eq!(erc20.dividend_of(accounts.alice), 750);
+            assert_eq!(erc20.dividend_of(accounts.bob), 250);
+
+            set_caller(accounts.bob);
+            let bob_balance = ink_env::test::get_account_balance::<Env>(accounts.bob).unwrap();
+            assert_eq!(erc20.claim_dividend(), Ok(()));
+            assert_eq!(ink_env::test::get_account_balance::<Env>(accounts.bob), Ok(bob_balance + 250));
+            assert_eq!(erc20.dividend_of(accounts.bob), 0);
+            assert_eq!(erc20.unclaimed_dividends, 750);
+
+            // later distributions follow the new balances
+            set_caller_with_value(accounts.alice, 100);
+            assert_eq!(erc20.distribute(100), Ok(()));
+            assert_eq!(erc20.dividend_of(accounts.alice), 800);
+            assert_eq!(erc20.dividend_of(accounts.bob), 50);
+        }
+
+        #[ink::test]
+        fn claim_dividend_failed() {
+            let accounts = accounts();
+            let contract = ink_env::account_id::<Env>().expect("contract account is set");
+            let mut erc20 = Erc20::new(100);
+            set_caller_with_value(accounts.alice, 1_000);
+            assert_eq!(erc20.claim_dividend(), Err(Error::NoDividend));
+
+            // what is owed to holders can't be swept
+            set_caller_with_value(accounts.alice, 1_000);
+            assert_eq!(erc20.distribute(1_000), Ok(()));
+            ink_env::test::set_account_balance::<Env>(contract, 1_000).unwrap();
+            assert_eq!(erc20.sweep_native(accounts.alice, 1), Err(Error::InsufficientBalance));
+        }
+
+        #[ink::test]
+        fn transfer_limits_works() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new(1_000);
+            set_caller(accounts.alice);
+            assert_eq!(erc20.set_transfer_limits(Some(50), Some(80)), Ok(()));
+            assert_eq!(erc20.transfer_limits(), (Some(50), Some(80)));
+            assert_eq!(erc20.transfer(accounts.bob, 50), Ok(()));
+            assert_eq!(erc20.transfer(accounts.bob, 30), Ok(()));
+
+            // the per-block allowance starts over in the next block
+            ink_env::test::advance_block::<Env>().unwrap();
+            assert_eq!(erc20.transfer(accounts.bob, 50), Ok(()));
+            assert_eq!(erc20.balance_of(accounts.bob), 130);
+
+            // exempt accounts aren't limited, and lifting the limits frees everyone
+            assert_eq!(erc20.set_limit_exempt(accounts.alice, true), Ok(()));
+            assert!(erc20.is_limit_exempt(accounts.alice));
+            assert_eq!(erc20.transfer(accounts.bob, 500), Ok(()));
+            assert_eq!(erc20.set_transfer_limits(None, None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(erc20.transfer(accounts.charlie, 630), Ok(()));
+            assert_eq!(erc20.balance_of(accounts.charlie), 630);
+        }
+
+        #[ink::test]
+        fn transfer_limits_failed() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new(1_000);
+            set_caller(accounts.bob);
+            assert_eq!(erc20.set_transfer_limits(Some(50), None), Err(Error::NotOwner));
+            assert_eq!(erc20.set_limit_exempt(accounts.bob, true), Err(Error::NotOwner));
+
+            set_caller(accounts.alice);
+            assert_eq!(erc20.set_transfer_limits(Some(50), Some(80)), Ok(()));
+            assert_eq!(erc20.transfer(accounts.bob, 51), Err(Error::TransferLimitExceeded));
+            assert_eq!(erc20.transfer(accounts.bob, 50), Ok(()));
+            assert_eq!(erc20.transfer(accounts.bob, 31), Err(Error::TransferLimitExceeded));
+            assert_eq!(erc20.balance_of(accounts.bob), 50);
+
+            // spenders are held to the owner's limits
+            assert_eq!(erc20.approve(accounts.bob, 100), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(
+                erc20.transfer_from(accounts.alice, accounts.charlie, 31),
+                Err(Error::TransferLimitExceeded)
+            );
+
+            // an exemption can be taken back
+            set_caller(accounts.alice);
+            assert_eq!(erc20.set_limit_exempt(accounts.alice, true), Ok(()));
+            assert_eq!(erc20.set_limit_exempt(accounts.alice, false), Ok(()));
+            assert_eq!(erc20.transfer(accounts.bob, 31), Err(Error::TransferLimitExceeded));
+        }
+
+        #[ink::test]
+        fn withdraw_failed() {
+            let accounts = accounts();
+            let contract = ink_env::account_id::<Env>().expect("contract account is set");
+            assert_eq!(Erc20::new(100).withdraw(10), Err(Error::NotWrapped));
+
+            let mut erc20 = Erc20::new_wrapped();
+            set_caller_with_value(accounts.alice, 100);
+            assert_eq!(erc20.deposit(), Ok(()));
+            ink_env::test::set_account_balance::<Env>(contract, 100).unwrap();
+            assert_eq!(erc20.withdraw(101), Err(Error::InsufficientBalance));
+
+            // withdrawals are held to the transfer limits
+            assert_eq!(erc20.set_transfer_limits(Some(50), None), Ok(()));
+            assert_eq!(erc20.withdraw(51), Err(Error::TransferLimitExceeded));
+            assert_eq!(erc20.withdraw(50), Ok(()));
+            assert_eq!(erc20.balance_of(accounts.alice), 50);
+        }
+
+        #[ink::test]
+        fn multicall_works() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new(1_000);
+            assert_eq!(erc20.transfer(accounts.bob, 100), Ok(()));
+            assert_eq!(erc20.approve(accounts.bob, 40), Ok(()));
+            assert_eq!(erc20.approve(accounts.charlie, 25), Ok(()));
+
+            assert_eq!(
+                erc20.multicall(vec![
+                    CallRequest::TotalSupply,
+                    CallRequest::BalanceOf(accounts.alice),
+                    CallRequest::BalanceOf(accounts.bob),
+                    CallRequest::Allowance(accounts.alice, accounts.bob),
+                    CallRequest::Allowance(accounts.alice, accounts.charlie),
+                    CallRequest::Allowance(accounts.bob, accounts.alice),
+                ]),
+                Ok(vec![1_000, 900, 100, 40, 25, 0])
+            );
+            assert_eq!(erc20.multicall(Vec::new()), Ok(Vec::new()));
+        }
+
+        #[ink::test]
+        fn multicall_failed() {
+            let accounts = accounts();
+            let erc20 = Erc20::new(1_000);
+            let requests = vec![CallRequest::BalanceOf(accounts.alice); MAX_CALL_REQUESTS + 1];
+            assert_eq!(erc20.multicall(requests), Err(Error::TooManyCallRequests));
+            let requests = vec![CallRequest::BalanceOf(accounts.alice); MAX_CALL_REQUESTS];
+            assert_eq!(erc20.multicall(requests).map(|balances| balances.len()), Ok(MAX_CALL_REQUESTS));
+        }
+
+        /// Stands in for the runtime's `current_deposit`.
+        struct MockedDeposit;
+
+        impl ink_env::test::ChainExtension for MockedDeposit {
+            fn func_id(&self) -> u32 {
+                0x1000
+            }
+
+            fn call(&mut self, _input: &[u8], output: &mut Vec<u8>) -> u32 {
+                Encode::encode_to(&(30 as Balance), output);
+                0
+            }
+        }
+
+        /// Stands in for the runtime's `mint_kitty`, minting kitty `next_id` unless it refuses.
+        struct MockedMint {
+            next_id: u32,
+            refuse: bool,
+        }
+
+        impl ink_env::test::ChainExtension for MockedMint {
+            fn func_id(&self) -> u32 {
+                0x1001
+            }
+
+            fn call(&mut self, _input: &[u8], output: &mut Vec<u8>) -> u32 {
+                if self.refuse {
+                    return 1;
+                }
+                Encode::encode_to(&self.next_id, output);
+                self.next_id += 1;
+                0
+            }
+        }
+
+        #[ink::test]
+        fn mint_kitty_works() {
+            let accounts = accounts();
+            ink_env::test::register_chain_extension(MockedDeposit);
+            ink_env::test::register_chain_extension(MockedMint { next_id: 7, refuse: false });
+            let mut erc20 = Erc20::new_wrapped();
+            set_caller_with_value(accounts.alice, 100);
+            assert_eq!(erc20.deposit(), Ok(()));
+
+            assert_eq!(erc20.mint_kitty(accounts.bob), Ok(7));
+            assert_eq!(erc20.mint_kitty(accounts.alice), Ok(8));
+            assert_eq!(erc20.balance_of(accounts.alice), 40);
+            assert_eq!(erc20.total_supply(), 40);
+            assert_eq!(transfer_events().last(), Some(&(Some(accounts.alice), None, 30)));
+        }
+
+        #[ink::test]
+        fn mint_kitty_failed() {
+            let accounts = accounts();
+            ink_env::test::register_chain_extension(MockedDeposit);
+            ink_env::test::register_chain_extension(MockedMint { next_id: 0, refuse: true });
+            assert_eq!(Erc20::new(100).mint_kitty(accounts.alice), Err(Error::NotWrapped));
+
+            let mut erc20 = Erc20::new_wrapped();
+            set_caller_with_value(accounts.alice, 29);
+            assert_eq!(erc20.deposit(), Ok(()));
+            assert_eq!(erc20.mint_kitty(accounts.alice), Err(Error::InsufficientBalance));
+
+            // nothing is burned when the runtime refuses
+            assert_eq!(erc20.deposit(), Ok(()));
+            assert_eq!(erc20.mint_kitty(accounts.alice), Err(Error::KittyMintFailed));
+            assert_eq!(erc20.balance_of(accounts.alice), 58);
+            assert_eq!(erc20.total_supply(), 58);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "InvalidDividend")]
+        fn distribute_failed_with_wrong_amount() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new(100);
+            set_caller_with_value(accounts.alice, 1_000);
+            let _ = erc20.distribute(999);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "InvalidDividend")]
+        fn distribute_failed_without_holders() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new_wrapped();
+            set_caller_with_value(accounts.alice, 1_000);
+            let _ = erc20.distribute(1_000);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "NotOwner")]
+        fn distribute_failed_for_others() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new(100);
+            set_caller_with_value(accounts.bob, 1_000);
+            let _ = erc20.distribute(1_000);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "NotWrapped")]
+        fn deposit_failed() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new(100);
+            set_caller_with_value(accounts.alice, 100);
+            let _ = erc20.deposit();
+        }
+
+        /// The value of every `Transfer` event emitted so far, with its parties.
+        fn transfer_events() -> Vec<(Option<AccountId>, Option<AccountId>, Balance)> {
+            ink_env::test::recorded_events()
+                .filter_map(|event| match Event::decode(&mut &event.data[..]) {
+                    Ok(Event::Transfer(Transfer { from, to, value })) => Some((from, to, value)),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        #[ink::test]
+        fn transfer_edge_cases_works() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new(100);
+
+            // sending to yourself changes nothing but is still reported
+            assert_eq!(erc20.transfer(accounts.alice, 60), Ok(()));
+            assert_eq!(erc20.balance_of(accounts.alice), 100);
+            // zero-value transfers go through, even from accounts holding nothing
+            assert_eq!(erc20.transfer(accounts.bob, 0), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(erc20.transfer(accounts.bob, 0), Ok(()));
+            assert_eq!(erc20.transfer_from(accounts.alice, accounts.bob, 0), Ok(()));
+            assert_eq!(erc20.balance_of(accounts.bob), 0);
+
+            // spending the whole allowance leaves none
+            set_caller(accounts.alice);
+            assert_eq!(erc20.approve(accounts.charlie, 30), Ok(()));
+            set_caller(accounts.charlie);
+            assert_eq!(erc20.transfer_from(accounts.alice, accounts.charlie, 30), Ok(()));
+            assert_eq!(erc20.allowance(accounts.alice, accounts.charlie), 0);
+            assert_eq!(erc20.balance_of(accounts.charlie), 30);
+            assert_eq!(erc20.total_supply(), 100);
+
+            assert_eq!(transfer_events(), vec![
+                (None, Some(accounts.alice), 100),
+                (Some(accounts.alice), Some(accounts.alice), 60),
+                (Some(accounts.alice), Some(accounts.bob), 0),
+                (Some(accounts.charlie), Some(accounts.bob), 0),
+                (Some(accounts.alice), Some(accounts.bob), 0),
+                (Some(accounts.alice), Some(accounts.charlie), 30),
+            ]);
+        }
+
+        #[ink::test]
+        fn approve_works() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.approve(accounts.bob, 40), Ok(()));
+            // approving again replaces the allowance rather than adding to it
+            assert_eq!(erc20.approve(accounts.bob, 15), Ok(()));
+            assert_eq!(erc20.allowance(accounts.alice, accounts.bob), 15);
+            // allowances may exceed the balance
+            assert_eq!(erc20.approve(accounts.charlie, 1_000), Ok(()));
+            assert_eq!(erc20.allowance(accounts.alice, accounts.charlie), 1_000);
+            assert_eq!(erc20.allowance(accounts.bob, accounts.alice), 0);
+
+            let events: Vec<_> = ink_env::test::recorded_events().collect();
+            let per_approval = if !cfg!(feature = "no-approval-event") { 2 } else { 1 };
+            assert_eq!(events.len(), 1 + 3 * per_approval);
+            if !cfg!(feature = "no-approval-event") {
+                assert_topics(&events[1], vec![
+                    topic(b"", &b"Erc20::Approval"),
+                    topic(b"Erc20::Approval::owner", &accounts.alice),
+                    topic(b"Erc20::Approval::spender", &accounts.bob),
+                ]);
+                let values: Vec<_> = events[1..]
+                    .iter()
+                    .filter_map(|event| match Event::decode(&mut &event.data[..]) {
+                        Ok(Event::Approval(Approval { value, .. })) => Some(value),
+                        _ => None,
+                    })
+                    .collect();
+                assert_eq!(values, vec![40, 15, 1_000]);
+            }
+            assert_eq!(allowance_events(), vec![
+                (accounts.alice, accounts.bob, 40),
+                (accounts.alice, accounts.bob, 15),
+                (accounts.alice, accounts.charlie, 1_000),
+            ]);
+        }
+
+        fn allowance_events() -> Vec<(AccountId, AccountId, Balance)> {
+            ink_env::test::recorded_events()
+                .filter_map(|event| match Event::decode(&mut &event.data[..]) {
+                    Ok(Event::AllowanceChanged(AllowanceChanged { owner, spender, allowance })) => {
+                        Some((owner, spender, allowance))
+                    }
+                    _ => None,
+                })
+                .collect()
+        }
+
+        #[ink::test]
+        fn allowance_changed_works() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.approve(accounts.bob, 50), Ok(()));
+
+            // spending part of an allowance reports what is left of it
+            set_caller(accounts.bob);
+            assert_eq!(erc20.transfer_from(accounts.alice, accounts.charlie, 20), Ok(()));
+            assert_eq!(erc20.transfer_from(accounts.alice, accounts.bob, 30), Ok(()));
+            // a refused transfer spends nothing, so reports nothing
+            assert_eq!(
+                erc20.transfer_from(accounts.alice, accounts.bob, 1),
+                Err(Error::InsufficientAllowance),
+            );
+            assert_eq!(allowance_events(), vec![
+                (accounts.alice, accounts.bob, 50),
+                (accounts.alice, accounts.bob, 30),
+                (accounts.alice, accounts.bob, 0),
+            ]);
+
+            let events: Vec<_> = ink_env::test::recorded_events().collect();
+            let spent = events
+                .iter()
+                .rev()
+                .find(|event| matches!(Event::decode(&mut &event.data[..]), Ok(Event::AllowanceChanged(_))))
+                .expect("an AllowanceChanged event");
+            assert_topics(spent, vec![
+                topic(b"", &b"Erc20::AllowanceChanged"),
+                topic(b"Erc20::AllowanceChanged::owner", &accounts.alice),
+                topic(b"Erc20::AllowanceChanged::spender", &accounts.bob),
+            ]);
+        }
+
+        #[ink::test]
+        fn transfer_from_failed() {
+            let accounts = accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.approve(accounts.bob, 150), Ok(()));
+
+            // an allowance is never spent below zero
+            set_caller(accounts.charlie);
+            assert_eq!(
+                erc20.transfer_from(accounts.alice, accounts.charlie, 1),
+                Err(Error::InsufficientAllowance),
+            );
+            set_caller(accounts.bob);
+            assert_eq!(
+                erc20.transfer_from(accounts.alice, accounts.bob, 151),
+                Err(Error::InsufficientAllowance),
+            );
+            // nor spent by a transfer the balance can't cover
+            assert_eq!(
+                erc20.transfer_from(accounts.alice, accounts.bob, 101),
+                Err(Error::InsufficientBalance),
+            );
+            assert_eq!(erc20.allowance(accounts.alice, accounts.bob), 150);
+            // nor by the owner moving their own tokens
+            set_caller(accounts.alice);
+            assert_eq!(erc20.transfer(accounts.bob, 101), Err(Error::InsufficientBalance));
+            assert_eq!(erc20.allowance(accounts.alice, accounts.bob), 150);
+            assert_eq!(erc20.balance_of(accounts.alice), 100);
+            assert_eq!(erc20.balance_of(accounts.bob), 0);
+
+            let refused: Vec<_> = ink_env::test::recorded_events()
+                .filter_map(|event| match Event::decode(&mut &event.data[..]) {
+                    Ok(Event::TransferFailed(TransferFailed { value, reason, .. })) => Some((value, reason)),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(refused, vec![
+                (1, Error::InsufficientAllowance),
+                (151, Error::InsufficientAllowance),
+                (101, Error::InsufficientBalance),
+                (101, Error::InsufficientBalance),
+            ]);
+            assert_eq!(transfer_events().len(), 1);
+        }
+
+        /// A xorshift generator, so fuzz runs are random-looking but reproducible from the seed.
+        struct Rng(u64);
+
+        impl Rng {
+            fn next(&mut self, bound: u64) -> u64 {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                self.0 % bound
+            }
+        }
+
+        #[ink::test]
+        fn random_transfers_conserve_supply() {
+            let accounts = accounts();
+            let holders = [accounts.alice, accounts.bob, accounts.charlie, accounts.django];
+            for seed in 1..=20u64 {
+                let mut rng = Rng(seed * 0x9e37_79b9_7f4a_7c15);
+                set_caller(accounts.alice);
+                let mut erc20 = Erc20::new(1_000);
+                // what every holder should have, and may spend for every other
+                let mut balances = [1_000, 0, 0, 0];
+                let mut allowances = [[0; 4]; 4];
+
+                for _ in 0..200 {
+                    let caller = rng.next(4) as usize;
+                    let from = rng.next(4) as usize;
+                    let to = rng.next(4) as usize;
+                    let value = rng.next(600) as Balance;
+                    set_caller(holders[caller]);
+                    match rng.next(3) {
+                        0 => {
+                            let expected = if value > balances[caller] {
+                                Err(Error::InsufficientBalance)
+                            } else {
+                                balances[caller] -= value;
+                                balances[to] += value;
+                                Ok(())
+                            };
+                            assert_eq!(erc20.transfer(holders[to], value), expected);
+                        }
+                        1 => {
+                            allowances[caller][to] = value;
+                            assert_eq!(erc20.approve(holders[to], value), Ok(()));
+                        }
+                        _ => {
+                            let expected = if value > allowances[from][caller] {
+                                Err(Error::InsufficientAllowance)
+                            } else if value > balances[from] {
+                                Err(Error::InsufficientBalance)
+                            } else {
+                                allowances[from][caller] -= value;
+                                balances[from] -= value;
+                                balances[to] += value;
+                                Ok(())
+                            };
+                            assert_eq!(erc20.transfer_from(holders[from], holders[to], value), expected);
+                        }
+                    }
+
+                    let held: Balance = holders.iter().map(|&holder| erc20.balance_of(holder)).sum();
+                    assert_eq!(held, erc20.total_supply());
+                    assert_eq!(erc20.total_supply(), 1_000);
+                }
+                for (i, &owner) in holders.iter().enumerate() {
+                    assert_eq!(erc20.balance_of(owner), balances[i]);
+                    for (j, &spender) in holders.iter().enumerate() {
+                        assert_eq!(erc20.allowance(owner, spender), allowances[i][j]);
+                    }
+                }
+            }
+        }
     }
 }
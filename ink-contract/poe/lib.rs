@@ -0,0 +1,229 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+#[ink::contract]
+mod poe {
+    use ink_storage::{
+        collections::HashMap as StorageHashMap,
+        traits::{PackedLayout, SpreadLayout},
+    };
+    use scale::{Encode, Decode};
+
+    #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct Claim {
+        owner: AccountId,
+        block_number: BlockNumber,
+        expires_at: Option<BlockNumber>,
+    }
+
+    #[ink(storage)]
+    pub struct Poe {
+        claims: StorageHashMap<Hash, Claim>,
+    }
+
+    #[ink(event)]
+    pub struct ClaimCreated {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        claim: Hash,
+        expires_at: Option<BlockNumber>,
+    }
+
+    #[ink(event)]
+    pub struct ClaimRevoked {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        claim: Hash,
+    }
+
+    #[ink(event)]
+    pub struct ClaimTransferred {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        #[ink(topic)]
+        claim: Hash,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Encode, Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        ClaimAlreadyExists,
+        NoSuchClaim,
+        NotClaimOwner,
+        ExpiryInThePast,
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    impl Poe {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                claims: StorageHashMap::new(),
+            }
+        }
+
+        /// The claim on `hash`, unless there is none or it has expired.
+        #[ink(message)]
+        pub fn get_claim(&self, hash: Hash) -> Option<Claim> {
+            self.live_claim(&hash).cloned()
+        }
+
+        /// Claim `hash` for the caller, optionally until block `expires_at`.
+        /// An expired claim can be claimed again by anyone.
+        #[ink(message)]
+        pub fn create_claim(&mut self, hash: Hash, expires_at: Option<BlockNumber>) -> Result<()> {
+            let owner = self.env().caller();
+            let block_number = self.env().block_number();
+            if self.live_claim(&hash).is_some() {
+                return Err(Error::ClaimAlreadyExists);
+            }
+            if matches!(expires_at, Some(expiry) if expiry <= block_number) {
+                return Err(Error::ExpiryInThePast);
+            }
+
+            self.claims.insert(hash, Claim { owner, block_number, expires_at });
+            self.env().emit_event( ClaimCreated {
+                owner,
+                claim: hash,
+                expires_at,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn revoke_claim(&mut self, hash: Hash) -> Result<()> {
+            let owner = self.ensure_owner(&hash)?;
+            self.claims.take(&hash);
+            self.env().emit_event( ClaimRevoked {
+                owner,
+                claim: hash,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn transfer_claim(&mut self, hash: Hash, to: AccountId) -> Result<()> {
+            let from = self.ensure_owner(&hash)?;
+            let block_number = self.env().block_number();
+            if let Some(claim) = self.claims.get_mut(&hash) {
+                claim.owner = to;
+                claim.block_number = block_number;
+            }
+            self.env().emit_event( ClaimTransferred {
+                from,
+                to,
+                claim: hash,
+            });
+            Ok(())
+        }
+
+        fn live_claim(&self, hash: &Hash) -> Option<&Claim> {
+            let now = self.env().block_number();
+            self.claims
+                .get(hash)
+                .filter(|claim| claim.expires_at.map_or(true, |expiry| expiry > now))
+        }
+
+        fn ensure_owner(&self, hash: &Hash) -> Result<AccountId> {
+            let caller = self.env().caller();
+            let claim = self.live_claim(hash).ok_or(Error::NoSuchClaim)?;
+            if claim.owner != caller {
+                return Err(Error::NotClaimOwner);
+            }
+            Ok(caller)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink_lang as ink;
+
+        type Env = ink_env::DefaultEnvironment;
+
+        fn accounts() -> ink_env::test::DefaultAccounts<Env> {
+            ink_env::test::default_accounts::<Env>().expect("off-chain environment is set up")
+        }
+
+        fn set_caller(caller: AccountId) {
+            let callee = ink_env::account_id::<Env>().unwrap_or([0x0; 32].into());
+            let data = ink_env::test::CallData::new(ink_env::call::Selector::new([0x00; 4]));
+            ink_env::test::push_execution_context::<Env>(caller, callee, 1_000_000, 1_000_000, data);
+        }
+
+        fn hash(byte: u8) -> Hash {
+            Hash::from([byte; 32])
+        }
+
+        #[ink::test]
+        fn create_claim_works() {
+            let accounts = accounts();
+            let mut poe = Poe::new();
+            assert_eq!(poe.create_claim(hash(1), None), Ok(()));
+            assert_eq!(
+                poe.get_claim(hash(1)),
+                Some(Claim { owner: accounts.alice, block_number: 0, expires_at: None }),
+            );
+            assert_eq!(ink_env::test::recorded_events().count(), 1);
+        }
+
+        #[ink::test]
+        fn create_claim_failed() {
+            let mut poe = Poe::new();
+            ink_env::test::advance_block::<Env>().unwrap();
+            assert_eq!(poe.create_claim(hash(1), Some(1)), Err(Error::ExpiryInThePast));
+            assert_eq!(poe.create_claim(hash(1), None), Ok(()));
+            assert_eq!(poe.create_claim(hash(1), None), Err(Error::ClaimAlreadyExists));
+        }
+
+        #[ink::test]
+        fn expired_claim_can_be_reclaimed() {
+            let accounts = accounts();
+            let mut poe = Poe::new();
+            assert_eq!(poe.create_claim(hash(1), Some(2)), Ok(()));
+            ink_env::test::advance_block::<Env>().unwrap();
+            ink_env::test::advance_block::<Env>().unwrap();
+            assert_eq!(poe.get_claim(hash(1)), None);
+
+            set_caller(accounts.bob);
+            assert_eq!(poe.create_claim(hash(1), None), Ok(()));
+            assert_eq!(poe.get_claim(hash(1)).map(|claim| claim.owner), Some(accounts.bob));
+        }
+
+        #[ink::test]
+        fn revoke_claim_works() {
+            let mut poe = Poe::new();
+            assert_eq!(poe.create_claim(hash(1), None), Ok(()));
+            assert_eq!(poe.revoke_claim(hash(1)), Ok(()));
+            assert_eq!(poe.get_claim(hash(1)), None);
+        }
+
+        #[ink::test]
+        fn revoke_claim_failed() {
+            let accounts = accounts();
+            let mut poe = Poe::new();
+            assert_eq!(poe.revoke_claim(hash(1)), Err(Error::NoSuchClaim));
+            assert_eq!(poe.create_claim(hash(1), None), Ok(()));
+            set_caller(accounts.bob);
+            assert_eq!(poe.revoke_claim(hash(1)), Err(Error::NotClaimOwner));
+        }
+
+        #[ink::test]
+        fn transfer_claim_works() {
+            let accounts = accounts();
+            let mut poe = Poe::new();
+            assert_eq!(poe.create_claim(hash(1), None), Ok(()));
+            assert_eq!(poe.transfer_claim(hash(1), accounts.bob), Ok(()));
+            assert_eq!(poe.get_claim(hash(1)).map(|claim| claim.owner), Some(accounts.bob));
+            assert_eq!(poe.transfer_claim(hash(1), accounts.charlie), Err(Error::NotClaimOwner));
+            assert_eq!(ink_env::test::recorded_events().count(), 2);
+        }
+    }
+}
@@ -1,4 +1,6 @@
 import React from 'react'
+import { stringToU8a, u8aConcat } from '@polkadot/util'
+import { blake2AsU8a } from '@polkadot/util-crypto'
 
 const IMAGES = {
   accessory: [
@@ -83,16 +85,22 @@ const IMAGES = {
   ]
 }
 
-const dnaToAttributes = dna => {
-  const attribute = (index, type) => IMAGES[type][dna[index] % IMAGES[type].length]
+// Layers bottom to top. Must match `kitties_primitives::render` on chain, see
+// `render-vectors.json` there.
+const LAYERS = ['body', 'fur', 'mouth', 'eyes', 'accessory']
+const RENDER_SEED_DOMAIN = stringToU8a('kitties/render-seed/v1')
+
+export const renderSeed = dna => blake2AsU8a(u8aConcat(RENDER_SEED_DOMAIN, dna), 256)
 
-  return {
-    body: attribute(0, 'body'),
-    eyes: attribute(1, 'eyes'),
-    accessory: attribute(2, 'accessory'),
-    fur: attribute(3, 'fur'),
-    mouth: attribute(4, 'mouth')
-  }
+// The sprite index of each layer: two little-endian seed bytes per layer, modulo its variants.
+export const spriteLayers = seed => LAYERS.reduce((sprites, type, i) => ({
+  ...sprites,
+  [type]: (seed[2 * i] | (seed[2 * i + 1] << 8)) % IMAGES[type].length
+}), {})
+
+const dnaToAttributes = dna => {
+  const sprites = spriteLayers(renderSeed(dna))
+  return LAYERS.reduce((cat, type) => ({ ...cat, [type]: IMAGES[type][sprites[type]] }), {})
 }
 
 const KittyAvatar = props => {
@@ -100,7 +108,7 @@ const KittyAvatar = props => {
   const innerStyle = { height: '150px', position: 'absolute', top: '3%', left: '50%' }
   const { dna } = props
 
-  if (!dna) return null
+  if (!dna || !dna.length) return null
 
   const cat = dnaToAttributes(dna)
   return <div style={outerStyle}>
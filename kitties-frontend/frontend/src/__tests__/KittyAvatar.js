@@ -0,0 +1,16 @@
+import { hexToU8a, u8aToHex } from '@polkadot/util'
+import { cryptoWaitReady } from '@polkadot/util-crypto'
+import { renderSeed, spriteLayers } from '../KittyAvatar'
+import vectors from '../../../../kitties-node/primitives/render-vectors.json'
+
+describe('KittyAvatar Test Suite', () => {
+  beforeAll(() => cryptoWaitReady())
+
+  it('matches the on-chain render vectors', () => {
+    vectors.forEach(({ dna, seed, layers }) => {
+      const derived = renderSeed(hexToU8a(dna))
+      expect(u8aToHex(derived)).toEqual(seed)
+      expect(spriteLayers(derived)).toEqual(layers)
+    })
+  })
+})
@@ -1,10 +1,14 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	AccountId, AuraConfig, Balance, BalancesConfig, FaucetConfig, GenesisConfig, GrandpaConfig,
+	KittiesModuleConfig, PoeModuleConfig, SS58Prefix, Signature, SudoConfig, SystemConfig, DAYS,
+	TOKEN_DECIMALS, TOKEN_SYMBOL, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_core::{sr25519, Pair, Public};
+use sp_core::{
+	hashing::{blake2_128, sha2_256},
+	sr25519, Pair, Public,
+};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{IdentifyAccount, Verify};
 
@@ -21,6 +25,16 @@ pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Pu
 		.public()
 }
 
+/// The address format and token wallets should use, from the runtime's own constants, so they
+/// agree with what `chain_getChainProperties` reports.
+fn properties() -> sc_service::Properties {
+	let mut properties = sc_service::Properties::new();
+	properties.insert("ss58Format".into(), SS58Prefix::get().into());
+	properties.insert("tokenDecimals".into(), TOKEN_DECIMALS.into());
+	properties.insert("tokenSymbol".into(), TOKEN_SYMBOL.into());
+	properties
+}
+
 type AccountPublic = <Signature as Verify>::Signer;
 
 /// Generate an account ID from seed.
@@ -31,6 +45,43 @@ where
 	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
 
+/// A kitty minted at genesis: its owner, dna and listing price, if listed.
+type GenesisKitty = (AccountId, [u8; 16], Option<Balance>);
+
+/// The well-known development accounts.
+fn dev_accounts() -> Vec<AccountId> {
+	["Alice", "Bob", "Charlie", "Dave", "Eve", "Ferdie"]
+		.iter()
+		.map(|seed| get_account_id_from_seed::<sr25519::Public>(seed))
+		.collect()
+}
+
+/// `count` kitties dealt out to `owners` in turn, with dna derived from `scenario` and the
+/// kitty's position, so every node building the spec gets the same kitties.
+fn genesis_kitties(scenario: &str, owners: &[AccountId], count: u32) -> Vec<GenesisKitty> {
+	(0..count)
+		.map(|i| {
+			let owner = owners[i as usize % owners.len()].clone();
+			(owner, blake2_128(&[scenario.as_bytes(), &i.to_le_bytes()].concat()), None)
+		})
+		.collect()
+}
+
+/// A claim made at genesis: its owner and proof.
+type GenesisClaim = (AccountId, Vec<u8>);
+
+/// `count` claims dealt out to `owners` in turn, each a SHA2-256 multihash of a document named
+/// after `scenario` and the claim's position.
+fn genesis_claims(scenario: &str, owners: &[AccountId], count: u32) -> Vec<GenesisClaim> {
+	(0..count)
+		.map(|i| {
+			let owner = owners[i as usize % owners.len()].clone();
+			let digest = sha2_256(format!("{} document {}", scenario, i).as_bytes());
+			(owner, [&[0x12, 0x20][..], &digest].concat())
+		})
+		.collect()
+}
+
 /// Generate an Aura authority key.
 pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
 	(get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
@@ -59,6 +110,8 @@ pub fn development_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
+				vec![],
+				vec![],
 				true,
 			)
 		},
@@ -69,7 +122,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
 		// Protocol ID
 		None,
 		// Properties
-		None,
+		Some(properties()),
 		// Extensions
 		None,
 	))
@@ -106,6 +159,55 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
+				vec![],
+				vec![],
+				true,
+			)
+		},
+		// Bootnodes
+		vec![],
+		// Telemetry
+		None,
+		// Protocol ID
+		None,
+		// Properties
+		Some(properties()),
+		// Extensions
+		None,
+	))
+}
+
+/// A development chain whose market is already busy: 300 kitties spread over the development
+/// accounts, every other one listed at prices from 1 to 50 UNIT, and 30 proof-of-existence
+/// claims.
+pub fn marketplace_demo_config() -> Result<ChainSpec, String> {
+	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+
+	Ok(ChainSpec::from_genesis(
+		// Name
+		"Marketplace Demo",
+		// ID
+		"marketplace_demo",
+		ChainType::Development,
+		move || {
+			let kitties = genesis_kitties("marketplace-demo", &dev_accounts(), 300)
+				.into_iter()
+				.enumerate()
+				.map(|(i, (owner, dna, _))| {
+					let price = (i % 2 == 0).then(|| (i as Balance % 50 + 1) * 1_000_000_000_000);
+					(owner, dna, price)
+				})
+				.collect();
+			testnet_genesis(
+				wasm_binary,
+				// Initial PoA authorities
+				vec![authority_keys_from_seed("Alice")],
+				// Sudo account
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// Pre-funded accounts
+				dev_accounts(),
+				kitties,
+				genesis_claims("marketplace-demo", &dev_accounts(), 30),
 				true,
 			)
 		},
@@ -116,7 +218,45 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 		// Protocol ID
 		None,
 		// Properties
+		Some(properties()),
+		// Extensions
 		None,
+	))
+}
+
+/// A development chain for load testing breeding: 1000 unlisted kitties spread over the
+/// development accounts.
+pub fn breeding_stress_config() -> Result<ChainSpec, String> {
+	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+
+	Ok(ChainSpec::from_genesis(
+		// Name
+		"Breeding Stress",
+		// ID
+		"breeding_stress",
+		ChainType::Development,
+		move || {
+			testnet_genesis(
+				wasm_binary,
+				// Initial PoA authorities
+				vec![authority_keys_from_seed("Alice")],
+				// Sudo account
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				// Pre-funded accounts
+				dev_accounts(),
+				genesis_kitties("breeding-stress", &dev_accounts(), 1_000),
+				vec![],
+				true,
+			)
+		},
+		// Bootnodes
+		vec![],
+		// Telemetry
+		None,
+		// Protocol ID
+		None,
+		// Properties
+		Some(properties()),
 		// Extensions
 		None,
 	))
@@ -128,6 +268,8 @@ fn testnet_genesis(
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
+	kitties: Vec<GenesisKitty>,
+	claims: Vec<GenesisClaim>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -151,5 +293,13 @@ fn testnet_genesis(
 			key: root_key,
 		},
 		transaction_payment: Default::default(),
+		faucet: FaucetConfig {
+			// One UNIT a day for each account, from a pot of a million.
+			drip_amount: 1_000_000_000_000,
+			drip_period: DAYS,
+			pot: 1_000_000_000_000_000_000,
+		},
+		kitties_module: KittiesModuleConfig { kitties },
+		poe_module: PoeModuleConfig { claims },
 	}
 }
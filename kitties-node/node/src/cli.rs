@@ -8,6 +8,11 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// Serve the `dev_*` RPC methods, which submit transactions signed with Alice's key.
+	/// Only meant for local development chains.
+	#[structopt(long)]
+	pub enable_dev_rpc: bool,
 }
 
 #[derive(Debug, StructOpt)]
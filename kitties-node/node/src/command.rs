@@ -36,6 +36,8 @@ impl SubstrateCli for Cli {
 		Ok(match id {
 			"dev" => Box::new(chain_spec::development_config()?),
 			"" | "local" => Box::new(chain_spec::local_testnet_config()?),
+			"marketplace-demo" => Box::new(chain_spec::marketplace_demo_config()?),
+			"breeding-stress" => Box::new(chain_spec::breeding_stress_config()?),
 			path =>
 				Box::new(chain_spec::ChainSpec::from_json_file(std::path::PathBuf::from(path))?),
 		})
@@ -110,8 +112,9 @@ pub fn run() -> sc_cli::Result<()> {
 			},
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
+			let enable_dev_rpc = cli.enable_dev_rpc;
 			runner.run_node_until_exit(|config| async move {
-				service::new_full(config).map_err(sc_cli::Error::Service)
+				service::new_full(config, enable_dev_rpc).map_err(sc_cli::Error::Service)
 			})
 		},
 	}
@@ -5,9 +5,17 @@
 
 #![warn(missing_docs)]
 
+mod dev;
+mod kitties;
+mod kitties_page;
+mod kitties_sales;
+mod system_info;
+
 use std::sync::Arc;
 
-use node_template_runtime::{opaque::Block, AccountId, Balance, Index};
+use node_template_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Hash, Index};
+use sc_client_api::{Backend, BlockchainEvents};
+use sc_rpc::SubscriptionTaskExecutor;
 pub use sc_rpc_api::DenyUnsafe;
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::ProvideRuntimeApi;
@@ -15,36 +23,69 @@ use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
 
 /// Full client dependencies.
-pub struct FullDeps<C, P> {
+pub struct FullDeps<C, P, B> {
 	/// The client instance to use.
 	pub client: Arc<C>,
+	/// The backend, whose offchain storage holds the kitties event log.
+	pub backend: Arc<B>,
 	/// Transaction pool instance.
 	pub pool: Arc<P>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Whether to serve the `dev_*` methods seeding development chains.
+	pub enable_dev_rpc: bool,
+	/// Executor running subscriptions, e.g. `kitties_subscribeSales`.
+	pub subscription_executor: SubscriptionTaskExecutor,
 }
 
 /// Instantiate all full RPC extensions.
-pub fn create_full<C, P>(deps: FullDeps<C, P>) -> jsonrpc_core::IoHandler<sc_rpc::Metadata>
+pub fn create_full<C, P, B>(deps: FullDeps<C, P, B>) -> jsonrpc_core::IoHandler<sc_rpc::Metadata>
 where
 	C: ProvideRuntimeApi<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
+	C: BlockchainEvents<Block>,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: pallet_contracts_rpc::ContractsRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash>,
 	C::Api: BlockBuilder<Block>,
-	P: TransactionPool + 'static,
+	C::Api: system_info_runtime_api::SystemInfoApi<Block>,
+	C::Api: pallet_kitties_runtime_api::KittiesApi<Block, Index, Balance, AccountId, BlockNumber>,
+	P: TransactionPool<Block = Block> + 'static,
+	B: Backend<Block> + Send + Sync + 'static,
 {
+	use dev::{Dev, DevApi};
+	use kitties::{Kitties, KittiesApi};
+	use kitties_page::{KittiesPage, KittiesPageApi};
+	use kitties_sales::{KittiesSales, KittiesSalesApi};
+	use pallet_contracts_rpc::{Contracts, ContractsApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
+	use system_info::{SystemInfo, SystemInfoApi};
 
 	let mut io = jsonrpc_core::IoHandler::default();
-	let FullDeps { client, pool, deny_unsafe } = deps;
+	let FullDeps { client, backend, pool, deny_unsafe, enable_dev_rpc, subscription_executor } = deps;
+
+	if enable_dev_rpc {
+		io.extend_with(DevApi::to_delegate(Dev::new(client.clone(), pool.clone(), deny_unsafe)));
+	}
 
 	io.extend_with(SystemApi::to_delegate(FullSystem::new(client.clone(), pool, deny_unsafe)));
 
 	io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone())));
 
+	io.extend_with(ContractsApi::to_delegate(Contracts::new(client.clone())));
+
+	io.extend_with(SystemInfoApi::to_delegate(SystemInfo::new(client.clone())));
+
+	io.extend_with(KittiesPageApi::to_delegate(KittiesPage::new(client.clone())));
+
+	io.extend_with(KittiesSalesApi::to_delegate(KittiesSales::new(client.clone(), subscription_executor)));
+
+	if let Some(storage) = backend.offchain_storage() {
+		io.extend_with(KittiesApi::to_delegate(Kitties::new(storage)));
+	}
+
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
 	// to call into the runtime.
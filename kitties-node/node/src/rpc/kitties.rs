@@ -0,0 +1,63 @@
+//! `kitties_*` RPC methods, served from the event log the kitties offchain worker keeps in
+//! the node's local storage (see `pallet_kitties::event_log`).
+
+use codec::Decode;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use node_template_runtime::BlockNumber;
+use pallet_kitties::event_log;
+use sp_core::{offchain::OffchainStorage, Bytes};
+
+/// The most events a single `kitties_recentEvents` call returns.
+const MAX_RECENT_EVENTS: u32 = 1_000;
+
+/// Kitties RPC methods.
+#[rpc]
+pub trait KittiesApi {
+	/// The last `limit` kitty events indexed by this node, newest first, as
+	/// `[block_number, SCALE-encoded pallet_kitties::Event]` pairs.
+	#[rpc(name = "kitties_recentEvents")]
+	fn recent_events(&self, limit: u32) -> Result<Vec<(BlockNumber, Bytes)>>;
+}
+
+/// Implements [`KittiesApi`] on top of the node's offchain storage.
+pub struct Kitties<S> {
+	storage: S,
+}
+
+impl<S> Kitties<S> {
+	/// Create a new `Kitties` reading from `storage`.
+	pub fn new(storage: S) -> Self {
+		Self { storage }
+	}
+}
+
+impl<S: OffchainStorage + 'static> KittiesApi for Kitties<S> {
+	fn recent_events(&self, limit: u32) -> Result<Vec<(BlockNumber, Bytes)>> {
+		let get = |key: &[u8]| self.storage.get(sp_offchain::STORAGE_PREFIX, key);
+		let count = match get(event_log::COUNT_KEY) {
+			Some(raw) => u64::decode(&mut &raw[..]).map_err(decode_error)?,
+			None => return Ok(Vec::new()),
+		};
+
+		let limit = u64::from(limit.min(MAX_RECENT_EVENTS));
+		(count.saturating_sub(limit)..count)
+			.rev()
+			// A worker reserves its slots before writing them, so the newest may still be empty.
+			.filter_map(|index| get(&event_log::key(index)))
+			.map(|raw| {
+				let mut input = &raw[..];
+				let block_number = BlockNumber::decode(&mut input).map_err(decode_error)?;
+				Ok((block_number, Bytes(input.to_vec())))
+			})
+			.collect()
+	}
+}
+
+fn decode_error(err: codec::Error) -> Error {
+	Error {
+		code: ErrorCode::InternalError,
+		message: "Corrupted kitties event log".into(),
+		data: Some(format!("{:?}", err).into()),
+	}
+}
@@ -0,0 +1,64 @@
+//! `kitties_page`, paging through every kitty on chain for explorers syncing the full set.
+
+use std::sync::Arc;
+
+use codec::Encode;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use kitties_primitives::kitty_id::KittyId;
+use node_template_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Hash, Index};
+use pallet_kitties_runtime_api::KittiesApi as KittiesRuntimeApi;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::generic::BlockId;
+
+/// Kitty paging RPC methods.
+#[rpc]
+pub trait KittiesPageApi<BlockHash, KittyIndex> {
+	/// Up to `limit` kitties (at least one) from id `start` on, as SCALE-encoded
+	/// `pallet_kitties_runtime_api::KittyInfo`s, and the id the next page starts at, or `null`
+	/// after the last kitty. Ids are strings like `"KITTY-000123"`. Pages are read at block
+	/// `at`, or the best block; pin `at` to get a consistent snapshot across pages.
+	#[rpc(name = "kitties_page")]
+	fn kitties_page(
+		&self,
+		start: KittyIndex,
+		limit: u32,
+		at: Option<BlockHash>,
+	) -> Result<(Vec<Bytes>, Option<KittyIndex>)>;
+}
+
+/// Implements [`KittiesPageApi`] by calling into the runtime.
+pub struct KittiesPage<C> {
+	client: Arc<C>,
+}
+
+impl<C> KittiesPage<C> {
+	/// Create a new `KittiesPage` querying `client`.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client }
+	}
+}
+
+impl<C> KittiesPageApi<Hash, KittyId> for KittiesPage<C>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: KittiesRuntimeApi<Block, Index, Balance, AccountId, BlockNumber>,
+{
+	fn kitties_page(
+		&self,
+		start: KittyId,
+		limit: u32,
+		at: Option<Hash>,
+	) -> Result<(Vec<Bytes>, Option<KittyId>)> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let (kitties, next) =
+			self.client.runtime_api().kitties_page(&at, start.into(), limit).map_err(|err| Error {
+				code: ErrorCode::InternalError,
+				message: "Unable to query the kitties page".into(),
+				data: Some(format!("{:?}", err).into()),
+			})?;
+		Ok((kitties.into_iter().map(|kitty| Bytes(kitty.encode())).collect(), next.map(KittyId)))
+	}
+}
@@ -0,0 +1,127 @@
+//! `kitties_subscribeSales`, pushing kitty sales to market bots as blocks are imported instead
+//! of having them poll for new blocks.
+
+use std::sync::Arc;
+
+use codec::Decode;
+use futures::{FutureExt, SinkExt, StreamExt};
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use kitties_primitives::kitty_id::KittyId;
+use node_template_runtime::{opaque::Block, AccountId, Balance, Event, Hash};
+use sc_client_api::BlockchainEvents;
+use sc_rpc::SubscriptionTaskExecutor;
+use serde::{Deserialize, Serialize};
+use sp_core::{hashing::twox_128, storage::StorageKey};
+
+/// A kitty sale, from a `pallet_kitties::Event::Bought`, or a `LazyMinted` for a kitty its
+/// creator sold before minting it.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sale {
+	/// The block the sale was made in.
+	pub block: Hash,
+	/// The account that bought the kitty.
+	pub buyer: AccountId,
+	/// The account that sold it, the creator of a lazily minted kitty.
+	pub seller: AccountId,
+	/// The kitty sold.
+	pub kitty_id: KittyId,
+	/// The price paid, fees and donation included.
+	pub price: Balance,
+	/// The part of the price donated, if any.
+	pub donation: Option<Balance>,
+}
+
+/// Kitty sale subscription RPC methods.
+#[rpc]
+pub trait KittiesSalesApi {
+	/// RPC metadata.
+	type Metadata;
+
+	/// Be sent every kitty sale as the block making it is imported. Sales in blocks that are
+	/// later retracted are not taken back, so wait for finality before relying on one.
+	#[pubsub(subscription = "kitties_sales", subscribe, name = "kitties_subscribeSales")]
+	fn subscribe_sales(&self, metadata: Self::Metadata, subscriber: Subscriber<Sale>);
+
+	/// Stop being sent sales.
+	#[pubsub(subscription = "kitties_sales", unsubscribe, name = "kitties_unsubscribeSales")]
+	fn unsubscribe_sales(&self, metadata: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool>;
+}
+
+/// Implements [`KittiesSalesApi`] on top of the client's storage change notifications.
+pub struct KittiesSales<C> {
+	client: Arc<C>,
+	manager: SubscriptionManager,
+}
+
+impl<C> KittiesSales<C> {
+	/// Create a new `KittiesSales` watching `client`, running subscriptions on `executor`.
+	pub fn new(client: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+		Self { client, manager: SubscriptionManager::new(Arc::new(executor)) }
+	}
+}
+
+impl<C> KittiesSalesApi for KittiesSales<C>
+where
+	C: BlockchainEvents<Block> + Send + Sync + 'static,
+{
+	type Metadata = sc_rpc::Metadata;
+
+	fn subscribe_sales(&self, _metadata: Self::Metadata, subscriber: Subscriber<Sale>) {
+		let events_key = StorageKey([twox_128(b"System"), twox_128(b"Events")].concat());
+		let changes = match self
+			.client
+			.storage_changes_notification_stream(Some(&[events_key]), None)
+		{
+			Ok(changes) => changes,
+			Err(err) => {
+				let _ = subscriber.reject(jsonrpc_core::Error {
+					code: jsonrpc_core::ErrorCode::InternalError,
+					message: "Unable to watch block events".into(),
+					data: Some(format!("{:?}", err).into()),
+				});
+				return
+			},
+		};
+
+		let sales = changes
+			.map(|(block, changes)| {
+				let sales = changes
+					.iter()
+					.filter_map(|(_, _, data)| data)
+					.flat_map(|data| decode_sales(block, &data.0))
+					.map(|sale| Ok(Ok::<_, jsonrpc_core::Error>(sale)))
+					.collect::<Vec<_>>();
+				futures::stream::iter(sales)
+			})
+			.flatten();
+
+		self.manager.add(subscriber, |sink| {
+			sales.forward(sink.sink_map_err(|_| ())).map(|_| ())
+		});
+	}
+
+	fn unsubscribe_sales(&self, _metadata: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool> {
+		Ok(self.manager.cancel(id))
+	}
+}
+
+/// The sales among a block's encoded `System::Events`.
+fn decode_sales(block: Hash, events: &[u8]) -> Vec<Sale> {
+	let records = match Vec::<frame_system::EventRecord<Event, Hash>>::decode(&mut &events[..]) {
+		Ok(records) => records,
+		Err(_) => return Vec::new(),
+	};
+	records
+		.into_iter()
+		.filter_map(|record| match record.event {
+			Event::KittiesModule(pallet_kitties::Event::Bought(buyer, seller, kitty_id, price, donation)) =>
+				Some(Sale { block, buyer, seller, kitty_id: KittyId(kitty_id), price, donation }),
+			Event::KittiesModule(pallet_kitties::Event::LazyMinted(buyer, seller, kitty_id, price)) =>
+				Some(Sale { block, buyer, seller, kitty_id: KittyId(kitty_id), price, donation: None }),
+			_ => None,
+		})
+		.collect()
+}
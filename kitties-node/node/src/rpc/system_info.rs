@@ -0,0 +1,77 @@
+//! `chain_getChainProperties`, describing the chain to wallets from the runtime itself, so they
+//! configure themselves correctly against either node whatever chain spec it was started with.
+
+use std::sync::Arc;
+
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use node_template_runtime::{opaque::Block, Hash};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::generic::BlockId;
+use system_info_runtime_api::SystemInfoApi as SystemInfoRuntimeApi;
+
+/// The runtime's versions, address format and native token.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainProperties {
+	/// The runtime's `spec_name`.
+	pub spec_name: String,
+	/// The runtime's `spec_version`.
+	pub spec_version: u32,
+	/// The runtime's `impl_version`.
+	pub impl_version: u32,
+	/// The runtime's `transaction_version`.
+	pub transaction_version: u32,
+	/// The SS58 prefix addresses are encoded with, named as in `system_properties`.
+	pub ss58_format: u16,
+	/// The number of decimals balances are displayed with.
+	pub token_decimals: u8,
+	/// The ticker of the native token.
+	pub token_symbol: String,
+}
+
+/// System information RPC methods.
+#[rpc]
+pub trait SystemInfoApi<BlockHash> {
+	/// The chain's properties as the runtime at block `at`, or the best block, reports them.
+	#[rpc(name = "chain_getChainProperties")]
+	fn chain_properties(&self, at: Option<BlockHash>) -> Result<ChainProperties>;
+}
+
+/// Implements [`SystemInfoApi`] by calling into the runtime.
+pub struct SystemInfo<C> {
+	client: Arc<C>,
+}
+
+impl<C> SystemInfo<C> {
+	/// Create a new `SystemInfo` querying `client`.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client }
+	}
+}
+
+impl<C> SystemInfoApi<Hash> for SystemInfo<C>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: SystemInfoRuntimeApi<Block>,
+{
+	fn chain_properties(&self, at: Option<Hash>) -> Result<ChainProperties> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let info = self.client.runtime_api().system_info(&at).map_err(|err| Error {
+			code: ErrorCode::InternalError,
+			message: "Unable to query the system info".into(),
+			data: Some(format!("{:?}", err).into()),
+		})?;
+		Ok(ChainProperties {
+			spec_name: String::from_utf8_lossy(&info.spec_name).into_owned(),
+			spec_version: info.spec_version,
+			impl_version: info.impl_version,
+			transaction_version: info.transaction_version,
+			ss58_format: info.ss58_prefix,
+			token_decimals: info.token_decimals,
+			token_symbol: String::from_utf8_lossy(&info.token_symbol).into_owned(),
+		})
+	}
+}
@@ -0,0 +1,199 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! A testnet faucet. Any account may be paid `DripAmount` from the faucet's pot once per
+//! `DripPeriod`, enough to cover a kitty deposit without asking anyone for funds. The pot is
+//! filled at genesis and may be topped up by anyone transferring to [`Pallet::account_id`];
+//! `ForceOrigin` sets the drip size and period.
+//!
+//! `request_funds` is an unsigned transaction, so accounts with nothing to pay fees with can
+//! use it. Instead of a fee, each request carries a nonce solving a small proof of work, see
+//! [`Pallet::drip_work`].
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::{
+        pallet_prelude::*,
+        traits::{Currency, ExistenceRequirement},
+        PalletId,
+    };
+    use frame_system::pallet_prelude::*;
+    use sp_io::hashing::blake2_128;
+    use sp_runtime::traits::{AccountIdConversion, Saturating, Zero};
+
+    type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        type Currency: Currency<Self::AccountId>;
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+        /// May change the drip size and period.
+        type ForceOrigin: EnsureOrigin<Self::Origin>;
+        /// How many leading zero bits [`Pallet::drip_work`] must have for a request to be
+        /// accepted.
+        #[pallet::constant]
+        type PowDifficulty: Get<u32>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    /// How much each request pays out.
+    #[pallet::storage]
+    #[pallet::getter(fn drip_amount)]
+    pub type DripAmount<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+    /// How many blocks an account waits between requests.
+    #[pallet::storage]
+    #[pallet::getter(fn drip_period)]
+    pub type DripPeriod<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+
+    /// The block each account last requested funds in.
+    #[pallet::storage]
+    #[pallet::getter(fn last_drip)]
+    pub type LastDrip<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, T::BlockNumber>;
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        pub drip_amount: BalanceOf<T>,
+        pub drip_period: T::BlockNumber,
+        /// Minted into the pot.
+        pub pot: BalanceOf<T>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            Self {
+                drip_amount: Zero::zero(),
+                drip_period: Zero::zero(),
+                pot: Zero::zero(),
+            }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        fn build(&self) {
+            DripAmount::<T>::put(self.drip_amount);
+            DripPeriod::<T>::put(self.drip_period);
+            let _ = T::Currency::deposit_creating(&Pallet::<T>::account_id(), self.pot);
+        }
+    }
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// [who, amount]
+        FundsDripped(T::AccountId, BalanceOf<T>),
+        /// [drip_amount, drip_period]
+        DripChanged(BalanceOf<T>, T::BlockNumber),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        TooSoon,
+        FaucetEmpty,
+        InsufficientWork,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Pay `DripAmount` from the pot to `who`, once per `DripPeriod` for each account.
+        /// `nonce` must make [`Pallet::drip_work`] meet `PowDifficulty`.
+        #[pallet::weight(10_000)]
+        pub fn request_funds(origin: OriginFor<T>, who: T::AccountId, nonce: u64) -> DispatchResult {
+            ensure_none(origin)?;
+            Self::ensure_can_drip(&who, nonce)?;
+
+            let amount = Self::drip_amount();
+            T::Currency::transfer(&Self::account_id(), &who, amount, ExistenceRequirement::KeepAlive)
+                .map_err(|_| Error::<T>::FaucetEmpty)?;
+            LastDrip::<T>::insert(&who, frame_system::Pallet::<T>::block_number());
+
+            Self::deposit_event(Event::FundsDripped(who, amount));
+            Ok(())
+        }
+
+        /// Set how much each request pays out and how often an account may request.
+        #[pallet::weight(1_000)]
+        pub fn set_drip(
+            origin: OriginFor<T>,
+            drip_amount: BalanceOf<T>,
+            drip_period: T::BlockNumber,
+        ) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            DripAmount::<T>::put(drip_amount);
+            DripPeriod::<T>::put(drip_period);
+
+            Self::deposit_event(Event::DripChanged(drip_amount, drip_period));
+            Ok(())
+        }
+    }
+
+    #[pallet::validate_unsigned]
+    impl<T: Config> ValidateUnsigned for Pallet<T> {
+        type Call = Call<T>;
+
+        /// Only let requests that would be paid into the pool, one per account.
+        fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+            let (who, nonce) = match call {
+                Call::request_funds { who, nonce } => (who, nonce),
+                _ => return InvalidTransaction::Call.into(),
+            };
+            match Self::ensure_can_drip(who, *nonce) {
+                Err(Error::<T>::TooSoon) => return InvalidTransaction::Future.into(),
+                Err(_) => return InvalidTransaction::BadProof.into(),
+                Ok(()) => {},
+            }
+            let pot = T::Currency::free_balance(&Self::account_id());
+            if pot < Self::drip_amount().saturating_add(T::Currency::minimum_balance()) {
+                return InvalidTransaction::Payment.into()
+            }
+
+            ValidTransaction::with_tag_prefix("Faucet")
+                .and_provides(who)
+                .longevity(64)
+                .propagate(true)
+                .build()
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// The account holding the faucet's pot.
+        pub fn account_id() -> T::AccountId {
+            T::PalletId::get().into_account()
+        }
+
+        /// The hash a request for `who` with `nonce` has to give enough leading zero bits. It
+        /// covers the block `who` was last paid in, so each drip needs fresh work.
+        pub fn drip_work(who: &T::AccountId, nonce: u64) -> [u8; 16] {
+            (who, Self::last_drip(who), nonce).using_encoded(blake2_128)
+        }
+
+        fn ensure_can_drip(who: &T::AccountId, nonce: u64) -> Result<(), Error<T>> {
+            let now = frame_system::Pallet::<T>::block_number();
+            if let Some(last) = Self::last_drip(who) {
+                ensure!(now >= last.saturating_add(Self::drip_period()), Error::<T>::TooSoon);
+            }
+            ensure!(
+                u128::from_be_bytes(Self::drip_work(who, nonce)).leading_zeros() >= T::PowDifficulty::get(),
+                Error::<T>::InsufficientWork
+            );
+            Ok(())
+        }
+    }
+}
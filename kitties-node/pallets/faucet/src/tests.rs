@@ -0,0 +1,70 @@
+use crate::{Call, Error, Event, mock::*};
+use frame_support::{assert_ok, assert_noop};
+use sp_runtime::{
+    traits::ValidateUnsigned,
+    transaction_validity::{InvalidTransaction, TransactionSource},
+};
+
+#[test]
+fn request_funds_works() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(Balances::free_balance(Faucet::account_id()), 5_000);
+        assert_eq!(Balances::total_issuance(), 100_005_000);
+
+        // new accounts are paid into existence
+        let call = Call::request_funds { who: 2, nonce: mine(2) };
+        assert!(Faucet::validate_unsigned(TransactionSource::External, &call).is_ok());
+        assert_ok!(Faucet::request_funds(Origin::none(), 2, mine(2)));
+        assert_eq!(Balances::free_balance(2), 2_000);
+        assert_eq!(Faucet::last_drip(2), Some(1));
+        System::assert_last_event(mock::Event::Faucet(Event::FundsDripped(2, 2_000)));
+
+        System::set_block_number(11);
+        assert_ok!(Faucet::request_funds(Origin::none(), 2, mine(2)));
+        assert_eq!(Balances::free_balance(2), 4_000);
+        assert_eq!(Balances::free_balance(Faucet::account_id()), 1_000);
+
+        assert_ok!(Faucet::set_drip(Origin::root(), 100, 5));
+        System::assert_last_event(mock::Event::Faucet(Event::DripChanged(100, 5)));
+        System::set_block_number(16);
+        assert_ok!(Faucet::request_funds(Origin::none(), 2, mine(2)));
+        assert_eq!(Balances::free_balance(2), 4_100);
+    });
+}
+
+#[test]
+fn request_funds_failed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Faucet::request_funds(Origin::signed(2), 2, mine(2)),
+            sp_runtime::DispatchError::BadOrigin
+        );
+
+        // every request needs fresh work
+        let nonce = mine(2);
+        let lazy = (0..).find(|n| u128::from_be_bytes(Faucet::drip_work(&2, *n)).leading_zeros() < 4).unwrap();
+        assert_noop!(Faucet::request_funds(Origin::none(), 2, lazy), Error::<Test>::InsufficientWork);
+        assert_eq!(
+            Faucet::validate_unsigned(TransactionSource::External, &Call::request_funds { who: 2, nonce: lazy }),
+            InvalidTransaction::BadProof.into()
+        );
+        assert_ok!(Faucet::request_funds(Origin::none(), 2, nonce));
+
+        System::set_block_number(10);
+        assert_noop!(Faucet::request_funds(Origin::none(), 2, mine(2)), Error::<Test>::TooSoon);
+        assert_eq!(
+            Faucet::validate_unsigned(TransactionSource::External, &Call::request_funds { who: 2, nonce: mine(2) }),
+            InvalidTransaction::Future.into()
+        );
+
+        // the pot is kept alive, and requests it can't pay are kept out of the pool
+        assert_ok!(Faucet::request_funds(Origin::none(), 3, mine(3)));
+        assert_eq!(
+            Faucet::validate_unsigned(TransactionSource::External, &Call::request_funds { who: 4, nonce: mine(4) }),
+            InvalidTransaction::Payment.into()
+        );
+        assert_noop!(Faucet::request_funds(Origin::none(), 4, mine(4)), Error::<Test>::FaucetEmpty);
+
+        assert_noop!(Faucet::set_drip(Origin::signed(1), 100, 5), sp_runtime::DispatchError::BadOrigin);
+    });
+}
@@ -0,0 +1,39 @@
+//! Runtime API definition for the kitties pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+pub use kitties_primitives::{
+	render, Dna, KittiesConfig, KittyInfo, KittyStats, Leaderboards, RarityTier,
+};
+
+sp_api::decl_runtime_apis! {
+	pub trait KittiesApi<KittyIndex, Balance, AccountId, BlockNumber> where
+		KittyIndex: Codec,
+		Balance: Codec,
+		AccountId: Codec,
+		BlockNumber: Codec,
+	{
+		/// The cheapest kitty listed for sale in the given rarity tier, with its price. `None` for
+		/// a block or so after the cheapest listing goes, while the next one is looked for.
+		fn cheapest_listing(tier: RarityTier) -> Option<(KittyIndex, Balance)>;
+		/// Aggregate transfer, sale and breeding counters.
+		fn stats() -> KittyStats<Balance>;
+		/// The most recent owners of a kitty, oldest first, with the block they got it in.
+		fn owner_history(kitty_id: KittyIndex) -> Vec<(AccountId, BlockNumber)>;
+		/// The pallet's limits and settings as they currently apply.
+		fn config() -> KittiesConfig<AccountId, Balance, BlockNumber>;
+		/// The top owners, sales and breeders.
+		fn leaderboards() -> Leaderboards<AccountId, KittyIndex, Balance>;
+		/// Up to `limit` kitties from id `start` on, and the id to continue from, if any. A `limit`
+		/// of 0 is read as 1.
+		fn kitties_page(
+			start: KittyIndex,
+			limit: u32,
+		) -> (Vec<KittyInfo<AccountId, KittyIndex, Balance>>, Option<KittyIndex>);
+		/// The seed front-ends derive a kitty's sprites from, as specified in
+		/// `kitties_primitives::render`.
+		fn render_seed(dna: Dna) -> [u8; 32];
+	}
+}
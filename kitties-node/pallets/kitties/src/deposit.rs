@@ -0,0 +1,71 @@
+//! Curves deciding how the kitty deposit grows with the number of kitties in existence.
+
+use frame_support::traits::Get;
+use sp_runtime::{
+    traits::{AtLeast32BitUnsigned, Saturating},
+    Permill,
+};
+use core::marker::PhantomData;
+
+pub trait DepositCurve<Balance> {
+    /// The deposit for the next kitty, given the base deposit and the current supply.
+    fn deposit(base: Balance, supply: u32) -> Balance;
+}
+
+/// Flat deposit: every kitty costs `base`.
+impl<Balance> DepositCurve<Balance> for () {
+    fn deposit(base: Balance, _supply: u32) -> Balance {
+        base
+    }
+}
+
+/// `base + base * Slope * supply`.
+pub struct Linear<Slope>(PhantomData<Slope>);
+
+impl<Balance: AtLeast32BitUnsigned + Copy, Slope: Get<Permill>> DepositCurve<Balance> for Linear<Slope> {
+    fn deposit(base: Balance, supply: u32) -> Balance {
+        base.saturating_add((Slope::get() * base).saturating_mul(supply.into()))
+    }
+}
+
+/// `base * (1 + supply / Size)`: the deposit goes up by `base` every `Size` kitties.
+pub struct Step<Size>(PhantomData<Size>);
+
+impl<Balance: AtLeast32BitUnsigned + Copy, Size: Get<u32>> DepositCurve<Balance> for Step<Size> {
+    fn deposit(base: Balance, supply: u32) -> Balance {
+        let steps = supply / Size::get().max(1);
+        base.saturating_mul(steps.saturating_add(1).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use frame_support::{parameter_types, traits::ConstU32};
+
+    parameter_types! {
+        pub const OnePercent: Permill = Permill::from_percent(1);
+    }
+
+    #[test]
+    fn flat_curve_works() {
+        assert_eq!(<() as DepositCurve<u128>>::deposit(1_000, 0), 1_000);
+        assert_eq!(<() as DepositCurve<u128>>::deposit(1_000, 1_000_000), 1_000);
+    }
+
+    #[test]
+    fn linear_curve_works() {
+        assert_eq!(Linear::<OnePercent>::deposit(1_000u128, 0), 1_000);
+        assert_eq!(Linear::<OnePercent>::deposit(1_000u128, 1), 1_010);
+        assert_eq!(Linear::<OnePercent>::deposit(1_000u128, 100), 2_000);
+        assert_eq!(Linear::<OnePercent>::deposit(u128::max_value(), u32::max_value()), u128::max_value());
+    }
+
+    #[test]
+    fn step_curve_works() {
+        assert_eq!(Step::<ConstU32<3>>::deposit(1_000u128, 2), 1_000);
+        assert_eq!(Step::<ConstU32<3>>::deposit(1_000u128, 3), 2_000);
+        assert_eq!(Step::<ConstU32<3>>::deposit(1_000u128, 9), 4_000);
+        assert_eq!(Step::<ConstU32<0>>::deposit(1_000u128, 2), 3_000);
+    }
+}
@@ -0,0 +1,27 @@
+//! Discounts other pallets, e.g. an achievements pallet, grant on the deposit an account
+//! reserves to mint or breed a kitty.
+
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use sp_runtime::{Permill, RuntimeDebug};
+
+/// What a discount is asked for.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum DiscountedAction {
+    /// Minting a kitty, by creating, mining or redeeming one.
+    Mint,
+    /// Breeding a kitty from two parents.
+    Breed,
+}
+
+pub trait DiscountProvider<AccountId> {
+    /// The share of the deposit `who` is let off for `action`.
+    fn discount(who: &AccountId, action: DiscountedAction) -> Permill;
+}
+
+/// No discounts: everyone reserves the full deposit.
+impl<AccountId> DiscountProvider<AccountId> for () {
+    fn discount(_who: &AccountId, _action: DiscountedAction) -> Permill {
+        Permill::zero()
+    }
+}
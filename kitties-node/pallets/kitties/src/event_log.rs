@@ -0,0 +1,21 @@
+//! Layout of the kitty event log the offchain worker keeps in persistent local storage.
+//!
+//! `COUNT_KEY` holds the number of events indexed so far as a `u64`; event `n` (0-based) is
+//! stored under `key(n)` as `(BlockNumber, Event)`. Events are only indexed for blocks the node
+//! runs offchain workers on, so an indexing node should run with `--offchain-worker always`,
+//! and events from blocks that are later retracted stay in the log.
+
+use sp_std::vec::Vec;
+
+/// Number of events in the log.
+pub const COUNT_KEY: &[u8] = b"kitties::events::count";
+
+/// Prefix of the event entries, followed by the big-endian event number.
+pub const EVENT_PREFIX: &[u8] = b"kitties::events::";
+
+/// Local storage key of event number `index`.
+pub fn key(index: u64) -> Vec<u8> {
+    let mut key = EVENT_PREFIX.to_vec();
+    key.extend_from_slice(&index.to_be_bytes());
+    key
+}
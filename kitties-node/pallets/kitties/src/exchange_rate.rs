@@ -0,0 +1,15 @@
+//! The exchange rate between the native currency and the reference unit kitties may be listed
+//! in, e.g. USD cents, as an oracle feed reports it.
+
+pub trait ExchangeRate<Balance> {
+    /// `amount` reference units in the native currency at the current rate, e.g. the median of
+    /// the oracle feed, or `None` if there is no rate.
+    fn to_native(amount: u128) -> Option<Balance>;
+}
+
+/// No rate: kitties can only be listed in the native currency.
+impl<Balance> ExchangeRate<Balance> for () {
+    fn to_native(_amount: u128) -> Option<Balance> {
+        None
+    }
+}
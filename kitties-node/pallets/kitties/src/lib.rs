@@ -2,6 +2,11 @@
 
 pub use pallet::*;
 
+pub mod deposit;
+pub mod discount;
+pub mod event_log;
+pub mod exchange_rate;
+
 #[cfg(test)]
 mod mock;
 #[cfg(test)]
@@ -12,23 +17,175 @@ pub mod pallet {
     use frame_support::{
         dispatch::{fmt::Debug, DispatchResult},
         pallet_prelude::*,
-        traits::{Randomness, ReservableCurrency, Currency, ExistenceRequirement},
+        traits::{
+            schedule::{DispatchTime, ScheduleNamed, LOWEST_PRIORITY},
+            tokens::nonfungible,
+            Randomness, ReservableCurrency, Currency, ExistenceRequirement, BalanceStatus,
+        },
         transactional,
+        PalletId,
     };    
     use frame_system::pallet_prelude::*;
     use codec::{Encode, Decode};
     use sp_io::hashing::blake2_128;
+    use sp_std::vec::Vec;
     use scale_info::TypeInfo;
-    use sp_runtime::traits::{MaybeDisplay, AtLeast32Bit, Bounded};
+    use sp_runtime::offchain::storage::StorageValueRef;
+    use sp_runtime::traits::{
+        MaybeDisplay, AtLeast32Bit, Bounded, Hash, IdentifyAccount, Verify, AccountIdConversion,
+        One, Saturating, Zero,
+    };
+    use sp_runtime::{PerThing, Permill, SaturatedConversion};
+    use crate::deposit::DepositCurve;
+    use crate::discount::{DiscountProvider, DiscountedAction};
+    use crate::exchange_rate::ExchangeRate;
+    use kitties_primitives::{
+        dna, genetics, ChainId, KittiesConfig, KittyCertificate, KittyInfo, KittyStats, Leaderboards,
+        RarityTier, CERTIFICATE_VERSION,
+    };
+
+    /// The most kitty ids a single `kitties_page` call looks at.
+    pub const MAX_PAGE_SIZE: u32 = 1_000;
+
+    /// Bits of a session's `allowed_calls_mask`, one for each kind of `SessionCall`. Bit 0
+    /// allowed transfers, which sessions no longer make.
+    pub const SESSION_BREED: u32 = 1 << 1;
+    pub const SESSION_REROLL: u32 = 1 << 2;
 
     #[derive(Encode, Decode, TypeInfo)]
     pub struct Kitty(pub [u8; 16]);
 
+    pub type PregnancyId = u64;
+
+    /// A kitty bred but not yet born.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub struct Pregnancy<AccountId, Balance, BlockNumber> {
+        /// The breeder, who gets the kitty.
+        pub owner: AccountId,
+        /// The parents' dna when they were bred.
+        pub parents: ([u8; 16], [u8; 16]),
+        pub generation: u32,
+        /// The kitty deposit, reserved from the owner when breeding.
+        pub deposit: Balance,
+        /// The kitty can be born from this block on.
+        pub due: BlockNumber,
+    }
+
+    /// Settings governance can change without a runtime upgrade. Unset ones fall back to the
+    /// matching `Config` constant.
+    #[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub struct KittyParameters<Balance, BlockNumber> {
+        pub deposit_base: Option<Balance>,
+        pub referral_reward: Option<Balance>,
+        pub max_listing_duration: Option<BlockNumber>,
+        pub pow_difficulty: Option<u32>,
+        pub sale_fee: Option<Permill>,
+    }
+
+    /// A change to one of the `KittyParameters`; `None` resets it to the `Config` constant.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub enum KittyParameter<Balance, BlockNumber> {
+        DepositBase(Option<Balance>),
+        ReferralReward(Option<Balance>),
+        MaxListingDuration(Option<BlockNumber>),
+        PowDifficulty(Option<u32>),
+        SaleFee(Option<Permill>),
+    }
+
     type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+    type IssuerOf<T> = <<<T as Config>::CertificateSignature as Verify>::Signer as IdentifyAccount>::AccountId;
+
+    /// Something a kitty's owner wants to do that its co-owners, or their guardian, must
+    /// approve.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub enum CoOwnerAction<AccountId, Balance> {
+        /// Transfer the kitty to the account.
+        Transfer(AccountId),
+        /// List the kitty for the price.
+        Sell(Balance),
+    }
+
+    /// Which way `reprice_all` moves prices.
+    #[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub enum PriceDirection {
+        Up,
+        Down,
+    }
+
+    /// A creator's offer, signed off-chain, to mint a kitty with `dna` to whoever pays `price`
+    /// for it first; see `lazy_buy`.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub struct MintAuthorization<AccountId, Balance, BlockNumber> {
+        pub creator: AccountId,
+        pub dna: [u8; 16],
+        pub price: Balance,
+        /// The creator's cut of every later sale of the kitty.
+        pub royalty: Permill,
+        /// Set by the creator; each nonce can be used once, and cancelled with
+        /// `cancel_mint_nonce`.
+        pub nonce: u64,
+        /// The offer can't be taken up after this block.
+        pub expires_at: BlockNumber,
+    }
+
+    impl<AccountId: Encode, Balance: Encode, BlockNumber: Encode> MintAuthorization<AccountId, Balance, BlockNumber> {
+        /// The bytes the creator signs, tied to the chain so they can't be replayed elsewhere.
+        pub fn signing_payload(&self, chain_id: &ChainId) -> Vec<u8> {
+            (b"kitties/lazy-mint", chain_id, self).encode()
+        }
+    }
+
+    /// The run of sales of a kitty between the same two accounts, whichever way round.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub struct TradeStreak<AccountId, BlockNumber> {
+        /// The two accounts, lowest first.
+        pub pair: (AccountId, AccountId),
+        pub trades: u32,
+        /// The block the first sale of the streak was made in.
+        pub since: BlockNumber,
+    }
+
+    /// A kitty being bought in installments, held in escrow by the pallet until it is paid
+    /// off or the buyer defaults.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub struct InstallmentPlan<AccountId, Balance, BlockNumber> {
+        pub buyer: AccountId,
+        pub seller: AccountId,
+        pub price: Balance,
+        /// Paid so far, reserved from the buyer.
+        pub paid: Balance,
+        pub installment: Balance,
+        /// The next installment must be paid before this block.
+        pub due: BlockNumber,
+    }
+
+    /// A kitty call an operator may make for a user who authorized a session with them. There
+    /// is deliberately no way to transfer, sell, buy or export: kitties only leave the user's
+    /// account when the user signs for it. Variants keep their indexes from when `Transfer`
+    /// was the first.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub enum SessionCall<KittyIndex> {
+        #[codec(index = 1)]
+        Breed { kitty_id1: KittyIndex, kitty_id2: KittyIndex },
+        #[codec(index = 2)]
+        RerollTrait { kitty_id: KittyIndex, gene_index: u8 },
+    }
+
+    impl<KittyIndex> SessionCall<KittyIndex> {
+        /// The bit of `allowed_calls_mask` allowing the call.
+        pub fn mask(&self) -> u32 {
+            match self {
+                SessionCall::Breed { .. } => SESSION_BREED,
+                SessionCall::RerollTrait { .. } => SESSION_REROLL,
+            }
+        }
+    }
 
     #[pallet::config]
     pub trait Config: frame_system::Config {
-        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        type Event: From<Event<Self>>
+            + IsType<<Self as frame_system::Config>::Event>
+            + TryInto<Event<Self>>;
         type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
         type KittyIndex: Parameter
             + Member 
@@ -40,8 +197,164 @@ pub mod pallet {
             + Copy
             + Encode;
         type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
+        /// The deposit for a kitty when there are none yet; see `DepositCurve`.
         #[pallet::constant]
         type KittyDepositBase: Get<BalanceOf<Self>>;
+        /// How the deposit grows with the number of kitties in existence.
+        type DepositCurve: DepositCurve<BalanceOf<Self>>;
+        /// Discounts on the deposit for minting and breeding, e.g. for achievements.
+        type DiscountProvider: DiscountProvider<Self::AccountId>;
+        /// Converts the reference unit kitties may be listed in to the native currency.
+        type ExchangeRate: ExchangeRate<BalanceOf<Self>>;
+        /// Signature scheme creators sign `MintAuthorization`s with.
+        type CreatorSignature: Verify<Signer = Self::CreatorPublic> + Parameter;
+        /// The public key behind a `CreatorSignature`, identifying the creator's account.
+        type CreatorPublic: IdentifyAccount<AccountId = Self::AccountId>;
+        /// Signature scheme used by the issuer of kitty certificates.
+        type CertificateSignature: Verify + Parameter;
+        /// The only account whose signature `import_kitty` accepts.
+        type CertificateIssuer: Get<IssuerOf<Self>>;
+        /// Identifier of this chain, written into exported certificates.
+        #[pallet::constant]
+        type ChainId: Get<ChainId>;
+        /// The pallet's id, used to derive the account holding the referral pot.
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+        /// Paid from the pot to the referrer when a referred account mints its first kitty.
+        #[pallet::constant]
+        type ReferralReward: Get<BalanceOf<Self>>;
+        /// Origin allowed to issue kitty vouchers.
+        type VoucherIssuer: EnsureOrigin<Self::Origin>;
+        /// Origin allowed to schedule breeding windows.
+        type ForceOrigin: EnsureOrigin<Self::Origin>;
+        /// The most breeding windows that can be scheduled at once.
+        #[pallet::constant]
+        type MaxBreedingWindows: Get<u32>;
+        /// How long a listing lasts at most, and by default.
+        #[pallet::constant]
+        type MaxListingDuration: Get<Self::BlockNumber>;
+        /// Leading zero bits the dna minted by `create_with_nonce` must have.
+        #[pallet::constant]
+        type PowDifficulty: Get<u32>;
+        /// How many blocks a `create_with_nonce` nonce stays valid for, see `pow_seed`.
+        #[pallet::constant]
+        type PowSeedPeriod: Get<Self::BlockNumber>;
+        /// The runtime call type, so listing expiries can be scheduled.
+        type ScheduledCall: Parameter + From<Call<Self>>;
+        /// The scheduler that expires listings.
+        type Scheduler: ScheduleNamed<Self::BlockNumber, Self::ScheduledCall, Self::PalletsOrigin>;
+        /// The caller origin, overarching type of all pallets origins.
+        type PalletsOrigin: From<frame_system::RawOrigin<Self::AccountId>>;
+        /// How many of its most recent owners each kitty remembers.
+        #[pallet::constant]
+        type MaxOwnersTracked: Get<u32>;
+        /// How many entries each leaderboard keeps.
+        #[pallet::constant]
+        type LeaderboardSize: Get<u32>;
+
+        /// How many co-owners a kitty can have besides its owner.
+        #[pallet::constant]
+        type MaxCoOwners: Get<u32>;
+
+        /// The fee for rerolling a gene, paid into the referral pot.
+        #[pallet::constant]
+        type RerollFee: Get<BalanceOf<Self>>;
+
+        /// How many times one kitty's genes can be rerolled in all.
+        #[pallet::constant]
+        type MaxRerolls: Get<u32>;
+
+        /// How many blocks after a reroll is requested its new gene is drawn.
+        #[pallet::constant]
+        type RerollDelay: Get<Self::BlockNumber>;
+
+        /// The cut of every sale price paid into the pot, unless governance sets another.
+        #[pallet::constant]
+        type SaleFee: Get<Permill>;
+
+        /// The lowest price a kitty can be listed for; no listing is ever free.
+        #[pallet::constant]
+        type MinListingPrice: Get<BalanceOf<Self>>;
+
+        /// The highest price a kitty can be listed for, catching prices mistyped by orders of
+        /// magnitude.
+        #[pallet::constant]
+        type MaxListingPrice: Get<BalanceOf<Self>>;
+
+        /// How many blocks a common kitty takes to be born. Each rarity tier of the rarer parent
+        /// above common adds as many again; with zero, kitties are born as they are bred.
+        #[pallet::constant]
+        type BaseGestation: Get<Self::BlockNumber>;
+
+        /// How many blocks a buyer in installments has to pay each installment.
+        #[pallet::constant]
+        type InstallmentPeriod: Get<Self::BlockNumber>;
+
+        /// The most installments a kitty may be paid in, the down payment included.
+        #[pallet::constant]
+        type MaxInstallments: Get<u32>;
+
+        /// The share of what they paid a buyer in installments loses to the seller on default.
+        #[pallet::constant]
+        type DefaultPenalty: Get<Permill>;
+
+        /// The most listings `delist_all` and `reprice_all` go through in one call.
+        #[pallet::constant]
+        type MaxBulkListings: Get<u32>;
+
+        /// The most kitties `claim_inheritance` goes through in one call.
+        #[pallet::constant]
+        type MaxInheritedKitties: Get<u32>;
+
+        /// How many blocks after a kitty is bought it can't be listed again, to curb wash
+        /// trading. Zero lets it be relisted at once.
+        #[pallet::constant]
+        type ResaleCooldown: Get<Self::BlockNumber>;
+
+        /// How many blocks sales of a kitty between the same two accounts are counted over.
+        #[pallet::constant]
+        type WashTradeWindow: Get<Self::BlockNumber>;
+
+        /// How many sales of a kitty between the same two accounts within `WashTradeWindow`
+        /// are allowed before they are reported as `SuspiciousTradePattern`.
+        #[pallet::constant]
+        type WashTradeThreshold: Get<u32>;
+
+        /// Added to the sale fee for every sale past `WashTradeThreshold`. Zero only reports.
+        #[pallet::constant]
+        type WashTradeFee: Get<Permill>;
+
+        /// The most kitties `fuse` burns into one.
+        #[pallet::constant]
+        type MaxFuse: Get<u32>;
+
+        /// How many blocks after `schedule_burn` a kitty is burned, during which the burn can
+        /// be cancelled. Zero burns at once.
+        #[pallet::constant]
+        type BurnDelay: Get<Self::BlockNumber>;
+
+        /// Sales above this price by an account with a guardian, and its transfers of kitties
+        /// holding a deposit above it, wait for the guardian to approve them.
+        #[pallet::constant]
+        type GuardianThreshold: Get<BalanceOf<Self>>;
+
+        /// The most actions a ward can have waiting for their guardian at once.
+        #[pallet::constant]
+        type MaxGuardedActions: Get<u32>;
+
+        /// How many blocks a guardian has to approve an action before it lapses.
+        #[pallet::constant]
+        type GuardedActionExpiry: Get<Self::BlockNumber>;
+
+        /// The length in blocks of a listing reward epoch. Epoch `n` ends at block
+        /// `(n + 1) * RewardEpoch`.
+        #[pallet::constant]
+        type RewardEpoch: Get<Self::BlockNumber>;
+
+        /// How many blocks before an epoch ends a listing must have been made to earn its
+        /// reward, so listing just for the end of the epoch doesn't pay.
+        #[pallet::constant]
+        type MinRewardListingDuration: Get<Self::BlockNumber>;
     }
 
     #[pallet::pallet]
@@ -64,12 +377,470 @@ pub mod pallet {
     #[pallet::getter(fn price)]
     pub type Price<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, Option<BalanceOf<T>>, ValueQuery>;
 
+    /// The number of kitties currently in existence.
+    #[pallet::storage]
+    #[pallet::getter(fn total_supply)]
+    pub type TotalSupply<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Who reserved the deposit for each kitty, and how much, so it is refunded exactly.
+    #[pallet::storage]
+    #[pallet::getter(fn kitty_deposit)]
+    pub type KittyDeposits<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, (T::AccountId, BalanceOf<T>)>;
+
+    /// The deposit base each kitty's deposit was last reserved under, `KittyDepositBase` if
+    /// missing. When governance changes the base, deposits are scaled to match as kitties are
+    /// rebalanced.
+    #[pallet::storage]
+    pub type DepositBases<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, BalanceOf<T>>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn generation)]
+    pub type Generation<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, u32, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn export_nonce)]
+    pub type ExportNonce<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    #[pallet::storage]
+    pub type TotalTransfers<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    #[pallet::storage]
+    pub type TotalSales<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    #[pallet::storage]
+    pub type TotalVolume<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+    #[pallet::storage]
+    pub type TotalBreeds<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    /// Kitties for sale grouped by rarity, mirroring `Price`.
+    #[pallet::storage]
+    pub type ListingsByRarity<T: Config> = StorageDoubleMap<
+        _, Twox64Concat, RarityTier, Blake2_128Concat, T::KittyIndex, BalanceOf<T>,
+    >;
+
+    /// The cheapest entry of each tier in `ListingsByRarity`, so finding it doesn't walk the
+    /// whole tier. When the cheapest listing goes or gets dearer, it is looked for again in
+    /// `on_idle`, see `CheapestRescans`.
+    #[pallet::storage]
+    pub type CheapestListings<T: Config> = StorageMap<_, Twox64Concat, RarityTier, (T::KittyIndex, BalanceOf<T>)>;
+
+    /// Tiers whose cheapest listing `on_idle` is looking for, a few listings a block: the last
+    /// listing read, and the cheapest found so far. Listings made meanwhile are counted in as
+    /// they are made.
+    #[pallet::storage]
+    pub type CheapestRescans<T: Config> = StorageMap<
+        _, Twox64Concat, RarityTier, (Option<T::KittyIndex>, Option<(T::KittyIndex, BalanceOf<T>)>),
+    >;
+
+    /// Accounts that have already minted a kitty with `create`.
+    #[pallet::storage]
+    #[pallet::getter(fn has_minted)]
+    pub type HasMinted<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, bool, ValueQuery>;
+
+    /// Who referred each account to its first mint.
+    #[pallet::storage]
+    #[pallet::getter(fn referred_by)]
+    pub type ReferredBy<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId>;
+
+    /// Unredeemed vouchers: hash of the voucher code => dna of the kitty it mints.
+    #[pallet::storage]
+    #[pallet::getter(fn vouchers)]
+    pub type Vouchers<T: Config> = StorageMap<_, Identity, T::Hash, [u8; 16]>;
+
+    /// Hashes of the certificates already imported, so none can be used twice.
+    #[pallet::storage]
+    pub type ImportedCertificates<T: Config> = StorageMap<_, Identity, T::Hash, ()>;
+
+    /// The block at which each listing stops being valid.
+    #[pallet::storage]
+    #[pallet::getter(fn listing_expiry)]
+    pub type ListingExpiry<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, T::BlockNumber>;
+
+    /// The price of each listing priced in the reference unit, converted to the native currency
+    /// when the kitty is bought. `Price` holds what it converted to when it was listed.
+    #[pallet::storage]
+    #[pallet::getter(fn reference_price)]
+    pub type ReferencePrices<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, u128>;
+
+    /// The most recent owners of each kitty, oldest first, with the block they got it in.
+    #[pallet::storage]
+    #[pallet::getter(fn owner_history)]
+    pub type OwnerHistory<T: Config> = StorageMap<
+        _, Blake2_128Concat, T::KittyIndex,
+        BoundedVec<(T::AccountId, T::BlockNumber), T::MaxOwnersTracked>, ValueQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn parameters)]
+    pub type Parameters<T: Config> =
+        StorageValue<_, KittyParameters<BalanceOf<T>, T::BlockNumber>, ValueQuery>;
+
+    /// How many kitties each account owns.
+    #[pallet::storage]
+    #[pallet::getter(fn owned_count)]
+    pub type OwnedCount<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    /// The kitties each account owns, mirroring `Owner`. Kitties owned since before it was kept
+    /// are added by the `on_idle` repair pass.
+    #[pallet::storage]
+    pub type KittiesOwned<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, T::AccountId, Blake2_128Concat, T::KittyIndex, (),
+    >;
+
+    /// How many kitties each account has bred.
+    #[pallet::storage]
+    #[pallet::getter(fn breed_count)]
+    pub type BreedCount<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    /// Accounts owning the most kitties, highest first.
+    ///
+    /// Like the other leaderboards this is updated as scores change, so an account whose score
+    /// drops isn't overtaken by accounts off the board until their own scores change.
+    #[pallet::storage]
+    pub type TopOwners<T: Config> =
+        StorageValue<_, BoundedVec<(T::AccountId, u32), T::LeaderboardSize>, ValueQuery>;
+
+    /// Kitties by the highest price they sold for, highest first.
+    #[pallet::storage]
+    pub type TopSales<T: Config> =
+        StorageValue<_, BoundedVec<(T::KittyIndex, BalanceOf<T>), T::LeaderboardSize>, ValueQuery>;
+
+    /// Accounts that bred the most kitties, highest first.
+    #[pallet::storage]
+    pub type TopBreeders<T: Config> =
+        StorageValue<_, BoundedVec<(T::AccountId, u32), T::LeaderboardSize>, ValueQuery>;
+
+    /// Dna already minted by `create_with_nonce`, so a mined nonce can't be used twice.
+    #[pallet::storage]
+    pub type MinedDna<T: Config> = StorageMap<_, Identity, [u8; 16], ()>;
+
+    /// The account buyers' donations go to.
+    #[pallet::storage]
+    #[pallet::getter(fn donation_beneficiary)]
+    pub type DonationBeneficiary<T: Config> = StorageValue<_, T::AccountId>;
+
+    #[pallet::storage]
+    pub type TotalDonations<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+    /// Each account's heir, with the number of blocks the account must stay inactive before the
+    /// heir can claim its kitties.
+    #[pallet::storage]
+    #[pallet::getter(fn heir)]
+    pub type Heirs<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, (T::AccountId, T::BlockNumber)>;
+
+    /// The last kitty handed over from each account whose heir is part way through claiming.
+    #[pallet::storage]
+    pub type InheritanceCursors<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, T::KittyIndex>;
+
+    /// The block of each account's latest kitty extrinsic.
+    #[pallet::storage]
+    #[pallet::getter(fn last_active)]
+    pub type LastActive<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, T::BlockNumber, ValueQuery>;
+
+    /// The co-owners of each kitty with their shares. The owner holds whatever is left.
+    #[pallet::storage]
+    #[pallet::getter(fn co_owners)]
+    pub type CoOwners<T: Config> = StorageMap<
+        _, Blake2_128Concat, T::KittyIndex,
+        BoundedVec<(T::AccountId, Permill), T::MaxCoOwners>, ValueQuery,
+    >;
+
+    /// The action the co-owners of each kitty are approving, with who approved it so far.
+    #[pallet::storage]
+    pub type CoOwnerApprovals<T: Config> = StorageMap<
+        _, Blake2_128Concat, T::KittyIndex,
+        (CoOwnerAction<T::AccountId, BalanceOf<T>>, BoundedVec<T::AccountId, T::MaxCoOwners>),
+    >;
+
+    /// How many times each kitty has had a gene rerolled.
+    #[pallet::storage]
+    #[pallet::getter(fn reroll_count)]
+    pub type RerollCount<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, u32, ValueQuery>;
+
+    /// The gene each kitty is waiting to have rerolled.
+    #[pallet::storage]
+    #[pallet::getter(fn pending_reroll)]
+    pub type PendingRerolls<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, u8>;
+
+    /// The calls each user lets each operator make for them, as an `allowed_calls_mask`, and
+    /// the block the session expires at.
+    #[pallet::storage]
+    #[pallet::getter(fn session)]
+    pub type Sessions<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, T::AccountId, Blake2_128Concat, T::AccountId, (u32, T::BlockNumber),
+    >;
+
+    /// Kitties held in escrow while they are bought in installments.
+    #[pallet::storage]
+    #[pallet::getter(fn installment_plan)]
+    pub type Installments<T: Config> = StorageMap<
+        _, Blake2_128Concat, T::KittyIndex,
+        InstallmentPlan<T::AccountId, BalanceOf<T>, T::BlockNumber>,
+    >;
+
+    #[pallet::storage]
+    pub type NextPregnancyId<T: Config> = StorageValue<_, PregnancyId, ValueQuery>;
+
+    /// Kitties bred and waiting to be born.
+    #[pallet::storage]
+    #[pallet::getter(fn pregnancies)]
+    pub type Pregnancies<T: Config> = StorageMap<
+        _, Twox64Concat, PregnancyId,
+        Pregnancy<T::AccountId, BalanceOf<T>, T::BlockNumber>,
+    >;
+
+    /// The block each kitty was last bought in. Kept across transfers, so a kitty can't be
+    /// handed to another account to relist it sooner.
+    #[pallet::storage]
+    #[pallet::getter(fn acquired_at)]
+    pub type AcquiredAt<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, T::BlockNumber>;
+
+    /// The current streak of sales of each kitty between the same two accounts, replaced when
+    /// another pair trades it or the window runs out.
+    #[pallet::storage]
+    #[pallet::getter(fn trade_streak)]
+    pub type TradeStreaks<T: Config> = StorageMap<
+        _, Blake2_128Concat, T::KittyIndex, TradeStreak<T::AccountId, T::BlockNumber>,
+    >;
+
+    /// The `MintAuthorization` nonces each creator has had used or cancelled.
+    #[pallet::storage]
+    pub type UsedMintNonces<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, T::AccountId, Twox64Concat, u64, (),
+    >;
+
+    /// The creator of each lazily minted kitty with a royalty, and their cut of its sales.
+    #[pallet::storage]
+    #[pallet::getter(fn royalty)]
+    pub type Royalties<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, (T::AccountId, Permill)>;
+
+    /// Kitties bound to their owner: they can't be transferred, sold or exported, only burned.
+    #[pallet::storage]
+    #[pallet::getter(fn is_soulbound)]
+    pub type Soulbound<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, bool, ValueQuery>;
+
+    /// Kitties waiting to be burned, with who scheduled the burn and the block it is due in.
+    #[pallet::storage]
+    #[pallet::getter(fn pending_burn)]
+    pub type PendingBurns<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::KittyIndex, (T::AccountId, T::BlockNumber)>;
+
+    /// The guardian of each ward, who approves the ward's larger transfers and sales.
+    #[pallet::storage]
+    #[pallet::getter(fn guardian)]
+    pub type Guardians<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, T::AccountId>;
+
+    /// Each ward's actions waiting for their guardian, as the kitty, the action and the block
+    /// it lapses in. At most one per kitty; lapsed ones are pruned whenever the list is touched.
+    #[pallet::storage]
+    #[pallet::getter(fn guarded_actions)]
+    pub type GuardedActions<T: Config> = StorageMap<
+        _, Blake2_128Concat, T::AccountId,
+        BoundedVec<
+            (T::KittyIndex, CoOwnerAction<T::AccountId, BalanceOf<T>>, T::BlockNumber),
+            T::MaxGuardedActions,
+        >,
+        ValueQuery,
+    >;
+
+    /// The block each live listing was made in. Repricing or extending a listing keeps it.
+    /// Listings made before this was kept have none, and count as made before any epoch.
+    #[pallet::storage]
+    #[pallet::getter(fn listed_since)]
+    pub type ListedSince<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, T::BlockNumber>;
+
+    /// What each listing live at the end of an epoch earns its seller, and the most paid out
+    /// for one epoch, as set by governance. A zero reward switches the program off.
+    #[pallet::storage]
+    #[pallet::getter(fn listing_rewards)]
+    pub type ListingRewards<T: Config> = StorageValue<_, (BalanceOf<T>, BalanceOf<T>), ValueQuery>;
+
+    /// The first epoch listing rewards can be claimed for: the one the program was last
+    /// switched on in.
+    #[pallet::storage]
+    #[pallet::getter(fn listing_rewards_start)]
+    pub type ListingRewardsStart<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// What has been paid out in listing rewards for each epoch.
+    #[pallet::storage]
+    pub type ListingRewardsPaid<T: Config> = StorageMap<_, Twox64Concat, u32, BalanceOf<T>, ValueQuery>;
+
+    /// The listings rewarded for each epoch, so no listing is rewarded twice.
+    #[pallet::storage]
+    pub type RewardedListings<T: Config> = StorageDoubleMap<
+        _, Twox64Concat, u32, Blake2_128Concat, T::KittyIndex, (),
+    >;
+
+    /// The epoch and last listing a `claim_listing_reward` that didn't get through all of an
+    /// account's listings read; the next claim for that epoch carries on after it.
+    #[pallet::storage]
+    pub type RewardCursors<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, (u32, T::KittyIndex)>;
+
+    /// The kitties each account has listed.
+    #[pallet::storage]
+    pub type ListingsBySeller<T: Config> = StorageDoubleMap<
+        _, Blake2_128Concat, T::AccountId, Blake2_128Concat, T::KittyIndex, (),
+    >;
+
+    /// The last listing a `reprice_all` that didn't get through all of an account's listings
+    /// repriced; the next call carries on after it.
+    #[pallet::storage]
+    pub type RepriceCursors<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, T::KittyIndex>;
+
+    /// Marketplaces approved by governance, with the sale fee they pay instead of `sale_fee`
+    /// when they settle a sale. It never exceeds `sale_fee`.
+    #[pallet::storage]
+    #[pallet::getter(fn approved_marketplace)]
+    pub type ApprovedMarketplaces<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, Permill>;
+
+    /// The next kitty the `on_idle` housekeeping looks at.
+    #[pallet::storage]
+    pub type RepairCursor<T: Config> = StorageValue<_, T::KittyIndex>;
+
+    /// Scheduled breeding windows as `[start, end)` block ranges. Breeding is unrestricted
+    /// while this is unset, and only allowed inside a window once it is set. Windows that have
+    /// ended are pruned whenever the list is touched, and the list is unset again once the
+    /// last one has ended.
+    #[pallet::storage]
+    #[pallet::getter(fn breeding_windows)]
+    pub type BreedingWindows<T: Config> =
+        StorageValue<_, BoundedVec<(T::BlockNumber, T::BlockNumber), T::MaxBreedingWindows>>;
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        /// Kitties to start with: their owner, dna and the price they are listed for, if any.
+        /// Deposits are reserved from the owners, so they must be endowed to cover them.
+        pub kitties: Vec<(T::AccountId, [u8; 16], Option<BalanceOf<T>>)>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            Self { kitties: Vec::new() }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        fn build(&self) {
+            let expires_at = Pallet::<T>::max_listing_duration();
+            for (owner, dna, price) in &self.kitties {
+                let kitty_id = Pallet::<T>::mint(owner, *dna, 0)
+                    .expect("genesis kitty owners can afford their deposits");
+                if let Some(price) = price {
+                    Pallet::<T>::set_listing(kitty_id, Some((*price, expires_at)))
+                        .expect("genesis listings can be scheduled to expire");
+                }
+            }
+        }
+    }
+
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
         KittyCreate(T::AccountId, T::KittyIndex),
         KittyTransfer(T::AccountId, T::AccountId, T::KittyIndex),
         KittySale(T::AccountId, T::KittyIndex, Option<BalanceOf<T>>),
+        KittyExported(T::AccountId, T::KittyIndex, KittyCertificate<T::AccountId, ()>),
+        KittyImported(T::AccountId, T::KittyIndex, ChainId),
+        /// [who, referrer]
+        KittyReferred(T::AccountId, T::AccountId),
+        /// [referrer, reward]
+        ReferralRewardPaid(T::AccountId, BalanceOf<T>),
+        /// [code_hash]
+        VoucherIssued(T::Hash),
+        /// [who, code_hash, kitty_id]
+        VoucherRedeemed(T::AccountId, T::Hash, T::KittyIndex),
+        /// [start, end]
+        BreedingWindowScheduled(T::BlockNumber, T::BlockNumber),
+        BreedingWindowsCleared,
+        /// [kitty_id]
+        ListingExpired(T::KittyIndex),
+        /// [buyer, seller, kitty_id, price, donation]
+        Bought(T::AccountId, T::AccountId, T::KittyIndex, BalanceOf<T>, Option<BalanceOf<T>>),
+        /// [beneficiary]
+        DonationBeneficiarySet(Option<T::AccountId>),
+        /// [parameter]
+        ParameterSet(KittyParameter<BalanceOf<T>, T::BlockNumber>),
+        /// [who, heir, inactivity_blocks]
+        HeirSet(T::AccountId, T::AccountId, T::BlockNumber),
+        /// [who]
+        HeirCleared(T::AccountId),
+        /// [heir, previous_owner, kitties, done]
+        InheritanceClaimed(T::AccountId, T::AccountId, u32, bool),
+        /// [kitty_id]
+        StorageRepaired(T::KittyIndex),
+        /// [kitty_id, co_owner, share]
+        CoOwnerAdded(T::KittyIndex, T::AccountId, Permill),
+        /// [kitty_id, co_owner, action]
+        CoOwnerApproved(T::KittyIndex, T::AccountId, CoOwnerAction<T::AccountId, BalanceOf<T>>),
+        /// [kitty_id, gene_index, reveal_at]
+        RerollRequested(T::KittyIndex, u8, T::BlockNumber),
+        /// [kitty_id, gene_index, old, new]
+        TraitRerolled(T::KittyIndex, u8, u8, u8),
+        /// [marketplace, fee]
+        MarketplaceApproved(T::AccountId, Permill),
+        /// [marketplace]
+        MarketplaceRevoked(T::AccountId),
+        /// [marketplace, kitty_id, fee_paid]
+        MarketplaceAttribution(T::AccountId, T::KittyIndex, BalanceOf<T>),
+        /// [owner, pregnancy_id, due]
+        Pregnant(T::AccountId, PregnancyId, T::BlockNumber),
+        /// [user, operator, allowed_calls_mask, expiry]
+        SessionAuthorized(T::AccountId, T::AccountId, u32, T::BlockNumber),
+        /// [user, operator]
+        SessionRevoked(T::AccountId, T::AccountId),
+        /// [user, operator]
+        SessionExpired(T::AccountId, T::AccountId),
+        /// [kitty_id, old_deposit, new_deposit]
+        DepositRebalanced(T::KittyIndex, BalanceOf<T>, BalanceOf<T>),
+        /// [buyer, kitty_id, price, down_payment]
+        InstallmentPurchaseStarted(T::AccountId, T::KittyIndex, BalanceOf<T>, BalanceOf<T>),
+        /// [kitty_id, amount, remaining]
+        InstallmentPaid(T::KittyIndex, BalanceOf<T>, BalanceOf<T>),
+        /// [buyer, kitty_id, penalty]
+        InstallmentDefaulted(T::AccountId, T::KittyIndex, BalanceOf<T>),
+        /// [seller, delisted, done]
+        ListingsDelisted(T::AccountId, u32, bool),
+        /// [seller, repriced, done]
+        ListingsRepriced(T::AccountId, u32, bool),
+        /// [buyer, seller, kitty_id, trades]
+        SuspiciousTradePattern(T::AccountId, T::AccountId, T::KittyIndex, u32),
+        /// [buyer, creator, kitty_id, price]
+        LazyMinted(T::AccountId, T::AccountId, T::KittyIndex, BalanceOf<T>),
+        /// [creator, kitty_id, royalty]
+        RoyaltyPaid(T::AccountId, T::KittyIndex, BalanceOf<T>),
+        /// [owner, burned, kitty_id]
+        KittiesFused(T::AccountId, Vec<T::KittyIndex>, T::KittyIndex),
+        /// [owner, kitty_id, reference_price]
+        KittySaleInReference(T::AccountId, T::KittyIndex, u128),
+        /// [owner, kitty_id]
+        SoulboundMinted(T::AccountId, T::KittyIndex),
+        /// [owner, kitty_id]
+        KittyBurned(T::AccountId, T::KittyIndex),
+        /// [owner, kitty_id, due]
+        BurnScheduled(T::AccountId, T::KittyIndex, T::BlockNumber),
+        /// [owner, kitty_id]
+        BurnCancelled(T::AccountId, T::KittyIndex),
+        /// [ward, guardian]
+        GuardianSet(T::AccountId, T::AccountId),
+        /// [ward, guardian]
+        WardReleased(T::AccountId, T::AccountId),
+        /// [ward, kitty_id, action, expires_at]
+        GuardianApprovalRequested(
+            T::AccountId, T::KittyIndex, CoOwnerAction<T::AccountId, BalanceOf<T>>, T::BlockNumber,
+        ),
+        /// [ward, kitty_id]
+        GuardedActionApproved(T::AccountId, T::KittyIndex),
+        /// [ward, kitty_id]
+        GuardedActionVetoed(T::AccountId, T::KittyIndex),
+        /// [reward_per_listing, epoch_budget]
+        ListingRewardsSet(BalanceOf<T>, BalanceOf<T>),
+        /// [seller, epoch, listings, amount, done]
+        ListingRewardClaimed(T::AccountId, u32, u32, BalanceOf<T>, bool),
+        /// [creator, nonce]
+        MintNonceCancelled(T::AccountId, u64),
     }
 
     #[pallet::error]
@@ -81,6 +852,95 @@ pub mod pallet {
         InsufficientBalance,
         BuyFromSelf,
         KittyNotForSale,
+        UnsupportedCertificateVersion,
+        InvalidCertificateSignature,
+        CertificateAlreadyImported,
+        SelfReferral,
+        AlreadyMinted,
+        VoucherAlreadyExists,
+        NoSuchVoucher,
+        BreedingClosed,
+        InvalidBreedingWindow,
+        TooManyBreedingWindows,
+        InvalidListingExpiry,
+        NoDonationBeneficiary,
+        InsufficientWork,
+        DnaAlreadyMined,
+        InvalidHeir,
+        NotHeir,
+        OwnerStillActive,
+        AlreadyCoOwner,
+        TooManyCoOwners,
+        SharesExceedWhole,
+        NotCoOwner,
+        NotApprovedByCoOwners,
+        KittyCoOwned,
+        InvalidGeneIndex,
+        TooManyRerolls,
+        RerollPending,
+        UnknownMarketplace,
+        InvalidDna,
+        PriceOutOfBounds,
+        NoSuchPregnancy,
+        PregnancyNotDue,
+        InvalidSessionExpiry,
+        NoSession,
+        CallNotAuthorized,
+        InvalidInstallments,
+        NoInstallmentPlan,
+        NotInstallmentBuyer,
+        InstallmentOverdue,
+        InstallmentNotOverdue,
+        NoListings,
+        ResaleTooSoon,
+        InvalidMintSignature,
+        MintNonceUsed,
+        NotEnoughToFuse,
+        NoExchangeRate,
+        PriceSlippageExceeded,
+        ReferencePriced,
+        KittySoulbound,
+        BurnPending,
+        NoPendingBurn,
+        BurnNotDue,
+        InvalidGuardian,
+        AlreadyGuarded,
+        NotGuardian,
+        NoGuardedAction,
+        TooManyGuardedActions,
+        ListingRewardsOff,
+        EpochNotOver,
+        NoRewardableListings,
+        EpochBudgetSpent,
+        RewardPotEmpty,
+        InvalidCallMask,
+        MintAuthorizationExpired,
+        SchedulingFailed,
+        CallerIsWard,
+        EpochBeforeRewards,
+        NotCertificateRecipient,
+        WrongDestinationChain,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn offchain_worker(block_number: T::BlockNumber) {
+            Self::index_events(block_number);
+        }
+
+        /// Carry on looking for the cheapest listings in `CheapestRescans`, then repair the
+        /// storage of kitties from `RepairCursor` on while there is weight left in the block,
+        /// wrapping around after the last kitty.
+        fn on_idle(_now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+            let rescans = Self::continue_rescans(remaining_weight);
+            let remaining_weight = remaining_weight.saturating_sub(rescans);
+            Self::repair_some(remaining_weight).saturating_add(rescans)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade() -> Result<(), &'static str> {
+            Self::do_try_state()
+        }
     }
 
     #[pallet::call]
@@ -88,26 +948,56 @@ pub mod pallet {
         #[pallet::weight(1_000)]
         pub fn create(origin: OriginFor<T>) -> DispatchResult {
             let who = ensure_signed(origin)?;
+            Self::do_create(&who, Self::random_value(&who))?;
+            Ok(())
+        }
 
-            // Generate kitty id and dna, checking the id is valid.
-            let kitty_id = Self::get_id();
-            ensure!(kitty_id != T::KittyIndex::max_value(), Error::<T>::KittiesCountOverflow);
-            let dna = Self::random_value(&who);
+        /// Create a kitty with dna `pow_dna(who, nonce)`, which must have at least
+        /// `PowDifficulty` leading zero bits. Lets users mine the dna they want off-chain.
+        #[pallet::weight(1_000)]
+        pub fn create_with_nonce(origin: OriginFor<T>, nonce: u64) -> DispatchResult {
+            let who = ensure_signed(origin)?;
 
-            // Reserve for create kitty
-            let deposit = T::KittyDepositBase::get();
-            T::Currency::reserve(&who, deposit.clone()).map_err(|_| Error::<T>::InsufficientBalance)?;
+            // Check the work, and that it hasn't been used before.
+            let dna = Self::pow_dna(&who, nonce);
+            ensure!(
+                u128::from_be_bytes(dna).leading_zeros() >= Self::pow_difficulty(),
+                Error::<T>::InsufficientWork
+            );
+            ensure!(!MinedDna::<T>::contains_key(dna), Error::<T>::DnaAlreadyMined);
 
-            // Update chain's data.
-            Kitties::<T>::insert(kitty_id, Some(Kitty(dna)));
-            Owner::<T>::insert(kitty_id, Some(who.clone()));
-            KittiesCount::<T>::put(kitty_id + 1u32.into());
+            Self::do_create(&who, dna)?;
+            MinedDna::<T>::insert(dna, ());
+            Ok(())
+        }
 
-            // Deposit a "KittyCreate" event.
-            Self::deposit_event(Event::KittyCreate(who, kitty_id));
+        /// Create the caller's first kitty, rewarding `referrer` from the referral pot.
+        #[transactional]
+        #[pallet::weight(1_000)]
+        pub fn create_with_referral(origin: OriginFor<T>, referrer: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            // Referrals only count for an account's first mint, and never for itself.
+            ensure!(who != referrer, Error::<T>::SelfReferral);
+            ensure!(!Self::has_minted(&who), Error::<T>::AlreadyMinted);
+
+            Self::do_create(&who, Self::random_value(&who))?;
+            ReferredBy::<T>::insert(&who, &referrer);
+            Self::deposit_event(Event::KittyReferred(who, referrer.clone()));
+
+            // Pay the referrer if the pot can afford it; the mint stands either way.
+            let reward = Self::referral_reward();
+            if T::Currency::transfer(
+                &Self::account_id(), &referrer,
+                reward, ExistenceRequirement::KeepAlive,
+            ).is_ok() {
+                Self::deposit_event(Event::ReferralRewardPaid(referrer, reward));
+            }
             Ok(())
         }
 
+        /// Transfer a kitty to `new_owner`. If the caller has a guardian and the kitty holds a
+        /// deposit above `GuardianThreshold`, the transfer waits for the guardian instead.
         #[pallet::weight(1_000)]
         pub fn transfer(
             origin: OriginFor<T>, 
@@ -116,18 +1006,16 @@ pub mod pallet {
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            // Check caller is kitty's owner.
-            ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotKittyOwner);
-
-            // Update the kitty's owner. (transfer to `new_owner`)
-            Owner::<T>::insert(kitty_id, Some(new_owner.clone()));
-
-            // Deposit a "KittyTransfer" event.
-            Self::deposit_event(Event::KittyTransfer(who, new_owner, kitty_id));
-            Ok(())
+            if Self::await_guardian(&who, kitty_id, CoOwnerAction::Transfer(new_owner.clone()))? {
+                return Ok(());
+            }
+            Self::do_transfer(who, new_owner, kitty_id)
         }
 
+        /// Breed two kitties. The child is born after a gestation period that grows with the
+        /// rarity of the parents, see `BaseGestation`, and its deposit is reserved now.
         #[pallet::weight(1_000)]
+        #[transactional]
         pub fn breed(
             origin: OriginFor<T>,
             kitty_id1: T::KittyIndex,
@@ -137,76 +1025,1742 @@ pub mod pallet {
 
             // Ensure the two kitty are different kitties, checking they are exist.
             ensure!(kitty_id1 != kitty_id2, Error::<T>::SameParentIndex);
+            Self::ensure_breeding_open()?;
             let kitty1 = Self::kitties(kitty_id1).ok_or(Error::<T>::InvalidKittyIndex)?;
             let kitty2 = Self::kitties(kitty_id2).ok_or(Error::<T>::InvalidKittyIndex)?;
+            let generation = Self::generation(kitty_id1).max(Self::generation(kitty_id2)) + 1;
 
-            // Generate kitty id and dna, checking the id is valid.
-            let kitty_id = Self::get_id();
-            ensure!(kitty_id != T::KittyIndex::max_value(), Error::<T>::KittiesCountOverflow);
-            let dna = Self::breed_dna(&who, &kitty1, &kitty2);
+            // Check the child can get an id, and reserve its deposit.
+            ensure!(Self::get_id() != T::KittyIndex::max_value(), Error::<T>::KittiesCountOverflow);
+            let deposit = Self::discounted_deposit(&who, DiscountedAction::Breed);
+            T::Currency::reserve(&who, deposit).map_err(|_| Error::<T>::InsufficientBalance)?;
 
-            // Update chain's data.
-            Kitties::<T>::insert(kitty_id, Some(Kitty(dna)));
-            Owner::<T>::insert(kitty_id, Some(who.clone()));
-            KittiesCount::<T>::put(kitty_id + 1u32.into());
+            // Start the pregnancy.
+            let rarity = RarityTier::from_dna(&kitty1.0).max(RarityTier::from_dna(&kitty2.0));
+            let gestation = T::BaseGestation::get().saturating_mul((rarity as u32 + 1).into());
+            let due = frame_system::Pallet::<T>::block_number().saturating_add(gestation);
+            let pregnancy_id = NextPregnancyId::<T>::get();
+            NextPregnancyId::<T>::put(pregnancy_id.saturating_add(1));
+            let pregnancy = Pregnancy {
+                owner: who.clone(),
+                parents: (kitty1.0, kitty2.0),
+                generation,
+                deposit,
+                due,
+            };
+            TotalBreeds::<T>::mutate(|n| *n = n.saturating_add(1));
+            let breeds = BreedCount::<T>::mutate(&who, |n| {
+                *n = n.saturating_add(1);
+                *n
+            });
+            TopBreeders::<T>::mutate(|board| Self::rank(board, who.clone(), breeds));
+            Self::touch(&who);
+            Self::touch_kitty(kitty_id1);
+            Self::touch_kitty(kitty_id2);
+            Self::deposit_event(Event::Pregnant(who, pregnancy_id, due));
 
-            // Deposit a "KittyCreate" event.
-            Self::deposit_event(Event::KittyCreate(who, kitty_id));
+            // Give birth at once, or have the scheduler do it when the kitty is due.
+            if gestation.is_zero() {
+                return Self::birth(pregnancy_id, pregnancy)
+            }
+            Pregnancies::<T>::insert(pregnancy_id, pregnancy);
+            T::Scheduler::schedule_named(
+                Self::birth_task_id(pregnancy_id),
+                DispatchTime::At(due),
+                None,
+                LOWEST_PRIORITY,
+                frame_system::RawOrigin::Root.into(),
+                Call::give_birth { pregnancy_id }.into(),
+            ).map_err(|_| Error::<T>::SchedulingFailed)?;
             Ok(())
         }
 
+        /// Give birth to a kitty that is due. The scheduler does so in the block it is due;
+        /// should that fail, e.g. because the kitty count overflowed, anyone may retry.
         #[pallet::weight(1_000)]
-        pub fn sell_kitty(
-            origin: OriginFor<T>,
+        pub fn give_birth(origin: OriginFor<T>, pregnancy_id: PregnancyId) -> DispatchResult {
+            let retry = ensure_signed(origin.clone()).is_ok();
+            if !retry {
+                ensure_root(origin)?;
+            }
+
+            let pregnancy = Self::pregnancies(pregnancy_id).ok_or(Error::<T>::NoSuchPregnancy)?;
+            ensure!(
+                pregnancy.due <= frame_system::Pallet::<T>::block_number(),
+                Error::<T>::PregnancyNotDue
+            );
+            Self::birth(pregnancy_id, pregnancy)?;
+            Pregnancies::<T>::remove(pregnancy_id);
+            if retry {
+                let _ = T::Scheduler::cancel_named(Self::birth_task_id(pregnancy_id));
+            }
+            Ok(())
+        }
+
+        /// List a kitty for `price`, or delist it with `None`. The listing lasts until
+        /// `expires_at`, which defaults to and can't be later than `MaxListingDuration` blocks
+        /// from now. If the caller has a guardian and `price` is above `GuardianThreshold`, the
+        /// listing waits for the guardian instead.
+        #[pallet::weight(1_000)]
+        pub fn sell_kitty(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            price: Option<BalanceOf<T>>,
+            expires_at: Option<T::BlockNumber>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            if let Some(price) = price {
+                if Self::await_guardian(&who, kitty_id, CoOwnerAction::Sell(price))? {
+                    return Ok(());
+                }
+            }
+            Self::do_sell(who, kitty_id, price, expires_at)
+        }
+
+        /// List a kitty for `reference_price` in the reference unit, e.g. USD cents. The buyer
+        /// pays what it converts to in the native currency when they buy, and has to buy with
+        /// `buy_kitty_at_rate`. Otherwise like `sell_kitty`; a listing waiting for a guardian is
+        /// made in the native currency, at what the price converted to when it was asked for.
+        #[transactional]
+        #[pallet::weight(1_000)]
+        pub fn sell_kitty_in_reference(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            reference_price: u128,
+            expires_at: Option<T::BlockNumber>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            // List the kitty for what the price converts to now, then mark it reference priced.
+            let price = T::ExchangeRate::to_native(reference_price).ok_or(Error::<T>::NoExchangeRate)?;
+            if Self::await_guardian(&who, kitty_id, CoOwnerAction::Sell(price))? {
+                return Ok(());
+            }
+            Self::do_sell(who.clone(), kitty_id, Some(price), expires_at)?;
+            ReferencePrices::<T>::insert(kitty_id, reference_price);
+
+            Self::deposit_event(Event::KittySaleInReference(who, kitty_id, reference_price));
+            Ok(())
+        }
+
+        /// Buy a listed kitty, optionally donating `donate` on top of the price to the
+        /// donation beneficiary.
+        #[transactional]
+        #[pallet::weight(1_000)]
+        pub fn buy_kitty(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            donate: Option<BalanceOf<T>>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::do_buy(who, kitty_id, None, donate)
+        }
+
+        /// Buy a listed kitty like `buy_kitty`, paying no more than `max_native_payment`. A kitty
+        /// listed in the reference unit costs what its price converts to at the current rate,
+        /// and can only be bought this way.
+        #[transactional]
+        #[pallet::weight(1_000)]
+        pub fn buy_kitty_at_rate(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            max_native_payment: BalanceOf<T>,
+            donate: Option<BalanceOf<T>>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::do_buy(who, kitty_id, Some(max_native_payment), donate)
+        }
+
+        /// Delist up to `MaxBulkListings` of the caller's kitties; call again while
+        /// `ListingsDelisted` reports it isn't done.
+        #[pallet::weight(1_000u64.saturating_mul(T::MaxBulkListings::get().into()))]
+        pub fn delist_all(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut listings = ListingsBySeller::<T>::iter_key_prefix(&who);
+            let batch: Vec<_> = listings.by_ref().take(T::MaxBulkListings::get() as usize).collect();
+            ensure!(!batch.is_empty(), Error::<T>::NoListings);
+            let done = listings.next().is_none();
+
+            for kitty_id in batch.iter() {
+                Self::remove_listing(*kitty_id);
+            }
+            RepriceCursors::<T>::remove(&who);
+            Self::touch(&who);
+
+            Self::deposit_event(Event::ListingsDelisted(who, batch.len() as u32, done));
+            Ok(())
+        }
+
+        /// Move the price of up to `MaxBulkListings` of the caller's live listings by
+        /// `adjustment` of itself, kept within the listing price bounds; call again while
+        /// `ListingsRepriced` reports it isn't done. Expiries are kept, and kitties whose
+        /// co-owners haven't approved the new price, or whose new price needs the caller's
+        /// guardian, are skipped.
+        #[pallet::weight(1_000u64.saturating_mul(T::MaxBulkListings::get().into()))]
+        pub fn reprice_all(
+            origin: OriginFor<T>,
+            adjustment: Permill,
+            direction: PriceDirection,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            // Carry on after the last listing repriced, if the previous call didn't finish, or
+            // start over if the listings after it have all gone since.
+            let listings_after = |cursor: Option<T::KittyIndex>| match cursor {
+                Some(last) => ListingsBySeller::<T>::iter_prefix_from(
+                    &who, ListingsBySeller::<T>::hashed_key_for(&who, last),
+                ),
+                None => ListingsBySeller::<T>::iter_prefix(&who),
+            };
+            let batch_size = T::MaxBulkListings::get() as usize;
+            let mut listings = listings_after(RepriceCursors::<T>::get(&who));
+            let mut batch: Vec<_> = listings.by_ref().take(batch_size).map(|(kitty_id, _)| kitty_id).collect();
+            if batch.is_empty() {
+                listings = listings_after(None);
+                batch = listings.by_ref().take(batch_size).map(|(kitty_id, _)| kitty_id).collect();
+            }
+            ensure!(!batch.is_empty(), Error::<T>::NoListings);
+            let done = listings.next().is_none();
+
+            let mut repriced = 0u32;
+            for kitty_id in batch.iter().copied() {
+                if let Some(price) = Self::live_price(kitty_id) {
+                    let change = adjustment * price;
+                    let price = match direction {
+                        PriceDirection::Up => price.saturating_add(change),
+                        PriceDirection::Down => price.saturating_sub(change),
+                    }.max(T::MinListingPrice::get()).min(T::MaxListingPrice::get());
+                    let action = CoOwnerAction::Sell(price);
+                    if !Self::needs_guardian(&who, kitty_id, &action) &&
+                        Self::ensure_co_owners_approve(kitty_id, action).is_ok()
+                    {
+                        Self::set_price(kitty_id, price);
+                        repriced += 1;
+                    }
+                }
+            }
+            match (done, batch.last()) {
+                (false, Some(last)) => RepriceCursors::<T>::insert(&who, last),
+                _ => RepriceCursors::<T>::remove(&who),
+            }
+            Self::touch(&who);
+
+            Self::deposit_event(Event::ListingsRepriced(who, repriced, done));
+            Ok(())
+        }
+
+        /// Buy a listed kitty in `installments` equal payments, each due within
+        /// `InstallmentPeriod` blocks of the last. The down payment, the first installment plus
+        /// any rounding, is reserved now, and the kitty is held in escrow until it is paid off.
+        #[transactional]
+        #[pallet::weight(1_000)]
+        pub fn buy_in_installments(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            installments: u32,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            // Ensure the kitty is listed, whole, and not the buyer's own.
+            let seller = Owner::<T>::get(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+            ensure!(who != seller, Error::<T>::BuyFromSelf);
+            let price = Self::live_price(kitty_id).ok_or(Error::<T>::KittyNotForSale)?;
+            ensure!(!ReferencePrices::<T>::contains_key(kitty_id), Error::<T>::ReferencePriced);
+            ensure!(Self::co_owners(kitty_id).is_empty(), Error::<T>::KittyCoOwned);
+            ensure!(
+                installments >= 2 && installments <= T::MaxInstallments::get(),
+                Error::<T>::InvalidInstallments
+            );
+
+            // Reserve the down payment.
+            let installment = price / installments.into();
+            let down_payment = price.saturating_sub(installment.saturating_mul((installments - 1).into()));
+            T::Currency::reserve(&who, down_payment).map_err(|_| Error::<T>::InsufficientBalance)?;
+
+            // Move the kitty into escrow.
+            Self::remove_listing(kitty_id);
+            Self::set_owner(kitty_id, &Self::account_id());
+            let due = frame_system::Pallet::<T>::block_number().saturating_add(T::InstallmentPeriod::get());
+            Installments::<T>::insert(kitty_id, InstallmentPlan {
+                buyer: who.clone(),
+                seller,
+                price,
+                paid: down_payment,
+                installment,
+                due,
+            });
+            Self::touch(&who);
+
+            Self::deposit_event(Event::InstallmentPurchaseStarted(who, kitty_id, price, down_payment));
+            Ok(())
+        }
+
+        /// Pay the next installment on a kitty bought in installments, before it is due. The
+        /// last one completes the sale: the seller is paid, less the sale fee, and the kitty
+        /// and its deposit go to the buyer.
+        #[transactional]
+        #[pallet::weight(1_000)]
+        pub fn pay_installment(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut plan = Self::installment_plan(kitty_id).ok_or(Error::<T>::NoInstallmentPlan)?;
+            ensure!(plan.buyer == who, Error::<T>::NotInstallmentBuyer);
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(now < plan.due, Error::<T>::InstallmentOverdue);
+
+            // Reserve the installment.
+            let amount = plan.installment.min(plan.price.saturating_sub(plan.paid));
+            T::Currency::reserve(&who, amount).map_err(|_| Error::<T>::InsufficientBalance)?;
+            plan.paid = plan.paid.saturating_add(amount);
+            plan.due = plan.due.saturating_add(T::InstallmentPeriod::get());
+            Self::touch(&who);
+            Self::deposit_event(Event::InstallmentPaid(kitty_id, amount, plan.price.saturating_sub(plan.paid)));
+
+            if plan.paid < plan.price {
+                Installments::<T>::insert(kitty_id, plan);
+                return Ok(())
+            }
+            Self::complete_installments(kitty_id, plan)
+        }
+
+        /// Settle a kitty whose buyer missed an installment: the seller keeps `DefaultPenalty`
+        /// of what was paid and gets the kitty back, and the buyer is refunded the rest. Anyone
+        /// may settle a default.
+        #[pallet::weight(1_000)]
+        pub fn settle_default(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            let plan = Self::installment_plan(kitty_id).ok_or(Error::<T>::NoInstallmentPlan)?;
+            ensure!(
+                frame_system::Pallet::<T>::block_number() >= plan.due,
+                Error::<T>::InstallmentNotOverdue
+            );
+
+            // Pay the penalty out of the buyer's reserve and release the rest.
+            let penalty = T::DefaultPenalty::get() * plan.paid;
+            let unpaid = T::Currency::repatriate_reserved(&plan.buyer, &plan.seller, penalty, BalanceStatus::Free)?;
+            let penalty = penalty.saturating_sub(unpaid);
+            T::Currency::unreserve(&plan.buyer, plan.paid.saturating_sub(penalty));
+
+            // Return the kitty from escrow.
+            Installments::<T>::remove(kitty_id);
+            Self::set_owner(kitty_id, &plan.seller);
+
+            Self::deposit_event(Event::InstallmentDefaulted(plan.buyer, kitty_id, penalty));
+            Ok(())
+        }
+
+        /// Burn a kitty and emit an unsigned certificate describing it, to be signed by the
+        /// certificate issuer and imported by `recipient` on `destination_chain`.
+        #[pallet::weight(1_000)]
+        pub fn export_kitty(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            recipient: T::AccountId,
+            destination_chain: ChainId,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            // Ensure caller is the sole kitty owner.
+            ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotKittyOwner);
+            ensure!(Self::co_owners(kitty_id).is_empty(), Error::<T>::KittyCoOwned);
+            ensure!(!Self::is_soulbound(kitty_id), Error::<T>::KittySoulbound);
+            Self::ensure_unguarded(&who)?;
+            let kitty = Self::kitties(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+
+            let nonce = Self::export_nonce();
+            let certificate = KittyCertificate::new(
+                kitty.0, Self::generation(kitty_id), T::ChainId::get(), nonce,
+                recipient, destination_chain,
+            );
+
+            // Burn the kitty and give the deposit back.
+            Self::burn(kitty_id, &who);
+            ExportNonce::<T>::put(nonce + 1);
+            Self::touch(&who);
+
+            // Deposit a "KittyExported" event.
+            Self::deposit_event(Event::KittyExported(who, kitty_id, certificate));
+            Ok(())
+        }
+
+        /// Mint a kitty from a certificate signed by the certificate issuer and addressed to the
+        /// caller on this chain.
+        #[pallet::weight(1_000)]
+        pub fn import_kitty(
+            origin: OriginFor<T>,
+            certificate: KittyCertificate<T::AccountId, T::CertificateSignature>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            // Check the certificate is genuine, meant for the caller on this chain, and has not
+            // been used before.
+            ensure!(certificate.version == CERTIFICATE_VERSION, Error::<T>::UnsupportedCertificateVersion);
+            ensure!(certificate.recipient == who, Error::<T>::NotCertificateRecipient);
+            ensure!(certificate.destination_chain == T::ChainId::get(), Error::<T>::WrongDestinationChain);
+            let payload = certificate.signing_payload();
+            ensure!(
+                certificate.sig.verify(&payload[..], &T::CertificateIssuer::get()),
+                Error::<T>::InvalidCertificateSignature
+            );
+            let certificate_hash = T::Hashing::hash(&payload);
+            ensure!(
+                !ImportedCertificates::<T>::contains_key(certificate_hash),
+                Error::<T>::CertificateAlreadyImported
+            );
+
+            // Mint the kitty described by the certificate.
+            Self::validate_dna(&certificate.dna)?;
+            let kitty_id = Self::mint(&who, certificate.dna, certificate.generation)?;
+            ImportedCertificates::<T>::insert(certificate_hash, ());
+            Self::touch(&who);
+
+            // Deposit a "KittyImported" event.
+            Self::deposit_event(Event::KittyImported(who, kitty_id, certificate.origin_chain));
+            Ok(())
+        }
+
+        /// Issue a voucher minting a kitty with `dna` to whoever presents the preimage of
+        /// `code_hash`. The dna must be sealed in the current layout, see `kitties_primitives::dna`.
+        #[pallet::weight(1_000)]
+        pub fn issue_voucher(origin: OriginFor<T>, code_hash: T::Hash, dna: [u8; 16]) -> DispatchResult {
+            T::VoucherIssuer::ensure_origin(origin)?;
+
+            Self::validate_dna(&dna)?;
+            ensure!(!Vouchers::<T>::contains_key(code_hash), Error::<T>::VoucherAlreadyExists);
+            Vouchers::<T>::insert(code_hash, dna);
+
+            Self::deposit_event(Event::VoucherIssued(code_hash));
+            Ok(())
+        }
+
+        /// Redeem the voucher whose code hashes to the hash it was issued under.
+        ///
+        /// The code is public once submitted, so codes should only be handed out to the
+        /// account expected to redeem them.
+        #[pallet::weight(1_000)]
+        pub fn redeem_voucher(origin: OriginFor<T>, code: Vec<u8>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let code_hash = T::Hashing::hash(&code);
+            let dna = Self::vouchers(code_hash).ok_or(Error::<T>::NoSuchVoucher)?;
+
+            // Mint the kitty, then consume the voucher.
+            Self::validate_dna(&dna)?;
+            let kitty_id = Self::mint(&who, dna, 0)?;
+            Vouchers::<T>::remove(code_hash);
+
+            Self::touch(&who);
+            Self::deposit_event(Event::VoucherRedeemed(who, code_hash, kitty_id));
+            Ok(())
+        }
+
+        /// Buy the kitty a creator offered in `authorization`, minting it to the caller. The
+        /// caller pays the price, less the sale fee, to the creator, and reserves the kitty
+        /// deposit; the creator pays nothing until someone buys.
+        #[transactional]
+        #[pallet::weight(1_000)]
+        pub fn lazy_buy(
+            origin: OriginFor<T>,
+            authorization: MintAuthorization<T::AccountId, BalanceOf<T>, T::BlockNumber>,
+            signature: T::CreatorSignature,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let MintAuthorization { creator, dna, price, royalty, nonce, expires_at } = authorization.clone();
+
+            // Check the offer is the creator's, live, unused, and for a valid kitty at a valid
+            // price.
+            ensure!(who != creator, Error::<T>::BuyFromSelf);
+            ensure!(
+                signature.verify(&authorization.signing_payload(&T::ChainId::get())[..], &creator),
+                Error::<T>::InvalidMintSignature
+            );
+            ensure!(
+                frame_system::Pallet::<T>::block_number() <= expires_at,
+                Error::<T>::MintAuthorizationExpired
+            );
+            ensure!(!UsedMintNonces::<T>::contains_key(&creator, nonce), Error::<T>::MintNonceUsed);
+            Self::validate_dna(&dna)?;
+            ensure!(
+                !price.is_zero() && T::MinListingPrice::get() <= price && price <= T::MaxListingPrice::get(),
+                Error::<T>::PriceOutOfBounds
+            );
+
+            // Pay the creator, then mint the kitty to the buyer.
+            let fee = Self::sale_fee() * price;
+            if !fee.is_zero() {
+                T::Currency::transfer(&who, &Self::account_id(), fee, ExistenceRequirement::KeepAlive)?;
+            }
+            T::Currency::transfer(&who, &creator, price.saturating_sub(fee), ExistenceRequirement::KeepAlive)?;
+            let kitty_id = Self::mint(&who, dna, 0)?;
+            UsedMintNonces::<T>::insert(&creator, nonce, ());
+            if !royalty.is_zero() {
+                Royalties::<T>::insert(kitty_id, (creator.clone(), royalty));
+            }
+            Self::touch(&who);
+
+            Self::deposit_event(Event::LazyMinted(who, creator, kitty_id, price));
+            Ok(())
+        }
+
+        /// Burn two or more of the caller's kitties into one new kitty, bred from all of them
+        /// with fresh randomness. The new kitty is at least as rare as the rarest one burned, and
+        /// a generation after the youngest. Their deposits are refunded and one is reserved for
+        /// the new kitty, which is soulbound if any of the fused ones was.
+        #[transactional]
+        #[pallet::weight(1_000)]
+        pub fn fuse(origin: OriginFor<T>, kitty_ids: BoundedVec<T::KittyIndex, T::MaxFuse>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            // Ensure the caller is the sole owner of at least two different kitties.
+            ensure!(kitty_ids.len() >= 2, Error::<T>::NotEnoughToFuse);
+            let mut dnas = Vec::with_capacity(kitty_ids.len());
+            let mut generation = 0;
+            let mut soulbound = false;
+            for (i, kitty_id) in kitty_ids.iter().enumerate() {
+                ensure!(!kitty_ids[..i].contains(kitty_id), Error::<T>::SameParentIndex);
+                ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotKittyOwner);
+                ensure!(Self::co_owners(kitty_id).is_empty(), Error::<T>::KittyCoOwned);
+                let kitty = Self::kitties(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+                dnas.push(kitty.0);
+                generation = generation.max(Self::generation(kitty_id));
+                soulbound |= Self::is_soulbound(kitty_id);
+            }
+
+            // Burn the kitties, then mint the fused one.
+            let dna = Self::fuse_dna(&kitty_ids, &dnas);
+            for kitty_id in kitty_ids.iter() {
+                Self::burn(*kitty_id, &who);
+            }
+            let kitty_id = Self::mint(&who, dna, generation.saturating_add(1))?;
+            if soulbound {
+                Soulbound::<T>::insert(kitty_id, true);
+            }
+            Self::touch(&who);
+
+            Self::deposit_event(Event::KittiesFused(who, kitty_ids.into_inner(), kitty_id));
+            Ok(())
+        }
+
+        /// Mint a soulbound kitty with `dna` to `owner`, e.g. to award an achievement. The dna
+        /// must be sealed in the current layout, and the deposit is reserved from `owner`.
+        #[pallet::weight(1_000)]
+        pub fn mint_soulbound(origin: OriginFor<T>, owner: T::AccountId, dna: [u8; 16]) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+            Self::do_mint_soulbound(&owner, dna)?;
+            Ok(())
+        }
+
+        /// Burn a kitty the caller solely owns once `BurnDelay` blocks have passed, refunding
+        /// its deposit. The one way to part with a soulbound kitty. Until then the burn can be
+        /// cancelled with `cancel_burn`, and is cancelled if the kitty changes hands.
+        #[pallet::weight(1_000)]
+        pub fn schedule_burn(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotKittyOwner);
+            ensure!(Self::co_owners(kitty_id).is_empty(), Error::<T>::KittyCoOwned);
+            ensure!(!PendingBurns::<T>::contains_key(kitty_id), Error::<T>::BurnPending);
+            Self::touch(&who);
+
+            // Burn at once, or have the scheduler do it when the delay is over.
+            let delay = T::BurnDelay::get();
+            if delay.is_zero() {
+                Self::burn(kitty_id, &who);
+                Self::deposit_event(Event::KittyBurned(who, kitty_id));
+                return Ok(())
+            }
+            let due = frame_system::Pallet::<T>::block_number().saturating_add(delay);
+            T::Scheduler::schedule_named(
+                Self::burn_task_id(kitty_id),
+                DispatchTime::At(due),
+                None,
+                LOWEST_PRIORITY,
+                frame_system::RawOrigin::Root.into(),
+                Call::execute_burn { kitty_id }.into(),
+            ).map_err(|_| Error::<T>::SchedulingFailed)?;
+            PendingBurns::<T>::insert(kitty_id, (&who, due));
+
+            Self::deposit_event(Event::BurnScheduled(who, kitty_id, due));
+            Ok(())
+        }
+
+        /// Call off a burn the caller scheduled and that hasn't happened yet.
+        #[pallet::weight(1_000)]
+        pub fn cancel_burn(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let (owner, _) = Self::pending_burn(kitty_id).ok_or(Error::<T>::NoPendingBurn)?;
+            ensure!(owner == who, Error::<T>::NotKittyOwner);
+            PendingBurns::<T>::remove(kitty_id);
+            let _ = T::Scheduler::cancel_named(Self::burn_task_id(kitty_id));
+            Self::touch(&who);
+
+            Self::deposit_event(Event::BurnCancelled(who, kitty_id));
+            Ok(())
+        }
+
+        /// Burn a kitty whose burn is due. The scheduler does so in the block it is due;
+        /// should that fail, anyone may retry.
+        #[pallet::weight(1_000)]
+        pub fn execute_burn(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            let retry = ensure_signed(origin.clone()).is_ok();
+            if !retry {
+                ensure_root(origin)?;
+            }
+
+            let (owner, due) = Self::pending_burn(kitty_id).ok_or(Error::<T>::NoPendingBurn)?;
+            ensure!(due <= frame_system::Pallet::<T>::block_number(), Error::<T>::BurnNotDue);
+            Self::burn(kitty_id, &owner);
+
+            Self::deposit_event(Event::KittyBurned(owner, kitty_id));
+            Ok(())
+        }
+
+        /// Schedule a breeding window over blocks `[start_block, end_block)`. While any window
+        /// is scheduled and yet to end, `breed` only works inside one.
+        #[pallet::weight(1_000)]
+        pub fn set_breeding_window(
+            origin: OriginFor<T>,
+            start_block: T::BlockNumber,
+            end_block: T::BlockNumber,
+        ) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(start_block < end_block && end_block > now, Error::<T>::InvalidBreedingWindow);
+
+            BreedingWindows::<T>::try_mutate(|windows| -> DispatchResult {
+                let windows = windows.get_or_insert_with(Default::default);
+                windows.retain(|(_, end)| *end > now);
+                windows
+                    .try_push((start_block, end_block))
+                    .map_err(|_| Error::<T>::TooManyBreedingWindows)?;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::BreedingWindowScheduled(start_block, end_block));
+            Ok(())
+        }
+
+        /// Drop all breeding windows, making breeding unrestricted again.
+        #[pallet::weight(1_000)]
+        pub fn clear_breeding_windows(origin: OriginFor<T>) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            BreedingWindows::<T>::kill();
+
+            Self::deposit_event(Event::BreedingWindowsCleared);
+            Ok(())
+        }
+
+        /// Remove a listing whose expiry has passed. Dispatched by the scheduler.
+        #[pallet::weight(1_000)]
+        pub fn expire_listing(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            ensure_root(origin)?;
+
+            if Self::is_expired(kitty_id) {
+                Self::remove_listing(kitty_id);
+                Self::deposit_event(Event::ListingExpired(kitty_id));
+            }
+            Ok(())
+        }
+
+        /// Set or clear the account that receives donations made with `buy_kitty`.
+        #[pallet::weight(1_000)]
+        pub fn set_donation_beneficiary(
+            origin: OriginFor<T>,
+            beneficiary: Option<T::AccountId>,
+        ) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            DonationBeneficiary::<T>::set(beneficiary.clone());
+
+            Self::deposit_event(Event::DonationBeneficiarySet(beneficiary));
+            Ok(())
+        }
+
+        /// Override one of the `KittyParameters`, or reset it to its `Config` default.
+        #[pallet::weight(1_000)]
+        pub fn set_parameter(
+            origin: OriginFor<T>,
+            parameter: KittyParameter<BalanceOf<T>, T::BlockNumber>,
+        ) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            Parameters::<T>::mutate(|parameters| match parameter.clone() {
+                KittyParameter::DepositBase(value) => parameters.deposit_base = value,
+                KittyParameter::ReferralReward(value) => parameters.referral_reward = value,
+                KittyParameter::MaxListingDuration(value) => parameters.max_listing_duration = value,
+                KittyParameter::PowDifficulty(value) => parameters.pow_difficulty = value,
+                KittyParameter::SaleFee(value) => parameters.sale_fee = value,
+            });
+
+            Self::deposit_event(Event::ParameterSet(parameter));
+            Ok(())
+        }
+
+        /// Let sales settled by `marketplace`, i.e. bought by it, pay `fee` of the price instead
+        /// of the sale fee, or the sale fee if that is lower.
+        #[pallet::weight(1_000)]
+        pub fn approve_marketplace(
+            origin: OriginFor<T>,
+            marketplace: T::AccountId,
+            fee: Permill,
+        ) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            ApprovedMarketplaces::<T>::insert(&marketplace, fee);
+
+            Self::deposit_event(Event::MarketplaceApproved(marketplace, fee));
+            Ok(())
+        }
+
+        /// Make sales settled by `marketplace` pay the full sale fee again.
+        #[pallet::weight(1_000)]
+        pub fn revoke_marketplace(origin: OriginFor<T>, marketplace: T::AccountId) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            ensure!(ApprovedMarketplaces::<T>::contains_key(&marketplace), Error::<T>::UnknownMarketplace);
+            ApprovedMarketplaces::<T>::remove(&marketplace);
+
+            Self::deposit_event(Event::MarketplaceRevoked(marketplace));
+            Ok(())
+        }
+
+        /// Give `co_owner` a `share` of a kitty out of the owner's own. Once a kitty is
+        /// co-owned, transferring or listing it needs the approval of more than half of it by
+        /// share, the owner counting as approving what they do, and sale proceeds are split by
+        /// share. Co-ownership ends when the kitty changes hands.
+        #[pallet::weight(1_000)]
+        pub fn add_co_owner(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            co_owner: T::AccountId,
+            share: Permill,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            // Ensure caller is the kitty owner, and keeps a share of their own.
+            ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotKittyOwner);
+            ensure!(co_owner != who, Error::<T>::AlreadyCoOwner);
+            Self::ensure_unguarded(&who)?;
+            CoOwners::<T>::try_mutate(kitty_id, |co_owners| -> DispatchResult {
+                ensure!(co_owners.iter().all(|(account, _)| *account != co_owner), Error::<T>::AlreadyCoOwner);
+                let total = co_owners.iter()
+                    .fold(share.deconstruct(), |total, (_, share)| total.saturating_add(share.deconstruct()));
+                ensure!(total <= Permill::ACCURACY, Error::<T>::SharesExceedWhole);
+                co_owners.try_push((co_owner.clone(), share)).map_err(|_| Error::<T>::TooManyCoOwners)?;
+                Ok(())
+            })?;
+
+            Self::touch(&who);
+            Self::deposit_event(Event::CoOwnerAdded(kitty_id, co_owner, share));
+            Ok(())
+        }
+
+        /// Approve `action` on a kitty the caller co-owns. Approvals collect for one action at a
+        /// time: approving a different one starts over.
+        #[pallet::weight(1_000)]
+        pub fn approve_co_owned(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            action: CoOwnerAction<T::AccountId, BalanceOf<T>>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                Self::co_owners(kitty_id).iter().any(|(account, _)| *account == who),
+                Error::<T>::NotCoOwner
+            );
+            CoOwnerApprovals::<T>::mutate(kitty_id, |approval| match approval {
+                Some((approved, approvers)) if *approved == action => {
+                    if !approvers.contains(&who) {
+                        let _ = approvers.try_push(who.clone());
+                    }
+                },
+                _ => {
+                    let mut approvers = BoundedVec::default();
+                    let _ = approvers.try_push(who.clone());
+                    *approval = Some((action.clone(), approvers));
+                },
+            });
+
+            Self::touch(&who);
+            Self::deposit_event(Event::CoOwnerApproved(kitty_id, who, action));
+            Ok(())
+        }
+
+        /// Pay `RerollFee` to replace gene `gene_index` of a kitty with a random one. The gene
+        /// is drawn `RerollDelay` blocks later, from randomness nobody knows when paying.
+        #[pallet::weight(1_000)]
+        pub fn reroll_trait(origin: OriginFor<T>, kitty_id: T::KittyIndex, gene_index: u8) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            // Ensure caller is the kitty owner, and the kitty can be rerolled.
+            ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotKittyOwner);
+            ensure!((gene_index as usize) < dna::GENES, Error::<T>::InvalidGeneIndex);
+            ensure!(Self::reroll_count(kitty_id) < T::MaxRerolls::get(), Error::<T>::TooManyRerolls);
+            ensure!(!PendingRerolls::<T>::contains_key(kitty_id), Error::<T>::RerollPending);
+
+            // Schedule the reveal, then take the fee.
+            let reveal_at = frame_system::Pallet::<T>::block_number().saturating_add(T::RerollDelay::get());
+            T::Scheduler::schedule_named(
+                Self::reroll_task_id(kitty_id),
+                DispatchTime::At(reveal_at),
+                None,
+                LOWEST_PRIORITY,
+                frame_system::RawOrigin::Root.into(),
+                Call::complete_reroll { kitty_id }.into(),
+            ).map_err(|_| Error::<T>::SchedulingFailed)?;
+            if let Err(err) = T::Currency::transfer(
+                &who, &Self::account_id(),
+                T::RerollFee::get(), ExistenceRequirement::KeepAlive,
+            ) {
+                let _ = T::Scheduler::cancel_named(Self::reroll_task_id(kitty_id));
+                return Err(err);
+            }
+            PendingRerolls::<T>::insert(kitty_id, gene_index);
+            RerollCount::<T>::mutate(kitty_id, |n| *n = n.saturating_add(1));
+
+            Self::touch(&who);
+            Self::deposit_event(Event::RerollRequested(kitty_id, gene_index, reveal_at));
+            Ok(())
+        }
+
+        /// Draw the new gene of a pending reroll. Dispatched by the scheduler.
+        #[pallet::weight(1_000)]
+        pub fn complete_reroll(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            ensure_root(origin)?;
+
+            let gene_index = match PendingRerolls::<T>::take(kitty_id) {
+                Some(gene_index) => gene_index as usize,
+                None => return Ok(()),
+            };
+            let mut kitty = Self::kitties(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+            let (seed, _) = T::Randomness::random(&(b"kitties/reroll", kitty_id).encode());
+            let old = kitty.0[gene_index];
+            let new = seed.using_encoded(blake2_128)[gene_index];
+
+            // The leading gene decides the rarity tier, so move any listing along with it.
+            let old_tier = RarityTier::from_dna(&kitty.0);
+            kitty.0[gene_index] = new;
+            kitty.0 = dna::seal(kitty.0);
+            let new_tier = RarityTier::from_dna(&kitty.0);
+            if let Some(price) = Self::price(kitty_id) {
+                Self::index_listing(old_tier, kitty_id, None);
+                Self::index_listing(new_tier, kitty_id, Some(price));
+            }
+            Kitties::<T>::insert(kitty_id, Some(kitty));
+
+            Self::deposit_event(Event::TraitRerolled(kitty_id, gene_index as u8, old, new));
+            Ok(())
+        }
+
+        /// Let `heir` claim all of the caller's kitties once the caller has made no kitty
+        /// extrinsic for `inactivity_blocks` blocks.
+        #[pallet::weight(1_000)]
+        pub fn set_heir(
+            origin: OriginFor<T>,
+            heir: T::AccountId,
+            inactivity_blocks: T::BlockNumber,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(heir != who && !inactivity_blocks.is_zero(), Error::<T>::InvalidHeir);
+            Self::ensure_unguarded(&who)?;
+            Heirs::<T>::insert(&who, (&heir, inactivity_blocks));
+            InheritanceCursors::<T>::remove(&who);
+
+            Self::touch(&who);
+            Self::deposit_event(Event::HeirSet(who, heir, inactivity_blocks));
+            Ok(())
+        }
+
+        #[pallet::weight(1_000)]
+        pub fn clear_heir(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(Heirs::<T>::contains_key(&who), Error::<T>::InvalidHeir);
+            Heirs::<T>::remove(&who);
+            InheritanceCursors::<T>::remove(&who);
+
+            Self::touch(&who);
+            Self::deposit_event(Event::HeirCleared(who));
+            Ok(())
+        }
+
+        /// Take over the kitties of `previous_owner`, who named the caller as heir and has been
+        /// inactive for the agreed period. Their listings are dropped and their deposits stay
+        /// reserved by whoever reserved them. Soulbound kitties stay with the previous owner, and
+        /// so do co-owned ones, which only move with their co-owners' approval.
+        ///
+        /// Goes through up to `MaxInheritedKitties` of their kitties; call again while
+        /// `InheritanceClaimed` reports it isn't done. The heir is cleared once it is.
+        #[pallet::weight(T::DbWeight::get().reads_writes(4, 3).saturating_add(
+            T::DbWeight::get().reads_writes(11, 14).saturating_mul(T::MaxInheritedKitties::get().into())
+        ))]
+        pub fn claim_inheritance(origin: OriginFor<T>, previous_owner: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            // Check the caller is the heir and the owner has been inactive long enough.
+            let (heir, inactivity_blocks) = Self::heir(&previous_owner).ok_or(Error::<T>::NotHeir)?;
+            ensure!(heir == who, Error::<T>::NotHeir);
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(
+                now >= Self::last_active(&previous_owner).saturating_add(inactivity_blocks),
+                Error::<T>::OwnerStillActive
+            );
+
+            // Carry on after the last kitty handed over, if the previous call didn't finish.
+            let mut owned = match InheritanceCursors::<T>::get(&previous_owner) {
+                Some(last) => KittiesOwned::<T>::iter_prefix_from(
+                    &previous_owner, KittiesOwned::<T>::hashed_key_for(&previous_owner, last),
+                ),
+                None => KittiesOwned::<T>::iter_prefix(&previous_owner),
+            }.map(|(kitty_id, _)| kitty_id);
+            let batch: Vec<_> = owned.by_ref().take(T::MaxInheritedKitties::get() as usize).collect();
+            let done = owned.next().is_none();
+
+            // Hand the kitties of the batch to the heir.
+            let mut claimed = 0u32;
+            let inheritable = |kitty_id: &T::KittyIndex| {
+                !Self::is_soulbound(*kitty_id) && Self::co_owners(kitty_id).is_empty()
+            };
+            for kitty_id in batch.iter().copied().filter(inheritable) {
+                Self::remove_listing(kitty_id);
+                Self::set_owner(kitty_id, &who);
+                claimed += 1;
+                Self::deposit_event(Event::KittyTransfer(previous_owner.clone(), who.clone(), kitty_id));
+            }
+            TotalTransfers::<T>::mutate(|n| *n = n.saturating_add(claimed.into()));
+            match (done, batch.last()) {
+                (false, Some(last)) => InheritanceCursors::<T>::insert(&previous_owner, last),
+                _ => {
+                    InheritanceCursors::<T>::remove(&previous_owner);
+                    Heirs::<T>::remove(&previous_owner);
+                },
+            }
+
+            Self::touch(&who);
+            Self::deposit_event(Event::InheritanceClaimed(who, previous_owner, claimed, done));
+            Ok(())
+        }
+
+        /// Top up or refund a kitty's deposit to match the current deposit base, from or to
+        /// whoever reserved it. Anyone may rebalance a kitty; transfers, listings and breeding
+        /// also rebalance the kitties involved when the depositor can afford it.
+        #[pallet::weight(1_000)]
+        pub fn rebalance_deposit(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            ensure_signed(origin)?;
+            Self::rebalance(kitty_id)
+        }
+
+        /// Let `operator`, e.g. a game server, make the kitty calls in `allowed_calls_mask` for
+        /// the caller with `session_call` until block `expiry`. Replaces any session the caller
+        /// already has with the operator. The mask may only hold `SESSION_BREED` and
+        /// `SESSION_REROLL`.
+        #[transactional]
+        #[pallet::weight(1_000)]
+        pub fn authorize_session(
+            origin: OriginFor<T>,
+            operator: T::AccountId,
+            allowed_calls_mask: u32,
+            expiry: T::BlockNumber,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(allowed_calls_mask & !(SESSION_BREED | SESSION_REROLL) == 0, Error::<T>::InvalidCallMask);
+            ensure!(expiry > frame_system::Pallet::<T>::block_number(), Error::<T>::InvalidSessionExpiry);
+            Self::ensure_unguarded(&who)?;
+
+            // Replace the expiry task of the previous session, if any.
+            let _ = T::Scheduler::cancel_named(Self::session_task_id(&who, &operator));
+            T::Scheduler::schedule_named(
+                Self::session_task_id(&who, &operator),
+                DispatchTime::At(expiry),
+                None,
+                LOWEST_PRIORITY,
+                frame_system::RawOrigin::Root.into(),
+                Call::expire_session { user: who.clone(), operator: operator.clone() }.into(),
+            ).map_err(|_| Error::<T>::SchedulingFailed)?;
+            Sessions::<T>::insert(&who, &operator, (allowed_calls_mask, expiry));
+
+            Self::touch(&who);
+            Self::deposit_event(Event::SessionAuthorized(who, operator, allowed_calls_mask, expiry));
+            Ok(())
+        }
+
+        /// End the caller's session with `operator`.
+        #[pallet::weight(1_000)]
+        pub fn revoke_session(origin: OriginFor<T>, operator: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Sessions::<T>::take(&who, &operator).ok_or(Error::<T>::NoSession)?;
+            let _ = T::Scheduler::cancel_named(Self::session_task_id(&who, &operator));
+
+            Self::deposit_event(Event::SessionRevoked(who, operator));
+            Ok(())
+        }
+
+        /// Remove a session whose expiry has passed. Dispatched by the scheduler.
+        #[pallet::weight(1_000)]
+        pub fn expire_session(origin: OriginFor<T>, user: T::AccountId, operator: T::AccountId) -> DispatchResult {
+            ensure_root(origin)?;
+
+            if let Some((_, expiry)) = Self::session(&user, &operator) {
+                if expiry <= frame_system::Pallet::<T>::block_number() {
+                    Sessions::<T>::remove(&user, &operator);
+                    Self::deposit_event(Event::SessionExpired(user, operator));
+                }
+            }
+            Ok(())
+        }
+
+        /// Make `call` for `user`, as if they had made it, within a live session they
+        /// authorized the caller for.
+        #[pallet::weight(2_000)]
+        pub fn session_call(
+            origin: OriginFor<T>,
+            user: T::AccountId,
+            call: SessionCall<T::KittyIndex>,
+        ) -> DispatchResult {
+            let operator = ensure_signed(origin)?;
+
+            // Check the session is live and allows the call.
+            ensure!(Self::has_session(&user, &operator, call.mask()), Error::<T>::CallNotAuthorized);
+
+            let origin: OriginFor<T> = frame_system::RawOrigin::Signed(user.clone()).into();
+            match call {
+                SessionCall::Breed { kitty_id1, kitty_id2 } => Self::breed(origin, kitty_id1, kitty_id2),
+                SessionCall::RerollTrait { kitty_id, gene_index } => Self::reroll_trait(origin, kitty_id, gene_index),
+            }
+        }
+
+        /// Make `guardian` the caller's guardian: from then on the caller's transfers and sales
+        /// above `GuardianThreshold` wait for the guardian to approve or veto them, and the
+        /// caller can't export kitties, add co-owners, set an heir or authorize sessions, which
+        /// the guardian has no way to review. Only the guardian can end the guardianship, with
+        /// `release_ward`.
+        #[pallet::weight(1_000)]
+        pub fn set_guardian(origin: OriginFor<T>, guardian: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(guardian != who, Error::<T>::InvalidGuardian);
+            ensure!(!Guardians::<T>::contains_key(&who), Error::<T>::AlreadyGuarded);
+            Guardians::<T>::insert(&who, &guardian);
+
+            Self::touch(&who);
+            Self::deposit_event(Event::GuardianSet(who, guardian));
+            Ok(())
+        }
+
+        /// End the caller's guardianship of `ward`, dropping the actions waiting for them.
+        #[pallet::weight(1_000)]
+        pub fn release_ward(origin: OriginFor<T>, ward: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(Self::guardian(&ward) == Some(who.clone()), Error::<T>::NotGuardian);
+            Guardians::<T>::remove(&ward);
+            GuardedActions::<T>::remove(&ward);
+
+            Self::deposit_event(Event::WardReleased(ward, who));
+            Ok(())
+        }
+
+        /// Approve `ward`'s action on a kitty, carrying it out as if the ward made it now
+        /// without a guardian. A sale is listed for as long as a listing may last.
+        #[transactional]
+        #[pallet::weight(1_000)]
+        pub fn approve_guarded(origin: OriginFor<T>, ward: T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            match Self::take_guarded_action(&who, &ward, kitty_id)? {
+                CoOwnerAction::Transfer(new_owner) => Self::do_transfer(ward.clone(), new_owner, kitty_id)?,
+                CoOwnerAction::Sell(price) => Self::do_sell(ward.clone(), kitty_id, Some(price), None)?,
+            }
+
+            Self::deposit_event(Event::GuardedActionApproved(ward, kitty_id));
+            Ok(())
+        }
+
+        /// Reward every listing live at the end of an epoch with `reward_per_listing` from the
+        /// listing reward pot, paying out no more than `epoch_budget` for any one epoch. A zero
+        /// reward ends the program. Switching it on starts it from the current epoch; changing
+        /// the reward while it is on also changes it for epochs not claimed yet.
+        #[pallet::weight(1_000)]
+        pub fn set_listing_rewards(
+            origin: OriginFor<T>,
+            reward_per_listing: BalanceOf<T>,
+            epoch_budget: BalanceOf<T>,
+        ) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            if Self::listing_rewards().0.is_zero() {
+                let now = frame_system::Pallet::<T>::block_number();
+                ListingRewardsStart::<T>::put((now / T::RewardEpoch::get().max(One::one())).saturated_into::<u32>());
+            }
+            ListingRewards::<T>::put((reward_per_listing, epoch_budget));
+
+            Self::deposit_event(Event::ListingRewardsSet(reward_per_listing, epoch_budget));
+            Ok(())
+        }
+
+        /// Claim the reward for `epoch` of the next `MaxBulkListings` of the caller's listings,
+        /// for those that were live at the end of it, having been made at least
+        /// `MinRewardListingDuration` blocks before. A listing only counts while it is still
+        /// live, so claim before delisting. Each listing is rewarded once an epoch; call again
+        /// while `ListingRewardClaimed` reports it isn't done. Claims are paid until the
+        /// epoch's budget runs out, and epochs before the program was switched on earn nothing.
+        #[pallet::weight({
+            let listings: Weight = T::MaxBulkListings::get().into();
+            // Each listing, when it was made, its expiry and its reward, plus the one after.
+            T::DbWeight::get()
+                .reads_writes(4 * listings + 10, listings + 4)
+                .saturating_add(1_000u64.saturating_mul(listings))
+        })]
+        pub fn claim_listing_reward(origin: OriginFor<T>, epoch: u32) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let (reward, budget) = Self::listing_rewards();
+            ensure!(!reward.is_zero(), Error::<T>::ListingRewardsOff);
+            ensure!(epoch >= Self::listing_rewards_start(), Error::<T>::EpochBeforeRewards);
+            let epoch_end = T::RewardEpoch::get().saturating_mul(epoch.saturating_add(1).into());
+            ensure!(epoch_end <= frame_system::Pallet::<T>::block_number(), Error::<T>::EpochNotOver);
+
+            // Carry on after the last listing read for this epoch, if the previous claim didn't
+            // get through them all, or start over if the listings after it have all gone since.
+            let listings_after = |cursor: Option<T::KittyIndex>| match cursor {
+                Some(last) => ListingsBySeller::<T>::iter_prefix_from(
+                    &who, ListingsBySeller::<T>::hashed_key_for(&who, last),
+                ),
+                None => ListingsBySeller::<T>::iter_prefix(&who),
+            };
+            let cursor = RewardCursors::<T>::get(&who)
+                .filter(|(cursor_epoch, _)| *cursor_epoch == epoch)
+                .map(|(_, last)| last);
+            let batch_size = T::MaxBulkListings::get() as usize;
+            let mut listings = listings_after(cursor);
+            let mut batch: Vec<_> = listings.by_ref().take(batch_size).map(|(kitty_id, _)| kitty_id).collect();
+            if batch.is_empty() {
+                listings = listings_after(None);
+                batch = listings.by_ref().take(batch_size).map(|(kitty_id, _)| kitty_id).collect();
+            }
+            let done = listings.next().is_none();
+
+            // A listing made early enough and expiring after the end was live all through it,
+            // since relisting starts it over.
+            let listed_by = epoch_end.saturating_sub(T::MinRewardListingDuration::get());
+            let rewarded: Vec<T::KittyIndex> = batch
+                .iter()
+                .copied()
+                .filter(|&kitty_id| {
+                    Self::listed_since(kitty_id).map_or(true, |since| since <= listed_by)
+                        && Self::listing_expiry(kitty_id).map_or(true, |expires_at| expires_at > epoch_end)
+                        && !RewardedListings::<T>::contains_key(epoch, kitty_id)
+                })
+                .collect();
+            ensure!(!rewarded.is_empty() || !done, Error::<T>::NoRewardableListings);
+
+            // Pay what the listings earned, as far as the epoch's budget goes.
+            let mut amount = Zero::zero();
+            if !rewarded.is_empty() {
+                let paid = ListingRewardsPaid::<T>::get(epoch);
+                amount = reward
+                    .saturating_mul((rewarded.len() as u32).into())
+                    .min(budget.saturating_sub(paid));
+                ensure!(!amount.is_zero(), Error::<T>::EpochBudgetSpent);
+                T::Currency::transfer(&Self::reward_pot_account(), &who, amount, ExistenceRequirement::KeepAlive)
+                    .map_err(|_| Error::<T>::RewardPotEmpty)?;
+                for &kitty_id in &rewarded {
+                    RewardedListings::<T>::insert(epoch, kitty_id, ());
+                }
+                ListingRewardsPaid::<T>::insert(epoch, paid.saturating_add(amount));
+            }
+            match (done, batch.last()) {
+                (false, Some(last)) => RewardCursors::<T>::insert(&who, (epoch, *last)),
+                _ => RewardCursors::<T>::remove(&who),
+            }
+
+            Self::touch(&who);
+            Self::deposit_event(Event::ListingRewardClaimed(who, epoch, rewarded.len() as u32, amount, done));
+            Ok(())
+        }
+
+        /// Veto `ward`'s action on a kitty.
+        #[pallet::weight(1_000)]
+        pub fn veto_guarded(origin: OriginFor<T>, ward: T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::take_guarded_action(&who, &ward, kitty_id)?;
+
+            Self::deposit_event(Event::GuardedActionVetoed(ward, kitty_id));
+            Ok(())
+        }
+
+        /// Withdraw a `MintAuthorization` the caller signed, by using up its nonce.
+        #[pallet::weight(1_000)]
+        pub fn cancel_mint_nonce(origin: OriginFor<T>, nonce: u64) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!UsedMintNonces::<T>::contains_key(&who, nonce), Error::<T>::MintNonceUsed);
+
+            UsedMintNonces::<T>::insert(&who, nonce, ());
+
+            Self::deposit_event(Event::MintNonceCancelled(who, nonce));
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// The account holding the referral pot.
+        pub fn account_id() -> T::AccountId {
+            T::PalletId::get().into_account()
+        }
+
+        /// The account listing rewards are paid from, topped up by transferring to it.
+        pub fn reward_pot_account() -> T::AccountId {
+            T::PalletId::get().into_sub_account(b"listing-rewards")
+        }
+
+        /// Transfer a kitty of `who` to `new_owner`, as `transfer` does.
+        fn do_transfer(who: T::AccountId, new_owner: T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
+            // Check caller is kitty's owner, and its co-owners agree.
+            ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotKittyOwner);
+            ensure!(!Self::is_soulbound(kitty_id), Error::<T>::KittySoulbound);
+            Self::ensure_co_owners_approve(kitty_id, CoOwnerAction::Transfer(new_owner.clone()))?;
+
+            // Update the kitty's owner. (transfer to `new_owner`)
+            Self::dissolve_co_ownership(kitty_id);
+            Self::set_owner(kitty_id, &new_owner);
+            TotalTransfers::<T>::mutate(|n| *n = n.saturating_add(1));
+            Self::touch(&who);
+            Self::touch_kitty(kitty_id);
+
+            // Deposit a "KittyTransfer" event.
+            Self::deposit_event(Event::KittyTransfer(who, new_owner, kitty_id));
+            Ok(())
+        }
+
+        /// List or delist a kitty for `who`, as `sell_kitty` does.
+        fn do_sell(
+            who: T::AccountId,
             kitty_id: T::KittyIndex,
             price: Option<BalanceOf<T>>,
+            expires_at: Option<T::BlockNumber>,
         ) -> DispatchResult {
-            let who = ensure_signed(origin)?;
-
             // Ensure caller is the kitty owner.
             ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotKittyOwner);
 
+            // Check the expiry lies within the allowed duration.
+            let now = frame_system::Pallet::<T>::block_number();
+            let latest = now.saturating_add(Self::max_listing_duration());
+            let expires_at = expires_at.unwrap_or(latest);
+            ensure!(now < expires_at && expires_at <= latest, Error::<T>::InvalidListingExpiry);
+            if let Some(price) = price {
+                ensure!(
+                    !price.is_zero() && T::MinListingPrice::get() <= price && price <= T::MaxListingPrice::get(),
+                    Error::<T>::PriceOutOfBounds
+                );
+                ensure!(!Self::is_soulbound(kitty_id), Error::<T>::KittySoulbound);
+                Self::ensure_co_owners_approve(kitty_id, CoOwnerAction::Sell(price))?;
+                Self::ensure_resale_allowed(kitty_id)?;
+            }
+
             // Update the kitty price.
-            Price::<T>::insert(kitty_id, price);
+            Self::set_listing(kitty_id, price.map(|price| (price, expires_at)))?;
+            Self::touch(&who);
+            Self::touch_kitty(kitty_id);
 
             // Deposit a "KittySale" event.
             Self::deposit_event(Event::KittySale(who, kitty_id, price));
             Ok(())
         }
 
-        #[transactional]
-        #[pallet::weight(1_000)]
-        pub fn buy_kitty(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+        fn do_create(who: &T::AccountId, dna: [u8; 16]) -> Result<T::KittyIndex, DispatchError> {
+            let kitty_id = Self::mint(who, dna, 0)?;
+            HasMinted::<T>::insert(who, true);
+            Self::touch(who);
+
+            // Deposit a "KittyCreate" event.
+            Self::deposit_event(Event::KittyCreate(who.clone(), kitty_id));
+            Ok(kitty_id)
+        }
+
+        /// Mint a kitty with random dna to `owner`, reserving its deposit from `payer`, who gets
+        /// it back when the kitty is burned. For contracts and pallets paying for kitties on
+        /// behalf of their users.
+        pub fn create_for(payer: &T::AccountId, owner: &T::AccountId) -> Result<T::KittyIndex, DispatchError> {
+            ensure!(Self::get_id() != T::KittyIndex::max_value(), Error::<T>::KittiesCountOverflow);
+            let deposit = Self::current_deposit();
+            T::Currency::reserve(payer, deposit).map_err(|_| Error::<T>::InsufficientBalance)?;
+            let kitty_id = Self::mint_reserved(owner, Self::random_value(owner), 0, deposit)?;
+            KittyDeposits::<T>::insert(kitty_id, (payer.clone(), deposit));
+            Self::touch(owner);
+
+            Self::deposit_event(Event::KittyCreate(owner.clone(), kitty_id));
+            Ok(kitty_id)
+        }
+
+        /// Mint a soulbound kitty with `dna` to `owner`, reserving the deposit from them. For
+        /// other pallets handing out kitties that must stay with whoever earned them.
+        pub fn do_mint_soulbound(owner: &T::AccountId, dna: [u8; 16]) -> Result<T::KittyIndex, DispatchError> {
+            Self::validate_dna(&dna)?;
+            let kitty_id = Self::mint(owner, dna, 0)?;
+            Soulbound::<T>::insert(kitty_id, true);
+
+            Self::deposit_event(Event::SoulboundMinted(owner.clone(), kitty_id));
+            Ok(kitty_id)
+        }
+
+        /// The base kitty deposit, as set by governance or else `KittyDepositBase`.
+        pub fn deposit_base() -> BalanceOf<T> {
+            Self::parameters().deposit_base.unwrap_or_else(T::KittyDepositBase::get)
+        }
+
+        /// The referral reward, as set by governance or else `ReferralReward`.
+        pub fn referral_reward() -> BalanceOf<T> {
+            Self::parameters().referral_reward.unwrap_or_else(T::ReferralReward::get)
+        }
+
+        /// The longest a listing may last, as set by governance or else `MaxListingDuration`.
+        pub fn max_listing_duration() -> T::BlockNumber {
+            Self::parameters().max_listing_duration.unwrap_or_else(T::MaxListingDuration::get)
+        }
+
+        /// The `create_with_nonce` difficulty, as set by governance or else `PowDifficulty`.
+        pub fn pow_difficulty() -> u32 {
+            Self::parameters().pow_difficulty.unwrap_or_else(T::PowDifficulty::get)
+        }
+
+        /// The cut of a sale price paid into the pot, as set by governance or else `SaleFee`.
+        pub fn sale_fee() -> Permill {
+            Self::parameters().sale_fee.unwrap_or_else(T::SaleFee::get)
+        }
+
+        /// The deposit the next minted kitty will reserve.
+        pub fn current_deposit() -> BalanceOf<T> {
+            T::DepositCurve::deposit(Self::deposit_base(), Self::total_supply())
+        }
+
+        /// The deposit `who` reserves for the next kitty through `action`, after any discount
+        /// `DiscountProvider` grants them.
+        pub fn discounted_deposit(who: &T::AccountId, action: DiscountedAction) -> BalanceOf<T> {
+            let deposit = Self::current_deposit();
+            deposit.saturating_sub(T::DiscountProvider::discount(who, action) * deposit)
+        }
+
+        /// Record that `who` just used the pallet, postponing any claim by their heir.
+        fn touch(who: &T::AccountId) {
+            LastActive::<T>::insert(who, frame_system::Pallet::<T>::block_number());
+        }
+
+        /// Check DNA supplied from outside the chain before it is minted.
+        fn validate_dna(dna: &[u8; 16]) -> DispatchResult {
+            ensure!(dna::is_valid(dna), Error::<T>::InvalidDna);
+            Ok(())
+        }
+
+        /// Mint a kitty with `dna`, sealed in the current layout, to `owner`, reserving the
+        /// current deposit, less their discount, from them.
+        fn mint(owner: &T::AccountId, dna: [u8; 16], generation: u32) -> Result<T::KittyIndex, DispatchError> {
+            // Check the id is valid before reserving for the new kitty.
+            ensure!(Self::get_id() != T::KittyIndex::max_value(), Error::<T>::KittiesCountOverflow);
+            let deposit = Self::discounted_deposit(owner, DiscountedAction::Mint);
+            T::Currency::reserve(owner, deposit).map_err(|_| Error::<T>::InsufficientBalance)?;
+            Self::mint_reserved(owner, dna, generation, deposit)
+        }
+
+        /// Mint a kitty like `mint`, for which `deposit` has already been reserved from `owner`.
+        fn mint_reserved(
+            owner: &T::AccountId,
+            dna: [u8; 16],
+            generation: u32,
+            deposit: BalanceOf<T>,
+        ) -> Result<T::KittyIndex, DispatchError> {
+            // Generate kitty id, checking the id is valid.
+            let kitty_id = Self::get_id();
+            ensure!(kitty_id != T::KittyIndex::max_value(), Error::<T>::KittiesCountOverflow);
+
+            // Update chain's data.
+            Kitties::<T>::insert(kitty_id, Some(Kitty(dna::seal(dna))));
+            Self::set_owner(kitty_id, owner);
+            Generation::<T>::insert(kitty_id, generation);
+            KittyDeposits::<T>::insert(kitty_id, (owner.clone(), deposit));
+            DepositBases::<T>::insert(kitty_id, Self::deposit_base());
+            KittiesCount::<T>::put(kitty_id + 1u32.into());
+            TotalSupply::<T>::mutate(|n| *n = n.saturating_add(1));
+            Ok(kitty_id)
+        }
+
+        /// Hand a kitty to `owner`, recording it in the kitty's owner history.
+        fn set_owner(kitty_id: T::KittyIndex, owner: &T::AccountId) {
+            if PendingBurns::<T>::take(kitty_id).is_some() {
+                let _ = T::Scheduler::cancel_named(Self::burn_task_id(kitty_id));
+            }
+            if let Some(previous) = Owner::<T>::get(kitty_id) {
+                Self::count_owned(&previous, false);
+                KittiesOwned::<T>::remove(&previous, kitty_id);
+                // A listing outlives a transfer, so it moves to the new owner.
+                if ListingsBySeller::<T>::take(&previous, kitty_id).is_some() {
+                    ListingsBySeller::<T>::insert(owner, kitty_id, ());
+                }
+            }
+            Owner::<T>::insert(kitty_id, Some(owner.clone()));
+            Self::count_owned(owner, true);
+            KittiesOwned::<T>::insert(owner, kitty_id, ());
+            OwnerHistory::<T>::mutate(kitty_id, |history| {
+                if history.len() as u32 >= T::MaxOwnersTracked::get() && !history.is_empty() {
+                    history.remove(0);
+                }
+                let _ = history.try_push((owner.clone(), frame_system::Pallet::<T>::block_number()));
+            });
+        }
+
+        /// Add or take one kitty from `who`'s owned count, and update the owners leaderboard.
+        /// The pallet's own account only holds kitties in escrow, so it isn't counted, and is
+        /// taken off the board should an earlier version have counted it.
+        fn count_owned(who: &T::AccountId, gained: bool) {
+            if *who == Self::account_id() {
+                OwnedCount::<T>::remove(who);
+                TopOwners::<T>::mutate(|board| Self::rank(board, who.clone(), 0));
+                return
+            }
+            let owned = OwnedCount::<T>::mutate(who, |n| {
+                *n = if gained { n.saturating_add(1) } else { n.saturating_sub(1) };
+                *n
+            });
+            TopOwners::<T>::mutate(|board| Self::rank(board, who.clone(), owned));
+        }
+
+        /// Set `key`'s score on a leaderboard kept sorted highest first. A zero score takes
+        /// `key` off the board, and the lowest entry drops off when the board is full.
+        fn rank<K: PartialEq, V: PartialOrd + Zero, S: Get<u32>>(
+            board: &mut BoundedVec<(K, V), S>,
+            key: K,
+            score: V,
+        ) {
+            let mut entries = core::mem::take(board).into_inner();
+            entries.retain(|(entry, _)| *entry != key);
+            if !score.is_zero() {
+                let position = entries.iter().position(|(_, s)| *s < score).unwrap_or(entries.len());
+                entries.insert(position, (key, score));
+                entries.truncate(S::get() as usize);
+            }
+            *board = entries.try_into().unwrap_or_default();
+        }
+
+        /// Fail unless the co-owners of a kitty, if any, approved `action` by a majority of
+        /// shares. The owner's own share counts as approving. Consumes the approvals.
+        fn ensure_co_owners_approve(
+            kitty_id: T::KittyIndex,
+            action: CoOwnerAction<T::AccountId, BalanceOf<T>>,
+        ) -> DispatchResult {
+            let co_owners = Self::co_owners(kitty_id);
+            if co_owners.is_empty() {
+                return Ok(());
+            }
+
+            let approvers = match CoOwnerApprovals::<T>::get(kitty_id) {
+                Some((approved, approvers)) if approved == action => approvers.into_inner(),
+                _ => Vec::new(),
+            };
+            let in_favour = co_owners.iter()
+                .filter(|(account, _)| !approvers.contains(account))
+                .fold(Permill::ACCURACY, |in_favour, (_, share)| in_favour.saturating_sub(share.deconstruct()));
+            ensure!(in_favour * 2 > Permill::ACCURACY, Error::<T>::NotApprovedByCoOwners);
 
+            CoOwnerApprovals::<T>::remove(kitty_id);
+            Ok(())
+        }
+
+        /// Whether `who`'s `action` on a kitty must wait for their guardian. A transfer is
+        /// valued at the deposit the kitty holds, a sale at its price.
+        fn needs_guardian(
+            who: &T::AccountId,
+            kitty_id: T::KittyIndex,
+            action: &CoOwnerAction<T::AccountId, BalanceOf<T>>,
+        ) -> bool {
+            if !Guardians::<T>::contains_key(who) {
+                return false;
+            }
+            let value = match action {
+                CoOwnerAction::Transfer(_) => KittyDeposits::<T>::get(kitty_id)
+                    .map_or_else(Zero::zero, |(_, deposit)| deposit),
+                CoOwnerAction::Sell(price) => *price,
+            };
+            value > T::GuardianThreshold::get()
+        }
+
+        /// Fail if `who` has a guardian, for calls the guardian can't review.
+        fn ensure_unguarded(who: &T::AccountId) -> DispatchResult {
+            ensure!(!Guardians::<T>::contains_key(who), Error::<T>::CallerIsWard);
+            Ok(())
+        }
+
+        /// Queue `who`'s `action` on a kitty for their guardian if it needs them, replacing
+        /// any action on the kitty already waiting. Returns whether it was queued.
+        fn await_guardian(
+            who: &T::AccountId,
+            kitty_id: T::KittyIndex,
+            action: CoOwnerAction<T::AccountId, BalanceOf<T>>,
+        ) -> Result<bool, DispatchError> {
+            if !Self::needs_guardian(who, kitty_id, &action) {
+                return Ok(false);
+            }
+            ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotKittyOwner);
+
+            let now = frame_system::Pallet::<T>::block_number();
+            let expires_at = now.saturating_add(T::GuardedActionExpiry::get());
+            GuardedActions::<T>::try_mutate(who, |actions| -> DispatchResult {
+                actions.retain(|(id, _, expires_at)| *id != kitty_id && *expires_at > now);
+                actions.try_push((kitty_id, action.clone(), expires_at))
+                    .map_err(|_| Error::<T>::TooManyGuardedActions)?;
+                Ok(())
+            })?;
+
+            Self::touch(who);
+            Self::deposit_event(Event::GuardianApprovalRequested(who.clone(), kitty_id, action, expires_at));
+            Ok(true)
+        }
+
+        /// Take `ward`'s action on a kitty waiting for `guardian`, unless it has lapsed.
+        fn take_guarded_action(
+            guardian: &T::AccountId,
+            ward: &T::AccountId,
+            kitty_id: T::KittyIndex,
+        ) -> Result<CoOwnerAction<T::AccountId, BalanceOf<T>>, DispatchError> {
+            ensure!(Self::guardian(ward).as_ref() == Some(guardian), Error::<T>::NotGuardian);
+
+            let now = frame_system::Pallet::<T>::block_number();
+            GuardedActions::<T>::try_mutate(ward, |actions| {
+                actions.retain(|(_, _, expires_at)| *expires_at > now);
+                let position = actions.iter()
+                    .position(|(id, _, _)| *id == kitty_id)
+                    .ok_or(Error::<T>::NoGuardedAction)?;
+                Ok(actions.remove(position).1)
+            })
+        }
+
+        fn dissolve_co_ownership(kitty_id: T::KittyIndex) {
+            CoOwners::<T>::remove(kitty_id);
+            CoOwnerApprovals::<T>::remove(kitty_id);
+        }
+
+        /// Remove a kitty and refund its deposit to whoever reserved it.
+        fn burn(kitty_id: T::KittyIndex, owner: &T::AccountId) {
+            Self::remove_listing(kitty_id);
+            Self::dissolve_co_ownership(kitty_id);
+            if PendingRerolls::<T>::take(kitty_id).is_some() {
+                let _ = T::Scheduler::cancel_named(Self::reroll_task_id(kitty_id));
+            }
+            if PendingBurns::<T>::take(kitty_id).is_some() {
+                let _ = T::Scheduler::cancel_named(Self::burn_task_id(kitty_id));
+            }
+            RerollCount::<T>::remove(kitty_id);
+            Kitties::<T>::remove(kitty_id);
+            Owner::<T>::remove(kitty_id);
+            Self::count_owned(owner, false);
+            KittiesOwned::<T>::remove(owner, kitty_id);
+            OwnerHistory::<T>::remove(kitty_id);
+            Generation::<T>::remove(kitty_id);
+            // Kitties minted before deposits were recorded reserved the base from their owner.
+            let (depositor, deposit) = KittyDeposits::<T>::take(kitty_id)
+                .unwrap_or_else(|| (owner.clone(), T::KittyDepositBase::get()));
+            T::Currency::unreserve(&depositor, deposit);
+            DepositBases::<T>::remove(kitty_id);
+            AcquiredAt::<T>::remove(kitty_id);
+            TradeStreaks::<T>::remove(kitty_id);
+            Royalties::<T>::remove(kitty_id);
+            Soulbound::<T>::remove(kitty_id);
+            TotalSupply::<T>::mutate(|n| *n = n.saturating_sub(1));
+        }
+
+        /// Buy `kitty_id` for `who`, paying no more than `max_payment`, if given.
+        fn do_buy(
+            who: T::AccountId,
+            kitty_id: T::KittyIndex,
+            max_payment: Option<BalanceOf<T>>,
+            donate: Option<BalanceOf<T>>,
+        ) -> DispatchResult {
             // Ensure the kitty is exist and its owner is not the buyer.
             ensure!(Kitties::<T>::contains_key(kitty_id), Error::<T>::InvalidKittyIndex);
             let from = Owner::<T>::get(kitty_id).unwrap();
             ensure!(who.clone() != from, Error::<T>::BuyFromSelf);
 
-            // Get the price, and do the reserve and unreserve things.
-            let price = Self::price(kitty_id).ok_or(Error::<T>::KittyNotForSale)?;
-            let reserve = T::KittyDepositBase::get();
-            T::Currency::reserve(&who, reserve).map_err(|_| Error::<T>::InsufficientBalance)?;
-            T::Currency::unreserve(&from, reserve);
+            // Get the price, converting one in the reference unit at the current rate, and move
+            // the kitty's deposit from the seller to the buyer.
+            let mut price = Self::live_price(kitty_id).ok_or(Error::<T>::KittyNotForSale)?;
+            if let Some(reference_price) = Self::reference_price(kitty_id) {
+                ensure!(max_payment.is_some(), Error::<T>::ReferencePriced);
+                price = T::ExchangeRate::to_native(reference_price).ok_or(Error::<T>::NoExchangeRate)?;
+            }
+            if let Some(max_payment) = max_payment {
+                ensure!(price <= max_payment, Error::<T>::PriceSlippageExceeded);
+            }
+            Self::move_deposit(kitty_id, &from, &who)?;
+
+            // Take the sale fee, reduced or waived when an approved marketplace settles the sale,
+            // and raised for every sale of the kitty between the two accounts past the threshold.
+            let marketplace_fee = Self::approved_marketplace(&who);
+            let fee = marketplace_fee.map_or(Self::sale_fee(), |fee| fee.min(Self::sale_fee())) * price;
+            let fee = fee.saturating_add(Self::wash_trade_fee(kitty_id, &who, &from, price)).min(price);
+            if !fee.is_zero() {
+                T::Currency::transfer(&who, &Self::account_id(), fee, ExistenceRequirement::KeepAlive)?;
+            }
 
-            // Transfer balance to kitty owner
+            // Pay the creator's royalty and the co-owners their shares of the rest, and the kitty
+            // owner what remains.
+            let mut proceeds = price.saturating_sub(fee);
+            if let Some((creator, royalty)) = Self::royalty_due(kitty_id, &from, proceeds) {
+                T::Currency::transfer(&who, &creator, royalty, ExistenceRequirement::KeepAlive)?;
+                proceeds = proceeds.saturating_sub(royalty);
+                Self::deposit_event(Event::RoyaltyPaid(creator, kitty_id, royalty));
+            }
+            let mut to_owner = proceeds;
+            for (co_owner, share) in Self::co_owners(kitty_id) {
+                let cut = share * proceeds;
+                T::Currency::transfer(&who, &co_owner, cut, ExistenceRequirement::KeepAlive)?;
+                to_owner = to_owner.saturating_sub(cut);
+            }
             T::Currency::transfer(
                 &who, &from, 
-                price, ExistenceRequirement::KeepAlive,
+                to_owner, ExistenceRequirement::KeepAlive,
             )?;
 
+            // Pass the donation on, if any.
+            if let Some(donation) = donate {
+                let beneficiary = Self::donation_beneficiary().ok_or(Error::<T>::NoDonationBeneficiary)?;
+                T::Currency::transfer(&who, &beneficiary, donation, ExistenceRequirement::KeepAlive)?;
+                TotalDonations::<T>::mutate(|total| *total = total.saturating_add(donation));
+            }
+
             // Update chain's data, changing the kitty owner to caller.
-            Price::<T>::remove(kitty_id);  // Not for sale.
-            Owner::<T>::insert(kitty_id, Some(who.clone()));
+            Self::remove_listing(kitty_id);  // Not for sale.
+            Self::dissolve_co_ownership(kitty_id);
+            Self::set_owner(kitty_id, &who);
+            AcquiredAt::<T>::insert(kitty_id, frame_system::Pallet::<T>::block_number());
+            TotalSales::<T>::mutate(|n| *n = n.saturating_add(1));
+            TotalVolume::<T>::mutate(|v| *v = v.saturating_add(price));
+            TopSales::<T>::mutate(|board| {
+                let best = board.iter()
+                    .find(|(id, _)| *id == kitty_id)
+                    .map_or(price, |(_, best)| price.max(*best));
+                Self::rank(board, kitty_id, best)
+            });
+            Self::touch(&who);
 
             // Deposit a "KittyTransfer" event.
-            Self::deposit_event(Event::KittyTransfer(from, who, kitty_id));
+            Self::deposit_event(Event::KittyTransfer(from.clone(), who.clone(), kitty_id));
+            Self::deposit_event(Event::Bought(who.clone(), from, kitty_id, price, donate));
+            if marketplace_fee.is_some() {
+                Self::deposit_event(Event::MarketplaceAttribution(who, kitty_id, fee));
+            }
             Ok(())
         }
-    }
 
-    impl<T: Config> Pallet<T> {
+        /// Pay the seller of a kitty paid off in installments out of the buyer's reserve, and
+        /// release the kitty from escrow to the buyer. Fails if the reserve falls short, e.g.
+        /// after part of it was slashed; the caller is transactional, so nothing is paid then.
+        fn complete_installments(
+            kitty_id: T::KittyIndex,
+            plan: InstallmentPlan<T::AccountId, BalanceOf<T>, T::BlockNumber>,
+        ) -> DispatchResult {
+            let InstallmentPlan { buyer, seller, price, .. } = plan;
+
+            // Take the sale fee, raised for wash trades as in `do_buy`, and any royalty, and pay
+            // the seller the rest.
+            let fee = (Self::sale_fee() * price)
+                .saturating_add(Self::wash_trade_fee(kitty_id, &buyer, &seller, price))
+                .min(price);
+            Self::pay_from_reserve(&buyer, &Self::account_id(), fee)?;
+            let mut proceeds = price.saturating_sub(fee);
+            if let Some((creator, royalty)) = Self::royalty_due(kitty_id, &seller, proceeds) {
+                Self::pay_from_reserve(&buyer, &creator, royalty)?;
+                proceeds = proceeds.saturating_sub(royalty);
+                Self::deposit_event(Event::RoyaltyPaid(creator, kitty_id, royalty));
+            }
+            Self::pay_from_reserve(&buyer, &seller, proceeds)?;
+            Self::move_deposit(kitty_id, &seller, &buyer)?;
+
+            // Update chain's data, changing the kitty owner to the buyer.
+            Installments::<T>::remove(kitty_id);
+            Self::set_owner(kitty_id, &buyer);
+            AcquiredAt::<T>::insert(kitty_id, frame_system::Pallet::<T>::block_number());
+            TotalSales::<T>::mutate(|n| *n = n.saturating_add(1));
+            TotalVolume::<T>::mutate(|v| *v = v.saturating_add(price));
+            TopSales::<T>::mutate(|board| {
+                let best = board.iter()
+                    .find(|(id, _)| *id == kitty_id)
+                    .map_or(price, |(_, best)| price.max(*best));
+                Self::rank(board, kitty_id, best)
+            });
+
+            Self::deposit_event(Event::KittyTransfer(seller.clone(), buyer.clone(), kitty_id));
+            Self::deposit_event(Event::Bought(buyer, seller, kitty_id, price, None));
+            Ok(())
+        }
+
+        /// Move `amount` of `payer`'s reserve to `payee`'s free balance, all or nothing.
+        fn pay_from_reserve(payer: &T::AccountId, payee: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+            let unpaid = T::Currency::repatriate_reserved(payer, payee, amount, BalanceStatus::Free)?;
+            ensure!(unpaid.is_zero(), Error::<T>::InsufficientBalance);
+            Ok(())
+        }
+
+        /// Hand the kitty's deposit over to `buyer`: the buyer reserves it, rebalanced to the
+        /// current deposit base, and whoever reserved it before gets it back.
+        fn move_deposit(kitty_id: T::KittyIndex, seller: &T::AccountId, buyer: &T::AccountId) -> DispatchResult {
+            let (depositor, held) = KittyDeposits::<T>::get(kitty_id)
+                .unwrap_or_else(|| (seller.clone(), T::KittyDepositBase::get()));
+            let (deposit, base) = Self::rebased_deposit(kitty_id, held);
+            T::Currency::reserve(buyer, deposit).map_err(|_| Error::<T>::InsufficientBalance)?;
+            T::Currency::unreserve(&depositor, held);
+            KittyDeposits::<T>::insert(kitty_id, (buyer.clone(), deposit));
+            DepositBases::<T>::insert(kitty_id, base);
+            Ok(())
+        }
+
+        /// What a kitty's deposit `held` comes to under the current deposit base, scaled from
+        /// the base it was reserved under, and the current base.
+        fn rebased_deposit(kitty_id: T::KittyIndex, held: BalanceOf<T>) -> (BalanceOf<T>, BalanceOf<T>) {
+            let base = Self::deposit_base();
+            let held_base = DepositBases::<T>::get(kitty_id).unwrap_or_else(T::KittyDepositBase::get);
+            if held_base == base {
+                (held, base)
+            } else if held_base.is_zero() {
+                (base, base)
+            } else {
+                (held.saturating_mul(base) / held_base, base)
+            }
+        }
+
+        /// Top up or refund a kitty's deposit to match the current deposit base.
+        fn rebalance(kitty_id: T::KittyIndex) -> DispatchResult {
+            let owner = Owner::<T>::get(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+            // Kitties minted before deposits were recorded reserved the base from their owner.
+            let (depositor, held) = KittyDeposits::<T>::get(kitty_id)
+                .unwrap_or_else(|| (owner, T::KittyDepositBase::get()));
+            let (deposit, base) = Self::rebased_deposit(kitty_id, held);
+            if deposit > held {
+                T::Currency::reserve(&depositor, deposit - held).map_err(|_| Error::<T>::InsufficientBalance)?;
+            } else {
+                T::Currency::unreserve(&depositor, held - deposit);
+            }
+            KittyDeposits::<T>::insert(kitty_id, (depositor, deposit));
+            DepositBases::<T>::insert(kitty_id, base);
+
+            if deposit != held {
+                Self::deposit_event(Event::DepositRebalanced(kitty_id, held, deposit));
+            }
+            Ok(())
+        }
+
+        /// Rebalance a kitty the caller just used, if its depositor can afford it.
+        fn touch_kitty(kitty_id: T::KittyIndex) {
+            let _ = Self::rebalance(kitty_id);
+        }
+
         pub fn random_value(sender: &T::AccountId) -> [u8; 16] {
             let payload = (
                 T::Randomness::random_seed(),
@@ -216,6 +2770,19 @@ pub mod pallet {
             payload.using_encoded(blake2_128)
         }
 
+        /// The block hash `create_with_nonce` mixes into the dna: the hash of the last block
+        /// numbered a multiple of `PowSeedPeriod`, so a mined nonce stays valid for that long.
+        pub fn pow_seed() -> T::Hash {
+            let parent = frame_system::Pallet::<T>::block_number().saturating_sub(One::one());
+            let period = T::PowSeedPeriod::get().max(One::one());
+            frame_system::Pallet::<T>::block_hash(parent - parent % period)
+        }
+
+        /// The dna `create_with_nonce(nonce)` mints for `who` right now.
+        pub fn pow_dna(who: &T::AccountId, nonce: u64) -> [u8; 16] {
+            (who, nonce, Self::pow_seed()).using_encoded(blake2_128)
+        }
+
         pub fn get_id() -> T::KittyIndex {
             match Self::kitties_count() {
                 Some(id) => id,
@@ -223,14 +2790,584 @@ pub mod pallet {
             }
         }
 
-        pub fn breed_dna(who: &T::AccountId, kitty1: &Kitty, kitty2: &Kitty) -> [u8; 16] {
-            let dna1 = kitty1.0;
-            let dna2 = kitty2.0;
-            let mut mix_dna = Self::random_value(&who);
-            for i in 0..dna1.len() {
-                mix_dna[i] = (mix_dna[i] & dna1[i]) | (!mix_dna[i] & dna2[i]);
+        /// The dna of the child of a pregnancy, inheriting one random allele of each gene from
+        /// each parent (see [`genetics`]). The alleles are drawn when the kitty is born.
+        pub fn breed_dna(pregnancy_id: PregnancyId, dna1: &[u8; 16], dna2: &[u8; 16]) -> [u8; 16] {
+            let (seed, _) = T::Randomness::random(&(b"kitties/birth", pregnancy_id).encode());
+            genetics::breed(dna1, dna2, &seed.using_encoded(blake2_128))
+        }
+
+        /// The dna `fuse` mints from `dnas`, the dna of `kitty_ids`: each bred into the result of
+        /// the ones before, then raised to the rarest tier among them.
+        pub fn fuse_dna(kitty_ids: &[T::KittyIndex], dnas: &[[u8; 16]]) -> [u8; 16] {
+            let (seed, _) = T::Randomness::random(&(b"kitties/fuse", kitty_ids).encode());
+            let mut dna = dnas.iter().enumerate().skip(1).fold(dnas[0], |dna, (i, other)| {
+                genetics::breed(&dna, other, &(&seed, i as u32).using_encoded(blake2_128))
+            });
+            let floor = dnas.iter().map(RarityTier::from_dna).max().unwrap_or(RarityTier::Common);
+            dna[0] = floor.raise(dna[0]);
+            dna
+        }
+
+        /// Mint the kitty of a pregnancy that is due.
+        fn birth(
+            pregnancy_id: PregnancyId,
+            pregnancy: Pregnancy<T::AccountId, BalanceOf<T>, T::BlockNumber>,
+        ) -> DispatchResult {
+            let (dna1, dna2) = pregnancy.parents;
+            let dna = Self::breed_dna(pregnancy_id, &dna1, &dna2);
+            let kitty_id = Self::mint_reserved(&pregnancy.owner, dna, pregnancy.generation, pregnancy.deposit)?;
+
+            // Deposit a "KittyCreate" event.
+            Self::deposit_event(Event::KittyCreate(pregnancy.owner, kitty_id));
+            Ok(())
+        }
+
+        /// Fail unless breeding is unrestricted or a scheduled window is open, pruning windows
+        /// that have ended along the way. Once the last window has ended, breeding is
+        /// unrestricted again.
+        fn ensure_breeding_open() -> DispatchResult {
+            BreedingWindows::<T>::try_mutate_exists(|maybe_windows| -> DispatchResult {
+                if let Some(windows) = maybe_windows {
+                    let now = frame_system::Pallet::<T>::block_number();
+                    windows.retain(|(_, end)| *end > now);
+                    if windows.is_empty() {
+                        *maybe_windows = None;
+                    } else {
+                        ensure!(windows.iter().any(|(start, _)| *start <= now), Error::<T>::BreedingClosed);
+                    }
+                }
+                Ok(())
+            })
+        }
+
+        pub fn stats() -> KittyStats<BalanceOf<T>> {
+            KittyStats {
+                total_transfers: TotalTransfers::<T>::get(),
+                total_sales: TotalSales::<T>::get(),
+                total_volume: TotalVolume::<T>::get(),
+                total_breeds: TotalBreeds::<T>::get(),
+            }
+        }
+
+        /// The limits and settings currently in force, for front-ends to display.
+        pub fn config() -> KittiesConfig<T::AccountId, BalanceOf<T>, T::BlockNumber> {
+            KittiesConfig {
+                chain_id: T::ChainId::get(),
+                deposit_base: Self::deposit_base(),
+                current_deposit: Self::current_deposit(),
+                referral_reward: Self::referral_reward(),
+                max_listing_duration: Self::max_listing_duration(),
+                pow_difficulty: Self::pow_difficulty(),
+                pow_seed_period: T::PowSeedPeriod::get(),
+                max_owners_tracked: T::MaxOwnersTracked::get(),
+                max_breeding_windows: T::MaxBreedingWindows::get(),
+                breeding_windows: Self::breeding_windows().map(|windows| windows.into_inner()),
+                donation_beneficiary: Self::donation_beneficiary(),
+                sale_fee: Self::sale_fee(),
+                min_listing_price: T::MinListingPrice::get(),
+                max_listing_price: T::MaxListingPrice::get(),
+            }
+        }
+
+        pub fn leaderboards() -> Leaderboards<T::AccountId, T::KittyIndex, BalanceOf<T>> {
+            Leaderboards {
+                top_owners: TopOwners::<T>::get().into_inner(),
+                top_sales: TopSales::<T>::get().into_inner(),
+                top_breeders: TopBreeders::<T>::get().into_inner(),
+            }
+        }
+
+        /// The kitties with ids from `start` up to `start + limit` (`limit` clamped to between 1
+        /// and `MAX_PAGE_SIZE`, so every page moves the cursor on), and the id the next page
+        /// starts at, or `None` after the last kitty. Ids are never reused, so the cursor stays
+        /// valid as kitties are minted and burned; burned kitties are skipped, so a page can
+        /// hold fewer than `limit` kitties.
+        pub fn kitties_page(
+            start: T::KittyIndex,
+            limit: u32,
+        ) -> (Vec<KittyInfo<T::AccountId, T::KittyIndex, BalanceOf<T>>>, Option<T::KittyIndex>) {
+            let count = Self::kitties_count().unwrap_or_else(Zero::zero);
+            let end = start.saturating_add(limit.clamp(1, MAX_PAGE_SIZE).into()).min(count);
+
+            let mut page = Vec::new();
+            let mut kitty_id = start;
+            while kitty_id < end {
+                if let (Some(kitty), Some(owner)) = (Self::kitties(kitty_id), Self::owner(kitty_id)) {
+                    page.push(KittyInfo {
+                        id: kitty_id,
+                        dna: kitty.0,
+                        owner,
+                        generation: Self::generation(kitty_id),
+                        price: Self::live_price(kitty_id),
+                    });
+                }
+                kitty_id += One::one();
+            }
+            (page, if end < count { Some(end) } else { None })
+        }
+
+        /// The cheapest kitty for sale in `tier`, with its price, from `CheapestListings`. `None`
+        /// while `on_idle` is still looking for it, or if it has expired but not been taken down
+        /// yet.
+        pub fn cheapest_listing(tier: RarityTier) -> Option<(T::KittyIndex, BalanceOf<T>)> {
+            CheapestListings::<T>::get(tier).filter(|(kitty_id, _)| !Self::is_expired(*kitty_id))
+        }
+
+        /// List `kitty_id` at `price` under `tier` in `ListingsByRarity`, or take it off with
+        /// `None`, keeping `CheapestListings` up to date in a bounded number of reads.
+        fn index_listing(tier: RarityTier, kitty_id: T::KittyIndex, price: Option<BalanceOf<T>>) {
+            match price {
+                Some(price) => ListingsByRarity::<T>::insert(tier, kitty_id, price),
+                None => ListingsByRarity::<T>::remove(tier, kitty_id),
+            }
+            if let Some((last, best)) = CheapestRescans::<T>::get(tier) {
+                // Losing the cheapest listing found so far starts the search over.
+                let rescan = match (best, price) {
+                    (Some((_, best_price)), Some(price)) if price <= best_price => (last, Some((kitty_id, price))),
+                    (Some((best, _)), _) if best == kitty_id => (None, None),
+                    (None, Some(price)) => (last, Some((kitty_id, price))),
+                    (best, _) => (last, best),
+                };
+                CheapestRescans::<T>::insert(tier, rescan);
+                return
+            }
+            match (CheapestListings::<T>::get(tier), price) {
+                (Some((_, cheapest_price)), Some(price)) if price <= cheapest_price =>
+                    CheapestListings::<T>::insert(tier, (kitty_id, price)),
+                // The cheapest listing went or got dearer, so another may be cheapest now.
+                (Some((cheapest, _)), _) if cheapest == kitty_id => {
+                    CheapestListings::<T>::remove(tier);
+                    CheapestRescans::<T>::insert(tier, (None, None));
+                },
+                // A tier without one is empty, unless it was listed in before `CheapestListings`
+                // was kept.
+                (None, Some(price)) => {
+                    if ListingsByRarity::<T>::iter_prefix(tier).nth(1).is_some() {
+                        CheapestRescans::<T>::insert(tier, (None, None));
+                    } else {
+                        CheapestListings::<T>::insert(tier, (kitty_id, price));
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        /// The most `index_listing` reads and writes: the search and cheapest listing of the tier,
+        /// and up to two of its listings to tell whether it has others.
+        fn index_listing_weight() -> Weight {
+            T::DbWeight::get().reads_writes(4, 3)
+        }
+
+        /// The most `remove_listing` reads and writes, taking the listing out of its tier too.
+        fn remove_listing_weight() -> Weight {
+            // Cancelling the expiry, the kitty and its owner, and the listing's entries.
+            T::DbWeight::get().reads_writes(4, 7).saturating_add(Self::index_listing_weight())
+        }
+
+        /// Repair the storage of kitties from `RepairCursor` on with up to `remaining_weight`.
+        /// Returns the weight used.
+        fn repair_some(remaining_weight: Weight) -> Weight {
+            let db = T::DbWeight::get();
+            // The most visiting a kitty can take, see `repair`: removing its listing twice and
+            // taking it out of, or filing it under, every tier besides everything else it may
+            // have left behind.
+            let per_kitty = db
+                .reads_writes(28, 9)
+                .saturating_add(Self::remove_listing_weight().saturating_mul(2))
+                .saturating_add(Self::index_listing_weight().saturating_mul(4));
+            // Reading the kitty count and the cursor, and writing the cursor back.
+            let mut used = db.reads_writes(2, 1);
+            let count = Self::get_id();
+            if count.is_zero() || used.saturating_add(per_kitty) > remaining_weight {
+                return 0;
+            }
+
+            let mut cursor = RepairCursor::<T>::get().filter(|id| *id < count).unwrap_or_default();
+            let mut visited = T::KittyIndex::zero();
+            while visited < count && used.saturating_add(per_kitty) <= remaining_weight {
+                let (repaired, weight) = Self::repair(cursor);
+                used = used.saturating_add(weight);
+                if repaired {
+                    Self::deposit_event(Event::StorageRepaired(cursor));
+                }
+                visited += One::one();
+                cursor = if cursor + One::one() < count { cursor + One::one() } else { Zero::zero() };
+            }
+            RepairCursor::<T>::put(cursor);
+            used
+        }
+
+        /// Carry on looking for the cheapest listing of the tiers in `CheapestRescans`, using up
+        /// to `remaining_weight`. Returns the weight used.
+        fn continue_rescans(remaining_weight: Weight) -> Weight {
+            let db = T::DbWeight::get();
+            let per_listing = db.reads(1);
+            // Reading which of the four tiers are being searched.
+            let mut used = db.reads(5);
+            if used > remaining_weight {
+                return 0
+            }
+            let tiers: Vec<RarityTier> = CheapestRescans::<T>::iter_keys().collect();
+            for tier in tiers {
+                // Reading the search and the listing after the last one read, and recording the
+                // search or the cheapest listing.
+                let per_tier = db.reads_writes(2, 2);
+                if used.saturating_add(per_tier).saturating_add(per_listing) > remaining_weight {
+                    break
+                }
+                used = used.saturating_add(per_tier);
+                let limit = (remaining_weight - used) / per_listing.max(1);
+                let read = Self::continue_rescan(tier, limit.min(u32::MAX.into()) as u32);
+                used = used.saturating_add(per_listing.saturating_mul(read.into()));
+            }
+            used
+        }
+
+        /// Read up to `limit` more listings of `tier` for its cheapest, recording it in
+        /// `CheapestListings` once the whole tier has been read. Returns how many were read.
+        fn continue_rescan(tier: RarityTier, limit: u32) -> u32 {
+            let (mut last, mut best) = match CheapestRescans::<T>::get(tier) {
+                Some(rescan) => rescan,
+                None => return 0,
+            };
+            let mut listings = match last {
+                Some(last) => ListingsByRarity::<T>::iter_prefix_from(
+                    tier, ListingsByRarity::<T>::hashed_key_for(tier, last),
+                ),
+                None => ListingsByRarity::<T>::iter_prefix(tier),
+            };
+            for read in 0..limit {
+                match listings.next() {
+                    Some((kitty_id, price)) => {
+                        last = Some(kitty_id);
+                        if best.map_or(true, |(_, best_price)| price < best_price) {
+                            best = Some((kitty_id, price));
+                        }
+                    },
+                    None => {
+                        match best {
+                            Some(cheapest) => CheapestListings::<T>::insert(tier, cheapest),
+                            None => CheapestListings::<T>::remove(tier),
+                        }
+                        CheapestRescans::<T>::remove(tier);
+                        return read
+                    },
+                }
+            }
+            CheapestRescans::<T>::insert(tier, (last, best));
+            limit
+        }
+
+        /// The price of a kitty, unless it isn't listed or the listing has expired.
+        pub fn live_price(kitty_id: T::KittyIndex) -> Option<BalanceOf<T>> {
+            Self::price(kitty_id).filter(|_| !Self::is_expired(kitty_id))
+        }
+
+        fn is_expired(kitty_id: T::KittyIndex) -> bool {
+            Self::listing_expiry(kitty_id)
+                .map_or(false, |expires_at| expires_at <= frame_system::Pallet::<T>::block_number())
+        }
+
+        /// Set or clear the listing of a kitty, replacing the expiry task of any previous
+        /// listing. If the new task can't be scheduled, the old one is left in place.
+        #[transactional]
+        fn set_listing(
+            kitty_id: T::KittyIndex,
+            listing: Option<(BalanceOf<T>, T::BlockNumber)>,
+        ) -> DispatchResult {
+            // Replace the expiry task of the previous listing, if any.
+            let _ = T::Scheduler::cancel_named(Self::expiry_task_id(kitty_id));
+            if let Some((_, expires_at)) = listing {
+                T::Scheduler::schedule_named(
+                    Self::expiry_task_id(kitty_id),
+                    DispatchTime::At(expires_at),
+                    None,
+                    LOWEST_PRIORITY,
+                    frame_system::RawOrigin::Root.into(),
+                    Call::expire_listing { kitty_id }.into(),
+                ).map_err(|_| Error::<T>::SchedulingFailed)?;
+            }
+
+            match listing {
+                Some(_) if Self::live_price(kitty_id).is_none() =>
+                    ListedSince::<T>::insert(kitty_id, frame_system::Pallet::<T>::block_number()),
+                Some(_) => {},
+                None => ListedSince::<T>::remove(kitty_id),
+            }
+            if let Some(kitty) = Self::kitties(kitty_id) {
+                Self::index_listing(RarityTier::from_dna(&kitty.0), kitty_id, listing.map(|(price, _)| price));
+            }
+            match listing {
+                Some((_, expires_at)) => ListingExpiry::<T>::insert(kitty_id, expires_at),
+                None => ListingExpiry::<T>::remove(kitty_id),
+            }
+            match listing {
+                Some((price, _)) => Price::<T>::insert(kitty_id, Some(price)),
+                None => Price::<T>::remove(kitty_id),
+            }
+            ReferencePrices::<T>::remove(kitty_id);
+            if let Some(owner) = Self::owner(kitty_id) {
+                match listing {
+                    Some(_) => ListingsBySeller::<T>::insert(owner, kitty_id, ()),
+                    None => ListingsBySeller::<T>::remove(owner, kitty_id),
+                }
+            }
+            Ok(())
+        }
+
+        /// Count a sale of `kitty_id` from `seller` to `buyer` towards the streak of sales
+        /// between the two, reporting it past `WashTradeThreshold`. Returns the streak's length.
+        fn record_trade(kitty_id: T::KittyIndex, buyer: &T::AccountId, seller: &T::AccountId) -> u32 {
+            let pair = if buyer < seller {
+                (buyer.clone(), seller.clone())
+            } else {
+                (seller.clone(), buyer.clone())
+            };
+            let now = frame_system::Pallet::<T>::block_number();
+            let streak = match Self::trade_streak(kitty_id) {
+                Some(streak) if streak.pair == pair && now < streak.since.saturating_add(T::WashTradeWindow::get()) =>
+                    TradeStreak { trades: streak.trades.saturating_add(1), ..streak },
+                _ => TradeStreak { pair, trades: 1, since: now },
+            };
+            let trades = streak.trades;
+            TradeStreaks::<T>::insert(kitty_id, streak);
+
+            if trades > T::WashTradeThreshold::get() {
+                Self::deposit_event(Event::SuspiciousTradePattern(buyer.clone(), seller.clone(), kitty_id, trades));
+            }
+            trades
+        }
+
+        /// Record a sale of `kitty_id` for `price`, returning the extra fee it owes: `WashTradeFee`
+        /// of the price for every sale between the two accounts past `WashTradeThreshold`.
+        fn wash_trade_fee(
+            kitty_id: T::KittyIndex,
+            buyer: &T::AccountId,
+            seller: &T::AccountId,
+            price: BalanceOf<T>,
+        ) -> BalanceOf<T> {
+            let excess_trades = Self::record_trade(kitty_id, buyer, seller)
+                .saturating_sub(T::WashTradeThreshold::get());
+            (T::WashTradeFee::get() * price).saturating_mul(excess_trades.into())
+        }
+
+        /// The creator of `kitty_id` and their royalty on `proceeds` of a sale by `seller`, if
+        /// the kitty has a royalty and the creator isn't the one selling.
+        fn royalty_due(
+            kitty_id: T::KittyIndex,
+            seller: &T::AccountId,
+            proceeds: BalanceOf<T>,
+        ) -> Option<(T::AccountId, BalanceOf<T>)> {
+            Self::royalty(kitty_id)
+                .filter(|(creator, _)| creator != seller)
+                .map(|(creator, royalty)| (creator, royalty * proceeds))
+        }
+
+        /// Check `ResaleCooldown` has passed since `kitty_id` was last bought.
+        fn ensure_resale_allowed(kitty_id: T::KittyIndex) -> DispatchResult {
+            if let Some(acquired_at) = Self::acquired_at(kitty_id) {
+                let now = frame_system::Pallet::<T>::block_number();
+                ensure!(now >= acquired_at.saturating_add(T::ResaleCooldown::get()), Error::<T>::ResaleTooSoon);
+            }
+            Ok(())
+        }
+
+        /// Change the price of a listed kitty to `price` in the native currency, keeping its
+        /// expiry.
+        fn set_price(kitty_id: T::KittyIndex, price: BalanceOf<T>) {
+            if let Some(kitty) = Self::kitties(kitty_id) {
+                Self::index_listing(RarityTier::from_dna(&kitty.0), kitty_id, Some(price));
+            }
+            Price::<T>::insert(kitty_id, Some(price));
+            ReferencePrices::<T>::remove(kitty_id);
+        }
+
+        fn remove_listing(kitty_id: T::KittyIndex) {
+            let _ = T::Scheduler::cancel_named(Self::expiry_task_id(kitty_id));
+            if let Some(kitty) = Self::kitties(kitty_id) {
+                Self::index_listing(RarityTier::from_dna(&kitty.0), kitty_id, None);
+            }
+            ListingExpiry::<T>::remove(kitty_id);
+            ListedSince::<T>::remove(kitty_id);
+            Price::<T>::remove(kitty_id);
+            ReferencePrices::<T>::remove(kitty_id);
+            if let Some(owner) = Self::owner(kitty_id) {
+                ListingsBySeller::<T>::remove(owner, kitty_id);
+            }
+        }
+
+        /// Name of the scheduler task completing the pending reroll of `kitty_id`.
+        fn reroll_task_id(kitty_id: T::KittyIndex) -> Vec<u8> {
+            (b"kitties/reroll", kitty_id).encode()
+        }
+
+        fn burn_task_id(kitty_id: T::KittyIndex) -> Vec<u8> {
+            (b"kitties/burn", kitty_id).encode()
+        }
+
+        fn birth_task_id(pregnancy_id: PregnancyId) -> Vec<u8> {
+            (b"kitties/birth", pregnancy_id).encode()
+        }
+
+        /// Remove what earlier versions of the pallet left behind for `kitty_id`: `None` values
+        /// stored as entries, and the owner, listing and deposit of a kitty that no longer
+        /// exists. Adds the kitty to `KittiesOwned` if it is missing, and files its listing under
+        /// the tier it is in now. Returns whether anything needed repairing, and the weight used.
+        fn repair(kitty_id: T::KittyIndex) -> (bool, Weight) {
+            let db = T::DbWeight::get();
+            // Looking for the entries below, the tiers the kitty is listed in, and whether it is
+            // owned.
+            let mut used = db.reads(21);
+            let mut repaired = false;
+            if Kitties::<T>::contains_key(kitty_id) && Self::kitties(kitty_id).is_none() {
+                Kitties::<T>::remove(kitty_id);
+                used = used.saturating_add(db.writes(1));
+                repaired = true;
+            }
+            if Price::<T>::contains_key(kitty_id) && Self::price(kitty_id).is_none() {
+                // Not listed, so nothing may be indexed as listed either.
+                Self::remove_listing(kitty_id);
+                used = used.saturating_add(Self::remove_listing_weight());
+                repaired = true;
+            }
+
+            let leftovers = Owner::<T>::contains_key(kitty_id)
+                || Price::<T>::contains_key(kitty_id)
+                || ListingExpiry::<T>::contains_key(kitty_id)
+                || Generation::<T>::contains_key(kitty_id)
+                || OwnerHistory::<T>::contains_key(kitty_id)
+                || KittyDeposits::<T>::contains_key(kitty_id)
+                || DepositBases::<T>::contains_key(kitty_id);
+            if Self::kitties(kitty_id).is_none() && leftovers {
+                // The listing first, while the owner is still known. Without the dna the tier
+                // it was indexed under isn't, so it is taken out of them all.
+                Self::remove_listing(kitty_id);
+                used = used.saturating_add(Self::remove_listing_weight());
+                for tier in [RarityTier::Common, RarityTier::Rare, RarityTier::Epic, RarityTier::Legendary] {
+                    if ListingsByRarity::<T>::contains_key(tier, kitty_id) {
+                        Self::index_listing(tier, kitty_id, None);
+                        used = used.saturating_add(Self::index_listing_weight());
+                    }
+                }
+                if let Some(owner) = Owner::<T>::take(kitty_id) {
+                    KittiesOwned::<T>::remove(owner, kitty_id);
+                }
+                Generation::<T>::remove(kitty_id);
+                OwnerHistory::<T>::remove(kitty_id);
+                if let Some((depositor, deposit)) = KittyDeposits::<T>::take(kitty_id) {
+                    T::Currency::unreserve(&depositor, deposit);
+                }
+                DepositBases::<T>::remove(kitty_id);
+                // The tiers, the owner, the deposit and the depositor's account, and the rest.
+                used = used.saturating_add(db.reads_writes(7, 7));
+                repaired = true;
+            }
+
+            // Listings made before the tiers were last redrawn may be filed under another one.
+            if let Some(kitty) = Self::kitties(kitty_id) {
+                let tier = RarityTier::from_dna(&kitty.0);
+                let price = Self::price(kitty_id);
+                for filed_under in [RarityTier::Common, RarityTier::Rare, RarityTier::Epic, RarityTier::Legendary] {
+                    let filed = ListingsByRarity::<T>::contains_key(filed_under, kitty_id);
+                    let listing = if filed_under == tier { price } else { None };
+                    if filed != listing.is_some() {
+                        Self::index_listing(filed_under, kitty_id, listing);
+                        used = used.saturating_add(Self::index_listing_weight());
+                        repaired = true;
+                    }
+                }
+            }
+
+            // Index kitties owned since before `KittiesOwned` was kept.
+            if let (Some(_), Some(owner)) = (Self::kitties(kitty_id), Self::owner(kitty_id)) {
+                if !KittiesOwned::<T>::contains_key(&owner, kitty_id) {
+                    KittiesOwned::<T>::insert(owner, kitty_id, ());
+                    used = used.saturating_add(db.writes(1));
+                    repaired = true;
+                }
+            }
+            (repaired, used)
+        }
+
+        /// Name of the scheduler task expiring the listing of `kitty_id`.
+        fn expiry_task_id(kitty_id: T::KittyIndex) -> Vec<u8> {
+            (b"kitties/expire", kitty_id).encode()
+        }
+
+        fn session_task_id(user: &T::AccountId, operator: &T::AccountId) -> Vec<u8> {
+            (b"kitties/session", user, operator).encode()
+        }
+
+        /// Whether `user` has a live session with `operator` allowing every call in `mask`.
+        fn has_session(user: &T::AccountId, operator: &T::AccountId, mask: u32) -> bool {
+            Self::session(user, operator).map_or(false, |(allowed, expiry)| {
+                allowed & mask == mask && frame_system::Pallet::<T>::block_number() < expiry
+            })
+        }
+
+        /// Append this block's kitty events to the offchain event log, see `crate::event_log`.
+        fn index_events(block_number: T::BlockNumber) {
+            let events: Vec<Event<T>> = frame_system::Pallet::<T>::events()
+                .into_iter()
+                .filter_map(|record| <T as Config>::Event::from(record.event).try_into().ok())
+                .collect();
+            if events.is_empty() {
+                return;
+            }
+
+            // Reserve the slots first so workers of concurrently imported blocks don't overlap.
+            let len = events.len() as u64;
+            let count = StorageValueRef::persistent(crate::event_log::COUNT_KEY);
+            let end = count.mutate::<u64, (), _>(|count| {
+                Ok(count.ok().flatten().unwrap_or_default().saturating_add(len))
+            });
+            if let Ok(end) = end {
+                for (index, event) in (end - len..end).zip(events) {
+                    StorageValueRef::persistent(&crate::event_log::key(index)).set(&(block_number, event));
+                }
+            }
+        }
+
+        /// Check that `ListingsByRarity`, `ListingExpiry` and `Price` describe the same set of
+        /// listings.
+        #[cfg(any(feature = "try-runtime", test))]
+        pub fn do_try_state() -> Result<(), &'static str> {
+            for (kitty_id, _) in ListingExpiry::<T>::iter() {
+                ensure!(Self::price(kitty_id).is_some(), "expiry recorded for an unlisted kitty");
+            }
+            for (tier, kitty_id, price) in ListingsByRarity::<T>::iter() {
+                let kitty = Self::kitties(kitty_id).ok_or("listed kitty does not exist")?;
+                ensure!(RarityTier::from_dna(&kitty.0) == tier, "listing indexed under the wrong tier");
+                ensure!(Self::price(kitty_id) == Some(price), "listing index out of sync with price");
             }
-            mix_dna
-        } 
+            for (kitty_id, price) in Price::<T>::iter() {
+                if let (Some(price), Some(kitty)) = (price, Self::kitties(kitty_id)) {
+                    let tier = RarityTier::from_dna(&kitty.0);
+                    ensure!(
+                        ListingsByRarity::<T>::get(tier, kitty_id) == Some(price),
+                        "price missing from listing index"
+                    );
+                }
+            }
+            for (tier, (kitty_id, price)) in CheapestListings::<T>::iter() {
+                ensure!(!CheapestRescans::<T>::contains_key(tier), "cheapest listing recorded while looking for it");
+                ensure!(ListingsByRarity::<T>::get(tier, kitty_id) == Some(price), "cheapest listing is not listed");
+                ensure!(
+                    ListingsByRarity::<T>::iter_prefix(tier).all(|(_, other)| price <= other),
+                    "cheapest listing is not the cheapest"
+                );
+            }
+            Ok(())
+        }
+    }
+
+    /// Lets other pallets, e.g. proof of existence, check who owns a kitty.
+    impl<T: Config> nonfungible::Inspect<T::AccountId> for Pallet<T> {
+        type InstanceId = T::KittyIndex;
+
+        fn owner(kitty_id: &T::KittyIndex) -> Option<T::AccountId> {
+            Owner::<T>::get(kitty_id)
+        }
     }
 }
@@ -1,8 +1,10 @@
 use crate as pallet_kitties;
 use sp_core::H256;
-use frame_support::parameter_types;
-use sp_runtime::{traits::{BlakeTwo256, IdentityLookup}, testing::Header};
+use frame_support::{parameter_types, traits::{ConstU128, ConstU32, ConstU64}, weights::Weight, PalletId};
+use sp_runtime::{traits::{BlakeTwo256, IdentityLookup}, testing::{Header, TestSignature, UintAuthorityId}, Permill};
 use frame_system as system;
+use pallet_kitties::discount::{DiscountProvider, DiscountedAction};
+use pallet_kitties::exchange_rate::ExchangeRate;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -23,6 +25,9 @@ frame_support::construct_runtime!(
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		Balances: pallet_balances::{Pallet, Call, Config<T>, Storage, Event<T>},
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Storage},
+		Scheduler: pallet_scheduler::{Pallet, Call, Storage, Event<T>},
+		Multisig: pallet_multisig::{Pallet, Call, Storage, Event<T>},
+		Proxy: pallet_proxy::{Pallet, Call, Storage, Event<T>},
 		KittiesModule: pallet_kitties::{Pallet, Call, Storage, Event<T>},
 	}
 );
@@ -66,6 +71,25 @@ parameter_types! {
     pub const ExistentialDeposit: u128 = 500;
     pub const MaxLocks: u32 = 50;
     pub const DepositBase: u32 = 1_000;
+    pub const CertificateIssuer: u64 = 99;
+    pub const KittiesChainId: [u8; 8] = *b"kittymck";
+    pub const KittiesPalletId: PalletId = PalletId(*b"py/kitty");
+    pub const ReferralReward: u128 = 100;
+    pub const MaxListingDuration: u64 = 10;
+    pub const PowSeedPeriod: u64 = 10;
+    pub const RerollFee: u128 = 100;
+    pub const SaleFee: Permill = Permill::from_percent(0);
+    pub const MinListingPrice: u128 = 1;
+    pub const MaxListingPrice: u128 = 1_000_000;
+    pub const MaximumSchedulerWeight: Weight = 1_000_000_000;
+    pub static BaseGestation: u64 = 0;
+    pub static MintDiscount: Permill = Permill::zero();
+    pub static BreedDiscount: Permill = Permill::zero();
+    pub static ResaleCooldown: u64 = 0;
+    pub static ReferenceRate: Option<u128> = None;
+    pub const WashTradeFee: Permill = Permill::from_percent(10);
+    pub const DefaultPenalty: Permill = Permill::from_percent(10);
+    pub const MaxSignatories: u16 = 3;
 }
 
 impl pallet_balances::Config for Test {
@@ -82,19 +106,126 @@ impl pallet_balances::Config for Test {
     type WeightInfo = pallet_balances::weights::SubstrateWeight<Test>;
 }
 
+impl pallet_scheduler::Config for Test {
+    type Event = Event;
+    type Origin = Origin;
+    type PalletsOrigin = OriginCaller;
+    type Call = Call;
+    type MaximumWeight = MaximumSchedulerWeight;
+    type ScheduleOrigin = frame_system::EnsureRoot<u64>;
+    type MaxScheduledPerBlock = ConstU32<50>;
+    type WeightInfo = ();
+    type OriginPrivilegeCmp = frame_support::traits::EqualPrivilegeOnly;
+}
+
+impl pallet_multisig::Config for Test {
+    type Event = Event;
+    type Call = Call;
+    type Currency = Balances;
+    type DepositBase = ConstU128<10>;
+    type DepositFactor = ConstU128<1>;
+    type MaxSignatories = MaxSignatories;
+    type WeightInfo = ();
+}
+
+impl pallet_proxy::Config for Test {
+    type Event = Event;
+    type Call = Call;
+    type Currency = Balances;
+    type ProxyType = ();
+    type ProxyDepositBase = ConstU128<10>;
+    type ProxyDepositFactor = ConstU128<1>;
+    type MaxProxies = ConstU32<4>;
+    type WeightInfo = ();
+    type MaxPending = ConstU32<4>;
+    type CallHasher = BlakeTwo256;
+    type AnnouncementDepositBase = ConstU128<10>;
+    type AnnouncementDepositFactor = ConstU128<1>;
+}
+
+/// Grants account 2 `MintDiscount` and `BreedDiscount`, as an achievements pallet might.
+pub struct TestDiscounts;
+
+impl DiscountProvider<u64> for TestDiscounts {
+    fn discount(who: &u64, action: DiscountedAction) -> Permill {
+        match (who, action) {
+            (2, DiscountedAction::Mint) => MintDiscount::get(),
+            (2, DiscountedAction::Breed) => BreedDiscount::get(),
+            _ => Permill::zero(),
+        }
+    }
+}
+
+/// Converts at `ReferenceRate` native units per reference unit, as an oracle might.
+pub struct TestExchangeRate;
+
+impl ExchangeRate<u128> for TestExchangeRate {
+    fn to_native(amount: u128) -> Option<u128> {
+        ReferenceRate::get().map(|rate| amount.saturating_mul(rate))
+    }
+}
+
 impl pallet_kitties::Config for Test {
     type Event = Event;
     type Randomness = RandomnessCollectiveFlip;
     type KittyIndex = Index;
     type Currency = Balances;
     type KittyDepositBase = DepositBase;
+    type DepositCurve = pallet_kitties::deposit::Step<ConstU32<3>>;
+    type DiscountProvider = TestDiscounts;
+    type ExchangeRate = TestExchangeRate;
+    type CreatorSignature = TestSignature;
+    type CreatorPublic = UintAuthorityId;
+    type CertificateSignature = TestSignature;
+    type CertificateIssuer = CertificateIssuer;
+    type ChainId = KittiesChainId;
+    type PalletId = KittiesPalletId;
+    type ReferralReward = ReferralReward;
+    type VoucherIssuer = frame_system::EnsureRoot<u64>;
+    type ForceOrigin = frame_system::EnsureRoot<u64>;
+    type MaxBreedingWindows = ConstU32<2>;
+    type MaxListingDuration = MaxListingDuration;
+    type PowDifficulty = ConstU32<4>;
+    type PowSeedPeriod = PowSeedPeriod;
+    type ScheduledCall = Call;
+    type Scheduler = Scheduler;
+    type PalletsOrigin = OriginCaller;
+    type MaxOwnersTracked = ConstU32<2>;
+    type LeaderboardSize = ConstU32<2>;
+    type MaxCoOwners = ConstU32<2>;
+    type RerollFee = RerollFee;
+    type MaxRerolls = ConstU32<1>;
+    type RerollDelay = ConstU64<2>;
+    type SaleFee = SaleFee;
+    type MinListingPrice = MinListingPrice;
+    type MaxListingPrice = MaxListingPrice;
+    type BaseGestation = BaseGestation;
+    type InstallmentPeriod = ConstU64<5>;
+    type MaxInstallments = ConstU32<4>;
+    type DefaultPenalty = DefaultPenalty;
+    type MaxBulkListings = ConstU32<2>;
+    type MaxInheritedKitties = ConstU32<2>;
+    type ResaleCooldown = ResaleCooldown;
+    type WashTradeWindow = ConstU64<10>;
+    type WashTradeThreshold = ConstU32<2>;
+    type WashTradeFee = WashTradeFee;
+    type MaxFuse = ConstU32<3>;
+    type BurnDelay = ConstU64<5>;
+    type GuardianThreshold = ConstU128<500>;
+    type MaxGuardedActions = ConstU32<2>;
+    type GuardedActionExpiry = ConstU64<5>;
+    type RewardEpoch = ConstU64<10>;
+    type MinRewardListingDuration = ConstU64<3>;
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
 	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
 	pallet_balances::GenesisConfig::<Test>{
-		balances: vec![(0, 100_000_000), (1, 100_000_000), (2, 100_000_000)],
+		balances: vec![
+			(0, 100_000_000), (1, 100_000_000), (2, 100_000_000),
+			(KittiesModule::account_id(), 1_000),
+		],
 	}.assimilate_storage(&mut t).unwrap();
 
 	let mut ext = sp_io::TestExternalities::new(t);
@@ -1,5 +1,10 @@
-use crate::{Error, Event, mock::*};
+use crate::{Error, Event, mock::*, discount::DiscountedAction};
+use codec::Encode;
 use frame_support::{assert_ok, assert_noop};
+use frame_support::traits::{Currency, ReservableCurrency};
+use frame_support::weights::GetDispatchInfo;
+use kitties_primitives::{dna, KittyCertificate, KittyInfo, KittyStats, RarityTier};
+use sp_runtime::{testing::TestSignature, traits::{BlakeTwo256, Hash}, Permill};
 use super::*;
 
 
@@ -78,6 +83,10 @@ fn breed_failed() {
 
         assert_ok!(KittiesModule::create(Origin::signed(1)));
         // kitty_id: [0, 1], owner: 1, count: 2
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 4, 0));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 4, 1));
+        assert_noop!(KittiesModule::breed(Origin::signed(4), 0, 1), Error::<Test>::InsufficientBalance);
+
         KittiesCount::<Test>::put(u32::max_value());
         assert_noop!(
             KittiesModule::breed(Origin::signed(1), 0, 1),
@@ -86,11 +95,65 @@ fn breed_failed() {
     }) 
 }
 
+#[test]
+fn delayed_birth_works() {
+    new_test_ext().execute_with(|| {
+        BaseGestation::set(&2);
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        let rarity = RarityTier::from_dna(&KittiesModule::kitties(0).unwrap().0)
+            .max(RarityTier::from_dna(&KittiesModule::kitties(1).unwrap().0));
+        let due = 1 + 2 * (rarity as u64 + 1);
+        let reserved = Balances::reserved_balance(1);
+
+        // the deposit is taken when breeding, the kitty comes when it is due
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+        System::assert_last_event(mock::Event::KittiesModule(Event::Pregnant(1, 0, due)));
+        assert_eq!(KittiesModule::pregnancies(0).map(|p| (p.owner, p.generation, p.due)), Some((1, 1, due)));
+        assert_eq!(Balances::reserved_balance(1), reserved + 1_000);
+        assert_eq!(KittiesCount::<Test>::get(), Some(2));
+        assert_eq!(TotalBreeds::<Test>::get(), 1);
+
+        System::set_block_number(due);
+        Scheduler::on_initialize(due);
+        assert_eq!(KittiesModule::pregnancies(0), None);
+        assert_eq!(Owner::<Test>::get(2), Some(1));
+        assert_eq!(KittyDeposits::<Test>::get(2), Some((1, 1_000)));
+        assert!(dna::is_valid(&KittiesModule::kitties(2).unwrap().0));
+        System::assert_has_event(mock::Event::KittiesModule(Event::KittyCreate(1, 2)));
+    })
+}
+
+#[test]
+fn delayed_birth_failed() {
+    new_test_ext().execute_with(|| {
+        BaseGestation::set(&2);
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+        let due = KittiesModule::pregnancies(0).unwrap().due;
+
+        assert_noop!(KittiesModule::give_birth(Origin::signed(2), 1), Error::<Test>::NoSuchPregnancy);
+        assert_noop!(KittiesModule::give_birth(Origin::signed(2), 0), Error::<Test>::PregnancyNotDue);
+
+        // a birth the scheduler could not complete can be retried by anyone
+        KittiesCount::<Test>::put(u32::max_value());
+        System::set_block_number(due);
+        Scheduler::on_initialize(due);
+        assert!(KittiesModule::pregnancies(0).is_some());
+        assert_noop!(KittiesModule::give_birth(Origin::signed(2), 0), Error::<Test>::KittiesCountOverflow);
+        KittiesCount::<Test>::put(2);
+        assert_ok!(KittiesModule::give_birth(Origin::signed(2), 0));
+        assert_eq!(Owner::<Test>::get(2), Some(1));
+        assert_noop!(KittiesModule::give_birth(Origin::signed(2), 0), Error::<Test>::NoSuchPregnancy);
+    })
+}
+
 #[test]
 fn sell_kitty_work() {
 	new_test_ext().execute_with(|| {
 		assert_ok!(KittiesModule::create(Origin::signed(1)));
-		assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(666)));
+		assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(666), None));
 
         // kitty_id: [0], owner: 1, price: [666], count: 1
         assert!(Kitties::<Test>::contains_key(0));
@@ -105,7 +168,7 @@ fn sell_kitty_work() {
 fn sell_kitty_failed() {
 	new_test_ext().execute_with(|| {
 		assert_ok!(KittiesModule::create(Origin::signed(1)));
-		assert_noop!(KittiesModule::sell_kitty(Origin::signed(2), 0, Some(666)), Error::<Test>::NotKittyOwner);
+		assert_noop!(KittiesModule::sell_kitty(Origin::signed(2), 0, Some(666), None), Error::<Test>::NotKittyOwner);
 	})
 }
 
@@ -113,12 +176,12 @@ fn sell_kitty_failed() {
 fn buy_kitty_works() {
     new_test_ext().execute_with(|| {
         assert_ok!(KittiesModule::create(Origin::signed(1)));
-        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(666)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(666), None));
 
         // kitty_id: [0], owner: 1, price: [666], count: 1
         assert_eq!(Price::<Test>::contains_key(0), true);
         assert_eq!(Price::<Test>::get(0), Some(666));
-        assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, None));
         assert_eq!(Price::<Test>::contains_key(0), false);
 
         // kitty_id: [0], owner: 2, price: [], count: 1
@@ -133,10 +196,2060 @@ fn buy_kitty_works() {
 fn buy_kitty_failed() {
     new_test_ext().execute_with(|| {
 		assert_ok!(KittiesModule::create(Origin::signed(1)));
-		assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 1), Error::<Test>::InvalidKittyIndex);
-		assert_noop!(KittiesModule::buy_kitty(Origin::signed(1), 0), Error::<Test>::BuyFromSelf);
-		assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 0), Error::<Test>::KittyNotForSale);
-		assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(666)));
-		assert_noop!(KittiesModule::buy_kitty(Origin::signed(4), 0), Error::<Test>::InsufficientBalance);
+		assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 1, None), Error::<Test>::InvalidKittyIndex);
+		assert_noop!(KittiesModule::buy_kitty(Origin::signed(1), 0, None), Error::<Test>::BuyFromSelf);
+		assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 0, None), Error::<Test>::KittyNotForSale);
+		assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(666), None));
+		assert_noop!(KittiesModule::buy_kitty(Origin::signed(4), 0, None), Error::<Test>::InsufficientBalance);
     }) 
-}
\ No newline at end of file
+}
+
+#[test]
+fn export_kitty_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        let dna = KittiesModule::kitties(0).unwrap().0;
+        assert_ok!(KittiesModule::export_kitty(Origin::signed(1), 0, 1, *b"otherchn"));
+
+        // kitty_id: [], count: 1, deposit returned
+        assert!(!Kitties::<Test>::contains_key(0));
+        assert_eq!(Owner::<Test>::get(0), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(ExportNonce::<Test>::get(), 1);
+        let certificate = KittyCertificate::new(dna, 0, *b"kittymck", 0, 1, *b"otherchn");
+        System::assert_has_event(mock::Event::KittiesModule(Event::KittyExported(1, 0, certificate)));
+    })
+}
+
+#[test]
+fn export_kitty_failed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(KittiesModule::export_kitty(Origin::signed(2), 0, 2, *b"otherchn"), Error::<Test>::NotKittyOwner);
+        assert_noop!(KittiesModule::export_kitty(Origin::signed(1), 1, 1, *b"otherchn"), Error::<Test>::NotKittyOwner);
+    })
+}
+
+#[test]
+fn import_kitty_works() {
+    new_test_ext().execute_with(|| {
+        let certificate = KittyCertificate::new(dna::seal([7u8; 16]), 3, *b"otherchn", 0, 2, *b"kittymck");
+        let sig = TestSignature(99, certificate.signing_payload());
+        assert_ok!(KittiesModule::import_kitty(Origin::signed(2), certificate.sign_with(sig)));
+
+        // kitty_id: [0], owner: 2, generation: 3, count: 1
+        assert_eq!(KittiesModule::kitties(0).unwrap().0, dna::seal([7u8; 16]));
+        assert_eq!(Owner::<Test>::get(0), Some(2));
+        assert_eq!(Generation::<Test>::get(0), 3);
+        assert_eq!(KittiesCount::<Test>::get(), Some(1));
+        assert_eq!(Balances::reserved_balance(2), 1_000);
+        System::assert_has_event(mock::Event::KittiesModule(Event::KittyImported(2, 0, *b"otherchn")));
+    })
+}
+
+#[test]
+fn import_kitty_failed() {
+    new_test_ext().execute_with(|| {
+        let certificate = KittyCertificate::new(dna::seal([7u8; 16]), 3, *b"otherchn", 0, 2, *b"kittymck");
+
+        // Signed by someone other than the issuer.
+        let forged = TestSignature(1, certificate.signing_payload());
+        assert_noop!(
+            KittiesModule::import_kitty(Origin::signed(2), certificate.clone().sign_with(forged)),
+            Error::<Test>::InvalidCertificateSignature
+        );
+
+        // Unknown certificate version.
+        let mut future = certificate.clone();
+        future.version = 3;
+        let sig = TestSignature(99, future.signing_payload());
+        assert_noop!(
+            KittiesModule::import_kitty(Origin::signed(2), future.sign_with(sig)),
+            Error::<Test>::UnsupportedCertificateVersion
+        );
+
+        // Genuine, but with DNA missing its checksum.
+        let unsealed = KittyCertificate::new([7u8; 16], 3, *b"otherchn", 1, 2, *b"kittymck");
+        let sig = TestSignature(99, unsealed.signing_payload());
+        assert_noop!(
+            KittiesModule::import_kitty(Origin::signed(2), unsealed.sign_with(sig)),
+            Error::<Test>::InvalidDna
+        );
+
+        // Only the recipient can import it, so it can't be front-run from the pool.
+        let sig = TestSignature(99, certificate.signing_payload());
+        let signed = certificate.sign_with(sig);
+        assert_noop!(
+            KittiesModule::import_kitty(Origin::signed(1), signed.clone()),
+            Error::<Test>::NotCertificateRecipient
+        );
+
+        // Nor on any chain but the one it was addressed to.
+        let elsewhere = KittyCertificate::new(dna::seal([7u8; 16]), 3, *b"otherchn", 2, 2, *b"otherchn");
+        let sig = TestSignature(99, elsewhere.signing_payload());
+        assert_noop!(
+            KittiesModule::import_kitty(Origin::signed(2), elsewhere.sign_with(sig)),
+            Error::<Test>::WrongDestinationChain
+        );
+
+        // The same certificate can only be imported once.
+        let broke = KittyCertificate::new(dna::seal([7u8; 16]), 3, *b"otherchn", 3, 4, *b"kittymck");
+        let sig = TestSignature(99, broke.signing_payload());
+        assert_noop!(
+            KittiesModule::import_kitty(Origin::signed(4), broke.sign_with(sig)),
+            Error::<Test>::InsufficientBalance
+        );
+        assert_ok!(KittiesModule::import_kitty(Origin::signed(2), signed.clone()));
+        assert_noop!(
+            KittiesModule::import_kitty(Origin::signed(2), signed),
+            Error::<Test>::CertificateAlreadyImported
+        );
+    })
+}
+
+#[test]
+fn listings_by_rarity_works() {
+    use frame_support::traits::Hooks;
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        Kitties::<Test>::insert(0, Some(Kitty([0xff; 16])));
+        Kitties::<Test>::insert(1, Some(Kitty([0xff; 16])));
+        Kitties::<Test>::insert(2, Some(Kitty([0x00; 16])));
+
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(800), None));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, Some(666), None));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 2, Some(10), None));
+        assert_eq!(ListingsByRarity::<Test>::get(RarityTier::Common, 2), Some(10));
+        assert_eq!(KittiesModule::cheapest_listing(RarityTier::Legendary), Some((1, 666)));
+        assert_eq!(KittiesModule::cheapest_listing(RarityTier::Epic), None);
+
+        // Delisting and buying both drop the entry.
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, None, None));
+        KittiesModule::on_idle(1, 1_000_000);
+        assert_eq!(KittiesModule::cheapest_listing(RarityTier::Legendary), Some((0, 800)));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, None));
+        KittiesModule::on_idle(1, 1_000_000);
+        assert_eq!(KittiesModule::cheapest_listing(RarityTier::Legendary), None);
+        assert_ok!(KittiesModule::do_try_state());
+
+        // An index entry without a matching price is caught.
+        ListingsByRarity::<Test>::insert(RarityTier::Legendary, 1, 1);
+        assert!(KittiesModule::do_try_state().is_err());
+    })
+}
+
+#[test]
+fn cheapest_listings_works() {
+    use frame_support::traits::Hooks;
+
+    new_test_ext().execute_with(|| {
+        for _ in 0..3 {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+        }
+        for kitty_id in 0..3 {
+            Kitties::<Test>::insert(kitty_id, Some(Kitty([0xff; 16])));
+        }
+
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(800), None));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, Some(666), None));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 2, Some(700), None));
+        assert_eq!(CheapestListings::<Test>::get(RarityTier::Legendary), Some((1, 666)));
+
+        // Raising the cheapest price or delisting it leaves the next cheapest to be looked for
+        // in `on_idle`, rather than walking the tier there and then.
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, Some(900), None));
+        assert_eq!(KittiesModule::cheapest_listing(RarityTier::Legendary), None);
+        assert_eq!(CheapestRescans::<Test>::get(RarityTier::Legendary), Some((None, None)));
+        assert_ok!(KittiesModule::do_try_state());
+        KittiesModule::on_idle(1, 1_000_000);
+        assert_eq!(CheapestListings::<Test>::get(RarityTier::Legendary), Some((2, 700)));
+        assert_eq!(CheapestRescans::<Test>::get(RarityTier::Legendary), None);
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 2, None, None));
+        KittiesModule::on_idle(1, 1_000_000);
+        assert_eq!(CheapestListings::<Test>::get(RarityTier::Legendary), Some((0, 800)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, Some(10), None));
+        assert_eq!(KittiesModule::cheapest_listing(RarityTier::Legendary), Some((1, 10)));
+        assert_ok!(KittiesModule::do_try_state());
+
+        // Listings made while looking count straight away, and losing the cheapest one found
+        // so far starts the search over.
+        CheapestListings::<Test>::remove(RarityTier::Legendary);
+        CheapestRescans::<Test>::insert(RarityTier::Legendary, (Some(0), Some((0, 800))));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 2, Some(700), None));
+        assert_eq!(CheapestRescans::<Test>::get(RarityTier::Legendary), Some((Some(0), Some((2, 700)))));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 2, None, None));
+        assert_eq!(CheapestRescans::<Test>::get(RarityTier::Legendary), Some((None, None)));
+        KittiesModule::on_idle(1, 1_000_000);
+        assert_eq!(KittiesModule::cheapest_listing(RarityTier::Legendary), Some((1, 10)));
+
+        // The last listing going empties the tier.
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, None, None));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, None, None));
+        KittiesModule::on_idle(1, 1_000_000);
+        assert_eq!(CheapestListings::<Test>::get(RarityTier::Legendary), None);
+        assert_eq!(CheapestRescans::<Test>::iter().count(), 0);
+
+        // A tier listed in before the cheapest listing was kept is looked through once.
+        ListingsByRarity::<Test>::insert(RarityTier::Legendary, 0, 5);
+        Price::<Test>::insert(0, Some(5));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 2, Some(50), None));
+        assert_eq!(KittiesModule::cheapest_listing(RarityTier::Legendary), None);
+        KittiesModule::on_idle(1, 1_000_000);
+        assert_eq!(CheapestListings::<Test>::get(RarityTier::Legendary), Some((0, 5)));
+    })
+}
+
+#[test]
+fn create_with_referral_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create_with_referral(Origin::signed(2), 1));
+
+        // kitty_id: [0], owner: 2, referrer: 1 paid from the pot
+        assert_eq!(Owner::<Test>::get(0), Some(2));
+        assert_eq!(ReferredBy::<Test>::get(2), Some(1));
+        assert_eq!(Balances::free_balance(1), 100_000_100);
+        assert_eq!(Balances::free_balance(KittiesModule::account_id()), 900);
+        System::assert_has_event(mock::Event::KittiesModule(Event::KittyReferred(2, 1)));
+        System::assert_has_event(mock::Event::KittiesModule(Event::ReferralRewardPaid(1, 100)));
+    })
+}
+
+#[test]
+fn create_with_referral_failed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            KittiesModule::create_with_referral(Origin::signed(1), 1),
+            Error::<Test>::SelfReferral
+        );
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(
+            KittiesModule::create_with_referral(Origin::signed(1), 2),
+            Error::<Test>::AlreadyMinted
+        );
+        assert_ok!(KittiesModule::create_with_referral(Origin::signed(2), 1));
+        assert_noop!(
+            KittiesModule::create_with_referral(Origin::signed(2), 0),
+            Error::<Test>::AlreadyMinted
+        );
+    })
+}
+
+#[test]
+fn stats_works() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(KittiesModule::stats(), KittyStats::default());
+
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, Some(666), None));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 1, None));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 2, Some(334), None));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(0), 2, None));
+
+        assert_eq!(KittiesModule::stats(), KittyStats {
+            total_transfers: 1,
+            total_sales: 2,
+            total_volume: 1_000,
+            total_breeds: 1,
+        });
+    })
+}
+
+#[test]
+fn deposit_follows_supply() {
+    new_test_ext().execute_with(|| {
+        // Step curve in the mock: the deposit goes up by the base every 3 kitties.
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_eq!(KittiesModule::current_deposit(), 2_000);
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_eq!(KittyDeposits::<Test>::get(3), Some((2, 2_000)));
+        assert_eq!(Balances::reserved_balance(1), 3_000);
+        assert_eq!(Balances::reserved_balance(2), 2_000);
+        assert_eq!(TotalSupply::<Test>::get(), 4);
+
+        // Each kitty refunds exactly what was reserved for it.
+        assert_ok!(KittiesModule::export_kitty(Origin::signed(2), 3, 2, *b"otherchn"));
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_ok!(KittiesModule::export_kitty(Origin::signed(1), 0, 1, *b"otherchn"));
+        assert_eq!(Balances::reserved_balance(1), 2_000);
+        assert_eq!(TotalSupply::<Test>::get(), 2);
+        assert_eq!(KittiesModule::current_deposit(), 1_000);
+
+        // A sale moves the recorded deposit to the buyer.
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, Some(10), None));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 1, None));
+        assert_eq!(KittyDeposits::<Test>::get(1), Some((2, 1_000)));
+        assert_eq!(Balances::reserved_balance(1), 1_000);
+        assert_eq!(Balances::reserved_balance(2), 1_000);
+    })
+}
+
+#[test]
+fn redeem_voucher_works() {
+    new_test_ext().execute_with(|| {
+        let code = b"HAPPY-KITTY-DAY".to_vec();
+        let code_hash = BlakeTwo256::hash(&code);
+        assert_ok!(KittiesModule::issue_voucher(Origin::root(), code_hash, dna::seal([9u8; 16])));
+        System::assert_has_event(mock::Event::KittiesModule(Event::VoucherIssued(code_hash)));
+
+        assert_ok!(KittiesModule::redeem_voucher(Origin::signed(2), code));
+
+        // kitty_id: [0], owner: 2, voucher consumed
+        assert_eq!(KittiesModule::kitties(0).unwrap().0, dna::seal([9u8; 16]));
+        assert_eq!(Owner::<Test>::get(0), Some(2));
+        assert_eq!(Vouchers::<Test>::get(code_hash), None);
+        System::assert_has_event(mock::Event::KittiesModule(Event::VoucherRedeemed(2, code_hash, 0)));
+    })
+}
+
+#[test]
+fn redeem_voucher_failed() {
+    new_test_ext().execute_with(|| {
+        let code = b"HAPPY-KITTY-DAY".to_vec();
+        let code_hash = BlakeTwo256::hash(&code);
+        assert_noop!(
+            KittiesModule::issue_voucher(Origin::signed(1), code_hash, dna::seal([9u8; 16])),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(KittiesModule::redeem_voucher(Origin::signed(2), code.clone()), Error::<Test>::NoSuchVoucher);
+        assert_noop!(
+            KittiesModule::issue_voucher(Origin::root(), code_hash, [9u8; 16]),
+            Error::<Test>::InvalidDna
+        );
+        assert_noop!(
+            KittiesModule::issue_voucher(Origin::root(), code_hash, [0u8; 16]),
+            Error::<Test>::InvalidDna
+        );
+
+        // a voucher stored without a checksum can't be redeemed either
+        let old_code = b"OLD-KITTY-DAY".to_vec();
+        Vouchers::<Test>::insert(BlakeTwo256::hash(&old_code), [9u8; 16]);
+        assert_noop!(KittiesModule::redeem_voucher(Origin::signed(2), old_code), Error::<Test>::InvalidDna);
+
+        assert_ok!(KittiesModule::issue_voucher(Origin::root(), code_hash, dna::seal([9u8; 16])));
+        assert_noop!(
+            KittiesModule::issue_voucher(Origin::root(), code_hash, dna::seal([1u8; 16])),
+            Error::<Test>::VoucherAlreadyExists
+        );
+        assert_noop!(KittiesModule::redeem_voucher(Origin::signed(4), code.clone()), Error::<Test>::InsufficientBalance);
+        assert_ok!(KittiesModule::redeem_voucher(Origin::signed(2), code.clone()));
+        assert_noop!(KittiesModule::redeem_voucher(Origin::signed(1), code), Error::<Test>::NoSuchVoucher);
+    })
+}
+
+#[test]
+fn offchain_worker_indexes_events() {
+    use frame_support::traits::Hooks;
+    use sp_core::offchain::{testing::TestOffchainExt, OffchainDbExt, OffchainWorkerExt};
+    use sp_runtime::offchain::storage::StorageValueRef;
+
+    let mut ext = new_test_ext();
+    let (offchain, _state) = TestOffchainExt::new();
+    ext.register_extension(OffchainDbExt::new(offchain.clone()));
+    ext.register_extension(OffchainWorkerExt::new(offchain));
+    ext.execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+        KittiesModule::offchain_worker(1);
+
+        // the balance events of the deposit are not indexed
+        let count = StorageValueRef::persistent(event_log::COUNT_KEY);
+        assert_eq!(count.get::<u64>(), Ok(Some(2)));
+        let event = |index| StorageValueRef::persistent(&event_log::key(index)).get::<(u64, Event<Test>)>();
+        assert_eq!(event(0), Ok(Some((1, Event::KittyCreate(1, 0)))));
+        assert_eq!(event(1), Ok(Some((1, Event::KittyTransfer(1, 2, 0)))));
+        assert_eq!(event(2), Ok(None));
+    });
+}
+
+#[test]
+fn breeding_window_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::set_breeding_window(Origin::root(), 3, 5));
+        System::assert_has_event(mock::Event::KittiesModule(Event::BreedingWindowScheduled(3, 5)));
+
+        // block 1: the window hasn't opened yet
+        assert_noop!(KittiesModule::breed(Origin::signed(1), 0, 1), Error::<Test>::BreedingClosed);
+
+        System::set_block_number(4);
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+
+        // the window has ended and is pruned on the next touch
+        System::set_block_number(5);
+        assert_ok!(KittiesModule::set_breeding_window(Origin::root(), 6, 7));
+        assert_eq!(KittiesModule::breeding_windows().unwrap().into_inner(), vec![(6, 7)]);
+        assert_noop!(KittiesModule::breed(Origin::signed(1), 0, 1), Error::<Test>::BreedingClosed);
+
+        assert_ok!(KittiesModule::clear_breeding_windows(Origin::root()));
+        assert_eq!(KittiesModule::breeding_windows(), None);
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+
+        // once the last window has ended, breeding is unrestricted again
+        assert_ok!(KittiesModule::set_breeding_window(Origin::root(), 8, 9));
+        System::set_block_number(9);
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+        assert_eq!(KittiesModule::breeding_windows(), None);
+    });
+}
+
+#[test]
+fn set_breeding_window_failed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            KittiesModule::set_breeding_window(Origin::signed(1), 2, 3),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            KittiesModule::set_breeding_window(Origin::root(), 3, 3),
+            Error::<Test>::InvalidBreedingWindow
+        );
+        assert_noop!(
+            KittiesModule::set_breeding_window(Origin::root(), 0, 1),
+            Error::<Test>::InvalidBreedingWindow
+        );
+
+        assert_ok!(KittiesModule::set_breeding_window(Origin::root(), 2, 3));
+        assert_ok!(KittiesModule::set_breeding_window(Origin::root(), 3, 4));
+        assert_noop!(
+            KittiesModule::set_breeding_window(Origin::root(), 4, 5),
+            Error::<Test>::TooManyBreedingWindows
+        );
+    });
+}
+
+#[test]
+fn listing_expiry_works() {
+    use frame_support::traits::Hooks;
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        // block 1: the default expiry is MaxListingDuration away
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(10), None));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, Some(20), Some(5)));
+        assert_eq!(KittiesModule::listing_expiry(0), Some(11));
+        assert_eq!(KittiesModule::listing_expiry(1), Some(5));
+
+        // expired listings can't be bought, even before they are cleaned up
+        System::set_block_number(5);
+        let tier = RarityTier::from_dna(&KittiesModule::kitties(1).unwrap().0);
+        assert_ne!(KittiesModule::cheapest_listing(tier).map(|(id, _)| id), Some(1));
+        assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 1, None), Error::<Test>::KittyNotForSale);
+
+        // the scheduler removes them at their expiry block
+        Scheduler::on_initialize(5);
+        assert_eq!(KittiesModule::price(1), None);
+        assert_eq!(KittiesModule::listing_expiry(1), None);
+        assert_eq!(KittiesModule::price(0), Some(10));
+        System::assert_has_event(mock::Event::KittiesModule(Event::ListingExpired(1)));
+        assert_ok!(KittiesModule::do_try_state());
+
+        // selling early cancels the expiry task
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, None));
+        assert_eq!(KittiesModule::listing_expiry(0), None);
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(2), 0, Some(10), Some(15)));
+        System::set_block_number(11);
+        Scheduler::on_initialize(11);
+        assert_eq!(KittiesModule::price(0), Some(10));
+    });
+}
+
+#[test]
+fn sell_kitty_failed_with_invalid_expiry() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(
+            KittiesModule::sell_kitty(Origin::signed(1), 0, Some(10), Some(1)),
+            Error::<Test>::InvalidListingExpiry
+        );
+        assert_noop!(
+            KittiesModule::sell_kitty(Origin::signed(1), 0, Some(10), Some(12)),
+            Error::<Test>::InvalidListingExpiry
+        );
+    });
+}
+
+#[test]
+fn sell_kitty_failed_with_price_out_of_bounds() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(
+            KittiesModule::sell_kitty(Origin::signed(1), 0, Some(0), None),
+            Error::<Test>::PriceOutOfBounds
+        );
+        assert_noop!(
+            KittiesModule::sell_kitty(Origin::signed(1), 0, Some(1_000_001), None),
+            Error::<Test>::PriceOutOfBounds
+        );
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(1_000_000), None));
+        // unlisting takes no price
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, None, None));
+    });
+}
+
+#[test]
+fn buy_kitty_with_donation_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(666), None));
+        assert_ok!(KittiesModule::set_donation_beneficiary(Origin::root(), Some(3)));
+
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, Some(500)));
+        assert_eq!(Balances::free_balance(3), 500);
+        assert_eq!(TotalDonations::<Test>::get(), 500);
+        System::assert_has_event(mock::Event::KittiesModule(Event::Bought(2, 1, 0, 666, Some(500))));
+    });
+}
+
+#[test]
+fn buy_kitty_with_donation_failed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(666), None));
+        assert_noop!(
+            KittiesModule::buy_kitty(Origin::signed(2), 0, Some(500)),
+            Error::<Test>::NoDonationBeneficiary
+        );
+        assert_noop!(
+            KittiesModule::set_donation_beneficiary(Origin::signed(1), Some(1)),
+            sp_runtime::DispatchError::BadOrigin
+        );
+    });
+}
+
+fn mine(who: u64, enough: bool) -> u64 {
+    (0..)
+        .find(|&nonce| (u128::from_be_bytes(KittiesModule::pow_dna(&who, nonce)).leading_zeros() >= 4) == enough)
+        .unwrap()
+}
+
+#[test]
+fn create_with_nonce_works() {
+    new_test_ext().execute_with(|| {
+        let nonce = mine(1, true);
+        assert_ok!(KittiesModule::create_with_nonce(Origin::signed(1), nonce));
+        assert_eq!(KittiesModule::kitties(0).map(|kitty| kitty.0), Some(dna::seal(KittiesModule::pow_dna(&1, nonce))));
+        System::assert_has_event(mock::Event::KittiesModule(Event::KittyCreate(1, 0)));
+
+        // the nonce stays valid until the seed block moves on
+        System::set_block_number(10);
+        assert_noop!(KittiesModule::create_with_nonce(Origin::signed(1), nonce), Error::<Test>::DnaAlreadyMined);
+    });
+}
+
+#[test]
+fn create_with_nonce_failed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            KittiesModule::create_with_nonce(Origin::signed(1), mine(1, false)),
+            Error::<Test>::InsufficientWork
+        );
+        assert_noop!(
+            KittiesModule::create_with_nonce(Origin::signed(4), mine(4, true)),
+            Error::<Test>::InsufficientBalance
+        );
+    });
+}
+
+#[test]
+fn owner_history_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_eq!(KittiesModule::owner_history(0).into_inner(), vec![(1, 1)]);
+
+        System::set_block_number(2);
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(2), 0, Some(10), None));
+        System::set_block_number(3);
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(0), 0, None));
+
+        // only the last MaxOwnersTracked owners are kept
+        assert_eq!(KittiesModule::owner_history(0).into_inner(), vec![(2, 2), (0, 3)]);
+
+        assert_ok!(KittiesModule::export_kitty(Origin::signed(0), 0, 0, *b"otherchn"));
+        assert!(KittiesModule::owner_history(0).is_empty());
+    });
+}
+
+#[test]
+fn config_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::set_breeding_window(Origin::root(), 2, 3));
+        assert_ok!(KittiesModule::set_donation_beneficiary(Origin::root(), Some(3)));
+
+        let config = KittiesModule::config();
+        assert_eq!(config.chain_id, *b"kittymck");
+        assert_eq!((config.deposit_base, config.current_deposit), (1_000, 1_000));
+        assert_eq!(config.max_listing_duration, 10);
+        assert_eq!(config.breeding_windows, Some(vec![(2, 3)]));
+        assert_eq!(config.donation_beneficiary, Some(3));
+    });
+}
+
+#[test]
+fn set_parameter_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::set_parameter(Origin::root(), KittyParameter::DepositBase(Some(2_000))));
+        System::assert_has_event(mock::Event::KittiesModule(Event::ParameterSet(KittyParameter::DepositBase(Some(2_000)))));
+        assert_eq!(KittiesModule::current_deposit(), 2_000);
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_eq!(Balances::reserved_balance(1), 2_000);
+
+        assert_ok!(KittiesModule::set_parameter(Origin::root(), KittyParameter::MaxListingDuration(Some(2))));
+        assert_noop!(
+            KittiesModule::sell_kitty(Origin::signed(1), 0, Some(10), Some(4)),
+            Error::<Test>::InvalidListingExpiry
+        );
+
+        // resetting falls back to the Config constant
+        assert_ok!(KittiesModule::set_parameter(Origin::root(), KittyParameter::DepositBase(None)));
+        assert_eq!(KittiesModule::deposit_base(), 1_000);
+        assert_eq!(KittiesModule::config().max_listing_duration, 2);
+    });
+}
+
+#[test]
+fn rebalance_deposit_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_eq!(Balances::reserved_balance(1), 1_000);
+
+        // raising the base tops up the deposit of whoever reserved it
+        assert_ok!(KittiesModule::set_parameter(Origin::root(), KittyParameter::DepositBase(Some(1_500))));
+        assert_ok!(KittiesModule::rebalance_deposit(Origin::signed(3), 0));
+        assert_eq!(Balances::reserved_balance(1), 1_500);
+        assert_eq!(KittiesModule::kitty_deposit(0), Some((1, 1_500)));
+        System::assert_last_event(mock::Event::KittiesModule(Event::DepositRebalanced(0, 1_000, 1_500)));
+        // rebalancing again changes nothing
+        assert_ok!(KittiesModule::rebalance_deposit(Origin::signed(3), 0));
+        assert_eq!(Balances::reserved_balance(1), 1_500);
+
+        // lowering it refunds, here when the kitty is transferred
+        assert_ok!(KittiesModule::set_parameter(Origin::root(), KittyParameter::DepositBase(Some(500))));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 3, 0));
+        assert_eq!(Balances::reserved_balance(1), 500);
+
+        // a buyer reserves the rebalanced deposit
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(2), 1, Some(10), None));
+        assert_eq!(Balances::reserved_balance(2), 500);
+        assert_ok!(KittiesModule::set_parameter(Origin::root(), KittyParameter::DepositBase(Some(2_000))));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(0), 1, None));
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(KittiesModule::kitty_deposit(1), Some((0, 2_000)));
+    });
+}
+
+#[test]
+fn rebalance_deposit_failed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(KittiesModule::rebalance_deposit(Origin::signed(1), 0), Error::<Test>::InvalidKittyIndex);
+
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::set_parameter(Origin::root(), KittyParameter::DepositBase(Some(2_000))));
+        Balances::make_free_balance_be(&1, 600);
+        assert_noop!(KittiesModule::rebalance_deposit(Origin::signed(2), 0), Error::<Test>::InsufficientBalance);
+        // a depositor short of the top-up doesn't stop the kitty being used
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+        assert_eq!(KittiesModule::kitty_deposit(0), Some((1, 1_000)));
+    });
+}
+
+#[test]
+fn set_parameter_failed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            KittiesModule::set_parameter(Origin::signed(1), KittyParameter::PowDifficulty(Some(0))),
+            sp_runtime::DispatchError::BadOrigin
+        );
+    });
+}
+
+#[test]
+fn leaderboards_work() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_ok!(KittiesModule::create(Origin::signed(0)));
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+        assert_eq!(KittiesModule::leaderboards().top_owners, vec![(1, 3), (2, 1)]);
+        assert_eq!(KittiesModule::leaderboards().top_breeders, vec![(1, 1)]);
+
+        // sales move kitties between accounts and rank each kitty by its best price
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(500), None));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, None));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(2), 0, Some(100), None));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(1), 0, None));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, Some(200), None));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(0), 1, None));
+
+        let leaderboards = KittiesModule::leaderboards();
+        assert_eq!(leaderboards.top_owners, vec![(1, 2), (0, 2)]);
+        assert_eq!(leaderboards.top_sales, vec![(0, 500), (1, 200)]);
+        assert_eq!(KittiesModule::owned_count(2), 1);
+    });
+}
+
+#[test]
+fn claim_inheritance_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 2, Some(10), None));
+        assert_ok!(KittiesModule::set_heir(Origin::signed(1), 0, 5));
+
+        // any kitty extrinsic restarts the inactivity period
+        System::set_block_number(4);
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+        assert_eq!(KittiesModule::last_active(1), 4);
+        System::set_block_number(9);
+
+        assert_ok!(KittiesModule::claim_inheritance(Origin::signed(0), 1));
+        System::assert_has_event(mock::Event::KittiesModule(Event::InheritanceClaimed(0, 1, 1, true)));
+        assert_eq!(Owner::<Test>::get(2), Some(0));
+        assert_eq!(Owner::<Test>::get(1), Some(2));
+        assert_eq!(KittiesModule::price(2), None);
+        assert_eq!(KittiesModule::owned_count(1), 0);
+        assert_eq!(KittiesModule::heir(1), None);
+    });
+}
+
+#[test]
+fn claim_inheritance_skips_co_owned_kitties() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::add_co_owner(Origin::signed(1), 0, 2, Permill::from_percent(30)));
+        assert_ok!(KittiesModule::set_heir(Origin::signed(1), 0, 5));
+        System::set_block_number(6);
+
+        // the co-owned kitty stays, with its co-owner's share
+        assert_ok!(KittiesModule::claim_inheritance(Origin::signed(0), 1));
+        System::assert_last_event(mock::Event::KittiesModule(Event::InheritanceClaimed(0, 1, 1, true)));
+        assert_eq!(Owner::<Test>::get(0), Some(1));
+        assert_eq!(Owner::<Test>::get(1), Some(0));
+        assert_eq!(KittiesModule::co_owners(0).into_inner(), vec![(2, Permill::from_percent(30))]);
+    });
+}
+
+#[test]
+fn claim_inheritance_in_batches_works() {
+    use frame_support::traits::Hooks;
+
+    new_test_ext().execute_with(|| {
+        for _ in 0..3 {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+        }
+        assert_ok!(KittiesModule::set_heir(Origin::signed(1), 0, 5));
+        System::set_block_number(6);
+
+        // MaxInheritedKitties: 2 per call, and the heir stays until all are handed over
+        assert_ok!(KittiesModule::claim_inheritance(Origin::signed(0), 1));
+        System::assert_last_event(mock::Event::KittiesModule(Event::InheritanceClaimed(0, 1, 2, false)));
+        assert_eq!(KittiesModule::owned_count(0), 2);
+        assert!(KittiesModule::heir(1).is_some());
+        assert_ok!(KittiesModule::claim_inheritance(Origin::signed(0), 1));
+        System::assert_last_event(mock::Event::KittiesModule(Event::InheritanceClaimed(0, 1, 1, true)));
+        assert_eq!(KittiesModule::owned_count(0), 3);
+        assert_eq!(KittiesModule::heir(1), None);
+        assert_eq!(KittiesOwned::<Test>::iter_prefix(1).count(), 0);
+        assert_eq!(KittiesOwned::<Test>::iter_prefix(0).count(), 3);
+
+        // kitties owned since before the index was kept are added by the repair pass
+        KittiesOwned::<Test>::remove(0, 1);
+        KittiesModule::on_idle(6, 1_000_000);
+        System::assert_has_event(mock::Event::KittiesModule(Event::StorageRepaired(1)));
+        assert!(KittiesOwned::<Test>::contains_key(0, 1));
+    });
+}
+
+#[test]
+fn claim_inheritance_failed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(KittiesModule::set_heir(Origin::signed(1), 1, 5), Error::<Test>::InvalidHeir);
+        assert_noop!(KittiesModule::set_heir(Origin::signed(1), 0, 0), Error::<Test>::InvalidHeir);
+        assert_noop!(KittiesModule::claim_inheritance(Origin::signed(0), 1), Error::<Test>::NotHeir);
+
+        assert_ok!(KittiesModule::set_heir(Origin::signed(1), 0, 5));
+        assert_noop!(KittiesModule::claim_inheritance(Origin::signed(2), 1), Error::<Test>::NotHeir);
+        System::set_block_number(5);
+        assert_noop!(KittiesModule::claim_inheritance(Origin::signed(0), 1), Error::<Test>::OwnerStillActive);
+
+        assert_ok!(KittiesModule::clear_heir(Origin::signed(1)));
+        System::set_block_number(6);
+        assert_noop!(KittiesModule::claim_inheritance(Origin::signed(0), 1), Error::<Test>::NotHeir);
+    });
+}
+
+#[test]
+fn on_idle_repairs_storage() {
+    use frame_support::traits::Hooks;
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+
+        // a stored `None` price, and the leftovers of a kitty removed on its own
+        Price::<Test>::insert(0, Option::<Balance>::None);
+        Kitties::<Test>::remove(1);
+        assert_eq!(Balances::reserved_balance(1), 2_000);
+
+        KittiesModule::on_idle(1, 1_000_000);
+        System::assert_has_event(mock::Event::KittiesModule(Event::StorageRepaired(0)));
+        System::assert_has_event(mock::Event::KittiesModule(Event::StorageRepaired(1)));
+        assert!(!Price::<Test>::contains_key(0));
+        assert!(!Owner::<Test>::contains_key(1));
+        assert!(!KittyDeposits::<Test>::contains_key(1));
+        assert_eq!(Balances::reserved_balance(1), 1_000);
+        assert_eq!(Owner::<Test>::get(2), Some(2));
+
+        // the cursor wraps around after a full sweep
+        assert_eq!(RepairCursor::<Test>::get(), Some(0));
+        assert_ok!(KittiesModule::do_try_state());
+    });
+}
+
+#[test]
+fn repair_clears_listing_indexes() {
+    use frame_support::traits::Hooks;
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(10), None));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, Some(20), None));
+        let tier0 = RarityTier::from_dna(&KittiesModule::kitties(0).unwrap().0);
+        let tier1 = RarityTier::from_dna(&KittiesModule::kitties(1).unwrap().0);
+
+        // a listing whose price was stored as `None`, and a listed kitty removed on its own
+        Price::<Test>::insert(0, Option::<Balance>::None);
+        Kitties::<Test>::remove(1);
+
+        KittiesModule::on_idle(1, 1_000_000);
+        for kitty_id in 0..2 {
+            assert_eq!(ListingsByRarity::<Test>::iter().filter(|(_, id, _)| *id == kitty_id).count(), 0);
+            assert!(!ListingsBySeller::<Test>::contains_key(1, kitty_id));
+            assert!(!ListingExpiry::<Test>::contains_key(kitty_id));
+        }
+        assert_eq!(KittiesModule::cheapest_listing(tier0), None);
+        assert_eq!(KittiesModule::cheapest_listing(tier1), None);
+        assert_eq!(CheapestListings::<Test>::iter().count(), 0);
+        assert_eq!(Balances::reserved_balance(1), 1_000);
+        assert_ok!(KittiesModule::do_try_state());
+    });
+}
+
+#[test]
+fn repair_refiles_listings_by_tier() {
+    use frame_support::traits::Hooks;
+
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        // a carrier of the rarest allele is epic
+        Kitties::<Test>::insert(0, Some(Kitty(dna::seal([0x3f; 16]))));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(10), None));
+        assert_eq!(ListingsByRarity::<Test>::get(RarityTier::Epic, 0), Some(10));
+
+        // a listing filed under the tier its kitty was in before the tiers were redrawn
+        ListingsByRarity::<Test>::remove(RarityTier::Epic, 0);
+        ListingsByRarity::<Test>::insert(RarityTier::Common, 0, 10);
+
+        KittiesModule::on_idle(1, 1_000_000);
+        System::assert_has_event(mock::Event::KittiesModule(Event::StorageRepaired(0)));
+        assert_eq!(ListingsByRarity::<Test>::get(RarityTier::Common, 0), None);
+        assert_eq!(ListingsByRarity::<Test>::get(RarityTier::Epic, 0), Some(10));
+        assert_eq!(KittiesModule::cheapest_listing(RarityTier::Epic), Some((0, 10)));
+        assert_ok!(KittiesModule::do_try_state());
+    });
+}
+
+#[test]
+fn co_ownership_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::add_co_owner(Origin::signed(1), 0, 2, Permill::from_percent(30)));
+        assert_ok!(KittiesModule::add_co_owner(Origin::signed(1), 0, 3, Permill::from_percent(30)));
+
+        // the owner's 40% alone is no majority
+        assert_noop!(
+            KittiesModule::sell_kitty(Origin::signed(1), 0, Some(10_000), None),
+            Error::<Test>::NotApprovedByCoOwners
+        );
+        assert_ok!(KittiesModule::approve_co_owned(Origin::signed(2), 0, CoOwnerAction::Sell(10_000)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(10_000), None));
+
+        // the price is split by share, and the buyer owns the kitty outright
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(0), 0, None));
+        assert_eq!(Balances::free_balance(1), 100_000_000 + 4_000);
+        assert_eq!(Balances::free_balance(2), 100_000_000 + 3_000);
+        assert_eq!(Balances::free_balance(3), 3_000);
+        assert!(KittiesModule::co_owners(0).is_empty());
+        assert_ok!(KittiesModule::transfer(Origin::signed(0), 1, 0));
+    });
+}
+
+#[test]
+fn co_ownership_failed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(
+            KittiesModule::add_co_owner(Origin::signed(2), 0, 2, Permill::from_percent(10)),
+            Error::<Test>::NotKittyOwner
+        );
+        assert_ok!(KittiesModule::add_co_owner(Origin::signed(1), 0, 2, Permill::from_percent(60)));
+        assert_noop!(
+            KittiesModule::add_co_owner(Origin::signed(1), 0, 2, Permill::from_percent(10)),
+            Error::<Test>::AlreadyCoOwner
+        );
+        assert_noop!(
+            KittiesModule::add_co_owner(Origin::signed(1), 0, 3, Permill::from_percent(50)),
+            Error::<Test>::SharesExceedWhole
+        );
+        assert_ok!(KittiesModule::add_co_owner(Origin::signed(1), 0, 3, Permill::from_percent(10)));
+        assert_noop!(
+            KittiesModule::add_co_owner(Origin::signed(1), 0, 0, Permill::from_percent(10)),
+            Error::<Test>::TooManyCoOwners
+        );
+
+        assert_noop!(
+            KittiesModule::approve_co_owned(Origin::signed(0), 0, CoOwnerAction::Transfer(0)),
+            Error::<Test>::NotCoOwner
+        );
+        // approvals only count for the action they were given for
+        assert_ok!(KittiesModule::approve_co_owned(Origin::signed(2), 0, CoOwnerAction::Transfer(3)));
+        assert_noop!(KittiesModule::transfer(Origin::signed(1), 0, 0), Error::<Test>::NotApprovedByCoOwners);
+        assert_noop!(KittiesModule::export_kitty(Origin::signed(1), 0, 1, *b"otherchn"), Error::<Test>::KittyCoOwned);
+    });
+}
+
+#[test]
+fn reroll_trait_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        let dna = KittiesModule::kitties(0).unwrap().0;
+        let pot = Balances::free_balance(KittiesModule::account_id());
+
+        assert_ok!(KittiesModule::reroll_trait(Origin::signed(1), 0, 3));
+        assert_eq!(Balances::free_balance(KittiesModule::account_id()), pot + 100);
+        assert_eq!(KittiesModule::pending_reroll(0), Some(3));
+        assert_eq!(KittiesModule::reroll_count(0), 1);
+        System::assert_last_event(mock::Event::KittiesModule(Event::RerollRequested(0, 3, 3)));
+        // nothing changes until the reveal block
+        assert_eq!(KittiesModule::kitties(0).unwrap().0, dna);
+
+        System::set_block_number(3);
+        Scheduler::on_initialize(3);
+        let rerolled = KittiesModule::kitties(0).unwrap().0;
+        assert_eq!(KittiesModule::pending_reroll(0), None);
+        System::assert_has_event(mock::Event::KittiesModule(
+            Event::TraitRerolled(0, 3, dna[3], rerolled[3])
+        ));
+        // only the chosen gene may change, and the dna stays sealed
+        assert!((0..dna::GENES).filter(|&i| i != 3).all(|i| rerolled[i] == dna[i]));
+        assert!(dna::is_valid(&rerolled));
+    });
+}
+
+#[test]
+fn reroll_trait_failed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(KittiesModule::reroll_trait(Origin::signed(2), 0, 0), Error::<Test>::NotKittyOwner);
+        assert_noop!(KittiesModule::reroll_trait(Origin::signed(1), 0, 16), Error::<Test>::InvalidGeneIndex);
+
+        assert_ok!(KittiesModule::reroll_trait(Origin::signed(1), 0, 0));
+        // MaxRerolls is 1, and the one spent counts even before it is revealed
+        assert_noop!(KittiesModule::reroll_trait(Origin::signed(1), 0, 1), Error::<Test>::TooManyRerolls);
+    });
+}
+
+#[test]
+fn marketplace_fee_works() {
+    new_test_ext().execute_with(|| {
+        let pot = KittiesModule::account_id();
+        assert_ok!(KittiesModule::set_parameter(Origin::root(), KittyParameter::SaleFee(Some(Permill::from_percent(10)))));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+        // peer-to-peer sales pay the full fee
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(10_000), None));
+        let (seller, pot_before) = (Balances::free_balance(1), Balances::free_balance(pot));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(0), 0, None));
+        // the seller also gets the kitty deposit back
+        assert_eq!(Balances::free_balance(1), seller + 9_000 + 1_000);
+        assert_eq!(Balances::free_balance(pot), pot_before + 1_000);
+
+        // sales settled by an approved marketplace pay its reduced fee
+        assert_ok!(KittiesModule::approve_marketplace(Origin::root(), 2, Permill::from_percent(5)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(0), 0, Some(10_000), None));
+        let (seller, pot_before) = (Balances::free_balance(0), Balances::free_balance(pot));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, None));
+        assert_eq!(Balances::free_balance(0), seller + 9_500 + 1_000);
+        assert_eq!(Balances::free_balance(pot), pot_before + 500);
+        System::assert_last_event(mock::Event::KittiesModule(Event::MarketplaceAttribution(2, 0, 500)));
+
+        // a marketplace fee above the sale fee is capped at it
+        assert_ok!(KittiesModule::approve_marketplace(Origin::root(), 1, Permill::from_percent(50)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(2), 0, Some(10_000), None));
+        let pot_before = Balances::free_balance(pot);
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(1), 0, None));
+        assert_eq!(Balances::free_balance(pot), pot_before + 1_000);
+
+        assert_ok!(KittiesModule::revoke_marketplace(Origin::root(), 2));
+        assert_eq!(KittiesModule::approved_marketplace(2), None);
+    });
+}
+
+#[test]
+fn marketplace_fee_failed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            KittiesModule::approve_marketplace(Origin::signed(2), 2, Permill::zero()),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(KittiesModule::revoke_marketplace(Origin::root(), 2), Error::<Test>::UnknownMarketplace);
+    });
+}
+
+#[test]
+fn nonfungible_owner_works() {
+    new_test_ext().execute_with(|| {
+        use frame_support::traits::tokens::nonfungible::Inspect;
+
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_eq!(<KittiesModule as Inspect<u64>>::owner(&0), Some(1));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+        assert_eq!(<KittiesModule as Inspect<u64>>::owner(&0), Some(2));
+        assert_eq!(<KittiesModule as Inspect<u64>>::owner(&1), None);
+    });
+}
+
+#[test]
+fn kitties_page_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_ok!(KittiesModule::export_kitty(Origin::signed(1), 1, 1, *b"otherchn"));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(2), 2, Some(666), None));
+        let info = |id: u32, owner: u64, price: Option<u128>| KittyInfo {
+            id,
+            dna: KittiesModule::kitties(id).unwrap().0,
+            owner,
+            generation: 0,
+            price,
+        };
+
+        // the burned kitty is skipped, and the cursor moves past it all the same
+        assert_eq!(KittiesModule::kitties_page(0, 2), (vec![info(0, 1, None)], Some(2)));
+        assert_eq!(KittiesModule::kitties_page(2, 2), (vec![info(2, 2, Some(666))], None));
+        assert_eq!(KittiesModule::kitties_page(3, 2), (vec![], None));
+        // an empty page would hand back the same cursor forever
+        assert_eq!(KittiesModule::kitties_page(0, 0), (vec![info(0, 1, None)], Some(1)));
+    });
+}
+
+#[test]
+fn session_call_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::authorize_session(Origin::signed(1), 9, SESSION_BREED | SESSION_REROLL, 5));
+        assert_ok!(KittiesModule::authorize_session(Origin::signed(2), 9, 0, 5));
+        System::assert_last_event(mock::Event::KittiesModule(Event::SessionAuthorized(2, 9, 0, 5)));
+        assert_eq!(KittiesModule::session(1, 9), Some((SESSION_BREED | SESSION_REROLL, 5)));
+
+        // the operator acts as the user, who pays the deposit
+        assert_ok!(KittiesModule::session_call(
+            Origin::signed(9), 1, SessionCall::Breed { kitty_id1: 0, kitty_id2: 1 }
+        ));
+        assert_eq!(Owner::<Test>::get(2), Some(1));
+        assert_eq!(Balances::reserved_balance(9), 0);
+        assert_ok!(KittiesModule::session_call(
+            Origin::signed(9), 1, SessionCall::RerollTrait { kitty_id: 2, gene_index: 0 }
+        ));
+        assert!(PendingRerolls::<Test>::contains_key(2));
+
+        assert_ok!(KittiesModule::revoke_session(Origin::signed(2), 9));
+        assert_eq!(KittiesModule::session(2, 9), None);
+
+        // the scheduler removes sessions once they expire
+        System::set_block_number(5);
+        Scheduler::on_initialize(5);
+        assert_eq!(KittiesModule::session(1, 9), None);
+        System::assert_has_event(mock::Event::KittiesModule(Event::SessionExpired(1, 9)));
+    });
+}
+
+#[test]
+fn session_call_failed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(
+            KittiesModule::authorize_session(Origin::signed(1), 9, SESSION_BREED, 1),
+            Error::<Test>::InvalidSessionExpiry
+        );
+        // sessions can't move kitties, so the old transfer bit is refused
+        assert_noop!(
+            KittiesModule::authorize_session(Origin::signed(1), 9, 1 << 0, 5),
+            Error::<Test>::InvalidCallMask
+        );
+        assert_noop!(
+            KittiesModule::session_call(Origin::signed(9), 1, SessionCall::Breed { kitty_id1: 0, kitty_id2: 1 }),
+            Error::<Test>::CallNotAuthorized
+        );
+        assert_noop!(KittiesModule::revoke_session(Origin::signed(1), 9), Error::<Test>::NoSession);
+
+        assert_ok!(KittiesModule::authorize_session(Origin::signed(1), 9, SESSION_BREED, 5));
+        // only the calls in the mask, and only by the operator
+        assert_noop!(
+            KittiesModule::session_call(Origin::signed(9), 1, SessionCall::RerollTrait { kitty_id: 0, gene_index: 0 }),
+            Error::<Test>::CallNotAuthorized
+        );
+        assert_noop!(
+            KittiesModule::session_call(Origin::signed(8), 1, SessionCall::Breed { kitty_id1: 0, kitty_id2: 1 }),
+            Error::<Test>::CallNotAuthorized
+        );
+        // the underlying call's checks still apply
+        assert_noop!(
+            KittiesModule::session_call(Origin::signed(9), 1, SessionCall::Breed { kitty_id1: 0, kitty_id2: 1 }),
+            Error::<Test>::InvalidKittyIndex
+        );
+
+        System::set_block_number(5);
+        assert_noop!(
+            KittiesModule::session_call(Origin::signed(9), 1, SessionCall::Breed { kitty_id1: 0, kitty_id2: 1 }),
+            Error::<Test>::CallNotAuthorized
+        );
+    });
+}
+
+#[test]
+fn buy_in_installments_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(100), None));
+        let seller_balance = Balances::free_balance(1);
+
+        // the down payment takes the rounding, and the kitty goes into escrow
+        assert_ok!(KittiesModule::buy_in_installments(Origin::signed(2), 0, 3));
+        System::assert_last_event(mock::Event::KittiesModule(Event::InstallmentPurchaseStarted(2, 0, 100, 34)));
+        assert_eq!(Balances::reserved_balance(2), 34);
+        assert_eq!(Owner::<Test>::get(0), Some(KittiesModule::account_id()));
+        assert_eq!(KittiesModule::live_price(0), None);
+        // escrow doesn't make the pallet an owner on the leaderboard
+        assert_eq!(OwnedCount::<Test>::get(KittiesModule::account_id()), 0);
+        assert_eq!(KittiesModule::leaderboards().top_owners, vec![]);
+
+        System::set_block_number(3);
+        assert_ok!(KittiesModule::pay_installment(Origin::signed(2), 0));
+        System::assert_last_event(mock::Event::KittiesModule(Event::InstallmentPaid(0, 33, 33)));
+        assert_eq!(KittiesModule::installment_plan(0).map(|plan| (plan.paid, plan.due)), Some((67, 11)));
+
+        // the last installment completes the sale
+        System::set_block_number(10);
+        assert_ok!(KittiesModule::pay_installment(Origin::signed(2), 0));
+        System::assert_has_event(mock::Event::KittiesModule(Event::Bought(2, 1, 0, 100, None)));
+        assert_eq!(KittiesModule::installment_plan(0), None);
+        assert_eq!(Owner::<Test>::get(0), Some(2));
+        assert_eq!(Balances::free_balance(1), seller_balance + 100);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(2), 1_000);
+        assert_eq!(KittiesModule::kitty_deposit(0), Some((2, 1_000)));
+    });
+}
+
+#[test]
+fn buy_in_installments_failed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(KittiesModule::buy_in_installments(Origin::signed(2), 0, 2), Error::<Test>::KittyNotForSale);
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(100), None));
+        assert_noop!(KittiesModule::buy_in_installments(Origin::signed(2), 1, 2), Error::<Test>::InvalidKittyIndex);
+        assert_noop!(KittiesModule::buy_in_installments(Origin::signed(1), 0, 2), Error::<Test>::BuyFromSelf);
+        assert_noop!(KittiesModule::buy_in_installments(Origin::signed(2), 0, 1), Error::<Test>::InvalidInstallments);
+        assert_noop!(KittiesModule::buy_in_installments(Origin::signed(2), 0, 5), Error::<Test>::InvalidInstallments);
+
+        assert_ok!(KittiesModule::buy_in_installments(Origin::signed(2), 0, 2));
+        // nobody can move a kitty in escrow
+        assert_noop!(KittiesModule::transfer(Origin::signed(1), 3, 0), Error::<Test>::NotKittyOwner);
+        assert_noop!(KittiesModule::pay_installment(Origin::signed(3), 0), Error::<Test>::NotInstallmentBuyer);
+        assert_noop!(KittiesModule::pay_installment(Origin::signed(2), 1), Error::<Test>::NoInstallmentPlan);
+        assert_noop!(KittiesModule::settle_default(Origin::signed(3), 0), Error::<Test>::InstallmentNotOverdue);
+
+        // a missed installment returns the kitty, and the seller keeps the penalty
+        System::set_block_number(6);
+        let seller_balance = Balances::free_balance(1);
+        assert_noop!(KittiesModule::pay_installment(Origin::signed(2), 0), Error::<Test>::InstallmentOverdue);
+        assert_ok!(KittiesModule::settle_default(Origin::signed(3), 0));
+        System::assert_last_event(mock::Event::KittiesModule(Event::InstallmentDefaulted(2, 0, 5)));
+        assert_eq!(Owner::<Test>::get(0), Some(1));
+        assert_eq!(Balances::free_balance(1), seller_balance + 5);
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(Balances::free_balance(2), 100_000_000 - 5);
+        assert_noop!(KittiesModule::settle_default(Origin::signed(3), 0), Error::<Test>::NoInstallmentPlan);
+
+        // the sale isn't completed out of a reserve that was slashed in the meantime
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, Some(100), None));
+        assert_ok!(KittiesModule::buy_in_installments(Origin::signed(2), 1, 2));
+        Balances::slash_reserved(&2, 10);
+        assert_noop!(KittiesModule::pay_installment(Origin::signed(2), 1), Error::<Test>::InsufficientBalance);
+    });
+}
+
+#[test]
+fn discounted_deposit_works() {
+    new_test_ext().execute_with(|| {
+        MintDiscount::set(&Permill::from_percent(20));
+        BreedDiscount::set(&Permill::from_percent(50));
+        assert_eq!(KittiesModule::discounted_deposit(&2, DiscountedAction::Mint), 800);
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_eq!(KittyDeposits::<Test>::get(0), Some((2, 800)));
+        assert_eq!(Balances::reserved_balance(2), 1_600);
+
+        assert_ok!(KittiesModule::breed(Origin::signed(2), 0, 1));
+        assert_eq!(KittyDeposits::<Test>::get(2), Some((2, 500)));
+        assert_eq!(Balances::reserved_balance(2), 2_100);
+
+        // the discount is kept when the deposit is rebalanced
+        assert_ok!(KittiesModule::set_parameter(Origin::root(), KittyParameter::DepositBase(Some(2_000))));
+        assert_ok!(KittiesModule::rebalance_deposit(Origin::signed(1), 0));
+        assert_eq!(KittyDeposits::<Test>::get(0), Some((2, 1_600)));
+    });
+}
+
+#[test]
+fn discounted_deposit_failed() {
+    new_test_ext().execute_with(|| {
+        // discounts are per account and per action
+        MintDiscount::set(&Permill::from_percent(20));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_eq!(KittyDeposits::<Test>::get(0), Some((1, 1_000)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_ok!(KittiesModule::breed(Origin::signed(2), 1, 2));
+        assert_eq!(KittyDeposits::<Test>::get(3), Some((2, 2_000)));
+
+        // a discount doesn't cover a deposit the account can't afford
+        MintDiscount::set(&Permill::from_percent(50));
+        let free = Balances::free_balance(2);
+        assert_ok!(Balances::reserve(&2, free - 999));
+        assert_noop!(KittiesModule::create(Origin::signed(2)), Error::<Test>::InsufficientBalance);
+    });
+}
+
+#[test]
+fn bulk_listings_works() {
+    new_test_ext().execute_with(|| {
+        for kitty_id in 0..3 {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), kitty_id, Some(100), None));
+        }
+
+        // each call goes through at most `MaxBulkListings` listings, carrying on where it left off
+        assert_ok!(KittiesModule::reprice_all(Origin::signed(1), Permill::from_percent(10), PriceDirection::Up));
+        System::assert_last_event(mock::Event::KittiesModule(Event::ListingsRepriced(1, 2, false)));
+        assert_ok!(KittiesModule::reprice_all(Origin::signed(1), Permill::from_percent(10), PriceDirection::Up));
+        System::assert_last_event(mock::Event::KittiesModule(Event::ListingsRepriced(1, 1, true)));
+        assert_eq!((0..3).map(KittiesModule::live_price).collect::<Vec<_>>(), vec![Some(110); 3]);
+        assert_eq!(ListingExpiry::<Test>::get(0), Some(11));
+
+        assert_ok!(KittiesModule::delist_all(Origin::signed(1)));
+        System::assert_last_event(mock::Event::KittiesModule(Event::ListingsDelisted(1, 2, false)));
+        assert_ok!(KittiesModule::delist_all(Origin::signed(1)));
+        System::assert_last_event(mock::Event::KittiesModule(Event::ListingsDelisted(1, 1, true)));
+        assert_eq!((0..3).map(KittiesModule::live_price).collect::<Vec<_>>(), vec![None; 3]);
+
+        // a listing moves with its kitty
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(100), None));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+        assert_ok!(KittiesModule::reprice_all(Origin::signed(2), Permill::from_percent(50), PriceDirection::Down));
+        assert_eq!(KittiesModule::live_price(0), Some(50));
+        assert_ok!(KittiesModule::delist_all(Origin::signed(2)));
+        assert_eq!(KittiesModule::live_price(0), None);
+    });
+}
+
+#[test]
+fn reprice_all_starts_over_after_stale_cursor() {
+    new_test_ext().execute_with(|| {
+        for kitty_id in 0..3 {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), kitty_id, Some(100), None));
+        }
+        assert_ok!(KittiesModule::reprice_all(Origin::signed(1), Permill::from_percent(10), PriceDirection::Up));
+        assert!(RepriceCursors::<Test>::get(1).is_some());
+
+        // the only listing after the cursor is taken down before the next call
+        let rest = (0..3).find(|kitty_id| KittiesModule::live_price(*kitty_id) == Some(100)).unwrap();
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), rest, None, None));
+        assert_ok!(KittiesModule::reprice_all(Origin::signed(1), Permill::from_percent(10), PriceDirection::Up));
+        System::assert_last_event(mock::Event::KittiesModule(Event::ListingsRepriced(1, 2, true)));
+        assert_eq!(RepriceCursors::<Test>::get(1), None);
+        let prices: Vec<_> = (0..3).filter(|kitty_id| *kitty_id != rest).map(KittiesModule::live_price).collect();
+        assert_eq!(prices, vec![Some(121); 2]);
+    });
+}
+
+#[test]
+fn bulk_listings_failed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(KittiesModule::delist_all(Origin::signed(1)), Error::<Test>::NoListings);
+        assert_noop!(
+            KittiesModule::reprice_all(Origin::signed(1), Permill::from_percent(10), PriceDirection::Up),
+            Error::<Test>::NoListings
+        );
+
+        // a kitty listed and then sold is no longer its seller's listing
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(100), None));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, None));
+        assert_noop!(KittiesModule::delist_all(Origin::signed(1)), Error::<Test>::NoListings);
+        assert_noop!(KittiesModule::delist_all(Origin::signed(2)), Error::<Test>::NoListings);
+
+        // prices stay within bounds, and expired listings are left alone
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, Some(1_000_000), None));
+        assert_ok!(KittiesModule::reprice_all(Origin::signed(1), Permill::from_percent(10), PriceDirection::Up));
+        assert_eq!(KittiesModule::live_price(1), Some(1_000_000));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, Some(10), Some(3)));
+        assert_ok!(KittiesModule::reprice_all(Origin::signed(1), Permill::one(), PriceDirection::Down));
+        assert_eq!(KittiesModule::live_price(1), Some(1));
+        System::set_block_number(3);
+        assert_ok!(KittiesModule::reprice_all(Origin::signed(1), Permill::from_percent(50), PriceDirection::Up));
+        System::assert_last_event(mock::Event::KittiesModule(Event::ListingsRepriced(1, 0, true)));
+        assert_eq!(KittiesModule::price(1), Some(1));
+    });
+}
+
+#[test]
+fn resale_cooldown_works() {
+    new_test_ext().execute_with(|| {
+        ResaleCooldown::set(&5);
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        // minted kitties can be listed at once
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(100), None));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, None));
+        assert_eq!(KittiesModule::acquired_at(0), Some(1));
+
+        System::set_block_number(6);
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(2), 0, Some(100), None));
+        assert_eq!(KittiesModule::live_price(0), Some(100));
+    });
+}
+
+#[test]
+fn resale_cooldown_failed() {
+    new_test_ext().execute_with(|| {
+        ResaleCooldown::set(&5);
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(100), None));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, None));
+
+        System::set_block_number(5);
+        assert_noop!(KittiesModule::sell_kitty(Origin::signed(2), 0, Some(100), None), Error::<Test>::ResaleTooSoon);
+        // delisting is always allowed
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(2), 0, None, None));
+
+        // handing the kitty to another account doesn't get round the cooldown
+        assert_ok!(KittiesModule::transfer(Origin::signed(2), 0, 0));
+        assert_noop!(KittiesModule::sell_kitty(Origin::signed(0), 0, Some(100), None), Error::<Test>::ResaleTooSoon);
+    });
+}
+
+#[test]
+fn wash_trade_works() {
+    new_test_ext().execute_with(|| {
+        let pot = KittiesModule::account_id();
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        for (seller, buyer) in [(1, 2), (2, 1)] {
+            assert_ok!(KittiesModule::sell_kitty(Origin::signed(seller), 0, Some(1_000), None));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(buyer), 0, None));
+        }
+        assert_eq!(KittiesModule::trade_streak(0).map(|streak| (streak.pair, streak.trades)), Some(((1, 2), 2)));
+
+        // every sale past the threshold is reported and costs more
+        for (seller, buyer, trades, fee) in [(1, 2, 3, 100), (2, 1, 4, 200)] {
+            assert_ok!(KittiesModule::sell_kitty(Origin::signed(seller), 0, Some(1_000), None));
+            let pot_before = Balances::free_balance(pot);
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(buyer), 0, None));
+            System::assert_has_event(mock::Event::KittiesModule(
+                Event::SuspiciousTradePattern(buyer, seller, 0, trades)
+            ));
+            assert_eq!(Balances::free_balance(pot), pot_before + fee);
+        }
+
+        // sales in installments count too
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(1_000), None));
+        assert_ok!(KittiesModule::buy_in_installments(Origin::signed(2), 0, 2));
+        let pot_before = Balances::free_balance(pot);
+        assert_ok!(KittiesModule::pay_installment(Origin::signed(2), 0));
+        System::assert_has_event(mock::Event::KittiesModule(Event::SuspiciousTradePattern(2, 1, 0, 5)));
+        assert_eq!(Balances::free_balance(pot), pot_before + 300);
+    });
+}
+
+#[test]
+fn wash_trade_failed() {
+    new_test_ext().execute_with(|| {
+        let pot = KittiesModule::account_id();
+        let pot_before = Balances::free_balance(pot);
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+        // a sale to another account starts a new streak
+        for (seller, buyer) in [(1, 2), (2, 1), (1, 0), (0, 1)] {
+            assert_ok!(KittiesModule::sell_kitty(Origin::signed(seller), 0, Some(1_000), None));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(buyer), 0, None));
+        }
+        assert_eq!(KittiesModule::trade_streak(0).map(|streak| (streak.pair, streak.trades)), Some(((0, 1), 2)));
+
+        // and so does a sale once the window has passed
+        System::set_block_number(11);
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(1_000), None));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(0), 0, None));
+        assert_eq!(KittiesModule::trade_streak(0).map(|streak| (streak.trades, streak.since)), Some((1, 11)));
+        assert_eq!(Balances::free_balance(pot), pot_before);
+        assert!(!System::events().iter().any(|record| matches!(
+            record.event,
+            mock::Event::KittiesModule(Event::SuspiciousTradePattern(..))
+        )));
+    });
+}
+
+fn mint_authorization(creator: u64, price: u128, nonce: u64) -> (MintAuthorization<u64, u128, u64>, TestSignature) {
+    let authorization = MintAuthorization {
+        creator,
+        dna: dna::seal([7u8; 16]),
+        price,
+        royalty: Permill::from_percent(10),
+        nonce,
+        expires_at: 10,
+    };
+    let signature = TestSignature(creator, authorization.signing_payload(&KittiesChainId::get()));
+    (authorization, signature)
+}
+
+#[test]
+fn lazy_buy_works() {
+    new_test_ext().execute_with(|| {
+        let (authorization, signature) = mint_authorization(1, 10_000, 0);
+        assert_ok!(KittiesModule::lazy_buy(Origin::signed(2), authorization, signature));
+        System::assert_last_event(mock::Event::KittiesModule(Event::LazyMinted(2, 1, 0, 10_000)));
+        assert_eq!(Owner::<Test>::get(0), Some(2));
+        assert_eq!(KittiesModule::kitties(0).unwrap().0, dna::seal([7u8; 16]));
+        // the creator is paid and reserves nothing, the buyer reserves the deposit
+        assert_eq!(Balances::free_balance(1), 100_010_000);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(2), 1_000);
+
+        // the creator gets their royalty on later sales
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(2), 0, Some(1_000), None));
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(0), 0, None));
+        System::assert_has_event(mock::Event::KittiesModule(Event::RoyaltyPaid(1, 0, 100)));
+        assert_eq!(Balances::free_balance(1), 100_010_100);
+    });
+}
+
+#[test]
+fn lazy_buy_failed() {
+    new_test_ext().execute_with(|| {
+        let (authorization, signature) = mint_authorization(1, 10_000, 0);
+        assert_noop!(
+            KittiesModule::lazy_buy(Origin::signed(1), authorization.clone(), signature.clone()),
+            Error::<Test>::BuyFromSelf
+        );
+        let forged = TestSignature(2, authorization.signing_payload(&KittiesChainId::get()));
+        assert_noop!(
+            KittiesModule::lazy_buy(Origin::signed(2), authorization.clone(), forged),
+            Error::<Test>::InvalidMintSignature
+        );
+        // an authorization is only good for the price signed
+        let mut cheaper = authorization.clone();
+        cheaper.price = 1;
+        assert_noop!(
+            KittiesModule::lazy_buy(Origin::signed(2), cheaper, signature.clone()),
+            Error::<Test>::InvalidMintSignature
+        );
+        let (free, free_signature) = mint_authorization(1, 0, 1);
+        assert_noop!(KittiesModule::lazy_buy(Origin::signed(2), free, free_signature), Error::<Test>::PriceOutOfBounds);
+
+        // each nonce can only be used once
+        assert_ok!(KittiesModule::lazy_buy(Origin::signed(2), authorization.clone(), signature.clone()));
+        assert_noop!(
+            KittiesModule::lazy_buy(Origin::signed(0), authorization, signature),
+            Error::<Test>::MintNonceUsed
+        );
+
+        // creators can withdraw an offer before anyone takes it up
+        let (cancelled, cancelled_signature) = mint_authorization(1, 10_000, 2);
+        assert_ok!(KittiesModule::cancel_mint_nonce(Origin::signed(1), 2));
+        System::assert_last_event(mock::Event::KittiesModule(Event::MintNonceCancelled(1, 2)));
+        assert_noop!(KittiesModule::cancel_mint_nonce(Origin::signed(1), 2), Error::<Test>::MintNonceUsed);
+        assert_noop!(
+            KittiesModule::lazy_buy(Origin::signed(2), cancelled, cancelled_signature),
+            Error::<Test>::MintNonceUsed
+        );
+
+        // and offers lapse once they expire
+        let (stale, stale_signature) = mint_authorization(1, 10_000, 3);
+        System::set_block_number(11);
+        assert_noop!(
+            KittiesModule::lazy_buy(Origin::signed(2), stale, stale_signature),
+            Error::<Test>::MintAuthorizationExpired
+        );
+    });
+}
+
+#[test]
+fn fuse_works() {
+    new_test_ext().execute_with(|| {
+        for _ in 0..3 {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+        }
+        Kitties::<Test>::insert(1, Some(Kitty(dna::seal([0xf5; 16]))));
+
+        assert_ok!(KittiesModule::fuse(Origin::signed(1), vec![0, 1, 2].try_into().unwrap()));
+        System::assert_last_event(mock::Event::KittiesModule(Event::KittiesFused(1, vec![0, 1, 2], 3)));
+        for kitty_id in 0..3 {
+            assert!(!Kitties::<Test>::contains_key(kitty_id));
+            assert_eq!(Owner::<Test>::get(kitty_id), None);
+        }
+        assert_eq!(Owner::<Test>::get(3), Some(1));
+        assert_eq!(KittiesModule::generation(3), 1);
+        // the fused kitty is at least as rare as the rarest burned
+        let dna = KittiesModule::kitties(3).unwrap().0;
+        assert!(dna::is_valid(&dna));
+        assert!(RarityTier::from_dna(&dna) >= RarityTier::Epic);
+        // only the fused kitty's deposit is still reserved
+        assert_eq!(Balances::reserved_balance(1), KittyDeposits::<Test>::get(3).unwrap().1);
+    });
+}
+
+#[test]
+fn fuse_failed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+
+        assert_noop!(
+            KittiesModule::fuse(Origin::signed(1), vec![0].try_into().unwrap()),
+            Error::<Test>::NotEnoughToFuse
+        );
+        assert_noop!(
+            KittiesModule::fuse(Origin::signed(1), vec![0, 0].try_into().unwrap()),
+            Error::<Test>::SameParentIndex
+        );
+        assert_noop!(
+            KittiesModule::fuse(Origin::signed(1), vec![0, 1, 2].try_into().unwrap()),
+            Error::<Test>::NotKittyOwner
+        );
+        assert_noop!(
+            KittiesModule::fuse(Origin::signed(1), vec![0, 1, 9].try_into().unwrap()),
+            Error::<Test>::NotKittyOwner
+        );
+        // no more than `MaxFuse` kitties at once
+        assert!(frame_support::BoundedVec::<Index, <Test as Config>::MaxFuse>::try_from(vec![0, 1, 2, 3]).is_err());
+    });
+}
+
+/// Dispatch `call` as the 2-of-3 multisig of accounts 0, 1 and 2, approved by 0 and then 1.
+fn as_multisig(call: mock::Call) {
+    let weight = call.get_dispatch_info().weight;
+    let opaque = || pallet_multisig::OpaqueCall::<Test>::from_encoded(call.encode());
+    assert_ok!(Multisig::as_multi(Origin::signed(0), 2, vec![1, 2], None, opaque(), false, 0));
+    let timepoint = Multisig::timepoint();
+    assert_ok!(Multisig::as_multi(Origin::signed(1), 2, vec![0, 2], Some(timepoint), opaque(), false, weight));
+}
+
+#[test]
+fn multisig_works() {
+    new_test_ext().execute_with(|| {
+        let multisig = Multisig::multi_account_id(&[0, 1, 2], 2);
+        assert_ok!(Balances::transfer(Origin::signed(0), multisig, 10_000));
+
+        // the multisig mints and sells a kitty like any account, reserving the deposit itself
+        as_multisig(mock::Call::KittiesModule(crate::Call::create {}));
+        assert_eq!(Owner::<Test>::get(0), Some(multisig));
+        let (depositor, deposit) = KittyDeposits::<Test>::get(0).unwrap();
+        assert_eq!(depositor, multisig);
+        assert_eq!(Balances::reserved_balance(multisig), deposit);
+        as_multisig(mock::Call::KittiesModule(crate::Call::sell_kitty {
+            kitty_id: 0,
+            price: Some(1_000),
+            expires_at: None,
+        }));
+        assert_eq!(KittiesModule::price(0), Some(1_000));
+
+        // account 1 buys it for account 2 as their proxy
+        assert_ok!(Proxy::add_proxy(Origin::signed(2), 1, (), 0));
+        let buy = mock::Call::KittiesModule(crate::Call::buy_kitty { kitty_id: 0, donate: None });
+        assert_ok!(Proxy::proxy(Origin::signed(1), 2, None, Box::new(buy)));
+        assert_eq!(Owner::<Test>::get(0), Some(2));
+        assert_eq!(Balances::reserved_balance(multisig), 0);
+        assert_eq!(Balances::free_balance(multisig), 11_000);
+    });
+}
+
+#[test]
+fn multisig_failed() {
+    new_test_ext().execute_with(|| {
+        // an unfunded multisig can't reserve the deposit
+        let multisig = Multisig::multi_account_id(&[0, 1, 2], 2);
+        as_multisig(mock::Call::KittiesModule(crate::Call::create {}));
+        assert_eq!(Owner::<Test>::get(0), None);
+
+        assert_ok!(Balances::transfer(Origin::signed(0), multisig, 10_000));
+        as_multisig(mock::Call::KittiesModule(crate::Call::create {}));
+        assert_eq!(Owner::<Test>::get(0), Some(multisig));
+        let sell = mock::Call::KittiesModule(crate::Call::sell_kitty {
+            kitty_id: 0,
+            price: Some(1_000),
+            expires_at: None,
+        });
+
+        // no signatory, other multisig or proxy of a signatory can sell the multisig's kitty
+        assert_noop!(
+            KittiesModule::sell_kitty(Origin::signed(0), 0, Some(1_000), None),
+            Error::<Test>::NotKittyOwner
+        );
+        assert_eq!(
+            Multisig::as_multi_threshold_1(Origin::signed(0), vec![1], Box::new(sell.clone())).map_err(|e| e.error),
+            Err(Error::<Test>::NotKittyOwner.into())
+        );
+        assert_ok!(Proxy::add_proxy(Origin::signed(0), 1, (), 0));
+        assert_ok!(Proxy::proxy(Origin::signed(1), 0, None, Box::new(sell.clone())));
+        assert_eq!(KittiesModule::price(0), None);
+
+        // a single approval isn't enough
+        let opaque = pallet_multisig::OpaqueCall::<Test>::from_encoded(sell.encode());
+        assert_ok!(Multisig::as_multi(Origin::signed(0), 2, vec![1, 2], None, opaque, false, 0));
+        assert_eq!(KittiesModule::price(0), None);
+    });
+}
+
+#[test]
+fn reference_price_works() {
+    new_test_ext().execute_with(|| {
+        ReferenceRate::set(&Some(3));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::sell_kitty_in_reference(Origin::signed(1), 0, 500, None));
+        System::assert_last_event(mock::Event::KittiesModule(Event::KittySaleInReference(1, 0, 500)));
+        assert_eq!(KittiesModule::reference_price(0), Some(500));
+        assert_eq!(KittiesModule::price(0), Some(1_500));
+
+        // the price is converted at the rate when the kitty is bought
+        ReferenceRate::set(&Some(4));
+        assert_ok!(KittiesModule::buy_kitty_at_rate(Origin::signed(2), 0, 2_000, None));
+        System::assert_has_event(mock::Event::KittiesModule(Event::Bought(2, 1, 0, 2_000, None)));
+        assert_eq!(Owner::<Test>::get(0), Some(2));
+        assert_eq!(KittiesModule::reference_price(0), None);
+
+        // relisting in the native currency drops the reference price, and a native listing can
+        // be bought with a slippage bound too
+        assert_ok!(KittiesModule::sell_kitty_in_reference(Origin::signed(2), 0, 500, None));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(2), 0, Some(100), None));
+        assert_eq!(KittiesModule::reference_price(0), None);
+        assert_ok!(KittiesModule::buy_kitty_at_rate(Origin::signed(0), 0, 100, None));
+        assert_eq!(Owner::<Test>::get(0), Some(0));
+    });
+}
+
+#[test]
+fn reference_price_failed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(
+            KittiesModule::sell_kitty_in_reference(Origin::signed(1), 0, 500, None),
+            Error::<Test>::NoExchangeRate
+        );
+        ReferenceRate::set(&Some(3));
+        assert_noop!(
+            KittiesModule::sell_kitty_in_reference(Origin::signed(2), 0, 500, None),
+            Error::<Test>::NotKittyOwner
+        );
+        assert_ok!(KittiesModule::sell_kitty_in_reference(Origin::signed(1), 0, 500, None));
+
+        // a reference price can only be paid with a slippage bound
+        assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 0, None), Error::<Test>::ReferencePriced);
+        assert_noop!(
+            KittiesModule::buy_in_installments(Origin::signed(2), 0, 2),
+            Error::<Test>::ReferencePriced
+        );
+        ReferenceRate::set(&Some(4));
+        assert_noop!(
+            KittiesModule::buy_kitty_at_rate(Origin::signed(2), 0, 1_999, None),
+            Error::<Test>::PriceSlippageExceeded
+        );
+        ReferenceRate::set(&None);
+        assert_noop!(
+            KittiesModule::buy_kitty_at_rate(Origin::signed(2), 0, 2_000, None),
+            Error::<Test>::NoExchangeRate
+        );
+    });
+}
+
+#[test]
+fn soulbound_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::mint_soulbound(Origin::root(), 1, dna::seal([7; 16])));
+        System::assert_last_event(mock::Event::KittiesModule(Event::SoulboundMinted(1, 0)));
+        assert!(KittiesModule::is_soulbound(0));
+        assert_eq!(Owner::<Test>::get(0), Some(1));
+        assert_eq!(Balances::reserved_balance(1), KittyDeposits::<Test>::get(0).unwrap().1);
+
+        // a soulbound kitty can still be delisted, and whatever it is fused into is soulbound
+        // too
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, None, None));
+        assert_ok!(KittiesModule::fuse(Origin::signed(1), vec![0, 1].try_into().unwrap()));
+        assert!(!KittiesModule::is_soulbound(0));
+        assert!(KittiesModule::is_soulbound(2));
+
+        // and burned by its owner
+        assert_ok!(KittiesModule::schedule_burn(Origin::signed(1), 2));
+        System::set_block_number(6);
+        Scheduler::on_initialize(6);
+        System::assert_last_event(mock::Event::KittiesModule(Event::KittyBurned(1, 2)));
+        assert!(!Kitties::<Test>::contains_key(2));
+        assert!(!KittiesModule::is_soulbound(2));
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn soulbound_failed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            KittiesModule::mint_soulbound(Origin::signed(1), 1, dna::seal([7; 16])),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(KittiesModule::mint_soulbound(Origin::root(), 1, [7; 16]), Error::<Test>::InvalidDna);
+        assert_ok!(KittiesModule::mint_soulbound(Origin::root(), 1, dna::seal([7; 16])));
+
+        assert_noop!(KittiesModule::transfer(Origin::signed(1), 2, 0), Error::<Test>::KittySoulbound);
+        assert_noop!(
+            KittiesModule::sell_kitty(Origin::signed(1), 0, Some(1_000), None),
+            Error::<Test>::KittySoulbound
+        );
+        assert_noop!(KittiesModule::export_kitty(Origin::signed(1), 0, 1, *b"otherchn"), Error::<Test>::KittySoulbound);
+        assert_noop!(KittiesModule::schedule_burn(Origin::signed(2), 0), Error::<Test>::NotKittyOwner);
+
+        // an heir takes everything but soulbound kitties
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::set_heir(Origin::signed(1), 2, 5));
+        System::set_block_number(10);
+        assert_ok!(KittiesModule::claim_inheritance(Origin::signed(2), 1));
+        assert_eq!(Owner::<Test>::get(0), Some(1));
+        assert_eq!(Owner::<Test>::get(1), Some(2));
+    });
+}
+
+#[test]
+fn genesis_config_works() {
+    let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+    pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 100_000_000), (2, 100_000_000)] }
+        .assimilate_storage(&mut t)
+        .unwrap();
+    crate::GenesisConfig::<Test> {
+        kitties: vec![(1, [7; 16], None), (2, [0xff; 16], Some(1_000)), (1, [3; 16], Some(500))],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    sp_io::TestExternalities::new(t).execute_with(|| {
+        assert_eq!(KittiesModule::total_supply(), 3);
+        assert_eq!(KittiesCount::<Test>::get(), Some(3));
+        assert_eq!(Owner::<Test>::get(1), Some(2));
+        assert_eq!(KittiesModule::owned_count(1), 2);
+        // genesis dna is sealed like any other
+        assert_eq!(KittiesModule::kitties(0).unwrap().0, dna::seal([7; 16]));
+        let deposit = |kitty_id| KittyDeposits::<Test>::get(kitty_id).unwrap().1;
+        assert_eq!(Balances::reserved_balance(1), deposit(0) + deposit(2));
+
+        assert_eq!(KittiesModule::price(0), None);
+        assert_eq!(KittiesModule::live_price(1), Some(1_000));
+        assert_eq!(KittiesModule::cheapest_listing(RarityTier::Common), Some((2, 500)));
+        assert_eq!(KittiesModule::listing_expiry(2), Some(KittiesModule::max_listing_duration()));
+
+        // and the listings can be bought once the chain runs
+        System::set_block_number(1);
+        assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 2, None));
+        assert_eq!(Owner::<Test>::get(2), Some(2));
+    });
+}
+
+#[test]
+fn delayed_burn_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+        // the scheduler burns the kitty once the delay is over
+        assert_ok!(KittiesModule::schedule_burn(Origin::signed(1), 0));
+        System::assert_last_event(mock::Event::KittiesModule(Event::BurnScheduled(1, 0, 6)));
+        assert_eq!(KittiesModule::pending_burn(0), Some((1, 6)));
+        System::set_block_number(6);
+        Scheduler::on_initialize(6);
+        System::assert_last_event(mock::Event::KittiesModule(Event::KittyBurned(1, 0)));
+        assert!(!Kitties::<Test>::contains_key(0));
+        assert_eq!(KittiesModule::pending_burn(0), None);
+        assert_eq!(Balances::reserved_balance(1), KittyDeposits::<Test>::get(1).unwrap().1);
+
+        // until then it can be cancelled, and the kitty is kept
+        assert_ok!(KittiesModule::schedule_burn(Origin::signed(1), 1));
+        assert_ok!(KittiesModule::cancel_burn(Origin::signed(1), 1));
+        System::assert_last_event(mock::Event::KittiesModule(Event::BurnCancelled(1, 1)));
+        System::set_block_number(11);
+        Scheduler::on_initialize(11);
+        assert!(Kitties::<Test>::contains_key(1));
+
+        // handing the kitty on cancels the burn too
+        assert_ok!(KittiesModule::schedule_burn(Origin::signed(1), 1));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
+        assert_eq!(KittiesModule::pending_burn(1), None);
+        System::set_block_number(16);
+        Scheduler::on_initialize(16);
+        assert_eq!(Owner::<Test>::get(1), Some(2));
+    });
+}
+
+#[test]
+fn delayed_burn_failed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(KittiesModule::schedule_burn(Origin::signed(2), 0), Error::<Test>::NotKittyOwner);
+        assert_noop!(KittiesModule::cancel_burn(Origin::signed(1), 0), Error::<Test>::NoPendingBurn);
+        assert_noop!(KittiesModule::execute_burn(Origin::signed(1), 0), Error::<Test>::NoPendingBurn);
+
+        assert_ok!(KittiesModule::schedule_burn(Origin::signed(1), 0));
+        assert_noop!(KittiesModule::schedule_burn(Origin::signed(1), 0), Error::<Test>::BurnPending);
+        assert_noop!(KittiesModule::cancel_burn(Origin::signed(2), 0), Error::<Test>::NotKittyOwner);
+        // nobody can hurry the burn along
+        System::set_block_number(5);
+        assert_noop!(KittiesModule::execute_burn(Origin::signed(2), 0), Error::<Test>::BurnNotDue);
+        assert_noop!(KittiesModule::execute_burn(Origin::root(), 0), Error::<Test>::BurnNotDue);
+    });
+}
+
+#[test]
+fn create_for_works() {
+    new_test_ext().execute_with(|| {
+        let kitty_id = KittiesModule::create_for(&0, &2).unwrap();
+        System::assert_last_event(mock::Event::KittiesModule(Event::KittyCreate(2, kitty_id)));
+        assert_eq!(Owner::<Test>::get(kitty_id), Some(2));
+        // the payer holds the deposit, and gets it back when the kitty is burned
+        let (payer, deposit) = KittyDeposits::<Test>::get(kitty_id).unwrap();
+        assert_eq!(payer, 0);
+        assert_eq!(Balances::reserved_balance(0), deposit);
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_ok!(KittiesModule::schedule_burn(Origin::signed(2), kitty_id));
+        System::set_block_number(6);
+        assert_ok!(KittiesModule::execute_burn(Origin::signed(2), kitty_id));
+        assert_eq!(Balances::reserved_balance(0), 0);
+
+        assert_noop!(KittiesModule::create_for(&4, &2), Error::<Test>::InsufficientBalance);
+    });
+}
+
+#[test]
+fn guardianship_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::set_guardian(Origin::signed(1), 2));
+        assert_eq!(KittiesModule::guardian(1), Some(2));
+
+        // the kitty's deposit is above the threshold, so the transfer waits for the guardian
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 0, 0));
+        System::assert_last_event(mock::Event::KittiesModule(
+            Event::GuardianApprovalRequested(1, 0, CoOwnerAction::Transfer(0), 6)
+        ));
+        assert_eq!(Owner::<Test>::get(0), Some(1));
+
+        // cheap listings don't, and a dear one replaces the waiting transfer
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(100), None));
+        assert_eq!(Price::<Test>::get(0), Some(100));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(1_000), None));
+        assert_eq!(Price::<Test>::get(0), Some(100));
+        assert_eq!(KittiesModule::guarded_actions(1).into_inner(), vec![(0, CoOwnerAction::Sell(1_000), 6)]);
+
+        assert_ok!(KittiesModule::veto_guarded(Origin::signed(2), 1, 0));
+        System::assert_last_event(mock::Event::KittiesModule(Event::GuardedActionVetoed(1, 0)));
+        assert_eq!(Price::<Test>::get(0), Some(100));
+        assert!(KittiesModule::guarded_actions(1).is_empty());
+
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 0, 0));
+        assert_ok!(KittiesModule::approve_guarded(Origin::signed(2), 1, 0));
+        System::assert_last_event(mock::Event::KittiesModule(Event::GuardedActionApproved(1, 0)));
+        assert_eq!(Owner::<Test>::get(0), Some(0));
+
+        // released wards act on their own again
+        assert_ok!(KittiesModule::release_ward(Origin::signed(2), 1));
+        System::assert_last_event(mock::Event::KittiesModule(Event::WardReleased(1, 2)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 0, 1));
+        assert_eq!(Owner::<Test>::get(1), Some(0));
+    });
+}
+
+#[test]
+fn guardianship_failed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(KittiesModule::set_guardian(Origin::signed(1), 1), Error::<Test>::InvalidGuardian);
+        assert_ok!(KittiesModule::set_guardian(Origin::signed(1), 2));
+        // only the guardian can end the guardianship
+        assert_noop!(KittiesModule::set_guardian(Origin::signed(1), 0), Error::<Test>::AlreadyGuarded);
+        assert_noop!(KittiesModule::release_ward(Origin::signed(1), 1), Error::<Test>::NotGuardian);
+
+        assert_ok!(KittiesModule::create(Origin::signed(0)));
+        assert_noop!(KittiesModule::transfer(Origin::signed(1), 2, 0), Error::<Test>::NotKittyOwner);
+        assert_noop!(KittiesModule::approve_guarded(Origin::signed(2), 1, 0), Error::<Test>::NoGuardedAction);
+
+        for _ in 0..3 {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+        }
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 0, 1));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 0, 2));
+        assert_noop!(KittiesModule::transfer(Origin::signed(1), 0, 3), Error::<Test>::TooManyGuardedActions);
+        assert_noop!(KittiesModule::approve_guarded(Origin::signed(0), 1, 1), Error::<Test>::NotGuardian);
+        assert_noop!(KittiesModule::veto_guarded(Origin::signed(0), 1, 1), Error::<Test>::NotGuardian);
+
+        // lapsed actions can't be approved, and make room for new ones
+        System::set_block_number(6);
+        assert_noop!(KittiesModule::approve_guarded(Origin::signed(2), 1, 1), Error::<Test>::NoGuardedAction);
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 0, 3));
+        assert_eq!(KittiesModule::guarded_actions(1).len(), 1);
+
+        // wards can't make the calls a guardian has no way to review
+        assert_noop!(KittiesModule::export_kitty(Origin::signed(1), 3, 1, *b"otherchn"), Error::<Test>::CallerIsWard);
+        assert_noop!(
+            KittiesModule::add_co_owner(Origin::signed(1), 3, 0, Permill::from_percent(60)),
+            Error::<Test>::CallerIsWard
+        );
+        assert_noop!(KittiesModule::set_heir(Origin::signed(1), 0, 10), Error::<Test>::CallerIsWard);
+        assert_noop!(
+            KittiesModule::authorize_session(Origin::signed(1), 0, SESSION_BREED, 20),
+            Error::<Test>::CallerIsWard
+        );
+
+        // an approved action is checked again when carried out
+        assert_ok!(KittiesModule::release_ward(Origin::signed(2), 1));
+        assert_ok!(KittiesModule::add_co_owner(Origin::signed(1), 3, 0, Permill::from_percent(60)));
+        assert_ok!(KittiesModule::set_guardian(Origin::signed(1), 2));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 3, Some(1_000), None));
+        assert_noop!(
+            KittiesModule::approve_guarded(Origin::signed(2), 1, 3),
+            Error::<Test>::NotApprovedByCoOwners
+        );
+    });
+}
+
+#[test]
+fn listing_rewards_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::set_listing_rewards(Origin::root(), 100, 250));
+        let pot = KittiesModule::reward_pot_account();
+        assert_ok!(Balances::transfer(Origin::signed(0), pot, 10_000));
+
+        for owner in [1, 1, 2] {
+            assert_ok!(KittiesModule::create(Origin::signed(owner)));
+        }
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(10), None));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, Some(10), None));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(2), 2, Some(10), None));
+        // repricing keeps the listing's age
+        System::set_block_number(8);
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 1, Some(20), Some(15)));
+        assert_eq!(KittiesModule::listed_since(1), Some(1));
+
+        System::set_block_number(10);
+        let balance = Balances::free_balance(1);
+        assert_ok!(KittiesModule::claim_listing_reward(Origin::signed(1), 0));
+        System::assert_last_event(mock::Event::KittiesModule(Event::ListingRewardClaimed(1, 0, 2, 200, true)));
+        assert_eq!(Balances::free_balance(1), balance + 200);
+
+        // the last claim of the epoch gets what is left of its budget
+        let balance = Balances::free_balance(2);
+        assert_ok!(KittiesModule::claim_listing_reward(Origin::signed(2), 0));
+        System::assert_last_event(mock::Event::KittiesModule(Event::ListingRewardClaimed(2, 0, 1, 50, true)));
+        assert_eq!(Balances::free_balance(2), balance + 50);
+        assert_eq!(Balances::free_balance(pot), 10_000 - 250);
+
+        // listings already rewarded don't stand in the way of the seller's others
+        System::set_block_number(11);
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        for kitty_id in [0, 1, 3] {
+            assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), kitty_id, Some(10), None));
+        }
+        System::set_block_number(20);
+        // a claim reads MaxBulkListings listings and the next carries on after them
+        assert_ok!(KittiesModule::claim_listing_reward(Origin::signed(1), 1));
+        System::assert_last_event(mock::Event::KittiesModule(Event::ListingRewardClaimed(1, 1, 2, 200, false)));
+        assert!(RewardCursors::<Test>::get(1).is_some());
+        assert_ok!(KittiesModule::claim_listing_reward(Origin::signed(1), 1));
+        System::assert_last_event(mock::Event::KittiesModule(Event::ListingRewardClaimed(1, 1, 1, 50, true)));
+        assert_eq!(RewardCursors::<Test>::get(1), None);
+
+        // a batch with nothing left to reward still moves the cursor on
+        assert_ok!(KittiesModule::claim_listing_reward(Origin::signed(1), 1));
+        System::assert_last_event(mock::Event::KittiesModule(Event::ListingRewardClaimed(1, 1, 0, 0, false)));
+        assert_noop!(KittiesModule::claim_listing_reward(Origin::signed(1), 1), Error::<Test>::NoRewardableListings);
+    });
+}
+
+#[test]
+fn listing_rewards_failed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(KittiesModule::claim_listing_reward(Origin::signed(1), 0), Error::<Test>::ListingRewardsOff);
+        assert_noop!(
+            KittiesModule::set_listing_rewards(Origin::signed(1), 100, 100),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(KittiesModule::set_listing_rewards(Origin::root(), 100, 100));
+
+        for owner in [1, 0, 1] {
+            assert_ok!(KittiesModule::create(Origin::signed(owner)));
+        }
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 0, Some(10), None));
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(0), 1, Some(10), None));
+        System::set_block_number(9);
+        assert_noop!(KittiesModule::claim_listing_reward(Origin::signed(1), 0), Error::<Test>::EpochNotOver);
+        // listed too close to the end of the epoch
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(1), 2, Some(10), None));
+
+        System::set_block_number(10);
+        assert_noop!(KittiesModule::claim_listing_reward(Origin::signed(1), 0), Error::<Test>::RewardPotEmpty);
+        assert_ok!(Balances::transfer(Origin::signed(0), KittiesModule::reward_pot_account(), 10_000));
+        assert_ok!(KittiesModule::claim_listing_reward(Origin::signed(1), 0));
+        System::assert_last_event(mock::Event::KittiesModule(Event::ListingRewardClaimed(1, 0, 1, 100, true)));
+        assert_noop!(KittiesModule::claim_listing_reward(Origin::signed(0), 0), Error::<Test>::EpochBudgetSpent);
+
+        // a listing is rewarded once an epoch, whoever holds it
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+        assert_noop!(KittiesModule::claim_listing_reward(Origin::signed(2), 0), Error::<Test>::NoRewardableListings);
+        // and only while it is live
+        assert_ok!(KittiesModule::sell_kitty(Origin::signed(0), 1, None, None));
+        assert_ok!(KittiesModule::set_listing_rewards(Origin::root(), 100, 1_000));
+        assert_noop!(KittiesModule::claim_listing_reward(Origin::signed(0), 0), Error::<Test>::NoRewardableListings);
+
+        // epochs before the program was switched back on earn nothing
+        assert_ok!(KittiesModule::set_listing_rewards(Origin::root(), 0, 0));
+        System::set_block_number(25);
+        assert_ok!(KittiesModule::set_listing_rewards(Origin::root(), 100, 1_000));
+        assert_eq!(KittiesModule::listing_rewards_start(), 2);
+        assert_noop!(KittiesModule::claim_listing_reward(Origin::signed(1), 1), Error::<Test>::EpochBeforeRewards);
+    });
+}
@@ -0,0 +1,303 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Lets kitty holders propose and vote on changes to the kitties pallet's parameters. Votes are
+//! cast per kitty, so voting power follows kitty ownership, and a kitty votes at most once on
+//! each proposal whoever owns it. Only kitties minted before a proposal was made may vote on
+//! it, so minting more kitties doesn't buy more votes. A vote stands if its kitty is burned
+//! later: the kitty can't vote again, and new kitties never reuse its index. Passed proposals
+//! call `pallet_kitties::set_parameter` with this pallet's [`RawOrigin::Approved`] origin, which
+//! the runtime accepts as a `ForceOrigin`.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+use kitties_primitives::RarityTier;
+
+/// How many votes a kitty of each rarity tier casts.
+pub trait VoteWeight {
+    fn weight(tier: RarityTier) -> u32;
+}
+
+/// Every kitty casts one vote.
+pub struct OneKittyOneVote;
+
+impl VoteWeight for OneKittyOneVote {
+    fn weight(_tier: RarityTier) -> u32 {
+        1
+    }
+}
+
+/// Rarer kitties cast more votes: 1, 2, 4 and 8 from common to legendary.
+pub struct RarityWeighted;
+
+impl VoteWeight for RarityWeighted {
+    fn weight(tier: RarityTier) -> u32 {
+        match tier {
+            RarityTier::Common => 1,
+            RarityTier::Rare => 2,
+            RarityTier::Epic => 4,
+            RarityTier::Legendary => 8,
+        }
+    }
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::{pallet_prelude::*, traits::{Currency, ReservableCurrency}};
+    use frame_system::pallet_prelude::*;
+    use codec::{Encode, Decode};
+    use scale_info::TypeInfo;
+    use sp_runtime::traits::Saturating;
+    use sp_std::vec::Vec;
+    use pallet_kitties::KittyParameter;
+    use kitties_primitives::RarityTier;
+    use crate::VoteWeight;
+
+    pub type ProposalIndex = u32;
+
+    type BalanceOf<T> =
+        <<T as pallet_kitties::Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+    type KittyParameterOf<T> = KittyParameter<BalanceOf<T>, <T as frame_system::Config>::BlockNumber>;
+
+    /// A proposed parameter change and the votes cast on it so far.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub struct Proposal<AccountId, Parameter, Balance, BlockNumber, KittyIndex> {
+        pub proposer: AccountId,
+        pub parameter: Parameter,
+        /// Reserved from the proposer until the proposal is closed.
+        pub deposit: Balance,
+        /// Voting is open until this block.
+        pub end: BlockNumber,
+        /// How many kitties had been minted when the proposal was made. Only kitties with a
+        /// lower index may vote.
+        pub kitties_count: KittyIndex,
+        pub ayes: u32,
+        pub nays: u32,
+    }
+
+    /// The origin of calls dispatched by passed proposals.
+    #[pallet::origin]
+    pub type Origin = RawOrigin;
+
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub enum RawOrigin {
+        /// Kitty holders voted for the call.
+        Approved,
+    }
+
+    /// Ensures a call was dispatched by a passed proposal.
+    pub struct EnsureApproved;
+
+    impl<O: Into<Result<RawOrigin, O>> + From<RawOrigin>> EnsureOrigin<O> for EnsureApproved {
+        type Success = ();
+
+        fn try_origin(o: O) -> Result<Self::Success, O> {
+            o.into().map(|RawOrigin::Approved| ())
+        }
+
+        #[cfg(feature = "runtime-benchmarks")]
+        fn successful_origin() -> O {
+            O::from(RawOrigin::Approved)
+        }
+    }
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config + pallet_kitties::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        type Origin: From<RawOrigin> + Into<<Self as frame_system::Config>::Origin>;
+        type VoteWeight: VoteWeight;
+        /// How many blocks a proposal is open for voting.
+        #[pallet::constant]
+        type VotingPeriod: Get<Self::BlockNumber>;
+        /// The fewest votes, aye and nay together, a proposal needs to pass.
+        #[pallet::constant]
+        type MinimumTurnout: Get<u32>;
+        #[pallet::constant]
+        type MaxKittiesPerVote: Get<u32>;
+        /// Reserved from the proposer while the proposal is open, so spamming proposals costs
+        /// more than the fee.
+        #[pallet::constant]
+        type ProposalDeposit: Get<BalanceOf<Self>>;
+        /// How many kitty votes one `close` or `clear_votes` call removes. Votes left over on a
+        /// closed proposal are removed by further `clear_votes` calls.
+        #[pallet::constant]
+        type MaxVotesCleared: Get<u32>;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    #[pallet::storage]
+    #[pallet::getter(fn proposal_count)]
+    pub type ProposalCount<T: Config> = StorageValue<_, ProposalIndex, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn proposals)]
+    pub type Proposals<T: Config> = StorageMap<
+        _, Twox64Concat, ProposalIndex,
+        Proposal<T::AccountId, KittyParameterOf<T>, BalanceOf<T>, T::BlockNumber, T::KittyIndex>,
+    >;
+
+    /// How each kitty voted on each proposal: aye or nay. Cleared in steps once the proposal
+    /// is closed.
+    #[pallet::storage]
+    pub type KittyVotes<T: Config> = StorageDoubleMap<
+        _, Twox64Concat, ProposalIndex, Blake2_128Concat, T::KittyIndex, bool,
+    >;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// [proposal_index, proposer, parameter, end]
+        Proposed(ProposalIndex, T::AccountId, KittyParameterOf<T>, T::BlockNumber),
+        /// [proposal_index, voter, aye, votes]
+        Voted(ProposalIndex, T::AccountId, bool, u32),
+        /// [proposal_index, result]
+        Passed(ProposalIndex, DispatchResult),
+        /// [proposal_index]
+        Rejected(ProposalIndex),
+        /// [proposal_index, all_cleared]
+        VotesCleared(ProposalIndex, bool),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        NotKittyOwner,
+        NoSuchProposal,
+        VotingClosed,
+        VotingStillOpen,
+        AlreadyVoted,
+        TooManyKitties,
+        ProposalNotClosed,
+        KittyTooNew,
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// Propose setting a kitties parameter. Only kitty holders may propose, showing one of
+        /// their kitties.
+        #[pallet::weight(1_000)]
+        pub fn propose(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            parameter: KittyParameterOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(pallet_kitties::Pallet::<T>::owner(kitty_id) == Some(who.clone()), Error::<T>::NotKittyOwner);
+
+            let deposit = T::ProposalDeposit::get();
+            <T as pallet_kitties::Config>::Currency::reserve(&who, deposit)?;
+
+            let index = Self::proposal_count();
+            let end = frame_system::Pallet::<T>::block_number().saturating_add(T::VotingPeriod::get());
+            Proposals::<T>::insert(index, Proposal {
+                proposer: who.clone(),
+                parameter: parameter.clone(),
+                deposit,
+                end,
+                kitties_count: pallet_kitties::Pallet::<T>::kitties_count().unwrap_or_default(),
+                ayes: 0,
+                nays: 0,
+            });
+            ProposalCount::<T>::put(index + 1);
+
+            Self::deposit_event(Event::Proposed(index, who, parameter, end));
+            Ok(())
+        }
+
+        /// Vote on an open proposal with each of `kitty_ids`, all of which the caller must own
+        /// and which must have been minted before the proposal was made.
+        #[pallet::weight(1_000 + 1_000 * kitty_ids.len() as Weight)]
+        pub fn vote(
+            origin: OriginFor<T>,
+            index: ProposalIndex,
+            kitty_ids: Vec<T::KittyIndex>,
+            aye: bool,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(kitty_ids.len() as u32 <= T::MaxKittiesPerVote::get(), Error::<T>::TooManyKitties);
+
+            let mut proposal = Self::proposals(index).ok_or(Error::<T>::NoSuchProposal)?;
+            ensure!(frame_system::Pallet::<T>::block_number() < proposal.end, Error::<T>::VotingClosed);
+
+            // Check every kitty before recording any vote.
+            let mut votes = 0u32;
+            for (i, kitty_id) in kitty_ids.iter().enumerate() {
+                ensure!(pallet_kitties::Pallet::<T>::owner(kitty_id) == Some(who.clone()), Error::<T>::NotKittyOwner);
+                ensure!(*kitty_id < proposal.kitties_count, Error::<T>::KittyTooNew);
+                ensure!(
+                    !KittyVotes::<T>::contains_key(index, kitty_id) && !kitty_ids[..i].contains(kitty_id),
+                    Error::<T>::AlreadyVoted
+                );
+                let kitty = pallet_kitties::Pallet::<T>::kitties(kitty_id).ok_or(Error::<T>::NotKittyOwner)?;
+                votes = votes.saturating_add(T::VoteWeight::weight(RarityTier::from_dna(&kitty.0)));
+            }
+            for kitty_id in kitty_ids {
+                KittyVotes::<T>::insert(index, kitty_id, aye);
+            }
+            if aye {
+                proposal.ayes = proposal.ayes.saturating_add(votes);
+            } else {
+                proposal.nays = proposal.nays.saturating_add(votes);
+            }
+            Proposals::<T>::insert(index, proposal);
+
+            Self::deposit_event(Event::Voted(index, who, aye, votes));
+            Ok(())
+        }
+
+        /// Settle a proposal whose voting period is over. It passes with more ayes than nays and
+        /// at least `MinimumTurnout` votes, and its parameter change is applied. Anyone may
+        /// close a proposal; the proposer's deposit is returned either way.
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, T::MaxVotesCleared::get() as Weight))]
+        pub fn close(origin: OriginFor<T>, index: ProposalIndex) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            let proposal = Self::proposals(index).ok_or(Error::<T>::NoSuchProposal)?;
+            ensure!(frame_system::Pallet::<T>::block_number() >= proposal.end, Error::<T>::VotingStillOpen);
+
+            Proposals::<T>::remove(index);
+            <T as pallet_kitties::Config>::Currency::unreserve(&proposal.proposer, proposal.deposit);
+            Self::clear_some_votes(index);
+
+            let turnout = proposal.ayes.saturating_add(proposal.nays);
+            if proposal.ayes > proposal.nays && turnout >= T::MinimumTurnout::get() {
+                let origin = <T as Config>::Origin::from(RawOrigin::Approved).into();
+                let result = pallet_kitties::Pallet::<T>::set_parameter(origin, proposal.parameter);
+                Self::deposit_event(Event::Passed(index, result));
+            } else {
+                Self::deposit_event(Event::Rejected(index));
+            }
+            Ok(())
+        }
+
+        /// Remove up to `MaxVotesCleared` more of the kitty votes cast on a closed proposal.
+        /// Anyone may clear votes.
+        #[pallet::weight(1_000 + T::DbWeight::get().reads_writes(1, T::MaxVotesCleared::get() as Weight))]
+        pub fn clear_votes(origin: OriginFor<T>, index: ProposalIndex) -> DispatchResult {
+            ensure_signed(origin)?;
+            ensure!(index < Self::proposal_count(), Error::<T>::NoSuchProposal);
+            ensure!(!Proposals::<T>::contains_key(index), Error::<T>::ProposalNotClosed);
+
+            Self::clear_some_votes(index);
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        fn clear_some_votes(index: ProposalIndex) {
+            let _ = KittyVotes::<T>::remove_prefix(index, Some(T::MaxVotesCleared::get()));
+            let all_cleared = KittyVotes::<T>::iter_prefix(index).next().is_none();
+            Self::deposit_event(Event::VotesCleared(index, all_cleared));
+        }
+    }
+}
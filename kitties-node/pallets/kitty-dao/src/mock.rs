@@ -0,0 +1,193 @@
+use crate as pallet_kitty_dao;
+use sp_core::H256;
+use frame_support::{parameter_types, traits::{ConstU128, ConstU32, ConstU64}, weights::Weight, PalletId};
+use sp_runtime::{traits::{BlakeTwo256, IdentityLookup}, testing::{Header, TestSignature, UintAuthorityId}, Permill};
+use frame_system as system;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+/// Balance of an account.
+pub type Balance = u128;
+
+/// Index of a transaction in the chain.
+pub type Index = u32;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Config<T>, Storage, Event<T>},
+		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Storage},
+		Scheduler: pallet_scheduler::{Pallet, Call, Storage, Event<T>},
+		KittiesModule: pallet_kitties::{Pallet, Call, Storage, Event<T>},
+		KittyDao: pallet_kitty_dao::{Pallet, Call, Storage, Event<T>, Origin},
+	}
+);
+
+impl pallet_randomness_collective_flip::Config for Test {}
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+    type BlockWeights = ();
+    type BlockLength = ();
+    type DbWeight = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<Balance>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = SS58Prefix;
+    type OnSetCode = ();
+}
+
+
+
+parameter_types! {
+    pub const ExistentialDeposit: u128 = 500;
+    pub const MaxLocks: u32 = 50;
+    pub const DepositBase: u32 = 1_000;
+    pub const CertificateIssuer: u64 = 99;
+    pub const KittiesChainId: [u8; 8] = *b"kittymck";
+    pub const KittiesPalletId: PalletId = PalletId(*b"py/kitty");
+    pub const ReferralReward: u128 = 100;
+    pub const MaxListingDuration: u64 = 10;
+    pub const PowSeedPeriod: u64 = 10;
+    pub const RerollFee: u128 = 100;
+    pub const SaleFee: Permill = Permill::from_percent(0);
+    pub const MinListingPrice: u128 = 1;
+    pub const MaxListingPrice: u128 = 1_000_000;
+    pub const MaximumSchedulerWeight: Weight = 1_000_000_000;
+    pub const DefaultPenalty: Permill = Permill::from_percent(10);
+    pub const WashTradeFee: Permill = Permill::from_percent(10);
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = MaxLocks;
+    type MaxReserves = ();
+    type ReserveIdentifier = [u8; 8];
+    /// The type for recording an account's balance.
+    type Balance = Balance;
+    /// The ubiquitous event type.
+    type Event = Event;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = pallet_balances::weights::SubstrateWeight<Test>;
+}
+
+impl pallet_scheduler::Config for Test {
+    type Event = Event;
+    type Origin = Origin;
+    type PalletsOrigin = OriginCaller;
+    type Call = Call;
+    type MaximumWeight = MaximumSchedulerWeight;
+    type ScheduleOrigin = frame_system::EnsureRoot<u64>;
+    type MaxScheduledPerBlock = ConstU32<50>;
+    type WeightInfo = ();
+    type OriginPrivilegeCmp = frame_support::traits::EqualPrivilegeOnly;
+}
+
+impl pallet_kitties::Config for Test {
+    type Event = Event;
+    type Randomness = RandomnessCollectiveFlip;
+    type KittyIndex = Index;
+    type Currency = Balances;
+    type KittyDepositBase = DepositBase;
+    type DepositCurve = pallet_kitties::deposit::Step<ConstU32<3>>;
+    type DiscountProvider = ();
+    type ExchangeRate = ();
+    type CreatorSignature = TestSignature;
+    type CreatorPublic = UintAuthorityId;
+    type CertificateSignature = TestSignature;
+    type CertificateIssuer = CertificateIssuer;
+    type ChainId = KittiesChainId;
+    type PalletId = KittiesPalletId;
+    type ReferralReward = ReferralReward;
+    type VoucherIssuer = frame_system::EnsureRoot<u64>;
+    type ForceOrigin = frame_system::EnsureOneOf<
+        u64,
+        frame_system::EnsureRoot<u64>,
+        pallet_kitty_dao::EnsureApproved,
+    >;
+    type MaxBreedingWindows = ConstU32<2>;
+    type MaxListingDuration = MaxListingDuration;
+    type PowDifficulty = ConstU32<4>;
+    type PowSeedPeriod = PowSeedPeriod;
+    type ScheduledCall = Call;
+    type Scheduler = Scheduler;
+    type PalletsOrigin = OriginCaller;
+    type MaxOwnersTracked = ConstU32<2>;
+    type LeaderboardSize = ConstU32<2>;
+    type MaxCoOwners = ConstU32<2>;
+    type RerollFee = RerollFee;
+    type MaxRerolls = ConstU32<1>;
+    type RerollDelay = ConstU64<2>;
+    type SaleFee = SaleFee;
+    type MinListingPrice = MinListingPrice;
+    type MaxListingPrice = MaxListingPrice;
+    type BaseGestation = ConstU64<0>;
+    type InstallmentPeriod = ConstU64<5>;
+    type MaxInstallments = ConstU32<4>;
+    type DefaultPenalty = DefaultPenalty;
+    type MaxBulkListings = ConstU32<2>;
+    type MaxInheritedKitties = ConstU32<2>;
+    type ResaleCooldown = ConstU64<0>;
+    type WashTradeWindow = ConstU64<10>;
+    type WashTradeThreshold = ConstU32<2>;
+    type WashTradeFee = WashTradeFee;
+    type MaxFuse = ConstU32<3>;
+    type BurnDelay = ConstU64<5>;
+    type GuardianThreshold = ConstU128<500>;
+    type MaxGuardedActions = ConstU32<2>;
+    type GuardedActionExpiry = ConstU64<5>;
+    type RewardEpoch = ConstU64<10>;
+    type MinRewardListingDuration = ConstU64<3>;
+}
+
+impl pallet_kitty_dao::Config for Test {
+    type Event = Event;
+    type Origin = Origin;
+    type VoteWeight = pallet_kitty_dao::OneKittyOneVote;
+    type VotingPeriod = ConstU64<5>;
+    type MinimumTurnout = ConstU32<2>;
+    type MaxKittiesPerVote = ConstU32<3>;
+    type ProposalDeposit = ConstU128<100>;
+    type MaxVotesCleared = ConstU32<2>;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test>{
+		balances: vec![
+			(0, 100_000_000), (1, 100_000_000), (2, 100_000_000),
+			(KittiesModule::account_id(), 1_000),
+		],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1)); //设置初始块高度
+	ext
+}
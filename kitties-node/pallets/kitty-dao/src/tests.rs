@@ -0,0 +1,87 @@
+use crate::{Error, Event, mock::*};
+use frame_support::{assert_ok, assert_noop, traits::{Currency, ReservableCurrency}};
+use pallet_kitties::KittyParameter;
+use super::*;
+
+#[test]
+fn proposal_works() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+
+        let reserved = Balances::reserved_balance(1);
+        assert_ok!(KittyDao::propose(Origin::signed(1), 0, KittyParameter::ReferralReward(Some(500))));
+        System::assert_last_event(mock::Event::KittyDao(
+            Event::Proposed(0, 1, KittyParameter::ReferralReward(Some(500)), 6)
+        ));
+        assert_eq!(Balances::reserved_balance(1), reserved + 100);
+        assert_ok!(KittyDao::vote(Origin::signed(1), 0, vec![0, 1], true));
+        assert_ok!(KittyDao::vote(Origin::signed(2), 0, vec![2], false));
+        assert_eq!(KittyDao::proposals(0).map(|p| (p.ayes, p.nays)), Some((2, 1)));
+
+        // a kitty votes once per proposal, whoever owns it
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+        assert_noop!(KittyDao::vote(Origin::signed(2), 0, vec![0], false), Error::<Test>::AlreadyVoted);
+
+        System::set_block_number(6);
+        assert_ok!(KittyDao::close(Origin::signed(2), 0));
+        System::assert_last_event(mock::Event::KittyDao(Event::Passed(0, Ok(()))));
+        assert_eq!(KittiesModule::referral_reward(), 500);
+        assert_eq!(KittyDao::proposals(0), None);
+        assert_eq!(Balances::reserved_balance(1), reserved);
+
+        // votes are cleared two at a time
+        assert_eq!(KittyVotes::<Test>::iter_prefix(0).count(), 1);
+        assert_ok!(KittyDao::clear_votes(Origin::signed(2), 0));
+        System::assert_last_event(mock::Event::KittyDao(Event::VotesCleared(0, true)));
+        assert_eq!(KittyVotes::<Test>::iter_prefix(0).count(), 0);
+    });
+}
+
+#[test]
+fn proposal_failed() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_noop!(
+            KittyDao::propose(Origin::signed(1), 1, KittyParameter::PowDifficulty(Some(8))),
+            Error::<Test>::NotKittyOwner
+        );
+        assert_ok!(KittyDao::propose(Origin::signed(1), 0, KittyParameter::PowDifficulty(Some(8))));
+
+        assert_noop!(KittyDao::vote(Origin::signed(1), 1, vec![0], true), Error::<Test>::NoSuchProposal);
+        assert_noop!(KittyDao::vote(Origin::signed(1), 0, vec![1], true), Error::<Test>::NotKittyOwner);
+        assert_noop!(KittyDao::vote(Origin::signed(1), 0, vec![0, 0], true), Error::<Test>::AlreadyVoted);
+        assert_noop!(KittyDao::vote(Origin::signed(1), 0, vec![0; 4], true), Error::<Test>::TooManyKitties);
+        // kitties minted after the proposal can't vote on it
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(KittyDao::vote(Origin::signed(1), 0, vec![2], true), Error::<Test>::KittyTooNew);
+        assert_noop!(KittyDao::vote(Origin::signed(1), 0, vec![0, 2], true), Error::<Test>::KittyTooNew);
+        assert_ok!(KittyDao::vote(Origin::signed(1), 0, vec![0], true));
+        assert_noop!(KittyDao::close(Origin::signed(1), 0), Error::<Test>::VotingStillOpen);
+        assert_noop!(KittyDao::clear_votes(Origin::signed(1), 0), Error::<Test>::ProposalNotClosed);
+        assert_noop!(KittyDao::clear_votes(Origin::signed(1), 1), Error::<Test>::NoSuchProposal);
+
+        // a single vote is short of the turnout
+        System::set_block_number(6);
+        assert_noop!(KittyDao::vote(Origin::signed(2), 0, vec![1], true), Error::<Test>::VotingClosed);
+        assert_ok!(KittyDao::close(Origin::signed(1), 0));
+        System::assert_last_event(mock::Event::KittyDao(Event::Rejected(0)));
+        assert_eq!(KittiesModule::pow_difficulty(), 4);
+
+        // proposing takes a deposit
+        let free = Balances::free_balance(1);
+        assert_ok!(Balances::reserve(&1, free - 50));
+        assert_noop!(
+            KittyDao::propose(Origin::signed(1), 0, KittyParameter::PowDifficulty(Some(8))),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+
+        // only passed proposals may set parameters
+        assert_noop!(
+            KittiesModule::set_parameter(Origin::signed(1), KittyParameter::PowDifficulty(Some(8))),
+            sp_runtime::DispatchError::BadOrigin
+        );
+    });
+}
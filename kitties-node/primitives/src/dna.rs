@@ -0,0 +1,73 @@
+//! The layout of kitty DNA and how to check it.
+//!
+//! The first [`GENES`] bytes are the genes. The next byte is the version of that layout,
+//! [`LAYOUT_VERSION`], and the last one a checksum over everything before it. The chain seals
+//! every kitty it mints, so DNA read off a kitty always validates; DNA supplied from outside
+//! (vouchers, certificates) has to validate before it is minted.
+
+use crate::Dna;
+
+/// How many leading bytes of the DNA are genes.
+pub const GENES: usize = 14;
+
+/// The current gene layout.
+pub const LAYOUT_VERSION: u8 = 1;
+
+const VERSION_BYTE: usize = GENES;
+const CHECKSUM_BYTE: usize = GENES + 1;
+
+/// The checksum of `dna`: the complement of the wrapping sum of every byte but the last.
+pub fn checksum(dna: &Dna) -> u8 {
+	!dna[..CHECKSUM_BYTE].iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+/// `dna` with the current layout version and a matching checksum written into it. The genes
+/// are kept as they are.
+pub fn seal(mut dna: Dna) -> Dna {
+	dna[VERSION_BYTE] = LAYOUT_VERSION;
+	dna[CHECKSUM_BYTE] = checksum(&dna);
+	dna
+}
+
+/// Whether `dna` is in the current layout, carries its checksum and has at least one non-zero
+/// gene.
+pub fn is_valid(dna: &Dna) -> bool {
+	dna[..GENES].iter().any(|&gene| gene != 0) &&
+		dna[VERSION_BYTE] == LAYOUT_VERSION &&
+		dna[CHECKSUM_BYTE] == checksum(dna)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn sealed_dna_is_valid() {
+		let dna = seal([7u8; 16]);
+		assert!(is_valid(&dna));
+		assert_eq!(dna[..GENES], [7u8; GENES]);
+		assert_eq!(dna[VERSION_BYTE], LAYOUT_VERSION);
+		// sealing is idempotent
+		assert_eq!(seal(dna), dna);
+		assert!(is_valid(&seal([0xff; 16])));
+	}
+
+	#[test]
+	fn invalid_dna_is_rejected() {
+		assert!(!is_valid(&[0u8; 16]));
+		assert!(!is_valid(&seal([0u8; 16])));
+		assert!(!is_valid(&[7u8; 16]));
+
+		let dna = seal([7u8; 16]);
+		let mut corrupted = dna;
+		corrupted[3] = 8;
+		assert!(!is_valid(&corrupted));
+		let mut bad_checksum = dna;
+		bad_checksum[CHECKSUM_BYTE] ^= 1;
+		assert!(!is_valid(&bad_checksum));
+		let mut unknown_layout = dna;
+		unknown_layout[VERSION_BYTE] = LAYOUT_VERSION + 1;
+		unknown_layout[CHECKSUM_BYTE] = checksum(&unknown_layout);
+		assert!(!is_valid(&unknown_layout));
+	}
+}
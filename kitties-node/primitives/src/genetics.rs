@@ -0,0 +1,166 @@
+//! How kitty genes are inherited.
+//!
+//! Each gene byte holds two alleles: the dominant one, which is expressed, in the high nibble and
+//! the recessive one, which is only carried, in the low nibble. A child gets one allele from each
+//! parent, picked at random, and of the two the lower-numbered allele dominates. High-numbered
+//! alleles, and with them the rarer looks, are therefore recessive: they surface only when both
+//! parents pass them on.
+
+use crate::Dna;
+
+/// The allele a gene expresses.
+pub fn dominant(gene: u8) -> u8 {
+	gene >> 4
+}
+
+/// The allele a gene carries without expressing it.
+pub fn recessive(gene: u8) -> u8 {
+	gene & 0x0f
+}
+
+/// The gene made of alleles `a` and `b`, the lower of which dominates.
+pub fn gene(a: u8, b: u8) -> u8 {
+	(a.min(b) << 4) | a.max(b)
+}
+
+/// The child gene of `gene1` and `gene2`. Bit 0 of `selector` picks which allele the first
+/// parent passes on and bit 1 which one the second does; the other bits are ignored.
+pub fn inherit(gene1: u8, gene2: u8, selector: u8) -> u8 {
+	let pick = |gene: u8, bit: u8| if selector & bit == 0 { dominant(gene) } else { recessive(gene) };
+	gene(pick(gene1, 0b01), pick(gene2, 0b10))
+}
+
+/// The child dna of `dna1` and `dna2`, inheriting each gene with the matching `selectors` byte.
+pub fn breed(dna1: &Dna, dna2: &Dna, selectors: &Dna) -> Dna {
+	let mut dna = [0u8; 16];
+	for i in 0..dna.len() {
+		dna[i] = inherit(dna1[i], dna2[i], selectors[i]);
+	}
+	dna
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::RarityTier;
+
+	/// How often each child gene comes out of `gene1` and `gene2`, over every selector byte.
+	fn distribution(gene1: u8, gene2: u8) -> Vec<(u8, u32)> {
+		let mut counts: Vec<(u8, u32)> = Vec::new();
+		for selector in 0..=u8::MAX {
+			let child = inherit(gene1, gene2, selector);
+			match counts.iter_mut().find(|(gene, _)| *gene == child) {
+				Some((_, count)) => *count += 1,
+				None => counts.push((child, 1)),
+			}
+		}
+		counts.sort();
+		counts
+	}
+
+	#[test]
+	fn inherit_takes_one_allele_from_each_parent() {
+		for gene1 in 0..=u8::MAX {
+			for gene2 in 0..=u8::MAX {
+				for selector in 0..4 {
+					let child = inherit(gene1, gene2, selector);
+					let (a, b) = (dominant(child), recessive(child));
+					let from1 = |allele| allele == dominant(gene1) || allele == recessive(gene1);
+					let from2 = |allele| allele == dominant(gene2) || allele == recessive(gene2);
+					assert!((from1(a) && from2(b)) || (from2(a) && from1(b)));
+					assert!(a <= b);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn every_allele_is_passed_on_half_the_time() {
+		for gene1 in 0..=u8::MAX {
+			for gene2 in 0..=u8::MAX {
+				let passed = |allele: u8| (0..=u8::MAX)
+					.map(|selector| inherit(gene1, gene2, selector))
+					.filter(|&child| dominant(child) == allele || recessive(child) == allele)
+					.count();
+				// an allele the other parent can't pass on shows up in exactly the half of the
+				// children that got it from the first parent
+				let allele = recessive(gene1);
+				if allele != dominant(gene1) && allele != dominant(gene2) && allele != recessive(gene2) {
+					assert_eq!(passed(allele), 128);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn recessive_traits_follow_mendel() {
+		// two carriers of the rare allele 15: one in four children expresses it
+		assert_eq!(distribution(0x3f, 0x3f), vec![(0x33, 64), (0x3f, 128), (0xff, 64)]);
+		// a carrier and an expresser: one in two
+		assert_eq!(distribution(0x3f, 0xff), vec![(0x3f, 128), (0xff, 128)]);
+		// a parent without it can't have children expressing it
+		assert_eq!(distribution(0x33, 0xff), vec![(0x3f, 256)]);
+		// two different carriers: one in four is a carrier of both
+		assert_eq!(distribution(0x1e, 0x2f), vec![(0x12, 64), (0x1f, 64), (0x2e, 64), (0xef, 64)]);
+	}
+
+	#[test]
+	fn legendary_kitties_breed_true() {
+		let legendary = [0xff; 16];
+		for selector in 0..=u8::MAX {
+			let child = breed(&legendary, &legendary, &[selector; 16]);
+			assert_eq!(child, legendary);
+			assert_eq!(RarityTier::from_dna(&child), RarityTier::Legendary);
+		}
+	}
+
+	#[test]
+	fn every_tier_can_be_bred() {
+		let mut tiers = Vec::new();
+		for gene1 in 0..=u8::MAX {
+			for gene2 in 0..=u8::MAX {
+				for selector in 0..4 {
+					let tier = RarityTier::from_gene(inherit(gene1, gene2, selector));
+					if !tiers.contains(&tier) {
+						tiers.push(tier);
+					}
+				}
+			}
+		}
+		tiers.sort();
+		assert_eq!(tiers, vec![RarityTier::Common, RarityTier::Rare, RarityTier::Epic, RarityTier::Legendary]);
+		// an allele only carried counts as much as one expressed
+		assert_eq!(RarityTier::from_gene(0x33), RarityTier::Common);
+		assert_eq!(RarityTier::from_gene(0x3c), RarityTier::Rare);
+		assert_eq!(RarityTier::from_gene(0x3f), RarityTier::Epic);
+	}
+
+	#[test]
+	fn raise_keeps_the_expressed_allele() {
+		for gene in 0..=u8::MAX {
+			for tier in [RarityTier::Common, RarityTier::Rare, RarityTier::Epic, RarityTier::Legendary] {
+				let raised = tier.raise(gene);
+				assert!(RarityTier::from_gene(raised) >= tier);
+				if RarityTier::from_gene(gene) >= tier {
+					assert_eq!(raised, gene);
+				} else if tier != RarityTier::Legendary {
+					assert_eq!(dominant(raised), dominant(gene).min(recessive(gene)));
+				}
+			}
+		}
+		assert_eq!(RarityTier::Epic.raise(0x35), 0x3f);
+	}
+
+	#[test]
+	fn breed_inherits_every_gene_independently() {
+		let dna1 = [0x3f; 16];
+		let dna2 = [0x3f; 16];
+		let mut selectors = [0b00; 16];
+		selectors[0] = 0b11;
+		selectors[5] = 0b01;
+		let child = breed(&dna1, &dna2, &selectors);
+		assert_eq!(child[0], 0xff);
+		assert_eq!(child[5], 0x3f);
+		assert!(child.iter().enumerate().all(|(i, &gene)| i == 0 || i == 5 || gene == 0x33));
+	}
+}
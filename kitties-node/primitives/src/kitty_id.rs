@@ -0,0 +1,118 @@
+//! [`KittyId`], the form kitty ids take outside the runtime.
+//!
+//! Inside the runtime a kitty id is just the pallet's `KittyIndex`, a bare integer that is easy
+//! to mix up with any other index or count. Clients see it as `KITTY-` followed by the index
+//! zero-padded to at least six digits, e.g. `KITTY-000123`, both in RPC responses and in the
+//! ids they pass back.
+
+use codec::{Decode, Encode};
+use core::{fmt, str::FromStr};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
+/// Prefixed to the index in a kitty id's string form.
+pub const KITTY_ID_PREFIX: &str = "KITTY-";
+
+/// The id of a kitty, wrapping its index.
+#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug, TypeInfo)]
+pub struct KittyId(pub u32);
+
+impl From<u32> for KittyId {
+	fn from(index: u32) -> Self {
+		KittyId(index)
+	}
+}
+
+impl From<KittyId> for u32 {
+	fn from(id: KittyId) -> Self {
+		id.0
+	}
+}
+
+impl fmt::Display for KittyId {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}{:06}", KITTY_ID_PREFIX, self.0)
+	}
+}
+
+/// Why a string isn't a [`KittyId`].
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ParseKittyIdError {
+	/// It doesn't start with [`KITTY_ID_PREFIX`].
+	MissingPrefix,
+	/// What follows the prefix isn't a decimal index that fits a `u32`.
+	InvalidIndex,
+}
+
+impl fmt::Display for ParseKittyIdError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ParseKittyIdError::MissingPrefix => write!(f, "kitty id must start with {}", KITTY_ID_PREFIX),
+			ParseKittyIdError::InvalidIndex => write!(f, "kitty id must end in a decimal index"),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseKittyIdError {}
+
+impl FromStr for KittyId {
+	type Err = ParseKittyIdError;
+
+	/// Parse `KITTY-<index>`. The padding is optional, so `KITTY-123` and `KITTY-000123` are the
+	/// same kitty, but nothing else may surround the index.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let index = s.strip_prefix(KITTY_ID_PREFIX).ok_or(ParseKittyIdError::MissingPrefix)?;
+		if index.is_empty() || !index.bytes().all(|byte| byte.is_ascii_digit()) {
+			return Err(ParseKittyIdError::InvalidIndex)
+		}
+		index.parse().map(KittyId).map_err(|_| ParseKittyIdError::InvalidIndex)
+	}
+}
+
+#[cfg(feature = "std")]
+impl serde::Serialize for KittyId {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+#[cfg(feature = "std")]
+impl<'de> serde::Deserialize<'de> for KittyId {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let s = String::deserialize(deserializer)?;
+		s.parse().map_err(serde::de::Error::custom)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn kitty_id_round_trips() {
+		assert_eq!(KittyId(123).to_string(), "KITTY-000123");
+		assert_eq!(KittyId(0).to_string(), "KITTY-000000");
+		assert_eq!(KittyId(u32::MAX).to_string(), "KITTY-4294967295");
+		for index in [0, 1, 123, 999_999, 1_000_000, u32::MAX] {
+			assert_eq!(KittyId(index).to_string().parse(), Ok(KittyId(index)));
+		}
+		// the padding is only for display
+		assert_eq!("KITTY-123".parse(), Ok(KittyId(123)));
+
+		assert_eq!(serde_json::to_string(&KittyId(42)).unwrap(), "\"KITTY-000042\"");
+		assert_eq!(serde_json::from_str::<KittyId>("\"KITTY-000042\"").unwrap(), KittyId(42));
+	}
+
+	#[test]
+	fn malformed_kitty_id_is_rejected() {
+		assert_eq!("000123".parse::<KittyId>(), Err(ParseKittyIdError::MissingPrefix));
+		assert_eq!("kitty-000123".parse::<KittyId>(), Err(ParseKittyIdError::MissingPrefix));
+		assert_eq!("KITTY-".parse::<KittyId>(), Err(ParseKittyIdError::InvalidIndex));
+		assert_eq!("KITTY-+123".parse::<KittyId>(), Err(ParseKittyIdError::InvalidIndex));
+		assert_eq!("KITTY-12a".parse::<KittyId>(), Err(ParseKittyIdError::InvalidIndex));
+		assert_eq!("KITTY-4294967296".parse::<KittyId>(), Err(ParseKittyIdError::InvalidIndex));
+		// a bare index is exactly the ambiguity the string form is there to avoid
+		assert!(serde_json::from_str::<KittyId>("123").is_err());
+	}
+}
@@ -0,0 +1,198 @@
+//! Types shared between the kitties pallet, the runtime and the node.
+//!
+//! Anything that has to be encoded identically on both sides of a boundary (runtime <-> node,
+//! chain <-> chain, chain <-> front-end) lives here so there is exactly one definition of it.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use sp_runtime::{Permill, RuntimeDebug};
+use sp_std::vec::Vec;
+
+pub mod dna;
+pub mod genetics;
+pub mod kitty_id;
+pub mod render;
+
+/// Raw kitty DNA.
+pub type Dna = [u8; 16];
+
+/// Identifier of the chain a kitty was minted on.
+pub type ChainId = [u8; 8];
+
+/// How rare a kitty is, decided by its leading gene.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug, TypeInfo)]
+pub enum RarityTier {
+	Common,
+	Rare,
+	Epic,
+	Legendary,
+}
+
+impl RarityTier {
+	pub fn from_dna(dna: &Dna) -> Self {
+		Self::from_gene(dna[0])
+	}
+
+	/// The tier of a leading gene, by the rarest allele it has, whether expressed or only
+	/// carried (see [`genetics`]): alleles 12 to 14 make it rare, 15 epic, and two 15s
+	/// legendary. Bred genes can be of every tier.
+	pub fn from_gene(gene: u8) -> Self {
+		let (a, b) = (genetics::dominant(gene), genetics::recessive(gene));
+		match (a.min(b), a.max(b)) {
+			(0xf, _) => RarityTier::Legendary,
+			(_, 0xf) => RarityTier::Epic,
+			(_, 0xc..=0xe) => RarityTier::Rare,
+			_ => RarityTier::Common,
+		}
+	}
+
+	/// `gene` made at least as rare as this tier: the allele it expresses is kept and the
+	/// other raised to the lowest of the tier. Genes already as rare are kept as they are.
+	pub fn raise(&self, gene: u8) -> u8 {
+		if Self::from_gene(gene) >= *self {
+			return gene
+		}
+		let expressed = genetics::dominant(gene).min(genetics::recessive(gene));
+		match self {
+			RarityTier::Legendary => 0xff,
+			RarityTier::Epic => genetics::gene(expressed, 0xf),
+			RarityTier::Rare => genetics::gene(expressed, 0xc),
+			RarityTier::Common => gene,
+		}
+	}
+}
+
+/// Headline numbers about the kitties market.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct KittyStats<Balance> {
+	pub total_transfers: u64,
+	pub total_sales: u64,
+	/// Sum of the prices of all sales.
+	pub total_volume: Balance,
+	pub total_breeds: u64,
+}
+
+/// The kitties leaderboards, each sorted highest first.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct Leaderboards<AccountId, KittyIndex, Balance> {
+	/// Accounts with the number of kitties they own.
+	pub top_owners: Vec<(AccountId, u32)>,
+	/// Kitties with the highest price they sold for.
+	pub top_sales: Vec<(KittyIndex, Balance)>,
+	/// Accounts with the number of kitties they bred.
+	pub top_breeders: Vec<(AccountId, u32)>,
+}
+
+/// One kitty, as bulk queries return it.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct KittyInfo<AccountId, KittyIndex, Balance> {
+	pub id: KittyIndex,
+	pub dna: Dna,
+	pub owner: AccountId,
+	pub generation: u32,
+	/// The asking price, while the kitty is listed and the listing hasn't expired.
+	pub price: Option<Balance>,
+}
+
+/// The kitties pallet's limits and settings as they currently apply, constants and
+/// governance-set values alike.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct KittiesConfig<AccountId, Balance, BlockNumber> {
+	pub chain_id: ChainId,
+	pub deposit_base: Balance,
+	/// The deposit the next minted kitty reserves.
+	pub current_deposit: Balance,
+	pub referral_reward: Balance,
+	pub max_listing_duration: BlockNumber,
+	pub pow_difficulty: u32,
+	pub pow_seed_period: BlockNumber,
+	pub max_owners_tracked: u32,
+	pub max_breeding_windows: u32,
+	/// `None` while breeding is unrestricted.
+	pub breeding_windows: Option<Vec<(BlockNumber, BlockNumber)>>,
+	pub donation_beneficiary: Option<AccountId>,
+	/// The cut of a sale price paid into the pot, unless an approved marketplace settles it.
+	pub sale_fee: Permill,
+	pub min_listing_price: Balance,
+	pub max_listing_price: Balance,
+}
+
+/// The current version of the kitty certificate format.
+pub const CERTIFICATE_VERSION: u8 = 2;
+
+/// A portable description of a kitty, used to move it from one chain to another.
+///
+/// The exporting chain emits the certificate unsigned (`Signature = ()`). A trusted issuer
+/// signs [`KittyCertificate::signing_payload`] off-chain and the owner submits the signed
+/// certificate to the importing chain. Only `recipient` can import it, and only on
+/// `destination_chain`, so a certificate seen in a transaction pool can't be taken or replayed
+/// elsewhere.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct KittyCertificate<AccountId, Signature> {
+	/// Format version, always [`CERTIFICATE_VERSION`] for certificates created by this crate.
+	pub version: u8,
+	pub dna: Dna,
+	pub generation: u32,
+	pub origin_chain: ChainId,
+	/// Per-chain export counter, so re-exporting the same kitty yields a fresh certificate.
+	pub nonce: u64,
+	/// The account the kitty is minted to on import.
+	pub recipient: AccountId,
+	pub destination_chain: ChainId,
+	pub sig: Signature,
+}
+
+impl<AccountId> KittyCertificate<AccountId, ()> {
+	/// Create an unsigned certificate in the current format.
+	pub fn new(
+		dna: Dna,
+		generation: u32,
+		origin_chain: ChainId,
+		nonce: u64,
+		recipient: AccountId,
+		destination_chain: ChainId,
+	) -> Self {
+		KittyCertificate {
+			version: CERTIFICATE_VERSION,
+			dna,
+			generation,
+			origin_chain,
+			nonce,
+			recipient,
+			destination_chain,
+			sig: (),
+		}
+	}
+
+	/// Attach the issuer's signature over [`Self::signing_payload`].
+	pub fn sign_with<Signature>(self, sig: Signature) -> KittyCertificate<AccountId, Signature> {
+		KittyCertificate {
+			version: self.version,
+			dna: self.dna,
+			generation: self.generation,
+			origin_chain: self.origin_chain,
+			nonce: self.nonce,
+			recipient: self.recipient,
+			destination_chain: self.destination_chain,
+			sig,
+		}
+	}
+}
+
+impl<AccountId: Encode, Signature> KittyCertificate<AccountId, Signature> {
+	/// The bytes the issuer signs: every field except the signature itself.
+	pub fn signing_payload(&self) -> Vec<u8> {
+		(
+			self.version,
+			&self.dna,
+			self.generation,
+			&self.origin_chain,
+			self.nonce,
+			&self.recipient,
+			&self.destination_chain,
+		)
+			.encode()
+	}
+}
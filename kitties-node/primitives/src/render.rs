@@ -0,0 +1,105 @@
+//! How front-ends turn kitty DNA into a picture, so every client draws the same cat.
+//!
+//! The render seed is `blake2b-256(RENDER_SEED_DOMAIN ++ dna)` over all 16 DNA bytes, with no
+//! key. A kitty is drawn as one sprite per [`Layer`], bottom to top in the order of
+//! [`Layer::ALL`]. Layer `i` reads bytes `2i` and `2i + 1` of the seed as a little-endian `u16`
+//! and takes it modulo the layer's [`Layer::variants`]; the result is the zero-based index of
+//! the sprite to draw.
+//!
+//! `render-vectors.json` next to this crate's manifest holds test vectors any implementation
+//! can check itself against. Changing any of this changes how existing kitties look, so it
+//! needs a new domain and a new set of vectors.
+
+use crate::Dna;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+
+/// Prefixed to the DNA before hashing, versioning the derivation.
+pub const RENDER_SEED_DOMAIN: &[u8] = b"kitties/render-seed/v1";
+
+/// The sprite layers a kitty is drawn from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Layer {
+	Body,
+	Fur,
+	Mouth,
+	Eyes,
+	Accessory,
+}
+
+impl Layer {
+	/// Every layer, in drawing order.
+	pub const ALL: [Layer; 5] = [Layer::Body, Layer::Fur, Layer::Mouth, Layer::Eyes, Layer::Accessory];
+
+	/// How many sprites there are to choose from for this layer.
+	pub fn variants(&self) -> u16 {
+		match self {
+			Layer::Body => 15,
+			Layer::Fur => 10,
+			Layer::Mouth => 10,
+			Layer::Eyes => 15,
+			Layer::Accessory => 20,
+		}
+	}
+}
+
+/// The seed every sprite choice for `dna` is derived from.
+pub fn derive_render_seed(dna: &Dna) -> [u8; 32] {
+	let mut input = [0u8; RENDER_SEED_DOMAIN.len() + 16];
+	input[..RENDER_SEED_DOMAIN.len()].copy_from_slice(RENDER_SEED_DOMAIN);
+	input[RENDER_SEED_DOMAIN.len()..].copy_from_slice(dna);
+	BlakeTwo256::hash(&input).to_fixed_bytes()
+}
+
+/// The sprite to draw for each layer of the kitty with render seed `seed`, in the order of
+/// [`Layer::ALL`].
+pub fn sprite_layers(seed: &[u8; 32]) -> [u16; 5] {
+	let mut sprites = [0u16; 5];
+	for (i, layer) in Layer::ALL.iter().enumerate() {
+		sprites[i] = u16::from_le_bytes([seed[2 * i], seed[2 * i + 1]]) % layer.variants();
+	}
+	sprites
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn from_hex(hex: &str) -> Vec<u8> {
+		let hex = hex.trim_start_matches("0x");
+		(0..hex.len())
+			.step_by(2)
+			.map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+			.collect()
+	}
+
+	#[test]
+	fn render_vectors_match() {
+		let vectors: serde_json::Value =
+			serde_json::from_str(include_str!("../render-vectors.json")).unwrap();
+		let names = ["body", "fur", "mouth", "eyes", "accessory"];
+		for vector in vectors.as_array().unwrap() {
+			let mut dna = [0u8; 16];
+			dna.copy_from_slice(&from_hex(vector["dna"].as_str().unwrap()));
+			let seed = derive_render_seed(&dna);
+			assert_eq!(seed.to_vec(), from_hex(vector["seed"].as_str().unwrap()));
+			let sprites = sprite_layers(&seed);
+			for (i, name) in names.iter().enumerate() {
+				assert_eq!(sprites[i] as u64, vector["layers"][name].as_u64().unwrap());
+			}
+		}
+	}
+
+	#[test]
+	fn sprites_stay_in_range() {
+		for byte in 0..=255u8 {
+			let sprites = sprite_layers(&derive_render_seed(&[byte; 16]));
+			for (layer, sprite) in Layer::ALL.iter().zip(sprites.iter()) {
+				assert!(*sprite < layer.variants());
+			}
+		}
+		// a single changed byte anywhere in the DNA gives a different seed
+		let mut dna = [0u8; 16];
+		dna[15] = 1;
+		assert_ne!(derive_render_seed(&[0u8; 16]), derive_render_seed(&dna));
+	}
+}
@@ -0,0 +1,136 @@
+//! Transaction-pool level check for kitty calls that need a deposit.
+
+use crate::{AccountId, Balances, Call, KittiesModule, Multisig};
+use codec::{Decode, Encode};
+use frame_support::traits::ReservableCurrency;
+use pallet_kitties::discount::DiscountedAction;
+use sp_runtime::{
+	traits::{DispatchInfoOf, SignedExtension},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+};
+
+/// `InvalidTransaction::Custom` code returned when the sender can't cover the kitty deposit.
+pub const INSUFFICIENT_KITTY_DEPOSIT: u8 = 1;
+
+/// Reject kitty calls that mint a kitty when the account paying the deposit can't reserve it, so
+/// they are dropped by the pool instead of failing inside a block. Calls made through a proxy
+/// or a 1-of-n multisig are checked against the account they are made for; multisig approvals
+/// only carry the call's hash, so those are left to fail in the block.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Default, scale_info::TypeInfo)]
+pub struct CheckKittyDeposit;
+
+impl sp_std::fmt::Debug for CheckKittyDeposit {
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "CheckKittyDeposit")
+	}
+}
+
+impl SignedExtension for CheckKittyDeposit {
+	const IDENTIFIER: &'static str = "CheckKittyDeposit";
+	type AccountId = AccountId;
+	type Call = Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		if let Some((payer, action)) = deposit_payer(who, call) {
+			let deposit = KittiesModule::discounted_deposit(&payer, action);
+			if !Balances::can_reserve(&payer, deposit) {
+				return Err(InvalidTransaction::Custom(INSUFFICIENT_KITTY_DEPOSIT).into())
+			}
+		}
+		Ok(ValidTransaction::default())
+	}
+}
+
+/// The account `call`, sent by `who`, reserves a kitty deposit from and what for, or `None` if
+/// it mints no kitty.
+fn deposit_payer(who: &AccountId, call: &Call) -> Option<(AccountId, DiscountedAction)> {
+	match call {
+		Call::KittiesModule(pallet_kitties::Call::breed { .. }) => Some((who.clone(), DiscountedAction::Breed)),
+		Call::KittiesModule(pallet_kitties::Call::create { .. }) |
+		Call::KittiesModule(pallet_kitties::Call::create_with_referral { .. }) |
+		Call::KittiesModule(pallet_kitties::Call::import_kitty { .. }) |
+		Call::KittiesModule(pallet_kitties::Call::create_with_nonce { .. }) |
+		Call::KittiesModule(pallet_kitties::Call::redeem_voucher { .. }) |
+		Call::KittiesModule(pallet_kitties::Call::lazy_buy { .. }) |
+		Call::KittiesModule(pallet_kitties::Call::fuse { .. }) => Some((who.clone(), DiscountedAction::Mint)),
+		// Breeding in a session reserves from the user the operator breeds for.
+		Call::KittiesModule(pallet_kitties::Call::session_call {
+			user,
+			call: pallet_kitties::SessionCall::Breed { .. },
+		}) => Some((user.clone(), DiscountedAction::Breed)),
+		Call::Proxy(pallet_proxy::Call::proxy { real, call, .. }) => deposit_payer(real, call),
+		Call::Multisig(pallet_multisig::Call::as_multi_threshold_1 { other_signatories, call }) => {
+			let mut signatories = other_signatories.clone();
+			signatories.push(who.clone());
+			signatories.sort();
+			deposit_payer(&Multisig::multi_account_id(&signatories, 1), call)
+		},
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::crypto::AccountId32;
+
+	fn account(byte: u8) -> AccountId {
+		AccountId32::new([byte; 32])
+	}
+
+	#[test]
+	fn deposit_payer_works() {
+		let (who, user) = (account(1), account(2));
+		let breed = pallet_kitties::SessionCall::Breed { kitty_id1: 0, kitty_id2: 1 };
+		let minting = vec![
+			pallet_kitties::Call::create {},
+			pallet_kitties::Call::create_with_nonce { nonce: 0 },
+			pallet_kitties::Call::redeem_voucher { code: b"code".to_vec() },
+			pallet_kitties::Call::fuse { kitty_ids: vec![0, 1].try_into().unwrap() },
+		];
+		for call in minting {
+			assert_eq!(deposit_payer(&who, &Call::KittiesModule(call)), Some((who.clone(), DiscountedAction::Mint)));
+		}
+		let call = Call::KittiesModule(pallet_kitties::Call::breed { kitty_id1: 0, kitty_id2: 1 });
+		assert_eq!(deposit_payer(&who, &call), Some((who.clone(), DiscountedAction::Breed)));
+
+		let call = Call::KittiesModule(pallet_kitties::Call::session_call { user: user.clone(), call: breed });
+		assert_eq!(deposit_payer(&who, &call), Some((user.clone(), DiscountedAction::Breed)));
+		let proxied = Call::Proxy(pallet_proxy::Call::proxy {
+			real: user.clone(),
+			force_proxy_type: None,
+			call: Box::new(Call::KittiesModule(pallet_kitties::Call::create {})),
+		});
+		assert_eq!(deposit_payer(&who, &proxied), Some((user, DiscountedAction::Mint)));
+	}
+
+	#[test]
+	fn deposit_payer_failed() {
+		let (who, user) = (account(1), account(2));
+		let not_minting = vec![
+			Call::KittiesModule(pallet_kitties::Call::transfer { new_owner: user.clone(), kitty_id: 0 }),
+			Call::KittiesModule(pallet_kitties::Call::session_call {
+				user,
+				call: pallet_kitties::SessionCall::RerollTrait { kitty_id: 0, gene_index: 0 },
+			}),
+			Call::System(frame_system::Call::remark { remark: Vec::new() }),
+		];
+		for call in not_minting {
+			assert_eq!(deposit_payer(&who, &call), None);
+		}
+	}
+}
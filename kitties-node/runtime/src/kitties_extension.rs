@@ -0,0 +1,86 @@
+//! Chain extension letting contracts mint kitties, e.g. a token contract selling kitties for
+//! its own token.
+//!
+//! Functions take their input SCALE encoded and write their output SCALE encoded:
+//!
+//! - [`CURRENT_DEPOSIT`]: no input, returns the `Balance` the next kitty reserves.
+//! - [`MINT_KITTY`]: takes the `AccountId` to mint the kitty to and returns its `Index`. The
+//!   calling contract pays the deposit to the account that called it, which reserves it and
+//!   gets it back when the kitty is burned, so the deposit goes to whoever the contract charged
+//!   for the kitty. Fails with status [`MINT_FAILED`], having done neither, if the contract
+//!   can't afford the deposit or the caller can't reserve it.
+
+use crate::{AccountId, Balances, Index, KittiesModule, Runtime};
+use codec::Encode;
+use frame_support::{
+	storage::{with_transaction, TransactionOutcome},
+	traits::{Currency, ExistenceRequirement, Get},
+	weights::Weight,
+};
+use pallet_contracts::chain_extension::{
+	ChainExtension, Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+use sp_runtime::DispatchError;
+
+/// Function id of the deposit query.
+pub const CURRENT_DEPOSIT: u32 = 0x1000;
+/// Function id of minting a kitty.
+pub const MINT_KITTY: u32 = 0x1001;
+
+/// Status returned when minting failed, e.g. because the contract can't reserve the deposit.
+pub const MINT_FAILED: u32 = 1;
+
+/// Charged for `MINT_KITTY` on top of the call itself, the same as the `create` extrinsic.
+const MINT_KITTY_WEIGHT: Weight = 1_000;
+
+/// Pay `caller` the kitty deposit from `contract` and mint a kitty to `owner` reserving the
+/// deposit from `caller`, or do neither.
+fn mint_for_caller(contract: &AccountId, caller: &AccountId, owner: &AccountId) -> Result<Index, DispatchError> {
+	with_transaction(|| {
+		let deposit = KittiesModule::current_deposit();
+		let minted = <Balances as Currency<AccountId>>::transfer(
+			contract,
+			caller,
+			deposit,
+			ExistenceRequirement::KeepAlive,
+		)
+		.and_then(|_| KittiesModule::create_for(caller, owner));
+		match minted {
+			Ok(kitty_id) => TransactionOutcome::Commit(Ok(kitty_id)),
+			Err(error) => TransactionOutcome::Rollback(Err(error)),
+		}
+	})
+}
+
+/// The kitties chain extension, see the module docs.
+pub struct KittiesExtension;
+
+impl ChainExtension<Runtime> for KittiesExtension {
+	fn call<E: Ext>(func_id: u32, env: Environment<E, InitState>) -> Result<RetVal, DispatchError>
+	where
+		E: Ext<T = Runtime>,
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+	{
+		match func_id {
+			CURRENT_DEPOSIT => {
+				let mut env = env.buf_in_buf_out();
+				// The kitty parameters and total supply.
+				env.charge_weight(<Runtime as frame_system::Config>::DbWeight::get().reads(2))?;
+				env.write(&KittiesModule::current_deposit().encode(), false, None)?;
+			},
+			MINT_KITTY => {
+				let mut env = env.buf_in_buf_out();
+				let owner: AccountId = env.read_as()?;
+				env.charge_weight(MINT_KITTY_WEIGHT)?;
+				let contract = env.ext().address().clone();
+				let caller = env.ext().caller().clone();
+				match mint_for_caller(&contract, &caller, &owner) {
+					Ok(kitty_id) => env.write(&kitty_id.encode(), false, None)?,
+					Err(_) => return Ok(RetVal::Converging(MINT_FAILED)),
+				}
+			},
+			_ => return Err(DispatchError::Other("Unknown kitties chain extension function")),
+		}
+		Ok(RetVal::Converging(0))
+	}
+}
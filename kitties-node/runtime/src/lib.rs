@@ -9,6 +9,7 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
+use codec::{Decode, Encode, MaxEncodedLen};
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
@@ -16,7 +17,7 @@ use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify},
 	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, MultiSignature,
+	ApplyExtrinsicResult, MultiSignature, RuntimeDebug,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -26,23 +27,32 @@ use sp_version::RuntimeVersion;
 // A few exports that help ease life for downstream crates.
 pub use frame_support::{
 	construct_runtime, parameter_types,
-	traits::{KeyOwnerProofSystem, Randomness, StorageInfo},
+	traits::{ConstU32, InstanceFilter, KeyOwnerProofSystem, Randomness, StorageInfo},
 	weights::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use pallet_balances::Call as BalancesCall;
+pub use pallet_sudo::Call as SudoCall;
 pub use pallet_timestamp::Call as TimestampCall;
 use pallet_transaction_payment::CurrencyAdapter;
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
 pub use sp_runtime::{Perbill, Permill};
 
+mod check_kitty_deposit;
+pub use check_kitty_deposit::CheckKittyDeposit;
+mod kitties_extension;
+pub use kitties_extension::KittiesExtension;
+
 /// Import the template pallet.
 pub use pallet_template;
 pub use pallet_kitties;
+pub use pallet_kitty_dao;
+pub use pallet_faucet;
+pub use pallet_poe;
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -105,6 +115,12 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	transaction_version: 1,
 };
 
+/// The number of decimals balances of the native token are displayed with.
+pub const TOKEN_DECIMALS: u8 = 12;
+
+/// The ticker of the native token.
+pub const TOKEN_SYMBOL: &str = "UNIT";
+
 /// This determines the average expected block time that we are targeting.
 /// Blocks will be produced at a minimum duration defined by `SLOT_DURATION`.
 /// `SLOT_DURATION` is picked up by `pallet_timestamp` which is in turn picked
@@ -276,11 +292,68 @@ impl pallet_sudo::Config for Runtime {
 	type Call = Call;
 }
 
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * BlockWeights::get().max_block;
+}
+
+impl pallet_scheduler::Config for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxScheduledPerBlock = ConstU32<50>;
+	type WeightInfo = pallet_scheduler::weights::SubstrateWeight<Runtime>;
+	type OriginPrivilegeCmp = frame_support::traits::EqualPrivilegeOnly;
+}
+
 /// Configure the pallet-template in pallets/template.
 impl pallet_template::Config for Runtime {
 	type Event = Event;
 }
 
+parameter_types! {
+	/// Dev certificate issuer: the `//Alice` sr25519 key.
+	pub CertificateIssuer: AccountId = AccountId::new([
+		0xd4, 0x35, 0x93, 0xc7, 0x15, 0xfd, 0xd3, 0x1c, 0x61, 0x14, 0x1a, 0xbd, 0x04, 0xa9, 0x9f, 0xd6,
+		0x82, 0x2c, 0x85, 0x58, 0x85, 0x4c, 0xcd, 0xe3, 0x9a, 0x56, 0x84, 0xe7, 0xa5, 0x6d, 0xa2, 0x7d,
+	]);
+	pub const KittiesChainId: [u8; 8] = *b"kitties0";
+	pub const KittiesPalletId: PalletId = PalletId(*b"py/kitty");
+	pub const ReferralReward: Balance = 100;
+	/// Each kitty in existence adds 0.1% of the base to the next deposit.
+	pub const DepositSlope: Permill = Permill::from_parts(1_000);
+	pub const MaxListingDuration: BlockNumber = 7 * DAYS;
+	pub const PowSeedPeriod: BlockNumber = 100;
+	pub const RerollFee: Balance = 100;
+	pub const RerollDelay: BlockNumber = 10;
+	pub const SaleFee: Permill = Permill::from_percent(2);
+	pub const MinListingPrice: Balance = 100;
+	/// A million UNIT.
+	pub const MaxListingPrice: Balance = 1_000_000_000_000_000_000;
+	pub const DaoVotingPeriod: BlockNumber = 3 * DAYS;
+	/// One UNIT.
+	pub const DaoProposalDeposit: Balance = 1_000_000_000_000;
+	/// Common kitties are born an hour after breeding, legendary ones four.
+	pub const BaseGestation: BlockNumber = HOURS;
+	pub const InstallmentPeriod: BlockNumber = 7 * DAYS;
+	/// Defaulting buyers lose a tenth of what they paid to the seller.
+	pub const DefaultPenalty: Permill = Permill::from_percent(10);
+	pub const ResaleCooldown: BlockNumber = DAYS;
+	pub const WashTradeWindow: BlockNumber = 7 * DAYS;
+	pub const WashTradeFee: Permill = Permill::from_percent(5);
+	/// A day to change one's mind before a kitty is burned.
+	pub const BurnDelay: BlockNumber = DAYS;
+	/// Below the kitty deposit, so every transfer by a ward waits for their guardian, as do
+	/// their sales above it.
+	pub const GuardianThreshold: Balance = 500;
+	pub const GuardedActionExpiry: BlockNumber = 3 * DAYS;
+	/// Listing rewards are paid weekly, for listings made at least a day before the week ends.
+	pub const RewardEpoch: BlockNumber = 7 * DAYS;
+	pub const MinRewardListingDuration: BlockNumber = DAYS;
+}
+
 /// Configure the pallet-kitties in pallets/kitties.
 impl pallet_kitties::Config for Runtime {
 	type Event = Event;
@@ -288,6 +361,241 @@ impl pallet_kitties::Config for Runtime {
 	type KittyIndex = Index;
 	type Currency = Balances;
 	type KittyDepositBase = DepositBase;
+	type DepositCurve = pallet_kitties::deposit::Linear<DepositSlope>;
+	type DiscountProvider = ();
+	// There is no oracle feed in this runtime, so kitties can only be listed natively.
+	type ExchangeRate = ();
+	type CreatorSignature = Signature;
+	type CreatorPublic = <Signature as Verify>::Signer;
+	type CertificateSignature = Signature;
+	type CertificateIssuer = CertificateIssuer;
+	type ChainId = KittiesChainId;
+	type PalletId = KittiesPalletId;
+	type ReferralReward = ReferralReward;
+	type VoucherIssuer = frame_system::EnsureRoot<AccountId>;
+	type ForceOrigin = frame_system::EnsureOneOf<
+		AccountId,
+		frame_system::EnsureRoot<AccountId>,
+		pallet_kitty_dao::EnsureApproved,
+	>;
+	type MaxBreedingWindows = ConstU32<8>;
+	type MaxListingDuration = MaxListingDuration;
+	type PowDifficulty = ConstU32<16>;
+	type PowSeedPeriod = PowSeedPeriod;
+	type ScheduledCall = Call;
+	type Scheduler = Scheduler;
+	type PalletsOrigin = OriginCaller;
+	type MaxOwnersTracked = ConstU32<10>;
+	type LeaderboardSize = ConstU32<10>;
+	// Each co-owner is paid out on every sale, so keep the list short.
+	type MaxCoOwners = ConstU32<10>;
+	type RerollFee = RerollFee;
+	type MaxRerolls = ConstU32<3>;
+	type RerollDelay = RerollDelay;
+	type SaleFee = SaleFee;
+	type MinListingPrice = MinListingPrice;
+	type MaxListingPrice = MaxListingPrice;
+	type BaseGestation = BaseGestation;
+	type InstallmentPeriod = InstallmentPeriod;
+	type MaxInstallments = ConstU32<12>;
+	type DefaultPenalty = DefaultPenalty;
+	type MaxBulkListings = ConstU32<100>;
+	type MaxInheritedKitties = ConstU32<100>;
+	type ResaleCooldown = ResaleCooldown;
+	type WashTradeWindow = WashTradeWindow;
+	type WashTradeThreshold = ConstU32<3>;
+	type WashTradeFee = WashTradeFee;
+	type MaxFuse = ConstU32<5>;
+	type BurnDelay = BurnDelay;
+	type GuardianThreshold = GuardianThreshold;
+	type MaxGuardedActions = ConstU32<16>;
+	type GuardedActionExpiry = GuardedActionExpiry;
+	type RewardEpoch = RewardEpoch;
+	type MinRewardListingDuration = MinRewardListingDuration;
+}
+
+/// Configure the pallet-kitty-dao in pallets/kitty-dao.
+impl pallet_kitty_dao::Config for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type VoteWeight = pallet_kitty_dao::RarityWeighted;
+	type VotingPeriod = DaoVotingPeriod;
+	type MinimumTurnout = ConstU32<10>;
+	type MaxKittiesPerVote = ConstU32<50>;
+	type ProposalDeposit = DaoProposalDeposit;
+	type MaxVotesCleared = ConstU32<500>;
+}
+
+parameter_types! {
+	pub const FaucetPalletId: PalletId = PalletId(*b"py/fauct");
+	/// About 65k hashes a request, a fraction of a second for a browser.
+	pub const FaucetPowDifficulty: u32 = 16;
+}
+
+/// Configure the pallet-faucet in pallets/faucet.
+impl pallet_faucet::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type PalletId = FaucetPalletId;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type PowDifficulty = FaucetPowDifficulty;
+}
+
+parameter_types! {
+	pub const PoeReferenceFee: Balance = 1_000;
+	pub const PoeReferenceOwnerShare: Permill = Permill::from_percent(80);
+	pub const PoeRetentionFee: Balance = 100;
+	pub const PoeDescriptorDeposit: Balance = 1_000;
+	pub const PoeRentEra: BlockNumber = 30 * DAYS;
+	pub const PoeGracePeriod: BlockNumber = 7 * DAYS;
+	pub const PoeTimestampInEvent: bool = true;
+}
+
+/// Configure the pallet-poe from the node template, so claims can be bound to the kitties
+/// living here.
+impl pallet_poe::Config for Runtime {
+	type Event = Event;
+	// A 512-bit digest behind its two-byte multihash header.
+	type LengthLimit = ConstU32<66>;
+	type MaxTags = ConstU32<8>;
+	type MaxTagLength = ConstU32<32>;
+	type Currency = Balances;
+	type ReferenceFee = PoeReferenceFee;
+	type OwnerShare = PoeReferenceOwnerShare;
+	// There is no treasury on this chain, so the treasury's share is burned.
+	type Treasury = ();
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxContentTypeNameLength = ConstU32<32>;
+	type RetentionFee = PoeRetentionFee;
+	type RentEra = PoeRentEra;
+	type GracePeriod = PoeGracePeriod;
+	type KittyId = Index;
+	type Kitties = KittiesModule;
+	type MaxClaimsPerKitty = ConstU32<16>;
+	type TimeProvider = Timestamp;
+	type TimestampInEvent = PoeTimestampInEvent;
+	type MaxClaimsPerBlock = ConstU32<1_000>;
+	type MaxLicenseIdLength = ConstU32<64>;
+	type MaxLicensees = ConstU32<1_000>;
+	type CounterSignature = Signature;
+	type CounterSigner = <Signature as Verify>::Signer;
+	// The preimage pallet isn't available on this Substrate version yet.
+	type Preimages = ();
+	type DescriptorDeposit = PoeDescriptorDeposit;
+	type MaxEquivalentClaims = ConstU32<16>;
+}
+
+parameter_types! {
+	pub const MultisigDepositBase: Balance = 1_000;
+	pub const MultisigDepositFactor: Balance = 100;
+	pub const MaxSignatories: u16 = 16;
+}
+
+parameter_types! {
+	pub const ContractDeposit: Balance = 1_000;
+	pub const DeletionQueueDepth: u32 = 128;
+	pub DeletionWeightLimit: Weight = Perbill::from_percent(10) * BlockWeights::get().max_block;
+	pub Schedule: pallet_contracts::Schedule<Runtime> = Default::default();
+}
+
+/// Whether contract calls made through the runtime API return the contract's debug messages.
+const CONTRACTS_DEBUG_OUTPUT: bool = true;
+
+/// Runs ink! contracts, which can mint kitties through `KittiesExtension`.
+impl pallet_contracts::Config for Runtime {
+	type Time = Timestamp;
+	type Randomness = RandomnessCollectiveFlip;
+	type Currency = Balances;
+	type Event = Event;
+	type Call = Call;
+	type CallFilter = frame_support::traits::Nothing;
+	type ContractDeposit = ContractDeposit;
+	type CallStack = [pallet_contracts::Frame<Self>; 31];
+	type WeightPrice = pallet_transaction_payment::Pallet<Self>;
+	type WeightInfo = pallet_contracts::weights::SubstrateWeight<Self>;
+	type ChainExtension = KittiesExtension;
+	type DeletionQueueDepth = DeletionQueueDepth;
+	type DeletionWeightLimit = DeletionWeightLimit;
+	type Schedule = Schedule;
+}
+
+/// Lets a group of accounts, e.g. a 2-of-3 of co-founders, hold kitties and the kitty deposits
+/// together. Calls a multisig approves are dispatched as signed by the multisig account, so
+/// every kitties extrinsic works through it unchanged.
+impl pallet_multisig::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type DepositBase = MultisigDepositBase;
+	type DepositFactor = MultisigDepositFactor;
+	type MaxSignatories = MaxSignatories;
+	type WeightInfo = pallet_multisig::weights::SubstrateWeight<Runtime>;
+}
+
+/// What a proxy may do on behalf of the account it proxies for.
+#[derive(
+	Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug, MaxEncodedLen,
+	scale_info::TypeInfo,
+)]
+pub enum ProxyType {
+	/// Anything.
+	Any,
+	/// Kitty calls that neither spend the proxied account's funds nor part with its kitties,
+	/// e.g. for a hot key tending the kitties of a cold one.
+	Kitties,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		Self::Any
+	}
+}
+
+impl InstanceFilter<Call> for ProxyType {
+	fn filter(&self, call: &Call) -> bool {
+		match self {
+			ProxyType::Any => true,
+			ProxyType::Kitties => matches!(
+				call,
+				Call::KittiesModule(pallet_kitties::Call::give_birth { .. }) |
+					Call::KittiesModule(pallet_kitties::Call::delist_all { .. }) |
+					Call::KittiesModule(pallet_kitties::Call::expire_listing { .. }) |
+					Call::KittiesModule(pallet_kitties::Call::complete_reroll { .. }) |
+					Call::KittiesModule(pallet_kitties::Call::cancel_burn { .. }) |
+					Call::KittiesModule(pallet_kitties::Call::clear_heir { .. }) |
+					Call::KittiesModule(pallet_kitties::Call::revoke_session { .. }) |
+					Call::KittiesModule(pallet_kitties::Call::expire_session { .. }) |
+					Call::KittiesModule(pallet_kitties::Call::claim_listing_reward { .. }) |
+					Call::KittiesModule(pallet_kitties::Call::cancel_mint_nonce { .. })
+			),
+		}
+	}
+
+	fn is_superset(&self, other: &Self) -> bool {
+		self == &ProxyType::Any || self == other
+	}
+}
+
+parameter_types! {
+	pub const ProxyDepositBase: Balance = 1_000;
+	pub const ProxyDepositFactor: Balance = 100;
+	pub const AnnouncementDepositBase: Balance = 1_000;
+	pub const AnnouncementDepositFactor: Balance = 100;
+}
+
+impl pallet_proxy::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = ConstU32<32>;
+	type WeightInfo = pallet_proxy::weights::SubstrateWeight<Runtime>;
+	type MaxPending = ConstU32<32>;
+	type CallHasher = BlakeTwo256;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -305,9 +613,16 @@ construct_runtime!(
 		Balances: pallet_balances,
 		TransactionPayment: pallet_transaction_payment,
 		Sudo: pallet_sudo,
+		Scheduler: pallet_scheduler,
+		Multisig: pallet_multisig,
+		Proxy: pallet_proxy,
+		Contracts: pallet_contracts,
 		// Include the custom logic from the pallet-template in the runtime.
 		TemplateModule: pallet_template,
 		KittiesModule: pallet_kitties,
+		KittyDao: pallet_kitty_dao,
+		Faucet: pallet_faucet,
+		PoeModule: pallet_poe,
 	}
 );
 
@@ -326,6 +641,7 @@ pub type SignedExtra = (
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
 	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	CheckKittyDeposit,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -454,6 +770,55 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl system_info_runtime_api::SystemInfoApi<Block> for Runtime {
+		fn system_info() -> system_info_runtime_api::SystemInfo {
+			system_info_runtime_api::SystemInfo {
+				spec_name: VERSION.spec_name.as_bytes().to_vec(),
+				spec_version: VERSION.spec_version,
+				impl_version: VERSION.impl_version,
+				transaction_version: VERSION.transaction_version,
+				ss58_prefix: SS58Prefix::get().into(),
+				token_decimals: TOKEN_DECIMALS,
+				token_symbol: TOKEN_SYMBOL.as_bytes().to_vec(),
+			}
+		}
+	}
+
+	impl pallet_kitties_runtime_api::KittiesApi<Block, Index, Balance, AccountId, BlockNumber> for Runtime {
+		fn cheapest_listing(
+			tier: pallet_kitties_runtime_api::RarityTier,
+		) -> Option<(Index, Balance)> {
+			KittiesModule::cheapest_listing(tier)
+		}
+
+		fn stats() -> pallet_kitties_runtime_api::KittyStats<Balance> {
+			KittiesModule::stats()
+		}
+
+		fn owner_history(kitty_id: Index) -> Vec<(AccountId, BlockNumber)> {
+			KittiesModule::owner_history(kitty_id).into_inner()
+		}
+
+		fn config() -> pallet_kitties_runtime_api::KittiesConfig<AccountId, Balance, BlockNumber> {
+			KittiesModule::config()
+		}
+
+		fn leaderboards() -> pallet_kitties_runtime_api::Leaderboards<AccountId, Index, Balance> {
+			KittiesModule::leaderboards()
+		}
+
+		fn kitties_page(
+			start: Index,
+			limit: u32,
+		) -> (Vec<pallet_kitties_runtime_api::KittyInfo<AccountId, Index, Balance>>, Option<Index>) {
+			KittiesModule::kitties_page(start, limit)
+		}
+
+		fn render_seed(dna: pallet_kitties_runtime_api::Dna) -> [u8; 32] {
+			pallet_kitties_runtime_api::render::derive_render_seed(&dna)
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
 		fn query_info(
 			uxt: <Block as BlockT>::Extrinsic,
@@ -469,6 +834,36 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_contracts_rpc_runtime_api::ContractsApi<Block, AccountId, Balance, BlockNumber, Hash> for Runtime {
+		fn call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			input_data: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractExecResult {
+			Contracts::bare_call(origin, dest, value, gas_limit, input_data, CONTRACTS_DEBUG_OUTPUT)
+		}
+
+		fn instantiate(
+			origin: AccountId,
+			endowment: Balance,
+			gas_limit: u64,
+			code: pallet_contracts_primitives::Code<Hash>,
+			data: Vec<u8>,
+			salt: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractInstantiateResult<AccountId> {
+			Contracts::bare_instantiate(origin, endowment, gas_limit, code, data, salt, CONTRACTS_DEBUG_OUTPUT)
+		}
+
+		fn get_storage(
+			address: AccountId,
+			key: [u8; 32],
+		) -> pallet_contracts_primitives::GetStorageResult {
+			Contracts::get_storage(address, key)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
@@ -530,3 +925,64 @@ impl_runtime_apis! {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::traits::GenesisBuild;
+	use sp_core::crypto::AccountId32;
+
+	#[test]
+	fn kitties_proxy_filter_works() {
+		let housekeeping = vec![
+			pallet_kitties::Call::give_birth { pregnancy_id: 0 },
+			pallet_kitties::Call::delist_all {},
+			pallet_kitties::Call::cancel_burn { kitty_id: 0 },
+			pallet_kitties::Call::claim_listing_reward { epoch: 0 },
+		];
+		for call in housekeeping {
+			assert!(ProxyType::Kitties.filter(&Call::KittiesModule(call)));
+		}
+	}
+
+	#[test]
+	fn kitties_proxy_filter_failed() {
+		let heir = AccountId32::new([2; 32]);
+		let spending = vec![
+			Call::KittiesModule(pallet_kitties::Call::buy_kitty { kitty_id: 0, donate: None }),
+			Call::KittiesModule(pallet_kitties::Call::transfer { new_owner: heir.clone(), kitty_id: 0 }),
+			Call::KittiesModule(pallet_kitties::Call::set_heir { heir: heir.clone(), inactivity_blocks: 10 }),
+			Call::Balances(pallet_balances::Call::transfer { dest: heir.into(), value: 1 }),
+		];
+		for call in spending {
+			assert!(!ProxyType::Kitties.filter(&call));
+			assert!(ProxyType::Any.filter(&call));
+		}
+	}
+
+	#[test]
+	fn faucet_pays_accounts_without_funds() {
+		let mut storage = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+		pallet_faucet::GenesisConfig::<Runtime> { drip_amount: 1_000, drip_period: 10, pot: 1_000_000 }
+			.assimilate_storage(&mut storage)
+			.unwrap();
+		sp_io::TestExternalities::new(storage).execute_with(|| {
+			let who = AccountId32::new([7; 32]);
+			assert_eq!(Balances::free_balance(&who), 0);
+
+			// The request goes into the pool and is paid without the account holding anything.
+			let nonce = (0..)
+				.find(|nonce| {
+					u128::from_be_bytes(Faucet::drip_work(&who, *nonce)).leading_zeros() >=
+						FaucetPowDifficulty::get()
+				})
+				.unwrap();
+			let call = pallet_faucet::Call::request_funds { who: who.clone(), nonce };
+			let xt = UncheckedExtrinsic::new_unsigned(Call::Faucet(call));
+			assert!(Executive::validate_transaction(TransactionSource::External, xt.clone(), Default::default())
+				.is_ok());
+			assert_eq!(Executive::apply_extrinsic(xt), Ok(Ok(())));
+			assert_eq!(Balances::free_balance(&who), 1_000);
+		});
+	}
+}
@@ -110,8 +110,9 @@ pub fn run() -> sc_cli::Result<()> {
 			},
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
+			let enable_dev_rpc = cli.enable_dev_rpc;
 			runner.run_node_until_exit(|config| async move {
-				service::new_full(config).map_err(sc_cli::Error::Service)
+				service::new_full(config, enable_dev_rpc).map_err(sc_cli::Error::Service)
 			})
 		},
 	}
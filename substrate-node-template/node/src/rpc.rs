@@ -5,6 +5,9 @@
 
 #![warn(missing_docs)]
 
+mod dev;
+mod system_info;
+
 use std::sync::Arc;
 
 use node_template_runtime::{opaque::Block, AccountId, Balance, Index};
@@ -22,6 +25,8 @@ pub struct FullDeps<C, P> {
 	pub pool: Arc<P>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Whether to serve the `dev_*` methods seeding development chains.
+	pub enable_dev_rpc: bool,
 }
 
 /// Instantiate all full RPC extensions.
@@ -33,18 +38,27 @@ where
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
 	C::Api: BlockBuilder<Block>,
-	P: TransactionPool + 'static,
+	C::Api: system_info_runtime_api::SystemInfoApi<Block>,
+	P: TransactionPool<Block = Block> + 'static,
 {
+	use dev::{Dev, DevApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
+	use system_info::{SystemInfo, SystemInfoApi};
 
 	let mut io = jsonrpc_core::IoHandler::default();
-	let FullDeps { client, pool, deny_unsafe } = deps;
+	let FullDeps { client, pool, deny_unsafe, enable_dev_rpc } = deps;
+
+	if enable_dev_rpc {
+		io.extend_with(DevApi::to_delegate(Dev::new(client.clone(), pool.clone(), deny_unsafe)));
+	}
 
 	io.extend_with(SystemApi::to_delegate(FullSystem::new(client.clone(), pool, deny_unsafe)));
 
 	io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone())));
 
+	io.extend_with(SystemInfoApi::to_delegate(SystemInfo::new(client.clone())));
+
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
 	// to call into the runtime.
@@ -0,0 +1,160 @@
+//! `dev_*` RPC methods seeding a local development chain, served with `--enable-dev-rpc`.
+//!
+//! Each call is wrapped in `Sudo::sudo_as` and submitted in a transaction signed with Alice's
+//! dev key, the sudo key of the `dev` and `local` chain specs. On any other chain the
+//! transactions are rejected by the sudo pallet.
+
+use std::sync::Arc;
+
+use codec::Encode;
+use futures::future::{ready, FutureExt};
+use jsonrpc_core::{BoxFuture, Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use node_template_runtime::{
+	opaque::Block, pallet_poe, AccountId, Address, Call, Index, SignedExtra, SudoCall,
+	UncheckedExtrinsic, VERSION,
+};
+use sc_rpc_api::DenyUnsafe;
+use sc_transaction_pool_api::{InPoolTransaction, TransactionPool, TransactionSource, TxHash};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_keyring::Sr25519Keyring;
+use sp_runtime::generic::{BlockId, Era, SignedPayload};
+use substrate_frame_rpc_system::AccountNonceApi;
+
+/// The account signing the dev transactions.
+const SUDO: Sr25519Keyring = Sr25519Keyring::Alice;
+
+/// Development RPC methods.
+#[rpc]
+pub trait DevApi<AccountId, Hash> {
+	/// Claim `proof`, a multihash-encoded document hash, for `account`.
+	/// Returns the hash of the submitted transaction.
+	#[rpc(name = "dev_createClaim")]
+	fn create_claim(&self, account: AccountId, proof: Bytes) -> BoxFuture<Result<Hash>>;
+}
+
+/// Implements [`DevApi`] by submitting sudo-signed transactions to the pool.
+pub struct Dev<C, P> {
+	client: Arc<C>,
+	pool: Arc<P>,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl<C, P> Dev<C, P> {
+	/// Create a new `Dev` submitting to `pool`.
+	pub fn new(client: Arc<C>, pool: Arc<P>, deny_unsafe: DenyUnsafe) -> Self {
+		Self { client, pool, deny_unsafe }
+	}
+}
+
+impl<C, P> Dev<C, P>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: AccountNonceApi<Block, AccountId, Index>,
+	P: TransactionPool<Block = Block> + 'static,
+{
+	/// Submit each of `calls` as `who`, through `sudo_as`.
+	fn submit_as(&self, who: AccountId, calls: Vec<Call>) -> BoxFuture<Result<Vec<TxHash<P>>>> {
+		if let Err(err) = self.deny_unsafe.check_if_safe() {
+			return ready(Err(err.into())).boxed()
+		}
+
+		let best = self.client.info().best_hash;
+		let nonce = match self.next_nonce(&BlockId::hash(best)) {
+			Ok(nonce) => nonce,
+			Err(err) => return ready(Err(err)).boxed(),
+		};
+		let xts = calls
+			.into_iter()
+			.zip(nonce..)
+			.map(|(call, nonce)| {
+				let call = Call::Sudo(SudoCall::sudo_as {
+					who: Address::Id(who.clone()),
+					call: Box::new(call),
+				});
+				self.sign(call, nonce).into()
+			})
+			.collect();
+
+		let pool = self.pool.clone();
+		async move {
+			let results = pool
+				.submit_at(&BlockId::hash(best), TransactionSource::Local, xts)
+				.await
+				.map_err(pool_error)?;
+			results.into_iter().map(|result| result.map_err(pool_error)).collect()
+		}
+		.boxed()
+	}
+
+	/// The sudo account's next nonce, counting its transactions already in the pool.
+	fn next_nonce(&self, at: &BlockId<Block>) -> Result<Index> {
+		let account = SUDO.to_account_id();
+		let mut nonce = self.client.runtime_api().account_nonce(at, account.clone()).map_err(|err| {
+			Error {
+				code: ErrorCode::InternalError,
+				message: "Unable to query the sudo nonce".into(),
+				data: Some(format!("{:?}", err).into()),
+			}
+		})?;
+		for tx in self.pool.ready() {
+			if tx.provides().first() == Some(&(&account, nonce).encode()) {
+				nonce += 1;
+			}
+		}
+		Ok(nonce)
+	}
+
+	fn sign(&self, call: Call, nonce: Index) -> UncheckedExtrinsic {
+		let genesis_hash = self.client.info().genesis_hash;
+		let extra: SignedExtra = (
+			frame_system::CheckSpecVersion::new(),
+			frame_system::CheckTxVersion::new(),
+			frame_system::CheckGenesis::new(),
+			frame_system::CheckEra::from(Era::Immortal),
+			frame_system::CheckNonce::from(nonce),
+			frame_system::CheckWeight::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::from(0),
+		);
+		let payload = SignedPayload::from_raw(
+			call,
+			extra,
+			(
+				VERSION.spec_version,
+				VERSION.transaction_version,
+				genesis_hash,
+				genesis_hash,
+				(),
+				(),
+				(),
+			),
+		);
+		let signature = payload.using_encoded(|payload| SUDO.sign(payload));
+		let (call, extra, _) = payload.deconstruct();
+		UncheckedExtrinsic::new_signed(call, Address::Id(SUDO.to_account_id()), signature.into(), extra)
+	}
+}
+
+impl<C, P> DevApi<AccountId, TxHash<P>> for Dev<C, P>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: AccountNonceApi<Block, AccountId, Index>,
+	P: TransactionPool<Block = Block> + 'static,
+{
+	fn create_claim(&self, account: AccountId, proof: Bytes) -> BoxFuture<Result<TxHash<P>>> {
+		let call = Call::PoeModule(pallet_poe::Call::create_claim { proof: proof.to_vec() });
+		self.submit_as(account, vec![call])
+			.map(|hashes| hashes.map(|mut hashes| hashes.remove(0)))
+			.boxed()
+	}
+}
+
+fn pool_error(err: impl std::fmt::Display) -> Error {
+	Error {
+		code: ErrorCode::InternalError,
+		message: "Transaction pool rejected the dev transaction".into(),
+		data: Some(err.to_string().into()),
+	}
+}
@@ -156,8 +156,8 @@ fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
 	Err("Remote Keystore not supported.")
 }
 
-/// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+/// Builds a new service for a full client, serving the `dev_*` RPC methods if `enable_dev_rpc`.
+pub fn new_full(mut config: Configuration, enable_dev_rpc: bool) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -220,8 +220,12 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		let pool = transaction_pool.clone();
 
 		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				enable_dev_rpc,
+			};
 
 			Ok(crate::rpc::create_full(deps))
 		})
@@ -0,0 +1,35 @@
+//! Runtime API definition for the poe pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_poe::{ClaimInfo, ClaimStats, Side};
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait PoeApi<Hash, AccountId, BlockNumber> where
+		Hash: Codec,
+		AccountId: Codec,
+		BlockNumber: Codec,
+	{
+		/// Whether `leaf` is part of the batch anchored under the Merkle `root`.
+		fn verify_leaf(root: Hash, leaf: Hash, proof: Vec<(Hash, Side)>) -> bool;
+		/// All claims carrying `tag`.
+		fn claims_by_tag(tag: Vec<u8>) -> Vec<Vec<u8>>;
+		/// Whether `(value, blinding)` opens the anchored Pedersen `commitment`.
+		fn verify_opening(commitment: Vec<u8>, value: u128, blinding: [u8; 32]) -> bool;
+		/// How many claims have been created, exist now and were revoked.
+		fn claim_stats() -> ClaimStats;
+		/// The claims created in blocks `from_block` to `to_block`, both included, and owned
+		/// by `owner` now, if given, and the block to carry on from. At most
+		/// `pallet_poe::MAX_RANGE_BLOCKS` blocks are read per call.
+		fn claims_in_range(
+			from_block: BlockNumber,
+			to_block: BlockNumber,
+			owner: Option<AccountId>,
+		) -> (Vec<ClaimInfo<AccountId, BlockNumber>>, Option<BlockNumber>);
+		/// The other claims linked as anchoring the same document as `proof`, e.g. under
+		/// another hash algorithm.
+		fn find_equivalent(proof: Vec<u8>) -> Vec<Vec<u8>>;
+	}
+}
@@ -0,0 +1,100 @@
+//! Pedersen commitments that can be anchored instead of a plain document hash.
+//!
+//! A commitment `C = v·G + r·H` binds its owner to the value `v` without revealing it until
+//! they publish the opening `(v, r)`, e.g. for sealed bids or private timestamps. `G` is the
+//! group's base point and `H` a second generator nobody knows the discrete log of relative to
+//! `G`, derived by hashing to the group.
+
+use codec::{Decode, Encode};
+use curve25519_dalek::{
+	constants::RISTRETTO_BASEPOINT_POINT,
+	ristretto::{CompressedRistretto, RistrettoPoint},
+	scalar::Scalar,
+};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
+/// The group and generators a commitment is made in.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum CommitmentScheme {
+	/// Pedersen over ristretto255, committed as a 32-byte compressed point.
+	Ristretto255Pedersen,
+}
+
+impl CommitmentScheme {
+	/// Length in bytes of an encoded commitment.
+	pub fn commitment_len(&self) -> usize {
+		match self {
+			CommitmentScheme::Ristretto255Pedersen => 32,
+		}
+	}
+
+	/// Whether `commitment` encodes a point of the scheme's group.
+	pub fn is_valid(&self, commitment: &[u8]) -> bool {
+		match self {
+			CommitmentScheme::Ristretto255Pedersen => decompress(commitment).is_some(),
+		}
+	}
+
+	/// Whether `(value, blinding)` opens `commitment`.
+	pub fn verify_opening(&self, commitment: &[u8], value: u128, blinding: [u8; 32]) -> bool {
+		match self {
+			CommitmentScheme::Ristretto255Pedersen => decompress(commitment)
+				.map_or(false, |point| point == pedersen(value, blinding)),
+		}
+	}
+}
+
+/// The ristretto255 Pedersen commitment to `value` with `blinding`, compressed.
+pub fn commit(value: u128, blinding: [u8; 32]) -> [u8; 32] {
+	pedersen(value, blinding).compress().to_bytes()
+}
+
+fn pedersen(value: u128, blinding: [u8; 32]) -> RistrettoPoint {
+	Scalar::from(value) * RISTRETTO_BASEPOINT_POINT +
+		Scalar::from_bytes_mod_order(blinding) * blinding_generator()
+}
+
+/// `H`, the generator the blinding factor multiplies.
+fn blinding_generator() -> RistrettoPoint {
+	let mut uniform = [0u8; 64];
+	uniform[..32].copy_from_slice(&sp_io::hashing::blake2_256(b"pallet-poe/pedersen/H/0"));
+	uniform[32..].copy_from_slice(&sp_io::hashing::blake2_256(b"pallet-poe/pedersen/H/1"));
+	RistrettoPoint::from_uniform_bytes(&uniform)
+}
+
+fn decompress(commitment: &[u8]) -> Option<RistrettoPoint> {
+	if commitment.len() != 32 {
+		return None
+	}
+	CompressedRistretto::from_slice(commitment).decompress()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const SCHEME: CommitmentScheme = CommitmentScheme::Ristretto255Pedersen;
+
+	#[test]
+	fn verify_opening_works() {
+		let commitment = commit(1_000, [7; 32]);
+		assert!(SCHEME.is_valid(&commitment));
+		assert!(SCHEME.verify_opening(&commitment, 1_000, [7; 32]));
+		// commitments are additively homomorphic
+		let sum = decompress(&commit(1_000, [7; 32])).unwrap() + decompress(&commit(5, [1; 32])).unwrap();
+		let blinding = Scalar::from_bytes_mod_order([7; 32]) + Scalar::from_bytes_mod_order([1; 32]);
+		assert!(SCHEME.verify_opening(&sum.compress().to_bytes(), 1_005, blinding.to_bytes()));
+	}
+
+	#[test]
+	fn verify_opening_failed() {
+		let commitment = commit(1_000, [7; 32]);
+		assert!(!SCHEME.verify_opening(&commitment, 1_001, [7; 32]));
+		assert!(!SCHEME.verify_opening(&commitment, 1_000, [8; 32]));
+		assert!(!SCHEME.verify_opening(&commitment[..31], 1_000, [7; 32]));
+		// not every 32 bytes encode a point
+		assert!(!SCHEME.is_valid(&[0xff; 32]));
+		assert!(!SCHEME.verify_opening(&[0xff; 32], 1_000, [7; 32]));
+	}
+}
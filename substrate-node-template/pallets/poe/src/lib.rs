@@ -3,6 +3,10 @@
 // Re-export pallet items so that they can be accessed from the crate namespace.
 pub use pallet::*;
 
+pub mod commitment;
+pub mod multihash;
+pub mod preimage;
+
 #[cfg(test)]
 mod mock;
 #[cfg(test)]
@@ -10,9 +14,51 @@ mod tests;
 
 #[frame_support::pallet]
 pub mod pallet {
-	use frame_support::pallet_prelude::*;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{
+			tokens::nonfungible, Currency, ExistenceRequirement, Imbalance, OnUnbalanced,
+			ReservableCurrency, Time, WithdrawReasons,
+		},
+	};
 	use frame_system::pallet_prelude::*;
+	use scale_info::TypeInfo;
+	use sp_runtime::{
+		traits::{Hash, IdentifyAccount, One, Saturating, Verify, Zero},
+		Permill, SaturatedConversion,
+	};
 	use sp_std::vec::Vec;
+	use crate::commitment::CommitmentScheme;
+	use crate::multihash::{self, HashAlgorithm, MultihashError};
+	use crate::preimage::PreimageProvider;
+
+	/// Weight charged per byte of claim data stored, on top of the database accesses.
+	pub const WEIGHT_PER_BYTE: Weight = 1_000;
+
+	/// Prefixed to a leaf before hashing it into a Merkle tree, so a leaf can't pass for an
+	/// inner node or the other way around.
+	pub const MERKLE_LEAF_PREFIX: u8 = 0x00;
+
+	/// Prefixed to a pair of children before hashing them into their parent node.
+	pub const MERKLE_NODE_PREFIX: u8 = 0x01;
+
+	/// The most blocks a single `claims_in_range` call looks at. Each block holds at most
+	/// `MaxClaimsPerBlock` claims, so this bounds what one call reads.
+	pub const MAX_RANGE_BLOCKS: u32 = 100;
+
+	/// Which side of the path a sibling hash sits on in a Merkle proof.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub enum Side {
+		Left,
+		Right,
+	}
+
+	type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::NegativeImbalance;
+	type MomentOf<T> = <<T as Config>::TimeProvider as Time>::Moment;
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
@@ -22,8 +68,113 @@ pub mod pallet {
 		/// The limit of the proof's length
 		// #[pallet::constant]
 		type LengthLimit: Get<u32>;
+		/// The maximum number of tags on one claim.
+		#[pallet::constant]
+		type MaxTags: Get<u32>;
+		/// The maximum length of a single tag.
+		#[pallet::constant]
+		type MaxTagLength: Get<u32>;
+		/// The currency reference fees are paid in.
+		type Currency: ReservableCurrency<Self::AccountId>;
+		/// The fee for referencing a claim.
+		#[pallet::constant]
+		type ReferenceFee: Get<BalanceOf<Self>>;
+		/// The part of the reference fee paid to the claim owner.
+		#[pallet::constant]
+		type OwnerShare: Get<Permill>;
+		/// Where the rest of the reference fee, and all retention fees, go.
+		type Treasury: OnUnbalanced<NegativeImbalanceOf<Self>>;
+		/// Origin allowed to register content types.
+		type ForceOrigin: EnsureOrigin<Self::Origin>;
+		/// The maximum length of a content type name.
+		#[pallet::constant]
+		type MaxContentTypeNameLength: Get<u32>;
+		/// The fee for keeping a claim for one more rent era.
+		#[pallet::constant]
+		type RetentionFee: Get<BalanceOf<Self>>;
+		/// The length of a rent era in blocks. New claims come with their first era paid.
+		#[pallet::constant]
+		type RentEra: Get<Self::BlockNumber>;
+		/// How long a lapsed claim can still be topped up before anyone may purge it.
+		#[pallet::constant]
+		type GracePeriod: Get<Self::BlockNumber>;
+		/// Identifier of a kitty claims can be bound to.
+		type KittyId: Parameter;
+		/// Who owns each kitty, usually the kitties pallet.
+		type Kitties: nonfungible::Inspect<Self::AccountId, InstanceId = Self::KittyId>;
+		/// The maximum number of claims bound to one kitty.
+		#[pallet::constant]
+		type MaxClaimsPerKitty: Get<u32>;
+		/// The clock claims are timestamped with, usually the timestamp pallet.
+		type TimeProvider: Time;
+		/// Whether creating a claim also emits `ClaimTimestamped` with its time, for clients
+		/// that want it without reading storage.
+		#[pallet::constant]
+		type TimestampInEvent: Get<bool>;
+		/// The most claims, batch roots and commitments that may be anchored in one block.
+		#[pallet::constant]
+		type MaxClaimsPerBlock: Get<u32>;
+		/// The maximum length of a license identifier.
+		#[pallet::constant]
+		type MaxLicenseIdLength: Get<u32>;
+		/// The most licensees one claim may have, so removing the claim removes a bounded number
+		/// of licenses.
+		#[pallet::constant]
+		type MaxLicensees: Get<u32>;
+		/// The signature a countersigner gives to `create_countersigned_claim`, e.g.
+		/// `MultiSignature` to accept sr25519 signatures from ordinary accounts.
+		type CounterSignature: Parameter + Verify<Signer = Self::CounterSigner>;
+		/// The signer behind a `CounterSignature`, identifying the countersigner's account.
+		type CounterSigner: IdentifyAccount<AccountId = Self::AccountId>;
+		/// Where large claim descriptors are noted, usually the preimage pallet.
+		type Preimages: PreimageProvider<Self::Hash>;
+		/// Reserved from a claim owner when they first link a descriptor to the claim, since the
+		/// link keeps the descriptor stored, and returned when the claim is removed.
+		#[pallet::constant]
+		type DescriptorDeposit: Get<BalanceOf<Self>>;
+		/// The most claims that may be linked as anchoring the same document.
+		#[pallet::constant]
+		type MaxEquivalentClaims: Get<u32>;
+	}
+
+	/// Claim counts for dashboards.
+	#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct ClaimStats {
+		/// Claims ever created.
+		pub total: u64,
+		/// Claims that currently exist.
+		pub active: u64,
+		/// Claims revoked, tombstoned or purged, by their owner, governance or anyone once
+		/// their retention lapsed.
+		pub revoked: u64,
+	}
+
+	/// How far the migration started by `on_runtime_upgrade` has got, see `MigrationProgress`.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub enum ClaimsMigration {
+		/// Filing the claims made before `ClaimsByBlock`, and counting them in `Stats` too if
+		/// they were made before it was kept, from the claim after `last`.
+		Claims { count: bool, last: Option<Vec<u8>> },
+		/// Counting the tombstones made before `Stats` was kept, from the one after `last`.
+		Tombstones { last: Option<Vec<u8>> },
 	}
 
+	/// A claim, as bulk queries return it.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct ClaimInfo<AccountId, BlockNumber> {
+		pub proof: Vec<u8>,
+		/// The claim's current owner.
+		pub owner: AccountId,
+		/// The block the claim was created in.
+		pub created_at: BlockNumber,
+	}
+
+	/// Identifier of a registered content type, e.g. PDF or source tarball.
+	pub type ContentTypeId = u32;
+
+	/// A short label used to group claims, e.g. "invoice" or "contract".
+	pub type Tag<T> = BoundedVec<u8, <T as Config>::MaxTagLength>;
+
 	/// Pallets use events to inform users when important changes are made.
 	/// Event documentation should end with an array that provides descriptive names for parameters.
 	#[pallet::event]
@@ -35,6 +186,45 @@ pub mod pallet {
 		ClaimRevoked(T::AccountId, Vec<u8>),
 		/// Event emitted when a claim is transfered from the owner to others. [from, to, claim]
 		ClaimTransfered(T::AccountId, T::AccountId, Vec<u8>),
+		/// Event emitted when a Merkle root of a batch of documents has been anchored.
+		/// [who, root, leaf_count]
+		BatchRootCreated(T::AccountId, T::Hash, u32),
+		/// Event emitted when the tags of a claim are replaced. [who, claim, tags]
+		ClaimTagged(T::AccountId, Vec<u8>, Vec<Vec<u8>>),
+		/// Event emitted when a claim is referenced by a third party. [who, claim, fee]
+		ClaimReferenced(T::AccountId, Vec<u8>, BalanceOf<T>),
+		/// Event emitted when a content type is added to the registry. [type_id, name]
+		ContentTypeRegistered(ContentTypeId, Vec<u8>),
+		/// Event emitted when a claim declares its content type. [who, claim, type_id]
+		ClaimTyped(T::AccountId, Vec<u8>, ContentTypeId),
+		/// Event emitted when retention of a claim is paid for. [who, claim, fee, paid_until]
+		ClaimToppedUp(T::AccountId, Vec<u8>, BalanceOf<T>, T::BlockNumber),
+		/// Event emitted when a claim is purged after its grace period. [claim]
+		ClaimPurged(Vec<u8>),
+		/// Event emitted when a claim is bound to a kitty. [who, claim, kitty_id, follows_kitty]
+		ClaimBoundToKitty(T::AccountId, Vec<u8>, T::KittyId, bool),
+		/// Event emitted when a commitment has been anchored. [who, commitment, scheme]
+		CommitmentAnchored(T::AccountId, Vec<u8>, CommitmentScheme),
+		/// Event emitted when a claim is stripped down to its hash and timestamp. [claim]
+		ClaimTombstoned(Vec<u8>),
+		/// Event emitted after `ClaimCreated` with the time the claim was created at, if
+		/// `TimestampInEvent` is set. [claim, moment]
+		ClaimTimestamped(Vec<u8>, MomentOf<T>),
+		/// Event emitted when the owner of a claim sets its license terms. [who, claim, license, fee]
+		LicenseSet(T::AccountId, Vec<u8>, Vec<u8>, Option<BalanceOf<T>>),
+		/// Event emitted when the owner of a claim withdraws its license. [who, claim]
+		LicenseRemoved(T::AccountId, Vec<u8>),
+		/// Event emitted when a license to a claim is acquired. [licensee, claim, fee]
+		LicenseAcquired(T::AccountId, Vec<u8>, BalanceOf<T>),
+		/// Event emitted after `ClaimCreated` when a second party countersigned the claim.
+		/// [who, countersigner, claim]
+		ClaimCountersigned(T::AccountId, T::AccountId, Vec<u8>),
+		/// Event emitted when a claim is linked to the preimage of its descriptor.
+		/// [who, claim, preimage_hash]
+		PreimageLinked(T::AccountId, Vec<u8>, T::Hash),
+		/// Event emitted when two claims are linked as anchoring the same document.
+		/// [who, claim_a, claim_b, group]
+		EquivalentClaimsLinked(T::AccountId, Vec<u8>, Vec<u8>, u64),
 	}
 
 	#[pallet::error]
@@ -47,30 +237,296 @@ pub mod pallet {
 		NotProofOwner,
 		/// The proof is too long to claimed.
 		ProofTooLong,
+		/// The Merkle root has already been anchored.
+		BatchRootAlreadyAnchored,
+		/// A batch must contain at least one document.
+		EmptyBatch,
+		/// More tags were given than `MaxTags` allows.
+		TooManyTags,
+		/// A tag is longer than `MaxTagLength`.
+		TagTooLong,
+		/// The claim owner can't reference their own claim.
+		CannotReferenceOwnClaim,
+		/// A content type with this id is already registered.
+		ContentTypeAlreadyRegistered,
+		/// The content type name is longer than `MaxContentTypeNameLength`.
+		ContentTypeNameTooLong,
+		/// No content type is registered under this id.
+		UnknownContentType,
+		/// The claim predates retention rent and is kept for free.
+		RentFree,
+		/// The top-up doesn't cover a single rent era.
+		TopUpTooSmall,
+		/// The claim's grace period is over, so it can only be purged.
+		RetentionExpired,
+		/// The claim is paid for or still in its grace period.
+		ClaimStillRetained,
+		/// The proof's multihash header names a hash function that isn't supported.
+		UnsupportedHashAlgorithm,
+		/// The proof is not a well-formed multihash.
+		InvalidMultihash,
+		/// The caller doesn't own the kitty the claim is bound to.
+		NotKittyOwner,
+		/// The kitty already has `MaxClaimsPerKitty` claims bound to it.
+		TooManyKittyClaims,
+		/// The claim moves with its kitty, so it can't be transferred on its own.
+		ClaimFollowsKitty,
+		/// The commitment has already been anchored.
+		CommitmentAlreadyAnchored,
+		/// The commitment is not a point of its scheme's group.
+		InvalidCommitment,
+		/// The proof was tombstoned, so it can never be claimed again.
+		ClaimTombstoned,
+		/// `MaxClaimsPerBlock` claims, batch roots and commitments have already been anchored in
+		/// this block.
+		TooManyClaimsThisBlock,
+		/// The license identifier is longer than `MaxLicenseIdLength`.
+		LicenseIdTooLong,
+		/// The claim is not offered under a license.
+		NoLicense,
+		/// The caller already holds a license to the claim.
+		AlreadyLicensed,
+		/// The claim owner can't license their own claim.
+		CannotLicenseOwnClaim,
+		/// The countersignature isn't the countersigner's over `countersign_payload`.
+		InvalidCountersignature,
+		/// The claim creator can't countersign their own claim.
+		CannotCountersignOwnClaim,
+		/// No preimage of the hash is noted in the preimage store.
+		PreimageNotFound,
+		/// A claim can't be linked to itself.
+		CannotLinkToItself,
+		/// The claims are already linked as anchoring the same document.
+		AlreadyEquivalent,
+		/// Linking would put more than `MaxEquivalentClaims` claims on one document.
+		TooManyEquivalentClaims,
+		/// The claim already has `MaxLicensees` licensees.
+		TooManyLicensees,
+		/// The license fee is more than the caller agreed to pay.
+		LicenseFeeTooHigh,
 	}
 
+	/// Version 1 added `Stats` and version 2 `ClaimsByBlock`, both filled in for claims made
+	/// before them by `on_idle` once `on_runtime_upgrade` has started the migration.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::storage]
 	pub(super) type Proofs<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, (T::AccountId, T::BlockNumber)>;
 
+	/// The tags of each claim.
+	#[pallet::storage]
+	pub(super) type Tags<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, BoundedVec<Tag<T>, T::MaxTags>, ValueQuery>;
+
+	/// Claims grouped by tag: tag => claim => ().
+	#[pallet::storage]
+	pub(super) type ClaimsByTag<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, Tag<T>, Blake2_128Concat, Vec<u8>, ()>;
+
+	/// Anchored Merkle roots: (owner, block number, number of leaves).
+	#[pallet::storage]
+	pub(super) type BatchRoots<T: Config> =
+		StorageMap<_, Identity, T::Hash, (T::AccountId, T::BlockNumber, u32)>;
+
+	/// How many times each claim has been referenced.
+	#[pallet::storage]
+	pub(super) type ReferenceCount<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, u32, ValueQuery>;
+
+	/// Registered content types: id => name.
+	#[pallet::storage]
+	pub(super) type ContentTypes<T: Config> =
+		StorageMap<_, Twox64Concat, ContentTypeId, BoundedVec<u8, T::MaxContentTypeNameLength>>;
+
+	/// The declared content type of each typed claim.
+	#[pallet::storage]
+	pub(super) type ClaimTypes<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, ContentTypeId>;
+
+	/// The block each claim's retention is paid until. Once it passes the claim is lapsed.
+	/// Claims anchored before retention rent was introduced have no entry and are kept for free.
+	#[pallet::storage]
+	pub(super) type PaidUntil<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, T::BlockNumber>;
+
+	/// The hash function each claim's digest was made with, as named by its multihash header.
+	#[pallet::storage]
+	pub(super) type ClaimAlgorithms<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, HashAlgorithm>;
+
+	/// Anchored commitments: (owner, block number, scheme).
+	#[pallet::storage]
+	pub(super) type Commitments<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, (T::AccountId, T::BlockNumber, CommitmentScheme)>;
+
+	/// The kitty each bound claim belongs to, and whether the claim follows the kitty to its
+	/// new owners.
+	#[pallet::storage]
+	pub(super) type KittyLinks<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, (T::KittyId, bool)>;
+
+	/// The claims bound to each kitty.
+	#[pallet::storage]
+	pub(super) type ClaimsByKitty<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::KittyId,
+		BoundedVec<Vec<u8>, T::MaxClaimsPerKitty>,
+		ValueQuery,
+	>;
+
+	/// Tombstoned claims: proof => the block recorded with the claim. Only its time in
+	/// `ClaimTimes` is kept besides, and the proof can't be claimed again.
+	#[pallet::storage]
+	pub(super) type Tombstones<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, T::BlockNumber>;
+
+	/// The time each claim was created at by `TimeProvider`, e.g. milliseconds since the Unix
+	/// epoch, kept next to the block number in `Proofs`, or in `Tombstones` once tombstoned.
+	#[pallet::storage]
+	pub(super) type ClaimTimes<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, MomentOf<T>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn claim_stats)]
+	pub(super) type Stats<T: Config> = StorageValue<_, ClaimStats, ValueQuery>;
+
+	/// How many claims, batch roots and commitments have been anchored in the current block.
+	/// Reset at the start of every block.
+	#[pallet::storage]
+	#[pallet::getter(fn claims_this_block)]
+	pub(super) type ClaimsThisBlock<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// The block each claim was created in. Unlike the block in `Proofs`, it is kept when the
+	/// claim is transferred.
+	#[pallet::storage]
+	pub(super) type ClaimBlocks<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, T::BlockNumber>;
+
+	/// Claims by the block they were created in: block => claim => (). Claims created before
+	/// the index was introduced are filed under the block in `Proofs`, which is the block they
+	/// were last transferred in if they were.
+	#[pallet::storage]
+	pub(super) type ClaimsByBlock<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::BlockNumber, Blake2_128Concat, Vec<u8>, ()>;
+
+	/// The license each licensable claim is offered under: (SPDX-style identifier, fee).
+	#[pallet::storage]
+	pub(super) type Licenses<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		(BoundedVec<u8, T::MaxLicenseIdLength>, Option<BalanceOf<T>>),
+	>;
+
+	/// The licensees of each claim: proof => licensee => the block the license was acquired in.
+	#[pallet::storage]
+	pub(super) type Licensees<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, Vec<u8>, Blake2_128Concat, T::AccountId, T::BlockNumber>;
+
+	/// How many licensees each claim has, at most `MaxLicensees`.
+	#[pallet::storage]
+	pub(super) type LicenseeCount<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, u32, ValueQuery>;
+
+	/// The second party to each countersigned claim. Kept when the claim is transferred.
+	#[pallet::storage]
+	pub(super) type Countersigners<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, T::AccountId>;
+
+	/// The hash of each linked claim's descriptor, kept requested in `Preimages`.
+	#[pallet::storage]
+	pub(super) type DescriptorPreimages<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, T::Hash>;
+
+	/// Who reserved the `DescriptorDeposit` for each linked claim, and how much, so it is
+	/// refunded exactly.
+	#[pallet::storage]
+	pub(super) type DescriptorDeposits<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, (T::AccountId, BalanceOf<T>)>;
+
+	/// The index the next group of equivalent claims is stored under. Indices are never reused,
+	/// so a claim revoked and claimed again can't join the group it left.
+	#[pallet::storage]
+	pub(super) type NextEquivalenceGroup<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+	/// The group of equivalent claims each linked claim is in. Claims never linked have none.
+	#[pallet::storage]
+	pub(super) type EquivalenceGroups<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, u64>;
+
+	/// The claims anchoring each document, by group index.
+	#[pallet::storage]
+	pub(super) type EquivalentClaims<T: Config> =
+		StorageMap<_, Twox64Concat, u64, BoundedVec<Vec<u8>, T::MaxEquivalentClaims>, ValueQuery>;
+
+	/// The migration `on_idle` is carrying on with a few claims a block, if any. Until it is
+	/// done, `Stats` and `ClaimsByBlock` leave out the claims it hasn't got to yet.
+	#[pallet::storage]
+	#[pallet::getter(fn migration_progress)]
+	pub(super) type MigrationProgress<T: Config> = StorageValue<_, ClaimsMigration>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Claims to start with: their owner and proof, a multihash within `LengthLimit`. Like
+		/// claims from before retention rent, they are kept for free.
+		pub claims: Vec<(T::AccountId, Vec<u8>)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { claims: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (owner, proof) in &self.claims {
+				assert!(proof.len() as u32 <= T::LengthLimit::get(), "genesis claims fit the length limit");
+				let algorithm = Pallet::<T>::hash_algorithm(proof)
+					.unwrap_or_else(|_| panic!("genesis claims are multihashes"));
+				assert!(!Proofs::<T>::contains_key(proof), "genesis claims are claimed once");
+				Proofs::<T>::insert(proof, (owner, T::BlockNumber::zero()));
+				ClaimAlgorithms::<T>::insert(proof, algorithm);
+				Pallet::<T>::note_new_claim(proof.clone());
+			}
+		}
+	}
+
 	#[pallet::hooks]
-	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_n: T::BlockNumber) -> Weight {
+			ClaimsThisBlock::<T>::kill();
+			T::DbWeight::get().writes(1)
+		}
+
+		fn on_idle(_n: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			Self::migrate_some(remaining_weight)
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			let version = StorageVersion::get::<Pallet<T>>();
+			if version >= 2 {
+				return T::DbWeight::get().reads(1)
+			}
+			// There may be too many claims to go through in one block, so `on_idle` does.
+			MigrationProgress::<T>::put(ClaimsMigration::Claims { count: version < 1, last: None });
+			STORAGE_VERSION.put::<Pallet<T>>();
+			T::DbWeight::get().reads_writes(1, 2)
+		}
+	}
 
 	// Dispatchable functions allow users to interact with the pallet and invoke state changes.
 	// These functions materialize as "extrinsics", which are often compared to transactions.
 	// Dispatchable functions must be annotated with a weight and must return a DispatchResult.
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		#[pallet::weight(1_000)]
+		#[pallet::weight(Pallet::<T>::claim_weight(proof.len(), 0, 0))]
 		pub fn create_claim(
 			origin: OriginFor<T>,
 			proof: Vec<u8>,
 		) -> DispatchResult {
-			// Check if the length of proof is over the limit.
+			// Check if the length of proof is over the limit, and that it is a multihash.
 			ensure!(proof.len() as u32 <= T::LengthLimit::get(), Error::<T>::ProofTooLong);
+			let algorithm = Self::hash_algorithm(&proof)?;
 
 			// Check that the extrinsic was signed and get the sender.
 			// This function will return an error if the extrinsic is not signed.
@@ -78,15 +534,47 @@ pub mod pallet {
 
 			// Verify that the specified proof has not already been claimed.
 			ensure!(!Proofs::<T>::contains_key(&proof), Error::<T>::ProofAlreadyClaimed);
+			ensure!(!Tombstones::<T>::contains_key(&proof), Error::<T>::ClaimTombstoned);
+			Self::ensure_block_capacity()?;
 
 			// Get the block number from the FRAME System pallet.
 			let cur_block = <frame_system::Pallet<T>>::block_number();
 
 			// Store the proof with the sender and block number.
 			Proofs::<T>::insert(&proof, (&sender, cur_block));
+			Self::start_retention(&proof);
+			ClaimAlgorithms::<T>::insert(&proof, algorithm);
 
 			// Emit an event that the claim was created.
-			Self::deposit_event(Event::ClaimCreated(sender, proof));
+			Self::deposit_event(Event::ClaimCreated(sender, proof.clone()));
+			Self::note_new_claim(proof);
+			Ok(())
+		}
+
+		/// Create a claim acknowledged by `countersigner`, who signed `countersign_payload` for
+		/// the caller and the proof off-chain. Both parties are recorded with the claim.
+		#[pallet::weight(Pallet::<T>::claim_weight(proof.len(), 0, 0).saturating_add(10_000))]
+		pub fn create_countersigned_claim(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+			countersigner: T::AccountId,
+			countersignature: T::CounterSignature,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin.clone())?;
+
+			// Verify that a second party signed for this claimant and proof.
+			ensure!(sender != countersigner, Error::<T>::CannotCountersignOwnClaim);
+			let payload = Self::countersign_payload(&sender, &proof);
+			ensure!(
+				countersignature.verify(&payload[..], &countersigner),
+				Error::<T>::InvalidCountersignature
+			);
+
+			// Create the claim as usual, then record the countersigner with it.
+			Self::create_claim(origin, proof.clone())?;
+			Countersigners::<T>::insert(&proof, &countersigner);
+
+			Self::deposit_event(Event::ClaimCountersigned(sender, countersigner, proof));
 			Ok(())
 		}
 
@@ -103,8 +591,15 @@ pub mod pallet {
 			let (owner, _) = Proofs::<T>::get(&proof).ok_or(Error::<T>::NoSuchProof)?;
 			ensure!(sender == owner, Error::<T>::NotProofOwner);
 
-			// Remove claim from storage.
-			Proofs::<T>::remove(&proof);
+			// A claim following its kitty belongs to the kitty's current owner, even before
+			// it has been synced to them.
+			if let Some((kitty_id, true)) = KittyLinks::<T>::get(&proof) {
+				ensure!(T::Kitties::owner(&kitty_id) == Some(sender.clone()), Error::<T>::NotKittyOwner);
+			}
+
+			// Remove claim and everything attached to it from storage.
+			Self::remove_claim(&proof);
+			Stats::<T>::mutate(|stats| stats.revoked = stats.revoked.saturating_add(1));
 
 			// Emit an event that the claim was revoked.
 			Self::deposit_event(Event::ClaimRevoked(sender, proof));
@@ -124,14 +619,902 @@ pub mod pallet {
 			// Verify that the proof has been claimed, and caller is the claim owner.
 			let (owner, _) = Proofs::<T>::get(&proof).ok_or(Error::<T>::NoSuchProof)?;
 			ensure!(sender == owner, Error::<T>::NotProofOwner);
+			ensure!(
+				!matches!(KittyLinks::<T>::get(&proof), Some((_, true))),
+				Error::<T>::ClaimFollowsKitty
+			);
 
 			// Change the claim's owner to `to`.
 			let cur_block = frame_system::Pallet::<T>::block_number();
 			Proofs::<T>::insert(&proof, (&to, cur_block));
+			// The new owner hasn't vouched for the links the old one made.
+			Self::unlink_equivalent(&proof);
+			Self::unlink_descriptor(&proof);
 
 			// Emit an event that the claim was transfered from the owner to `to`.
 			Self::deposit_event(Event::ClaimTransfered(sender, to, proof));
 			Ok(())
 		}
+
+		#[pallet::weight(Pallet::<T>::claim_weight(proof.len(), tags.len(), Pallet::<T>::tags_len(tags)))]
+		pub fn create_claim_with_tags(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+			tags: Vec<Vec<u8>>,
+		) -> DispatchResult {
+			// Check if the length of proof is over the limit, and the tags are valid.
+			ensure!(proof.len() as u32 <= T::LengthLimit::get(), Error::<T>::ProofTooLong);
+			let algorithm = Self::hash_algorithm(&proof)?;
+			let bounded_tags = Self::bound_tags(tags.clone())?;
+
+			// Check that the extrinsic was signed and get the sender.
+			let sender = ensure_signed(origin)?;
+
+			// Verify that the specified proof has not already been claimed.
+			ensure!(!Proofs::<T>::contains_key(&proof), Error::<T>::ProofAlreadyClaimed);
+			ensure!(!Tombstones::<T>::contains_key(&proof), Error::<T>::ClaimTombstoned);
+			Self::ensure_block_capacity()?;
+
+			// Store the proof with the sender and block number, then its tags.
+			let cur_block = <frame_system::Pallet<T>>::block_number();
+			Proofs::<T>::insert(&proof, (&sender, cur_block));
+			Self::start_retention(&proof);
+			ClaimAlgorithms::<T>::insert(&proof, algorithm);
+			Self::put_tags(&proof, bounded_tags);
+
+			// Emit events that the claim was created and tagged.
+			Self::deposit_event(Event::ClaimCreated(sender.clone(), proof.clone()));
+			Self::note_new_claim(proof.clone());
+			Self::deposit_event(Event::ClaimTagged(sender, proof, tags));
+			Ok(())
+		}
+
+		#[pallet::weight(
+			Pallet::<T>::claim_weight(0, tags.len() + T::MaxTags::get() as usize, Pallet::<T>::tags_len(tags))
+		)]
+		pub fn set_tags(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+			tags: Vec<Vec<u8>>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			let bounded_tags = Self::bound_tags(tags.clone())?;
+
+			// Verify that the proof has been claimed, and caller is the claim owner.
+			let (owner, _) = Proofs::<T>::get(&proof).ok_or(Error::<T>::NoSuchProof)?;
+			ensure!(sender == owner, Error::<T>::NotProofOwner);
+
+			// Replace the old tags.
+			Self::clear_tags(&proof);
+			Self::put_tags(&proof, bounded_tags);
+
+			Self::deposit_event(Event::ClaimTagged(sender, proof, tags));
+			Ok(())
+		}
+
+		#[pallet::weight(Pallet::<T>::claim_weight(proof.len(), 1, 0))]
+		pub fn create_claim_with_meta(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+			content_type: ContentTypeId,
+		) -> DispatchResult {
+			// Check if the length of proof is over the limit, and the content type is known.
+			ensure!(proof.len() as u32 <= T::LengthLimit::get(), Error::<T>::ProofTooLong);
+			let algorithm = Self::hash_algorithm(&proof)?;
+			ensure!(ContentTypes::<T>::contains_key(content_type), Error::<T>::UnknownContentType);
+
+			// Check that the extrinsic was signed and get the sender.
+			let sender = ensure_signed(origin)?;
+
+			// Verify that the specified proof has not already been claimed.
+			ensure!(!Proofs::<T>::contains_key(&proof), Error::<T>::ProofAlreadyClaimed);
+			ensure!(!Tombstones::<T>::contains_key(&proof), Error::<T>::ClaimTombstoned);
+			Self::ensure_block_capacity()?;
+
+			// Store the proof with the sender and block number, then its type.
+			let cur_block = <frame_system::Pallet<T>>::block_number();
+			Proofs::<T>::insert(&proof, (&sender, cur_block));
+			Self::start_retention(&proof);
+			ClaimAlgorithms::<T>::insert(&proof, algorithm);
+			ClaimTypes::<T>::insert(&proof, content_type);
+
+			// Emit events that the claim was created and typed.
+			Self::deposit_event(Event::ClaimCreated(sender.clone(), proof.clone()));
+			Self::note_new_claim(proof.clone());
+			Self::deposit_event(Event::ClaimTyped(sender, proof, content_type));
+			Ok(())
+		}
+
+		/// Claim `proof` bound to `kitty_id`, which the caller must own. With `follow_kitty`
+		/// the claim passes to whoever the kitty is transferred to, see `sync_kitty_claims`.
+		#[pallet::weight(Pallet::<T>::claim_weight(proof.len(), 1, 0))]
+		pub fn create_claim_for_kitty(
+			origin: OriginFor<T>,
+			kitty_id: T::KittyId,
+			proof: Vec<u8>,
+			follow_kitty: bool,
+		) -> DispatchResult {
+			// Check if the length of proof is over the limit, and that it is a multihash.
+			ensure!(proof.len() as u32 <= T::LengthLimit::get(), Error::<T>::ProofTooLong);
+			let algorithm = Self::hash_algorithm(&proof)?;
+
+			// Check that the extrinsic was signed by the kitty owner.
+			let sender = ensure_signed(origin)?;
+			ensure!(T::Kitties::owner(&kitty_id) == Some(sender.clone()), Error::<T>::NotKittyOwner);
+
+			// Verify that the specified proof has not already been claimed.
+			ensure!(!Proofs::<T>::contains_key(&proof), Error::<T>::ProofAlreadyClaimed);
+			ensure!(!Tombstones::<T>::contains_key(&proof), Error::<T>::ClaimTombstoned);
+			Self::ensure_block_capacity()?;
+
+			// Bind the proof to the kitty, then store it with the sender and block number.
+			ClaimsByKitty::<T>::try_mutate(&kitty_id, |claims| claims.try_push(proof.clone()))
+				.map_err(|_| Error::<T>::TooManyKittyClaims)?;
+			KittyLinks::<T>::insert(&proof, (kitty_id.clone(), follow_kitty));
+			let cur_block = <frame_system::Pallet<T>>::block_number();
+			Proofs::<T>::insert(&proof, (&sender, cur_block));
+			Self::start_retention(&proof);
+			ClaimAlgorithms::<T>::insert(&proof, algorithm);
+
+			// Emit events that the claim was created and bound to the kitty.
+			Self::deposit_event(Event::ClaimCreated(sender.clone(), proof.clone()));
+			Self::note_new_claim(proof.clone());
+			Self::deposit_event(Event::ClaimBoundToKitty(sender, proof, kitty_id, follow_kitty));
+			Ok(())
+		}
+
+		/// Hand the claims following `kitty_id` to the kitty's current owner. Anyone may sync a
+		/// kitty after it has been transferred.
+		#[pallet::weight(
+			T::DbWeight::get().reads_writes(2, T::MaxClaimsPerKitty::get() as Weight).saturating_add(10_000)
+		)]
+		pub fn sync_kitty_claims(origin: OriginFor<T>, kitty_id: T::KittyId) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			let owner = T::Kitties::owner(&kitty_id).ok_or(Error::<T>::NotKittyOwner)?;
+			Self::kitty_transferred(&kitty_id, &owner);
+			Ok(())
+		}
+
+		/// Anchor a commitment to a value that stays hidden until its opening is published,
+		/// see [`crate::commitment`].
+		#[pallet::weight(Pallet::<T>::claim_weight(commitment.len(), 0, 0).saturating_add(100_000))]
+		pub fn create_commitment(
+			origin: OriginFor<T>,
+			scheme: CommitmentScheme,
+			commitment: Vec<u8>,
+		) -> DispatchResult {
+			// Check that the commitment is a well-formed point of the scheme's group.
+			ensure!(
+				commitment.len() == scheme.commitment_len() && scheme.is_valid(&commitment),
+				Error::<T>::InvalidCommitment
+			);
+
+			let sender = ensure_signed(origin)?;
+
+			// Verify that the commitment has not already been anchored and the block has room.
+			ensure!(!Commitments::<T>::contains_key(&commitment), Error::<T>::CommitmentAlreadyAnchored);
+			Self::ensure_block_capacity()?;
+
+			// Store the commitment with the sender, block number and scheme.
+			let cur_block = <frame_system::Pallet<T>>::block_number();
+			Commitments::<T>::insert(&commitment, (&sender, cur_block, scheme));
+			ClaimsThisBlock::<T>::mutate(|n| *n = n.saturating_add(1));
+
+			Self::deposit_event(Event::CommitmentAnchored(sender, commitment, scheme));
+			Ok(())
+		}
+
+		/// One root stands for any number of documents, so anchoring a batch is an ordinary
+		/// paid transaction whatever its size.
+		#[pallet::weight((
+			T::DbWeight::get().reads_writes(1, 1).saturating_add(10_000),
+			DispatchClass::Normal,
+			Pays::Yes,
+		))]
+		pub fn create_batch_root(
+			origin: OriginFor<T>,
+			root_hash: T::Hash,
+			leaf_count: u32,
+		) -> DispatchResult {
+			// Check that the extrinsic was signed and get the sender.
+			// This function will return an error if the extrinsic is not signed.
+			let sender = ensure_signed(origin)?;
+
+			// Verify that the batch is not empty, the root has not already been anchored and the
+			// block has room.
+			ensure!(leaf_count > 0, Error::<T>::EmptyBatch);
+			ensure!(!BatchRoots::<T>::contains_key(&root_hash), Error::<T>::BatchRootAlreadyAnchored);
+			Self::ensure_block_capacity()?;
+
+			// Store the root with the sender, block number and batch size.
+			let cur_block = <frame_system::Pallet<T>>::block_number();
+			BatchRoots::<T>::insert(&root_hash, (&sender, cur_block, leaf_count));
+			ClaimsThisBlock::<T>::mutate(|n| *n = n.saturating_add(1));
+
+			// Emit an event that the batch root was anchored.
+			Self::deposit_event(Event::BatchRootCreated(sender, root_hash, leaf_count));
+			Ok(())
+		}
+
+		/// Cite an anchored claim, paying `ReferenceFee`. The claim owner gets `OwnerShare` of
+		/// the fee and the rest goes to `Treasury`.
+		#[pallet::weight(10_000)]
+		pub fn reference_claim(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Verify that the proof has been claimed by someone else.
+			let (owner, _) = Proofs::<T>::get(&proof).ok_or(Error::<T>::NoSuchProof)?;
+			ensure!(sender != owner, Error::<T>::CannotReferenceOwnClaim);
+
+			// Take the fee and split it between the owner and the treasury.
+			let fee = T::ReferenceFee::get();
+			let paid = T::Currency::withdraw(
+				&sender,
+				fee,
+				WithdrawReasons::FEE,
+				ExistenceRequirement::KeepAlive,
+			)?;
+			let (to_owner, to_treasury) = paid.split(T::OwnerShare::get() * fee);
+			T::Currency::resolve_creating(&owner, to_owner);
+			T::Treasury::on_unbalanced(to_treasury);
+
+			ReferenceCount::<T>::mutate(&proof, |count| *count = count.saturating_add(1));
+
+			Self::deposit_event(Event::ClaimReferenced(sender, proof, fee));
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn register_content_type(
+			origin: OriginFor<T>,
+			type_id: ContentTypeId,
+			name: Vec<u8>,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			let bounded_name: BoundedVec<u8, T::MaxContentTypeNameLength> =
+				name.clone().try_into().map_err(|_| Error::<T>::ContentTypeNameTooLong)?;
+			ensure!(!ContentTypes::<T>::contains_key(type_id), Error::<T>::ContentTypeAlreadyRegistered);
+			ContentTypes::<T>::insert(type_id, bounded_name);
+
+			Self::deposit_event(Event::ContentTypeRegistered(type_id, name));
+			Ok(())
+		}
+
+		/// Pay for keeping a claim for as many more rent eras as `amount` covers; only whole
+		/// eras are charged. Anyone may pay, and a lapsed claim is re-activated once its
+		/// retention is paid past the current block.
+		#[pallet::weight(10_000)]
+		pub fn top_up_claim(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Verify that the claim pays rent and hasn't outlived its grace period.
+			ensure!(Proofs::<T>::contains_key(&proof), Error::<T>::NoSuchProof);
+			let paid_until = PaidUntil::<T>::get(&proof).ok_or(Error::<T>::RentFree)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(now < paid_until.saturating_add(T::GracePeriod::get()), Error::<T>::RetentionExpired);
+
+			// Charge the whole eras `amount` covers.
+			let era_fee = T::RetentionFee::get();
+			let eras: u32 = if era_fee.is_zero() { 0 } else { (amount / era_fee).saturated_into() };
+			ensure!(eras > 0, Error::<T>::TopUpTooSmall);
+			let fee = era_fee.saturating_mul(eras.into());
+			let paid = T::Currency::withdraw(
+				&sender,
+				fee,
+				WithdrawReasons::FEE,
+				ExistenceRequirement::KeepAlive,
+			)?;
+			T::Treasury::on_unbalanced(paid);
+
+			let paid_until = paid_until.saturating_add(T::RentEra::get().saturating_mul(eras.into()));
+			PaidUntil::<T>::insert(&proof, paid_until);
+
+			Self::deposit_event(Event::ClaimToppedUp(sender, proof, fee, paid_until));
+			Ok(())
+		}
+
+		/// Erase who a claim belonged to, along with its tags, type, references, retention and
+		/// kitty binding, keeping only the proof and the block and time recorded with it. The
+		/// owner or `ForceOrigin` may tombstone a claim, e.g. to honour an erasure request.
+		#[pallet::weight(10_000)]
+		pub fn tombstone_claim(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+		) -> DispatchResult {
+			let sender = match T::ForceOrigin::try_origin(origin) {
+				Ok(_) => None,
+				Err(origin) => Some(ensure_signed(origin)?),
+			};
+
+			// Verify that the proof has been claimed, and a signed caller is the claim owner.
+			let (owner, claimed_at) = Proofs::<T>::get(&proof).ok_or(Error::<T>::NoSuchProof)?;
+			if let Some(sender) = sender {
+				ensure!(sender == owner, Error::<T>::NotProofOwner);
+				if let Some((kitty_id, true)) = KittyLinks::<T>::get(&proof) {
+					ensure!(T::Kitties::owner(&kitty_id) == Some(sender), Error::<T>::NotKittyOwner);
+				}
+			}
+
+			let claim_time = ClaimTimes::<T>::get(&proof);
+			let counted_later = Self::tombstone_counted_later(&proof);
+			Self::remove_claim(&proof);
+			Tombstones::<T>::insert(&proof, claimed_at);
+			if let Some(claim_time) = claim_time {
+				ClaimTimes::<T>::insert(&proof, claim_time);
+			}
+			if counted_later {
+				// The migration will count the tombstone as a claim made and revoked.
+				Stats::<T>::mutate(|stats| stats.total = stats.total.saturating_sub(1));
+			} else {
+				Stats::<T>::mutate(|stats| stats.revoked = stats.revoked.saturating_add(1));
+			}
+
+			Self::deposit_event(Event::ClaimTombstoned(proof));
+			Ok(())
+		}
+
+		/// Remove a claim whose retention lapsed more than `GracePeriod` blocks ago. Anyone may
+		/// purge it.
+		#[pallet::weight(10_000)]
+		pub fn purge_claim(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			let paid_until = PaidUntil::<T>::get(&proof).ok_or(Error::<T>::NoSuchProof)?;
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(now >= paid_until.saturating_add(T::GracePeriod::get()), Error::<T>::ClaimStillRetained);
+
+			Self::remove_claim(&proof);
+			Stats::<T>::mutate(|stats| stats.revoked = stats.revoked.saturating_add(1));
+
+			Self::deposit_event(Event::ClaimPurged(proof));
+			Ok(())
+		}
+
+		/// Offer a claim under `license`, an SPDX-style identifier such as "CC-BY-4.0", for
+		/// `fee`, or for free. Replaces any license set before; existing licensees keep theirs.
+		#[pallet::weight(10_000)]
+		pub fn set_license(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+			license: Vec<u8>,
+			fee: Option<BalanceOf<T>>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Verify that the proof has been claimed, and caller is the claim owner.
+			let (owner, _) = Proofs::<T>::get(&proof).ok_or(Error::<T>::NoSuchProof)?;
+			ensure!(sender == owner, Error::<T>::NotProofOwner);
+			let bounded_license: BoundedVec<u8, T::MaxLicenseIdLength> =
+				license.clone().try_into().map_err(|_| Error::<T>::LicenseIdTooLong)?;
+
+			Licenses::<T>::insert(&proof, (bounded_license, fee));
+
+			Self::deposit_event(Event::LicenseSet(sender, proof, license, fee));
+			Ok(())
+		}
+
+		/// Stop offering a claim under a license. Existing licensees keep theirs.
+		#[pallet::weight(10_000)]
+		pub fn remove_license(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Verify that the proof has been claimed, and caller is the claim owner.
+			let (owner, _) = Proofs::<T>::get(&proof).ok_or(Error::<T>::NoSuchProof)?;
+			ensure!(sender == owner, Error::<T>::NotProofOwner);
+			ensure!(Licenses::<T>::take(&proof).is_some(), Error::<T>::NoLicense);
+
+			Self::deposit_event(Event::LicenseRemoved(sender, proof));
+			Ok(())
+		}
+
+		/// Acquire a license to a claim, paying its fee, if any, to the claim owner. The call
+		/// fails if the fee is more than `max_fee`, so the owner can't raise it while the call
+		/// is pending.
+		#[pallet::weight(10_000)]
+		pub fn acquire_license(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+			max_fee: BalanceOf<T>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Verify that the claim is someone else's, licensable at an agreed fee, not licensed
+			// to the caller, and has room for another licensee.
+			let (owner, _) = Proofs::<T>::get(&proof).ok_or(Error::<T>::NoSuchProof)?;
+			ensure!(sender != owner, Error::<T>::CannotLicenseOwnClaim);
+			let (_, fee) = Licenses::<T>::get(&proof).ok_or(Error::<T>::NoLicense)?;
+			let fee = fee.unwrap_or_else(Zero::zero);
+			ensure!(fee <= max_fee, Error::<T>::LicenseFeeTooHigh);
+			ensure!(!Licensees::<T>::contains_key(&proof, &sender), Error::<T>::AlreadyLicensed);
+			ensure!(LicenseeCount::<T>::get(&proof) < T::MaxLicensees::get(), Error::<T>::TooManyLicensees);
+
+			// Pay the owner and record the license.
+			if !fee.is_zero() {
+				T::Currency::transfer(&sender, &owner, fee, ExistenceRequirement::KeepAlive)?;
+			}
+			Licensees::<T>::insert(&proof, &sender, frame_system::Pallet::<T>::block_number());
+			LicenseeCount::<T>::mutate(&proof, |count| *count = count.saturating_add(1));
+
+			Self::deposit_event(Event::LicenseAcquired(sender, proof, fee));
+			Ok(())
+		}
+
+		/// Link a claim to its descriptor, noted in the preimage store under `preimage_hash`,
+		/// replacing any descriptor linked before. The descriptor is kept requested, so it stays
+		/// available until the link is replaced or the claim transferred or removed, and the
+		/// caller reserves `DescriptorDeposit` the first time until then.
+		#[pallet::weight(10_000)]
+		pub fn link_preimage(
+			origin: OriginFor<T>,
+			proof: Vec<u8>,
+			preimage_hash: T::Hash,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Verify that the proof has been claimed, caller is the claim owner, and the
+			// descriptor has been noted.
+			let (owner, _) = Proofs::<T>::get(&proof).ok_or(Error::<T>::NoSuchProof)?;
+			ensure!(sender == owner, Error::<T>::NotProofOwner);
+			ensure!(T::Preimages::have_preimage(&preimage_hash), Error::<T>::PreimageNotFound);
+
+			// Reserve the deposit the first time, keeping it when the descriptor is replaced.
+			if !DescriptorDeposits::<T>::contains_key(&proof) {
+				let deposit = T::DescriptorDeposit::get();
+				T::Currency::reserve(&sender, deposit)?;
+				DescriptorDeposits::<T>::insert(&proof, (&sender, deposit));
+			}
+			T::Preimages::request_preimage(&preimage_hash);
+			if let Some(previous) = DescriptorPreimages::<T>::mutate(&proof, |linked| linked.replace(preimage_hash)) {
+				T::Preimages::unrequest_preimage(&previous);
+			}
+
+			Self::deposit_event(Event::PreimageLinked(sender, proof, preimage_hash));
+			Ok(())
+		}
+
+		/// Record that `proof_a` and `proof_b`, both the caller's, anchor the same document, e.g.
+		/// hashed with different algorithms. Links are transitive: any claims already linked to
+		/// `proof_b` join `proof_a`'s group, or a new one if `proof_a` is in none.
+		#[pallet::weight(
+			T::DbWeight::get()
+				.reads_writes(7, 3 + T::MaxEquivalentClaims::get() as Weight)
+				.saturating_add(10_000)
+		)]
+		pub fn link_equivalent(
+			origin: OriginFor<T>,
+			proof_a: Vec<u8>,
+			proof_b: Vec<u8>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Verify that both proofs have been claimed by the caller.
+			ensure!(proof_a != proof_b, Error::<T>::CannotLinkToItself);
+			let (owner_a, _) = Proofs::<T>::get(&proof_a).ok_or(Error::<T>::NoSuchProof)?;
+			let (owner_b, _) = Proofs::<T>::get(&proof_b).ok_or(Error::<T>::NoSuchProof)?;
+			ensure!(sender == owner_a && sender == owner_b, Error::<T>::NotProofOwner);
+
+			// Gather `proof_b` and whatever it is linked to into `proof_a`'s group.
+			let current = EquivalenceGroups::<T>::get(&proof_a);
+			let previous = EquivalenceGroups::<T>::get(&proof_b);
+			ensure!(current.is_none() || previous != current, Error::<T>::AlreadyEquivalent);
+			let index = current.unwrap_or_else(NextEquivalenceGroup::<T>::get);
+			let joining = match previous {
+				Some(previous) => EquivalentClaims::<T>::get(previous).into_inner(),
+				None => sp_std::vec![proof_b.clone()],
+			};
+			let mut group = EquivalentClaims::<T>::get(index);
+			for proof in sp_std::iter::once(proof_a.clone()).chain(joining) {
+				if !group.contains(&proof) {
+					group.try_push(proof).map_err(|_| Error::<T>::TooManyEquivalentClaims)?;
+				}
+			}
+
+			if current.is_none() {
+				NextEquivalenceGroup::<T>::put(index.saturating_add(1));
+			}
+			if let Some(previous) = previous {
+				EquivalentClaims::<T>::remove(previous);
+			}
+			for proof in group.iter() {
+				EquivalenceGroups::<T>::insert(proof, index);
+			}
+			EquivalentClaims::<T>::insert(index, group);
+
+			Self::deposit_event(Event::EquivalentClaimsLinked(sender, proof_a, proof_b, index));
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Weight of storing a claim of `proof_len` bytes and touching `tag_count` tag entries
+		/// holding `tags_len` bytes in total.
+		pub fn claim_weight(proof_len: usize, tag_count: usize, tags_len: usize) -> Weight {
+			let bytes = proof_len.saturating_add(tags_len) as Weight;
+			T::DbWeight::get()
+				.reads_writes(1, 1 + tag_count as Weight)
+				.saturating_add(10_000)
+				.saturating_add(bytes.saturating_mul(WEIGHT_PER_BYTE))
+		}
+
+		/// Whether the retention of a claim has run out. Lapsed claims can be topped up until
+		/// their grace period ends, and purged after that.
+		pub fn is_lapsed(proof: &[u8]) -> bool {
+			PaidUntil::<T>::get(proof)
+				.map_or(false, |paid_until| paid_until <= frame_system::Pallet::<T>::block_number())
+		}
+
+		/// Record a new claim's retention, paid for its first rent era.
+		fn start_retention(proof: &[u8]) {
+			let now = frame_system::Pallet::<T>::block_number();
+			PaidUntil::<T>::insert(proof, now.saturating_add(T::RentEra::get()));
+		}
+
+		/// Check another claim, batch root or commitment may be anchored in this block.
+		fn ensure_block_capacity() -> DispatchResult {
+			ensure!(
+				Self::claims_this_block() < T::MaxClaimsPerBlock::get(),
+				Error::<T>::TooManyClaimsThisBlock
+			);
+			Ok(())
+		}
+
+		/// Carry on with the migration in `MigrationProgress` with up to `remaining_weight`, a
+		/// claim or tombstone at a time. Returns the weight used.
+		///
+		/// Claims made before `ClaimsByBlock` are filed under the block in `Proofs`, the closest
+		/// record of when they were made. Those made before `Stats` was kept are counted as
+		/// active as they are filed, and the tombstones then as revoked. Claims revoked outright
+		/// back then left nothing behind, so `total` and `revoked` don't include them.
+		fn migrate_some(remaining_weight: Weight) -> Weight {
+			let db = T::DbWeight::get();
+			// Reading the progress, and writing it and `Stats` back.
+			let mut used = db.reads_writes(2, 2);
+			// Reading a claim and whether it is filed, and filing it.
+			let per_claim = db.reads_writes(2, 2);
+			if used.saturating_add(per_claim) > remaining_weight {
+				return 0
+			}
+			let mut progress = match MigrationProgress::<T>::get() {
+				Some(progress) => Some(progress),
+				None => return db.reads(1),
+			};
+
+			let (mut active, mut revoked) = (0u64, 0u64);
+			while used.saturating_add(per_claim) <= remaining_weight {
+				let step = match progress.take() {
+					Some(step) => step,
+					None => break,
+				};
+				used = used.saturating_add(per_claim);
+				progress = match step {
+					ClaimsMigration::Claims { count, last } => {
+						let next = match &last {
+							Some(last) => Proofs::<T>::iter_from(Proofs::<T>::hashed_key_for(last)).next(),
+							None => Proofs::<T>::iter().next(),
+						};
+						match next {
+							Some((proof, (_, block))) => {
+								if !ClaimBlocks::<T>::contains_key(&proof) {
+									ClaimBlocks::<T>::insert(&proof, block);
+									ClaimsByBlock::<T>::insert(block, &proof, ());
+									if count {
+										active = active.saturating_add(1);
+									}
+								}
+								Some(ClaimsMigration::Claims { count, last: Some(proof) })
+							},
+							None if count => Some(ClaimsMigration::Tombstones { last: None }),
+							None => None,
+						}
+					},
+					ClaimsMigration::Tombstones { last } => {
+						let next = match &last {
+							Some(last) =>
+								Tombstones::<T>::iter_keys_from(Tombstones::<T>::hashed_key_for(last)).next(),
+							None => Tombstones::<T>::iter_keys().next(),
+						};
+						next.map(|proof| {
+							revoked = revoked.saturating_add(1);
+							ClaimsMigration::Tombstones { last: Some(proof) }
+						})
+					},
+				};
+			}
+
+			match progress {
+				Some(progress) => MigrationProgress::<T>::put(progress),
+				None => MigrationProgress::<T>::kill(),
+			}
+			if active > 0 || revoked > 0 {
+				Stats::<T>::mutate(|stats| {
+					stats.total = stats.total.saturating_add(active).saturating_add(revoked);
+					stats.active = stats.active.saturating_add(active);
+					stats.revoked = stats.revoked.saturating_add(revoked);
+				});
+			}
+			used
+		}
+
+		/// Whether the migration is still counting the claims made before `Stats` was kept.
+		/// Those it hasn't filed in `ClaimBlocks` yet aren't counted.
+		fn counting_claims() -> bool {
+			matches!(MigrationProgress::<T>::get(), Some(ClaimsMigration::Claims { count: true, .. }))
+		}
+
+		/// Whether the migration will count a tombstone for `proof` made now, having not read
+		/// that far yet.
+		fn tombstone_counted_later(proof: &[u8]) -> bool {
+			match MigrationProgress::<T>::get() {
+				Some(ClaimsMigration::Claims { count, .. }) => count,
+				Some(ClaimsMigration::Tombstones { last: Some(last) }) =>
+					Tombstones::<T>::hashed_key_for(proof) > Tombstones::<T>::hashed_key_for(&last),
+				Some(ClaimsMigration::Tombstones { last: None }) => true,
+				None => false,
+			}
+		}
+
+		/// Count and index a new claim, and record the time it was created at, announcing it if
+		/// `TimestampInEvent`.
+		fn note_new_claim(proof: Vec<u8>) {
+			ClaimsThisBlock::<T>::mutate(|n| *n = n.saturating_add(1));
+			let cur_block = frame_system::Pallet::<T>::block_number();
+			ClaimBlocks::<T>::insert(&proof, cur_block);
+			ClaimsByBlock::<T>::insert(cur_block, &proof, ());
+			Stats::<T>::mutate(|stats| {
+				stats.total = stats.total.saturating_add(1);
+				stats.active = stats.active.saturating_add(1);
+			});
+			let now = T::TimeProvider::now();
+			ClaimTimes::<T>::insert(&proof, now);
+			if T::TimestampInEvent::get() {
+				Self::deposit_event(Event::ClaimTimestamped(proof, now));
+			}
+		}
+
+		/// The bytes a countersigner signs to acknowledge `creator` claiming `proof`: the
+		/// proof's hash, bound to the creator so nobody else can submit the signature.
+		pub fn countersign_payload(creator: &T::AccountId, proof: &[u8]) -> Vec<u8> {
+			(b"poe/countersign", T::Hashing::hash(proof), creator).encode()
+		}
+
+		/// The second party to `proof`, if it was countersigned.
+		pub fn countersigner(proof: &[u8]) -> Option<T::AccountId> {
+			Countersigners::<T>::get(proof)
+		}
+
+		/// The hash of `proof`'s descriptor in the preimage store, if one is linked.
+		pub fn descriptor_preimage(proof: &[u8]) -> Option<T::Hash> {
+			DescriptorPreimages::<T>::get(proof)
+		}
+
+		/// The group of claims anchoring the same document `proof` is in, if it is linked to any.
+		pub fn equivalence_group(proof: &[u8]) -> Option<u64> {
+			EquivalenceGroups::<T>::get(proof)
+		}
+
+		/// The other claims linked as anchoring the same document as `proof`.
+		pub fn find_equivalent(proof: Vec<u8>) -> Vec<Vec<u8>> {
+			match EquivalenceGroups::<T>::get(&proof) {
+				Some(index) => EquivalentClaims::<T>::get(index)
+					.into_inner()
+					.into_iter()
+					.filter(|claim| *claim != proof)
+					.collect(),
+				None => Vec::new(),
+			}
+		}
+
+		/// Whether `who` holds a license to `proof`.
+		pub fn is_licensee(proof: &[u8], who: &T::AccountId) -> bool {
+			Licensees::<T>::contains_key(proof, who)
+		}
+
+		/// The time `proof` was claimed at, if it is claimed or was tombstoned.
+		pub fn claim_time(proof: &[u8]) -> Option<MomentOf<T>> {
+			ClaimTimes::<T>::get(proof)
+		}
+
+		/// Move the claims following `kitty_id` to `owner`, its new owner. Runtimes whose kitties
+		/// pallet reports transfers can call this directly instead of waiting for a sync.
+		pub fn kitty_transferred(kitty_id: &T::KittyId, owner: &T::AccountId) {
+			let cur_block = frame_system::Pallet::<T>::block_number();
+			for proof in ClaimsByKitty::<T>::get(kitty_id).into_inner() {
+				if !matches!(KittyLinks::<T>::get(&proof), Some((_, true))) {
+					continue
+				}
+				if let Some((from, _)) = Proofs::<T>::get(&proof) {
+					if &from != owner {
+						Proofs::<T>::insert(&proof, (owner, cur_block));
+						Self::unlink_equivalent(&proof);
+						Self::unlink_descriptor(&proof);
+						Self::deposit_event(Event::ClaimTransfered(from, owner.clone(), proof));
+					}
+				}
+			}
+		}
+
+		/// Remove a claim along with its time, tags, type, references, retention, hash algorithm,
+		/// kitty binding, descriptor link and deposit, and links to equivalent claims.
+		fn remove_claim(proof: &[u8]) {
+			let block = ClaimBlocks::<T>::take(proof);
+			if Proofs::<T>::take(proof).is_some() {
+				if block.is_none() && Self::counting_claims() {
+					// Made before `Stats` was kept and not counted by the migration yet, so it
+					// is counted now, as a claim made that is gone.
+					Stats::<T>::mutate(|stats| stats.total = stats.total.saturating_add(1));
+				} else {
+					Stats::<T>::mutate(|stats| stats.active = stats.active.saturating_sub(1));
+				}
+			}
+			ClaimTimes::<T>::remove(proof);
+			if let Some(block) = block {
+				ClaimsByBlock::<T>::remove(block, proof);
+			}
+			Self::clear_tags(proof);
+			ClaimTypes::<T>::remove(proof);
+			ReferenceCount::<T>::remove(proof);
+			PaidUntil::<T>::remove(proof);
+			ClaimAlgorithms::<T>::remove(proof);
+			Licenses::<T>::remove(proof);
+			Countersigners::<T>::remove(proof);
+			Self::unlink_descriptor(proof);
+			let _ = Licensees::<T>::remove_prefix(proof, Some(LicenseeCount::<T>::take(proof)));
+			Self::unlink_equivalent(proof);
+			if let Some((kitty_id, _)) = KittyLinks::<T>::take(proof) {
+				ClaimsByKitty::<T>::mutate(&kitty_id, |claims| claims.retain(|claim| claim != proof));
+			}
+		}
+
+		/// Stop keeping the descriptor linked to `proof`, if any, and return the deposit to
+		/// whoever linked it.
+		fn unlink_descriptor(proof: &[u8]) {
+			if let Some(preimage_hash) = DescriptorPreimages::<T>::take(proof) {
+				T::Preimages::unrequest_preimage(&preimage_hash);
+			}
+			if let Some((depositor, deposit)) = DescriptorDeposits::<T>::take(proof) {
+				T::Currency::unreserve(&depositor, deposit);
+			}
+		}
+
+		/// Take `proof` out of its group of equivalent claims, if any.
+		fn unlink_equivalent(proof: &[u8]) {
+			if let Some(index) = EquivalenceGroups::<T>::take(proof) {
+				let mut group = EquivalentClaims::<T>::take(index);
+				group.retain(|claim| claim != proof);
+				// A claim left on its own has nothing to be equivalent to.
+				if group.len() > 1 {
+					EquivalentClaims::<T>::insert(index, group);
+				} else {
+					for claim in group.iter() {
+						EquivalenceGroups::<T>::remove(claim);
+					}
+				}
+			}
+		}
+
+		/// The hash function named by the multihash header of `proof`.
+		fn hash_algorithm(proof: &[u8]) -> Result<HashAlgorithm, Error<T>> {
+			multihash::parse(proof).map(|(algorithm, _)| algorithm).map_err(|err| match err {
+				MultihashError::UnsupportedAlgorithm => Error::<T>::UnsupportedHashAlgorithm,
+				MultihashError::Malformed => Error::<T>::InvalidMultihash,
+			})
+		}
+
+		/// Total length of `tags`.
+		pub fn tags_len(tags: &[Vec<u8>]) -> usize {
+			tags.iter().map(|tag| tag.len()).sum()
+		}
+
+		/// The claims created in blocks `from_block` to `to_block`, both included, and owned by
+		/// `owner` now, if given, along with the block to carry on from, or `None` once
+		/// `to_block` is reached. At most `MAX_RANGE_BLOCKS` blocks are read per call.
+		pub fn claims_in_range(
+			from_block: T::BlockNumber,
+			to_block: T::BlockNumber,
+			owner: Option<T::AccountId>,
+		) -> (Vec<ClaimInfo<T::AccountId, T::BlockNumber>>, Option<T::BlockNumber>) {
+			let last_block = from_block.saturating_add((MAX_RANGE_BLOCKS - 1).into());
+			let (to_block, next) = if last_block < to_block {
+				(last_block, Some(last_block.saturating_add(One::one())))
+			} else {
+				(to_block, None)
+			};
+
+			let mut claims = Vec::new();
+			let mut block = from_block;
+			while block <= to_block {
+				for proof in ClaimsByBlock::<T>::iter_key_prefix(block) {
+					match Proofs::<T>::get(&proof) {
+						Some((claim_owner, _)) if owner.as_ref().map_or(true, |owner| *owner == claim_owner) =>
+							claims.push(ClaimInfo { proof, owner: claim_owner, created_at: block }),
+						_ => {},
+					}
+				}
+				if block == to_block {
+					break
+				}
+				block = block.saturating_add(One::one());
+			}
+			(claims, next)
+		}
+
+		/// All claims carrying `tag`.
+		pub fn claims_by_tag(tag: Vec<u8>) -> Vec<Vec<u8>> {
+			match Tag::<T>::try_from(tag) {
+				Ok(tag) => ClaimsByTag::<T>::iter_key_prefix(tag).collect(),
+				Err(_) => Vec::new(),
+			}
+		}
+
+		fn bound_tags(mut tags: Vec<Vec<u8>>) -> Result<BoundedVec<Tag<T>, T::MaxTags>, Error<T>> {
+			tags.sort();
+			tags.dedup();
+			let tags = tags
+				.into_iter()
+				.map(|tag| tag.try_into().map_err(|_| Error::<T>::TagTooLong))
+				.collect::<Result<Vec<Tag<T>>, _>>()?;
+			tags.try_into().map_err(|_| Error::<T>::TooManyTags)
+		}
+
+		fn put_tags(proof: &[u8], tags: BoundedVec<Tag<T>, T::MaxTags>) {
+			for tag in tags.iter() {
+				ClaimsByTag::<T>::insert(tag, proof, ());
+			}
+			Tags::<T>::insert(proof, tags);
+		}
+
+		fn clear_tags(proof: &[u8]) {
+			for tag in Tags::<T>::take(proof).into_inner() {
+				ClaimsByTag::<T>::remove(&tag, proof);
+			}
+		}
+
+		/// Whether `(value, blinding)` opens the anchored `commitment`. Unanchored commitments
+		/// open to nothing.
+		pub fn verify_opening(commitment: Vec<u8>, value: u128, blinding: [u8; 32]) -> bool {
+			Commitments::<T>::get(&commitment).map_or(false, |(_, _, scheme)| {
+				scheme.verify_opening(&commitment, value, blinding)
+			})
+		}
+
+		/// Check that `leaf` is part of the batch anchored under `root`.
+		///
+		/// Each proof step is a sibling hash and the side it sits on, from the leaf upwards.
+		/// The bottom node of a leaf is the hash of `(MERKLE_LEAF_PREFIX, leaf)`, and a parent
+		/// node is the hash of `(MERKLE_NODE_PREFIX, left, right)`, all SCALE-encoded.
+		pub fn verify_leaf(root: T::Hash, leaf: T::Hash, proof: Vec<(T::Hash, Side)>) -> bool {
+			let leaf_count = match BatchRoots::<T>::get(&root) {
+				Some((_, _, leaf_count)) => leaf_count,
+				None => return false,
+			};
+
+			// A tree of `leaf_count` leaves is never deeper than ceil(log2(leaf_count)).
+			let max_depth = 32 - (leaf_count - 1).leading_zeros();
+			if proof.len() as u32 > max_depth {
+				return false
+			}
+
+			let bottom = T::Hashing::hash_of(&(MERKLE_LEAF_PREFIX, leaf));
+			let computed = proof.into_iter().fold(bottom, |node, (sibling, side)| match side {
+				Side::Left => T::Hashing::hash_of(&(MERKLE_NODE_PREFIX, sibling, node)),
+				Side::Right => T::Hashing::hash_of(&(MERKLE_NODE_PREFIX, node, sibling)),
+			});
+			computed == root
+		}
 	}
 }
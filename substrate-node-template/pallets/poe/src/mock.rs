@@ -1,11 +1,13 @@
 use crate as pallet_poe;
 use frame_support::parameter_types;
-use frame_support::traits::ConstU32;
+use frame_support::traits::{tokens::nonfungible, ConstU128, ConstU32, ConstU64};
 use frame_system as system;
 use sp_core::H256;
+use std::{cell::RefCell, collections::BTreeMap};
 use sp_runtime::{
-	testing::Header,
-	traits::{BlakeTwo256, IdentityLookup},
+	testing::{Header, TestSignature, UintAuthorityId},
+	traits::{BlakeTwo256, Hash, IdentityLookup},
+	Permill,
 };
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -19,6 +21,8 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
 		PoeModule: pallet_poe::{Pallet, Call, Storage, Event<T>},
 	}
 );
@@ -26,6 +30,11 @@ frame_support::construct_runtime!(
 parameter_types! {
 	pub const BlockHashCount: u64 = 250;
 	pub const SS58Prefix: u8 = 42;
+	pub const ReferenceFee: u128 = 100;
+	pub const OwnerShare: Permill = Permill::from_percent(80);
+	pub const RetentionFee: u128 = 10;
+	pub const DescriptorDeposit: u128 = 50;
+	pub static TimestampInEvent: bool = false;
 }
 
 impl system::Config for Test {
@@ -46,7 +55,7 @@ impl system::Config for Test {
 	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u128>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -54,12 +63,114 @@ impl system::Config for Test {
 	type OnSetCode = ();
 }
 
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u128;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU128<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<5>;
+	type WeightInfo = ();
+}
+
+thread_local! {
+	static KITTY_OWNERS: RefCell<BTreeMap<u32, u64>> = RefCell::new(BTreeMap::new());
+	static PREIMAGES: RefCell<BTreeMap<H256, u32>> = RefCell::new(BTreeMap::new());
+}
+
+/// Stands in for the kitties pallet: kitties are owned as set with `set_kitty_owner`.
+pub struct Kitties;
+
+impl nonfungible::Inspect<u64> for Kitties {
+	type InstanceId = u32;
+
+	fn owner(kitty_id: &u32) -> Option<u64> {
+		KITTY_OWNERS.with(|owners| owners.borrow().get(kitty_id).copied())
+	}
+}
+
+pub fn set_kitty_owner(kitty_id: u32, owner: u64) {
+	KITTY_OWNERS.with(|owners| owners.borrow_mut().insert(kitty_id, owner));
+}
+
+/// Stands in for the preimage pallet: preimages are noted with `note_preimage`, and counted
+/// as they are requested.
+pub struct Preimages;
+
+impl pallet_poe::preimage::PreimageProvider<H256> for Preimages {
+	fn have_preimage(hash: &H256) -> bool {
+		PREIMAGES.with(|preimages| preimages.borrow().contains_key(hash))
+	}
+
+	fn request_preimage(hash: &H256) {
+		PREIMAGES.with(|preimages| *preimages.borrow_mut().entry(*hash).or_default() += 1);
+	}
+
+	fn unrequest_preimage(hash: &H256) {
+		PREIMAGES.with(|preimages| {
+			if let Some(requests) = preimages.borrow_mut().get_mut(hash) {
+				*requests = requests.saturating_sub(1);
+			}
+		});
+	}
+}
+
+pub fn note_preimage(preimage: &[u8]) -> H256 {
+	let hash = BlakeTwo256::hash(preimage);
+	PREIMAGES.with(|preimages| preimages.borrow_mut().entry(hash).or_default());
+	hash
+}
+
+/// How many times the preimage of `hash` is requested.
+pub fn preimage_requests(hash: &H256) -> u32 {
+	PREIMAGES.with(|preimages| preimages.borrow().get(hash).copied().unwrap_or(0))
+}
+
 impl pallet_poe::Config for Test {
 	type Event = Event;
 	type LengthLimit = ConstU32<6>;
+	type MaxTags = ConstU32<2>;
+	type MaxTagLength = ConstU32<8>;
+	type Currency = Balances;
+	type ReferenceFee = ReferenceFee;
+	type OwnerShare = OwnerShare;
+	type Treasury = ();
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type MaxContentTypeNameLength = ConstU32<8>;
+	type RetentionFee = RetentionFee;
+	type RentEra = ConstU64<10>;
+	type GracePeriod = ConstU64<5>;
+	type KittyId = u32;
+	type Kitties = Kitties;
+	type MaxClaimsPerKitty = ConstU32<2>;
+	type TimeProvider = Timestamp;
+	type TimestampInEvent = TimestampInEvent;
+	type MaxClaimsPerBlock = ConstU32<3>;
+	type MaxLicenseIdLength = ConstU32<16>;
+	type MaxLicensees = ConstU32<2>;
+	type CounterSignature = TestSignature;
+	type CounterSigner = UintAuthorityId;
+	type Preimages = Preimages;
+	type DescriptorDeposit = DescriptorDeposit;
+	type MaxEquivalentClaims = ConstU32<3>;
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 1_000), (2, 1_000), (3, 100)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+	KITTY_OWNERS.with(|owners| owners.borrow_mut().clear());
+	PREIMAGES.with(|preimages| preimages.borrow_mut().clear());
+	t.into()
 }
@@ -0,0 +1,122 @@
+//! Parsing of the self-describing multihash header claims are anchored with.
+//!
+//! A multihash is `<varint hash code><varint digest length><digest>`, see
+//! <https://github.com/multiformats/multihash>. The digest may be a truncated prefix of the
+//! full hash, but never longer than it.
+
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+
+/// The hash functions claims can be anchored with, by their multicodec code.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum HashAlgorithm {
+	Sha2_256,
+	Sha2_512,
+	Sha3_512,
+	Sha3_256,
+	Blake3,
+}
+
+impl HashAlgorithm {
+	pub fn from_code(code: u64) -> Option<Self> {
+		match code {
+			0x12 => Some(HashAlgorithm::Sha2_256),
+			0x13 => Some(HashAlgorithm::Sha2_512),
+			0x14 => Some(HashAlgorithm::Sha3_512),
+			0x16 => Some(HashAlgorithm::Sha3_256),
+			0x1e => Some(HashAlgorithm::Blake3),
+			_ => None,
+		}
+	}
+
+	pub fn code(&self) -> u64 {
+		match self {
+			HashAlgorithm::Sha2_256 => 0x12,
+			HashAlgorithm::Sha2_512 => 0x13,
+			HashAlgorithm::Sha3_512 => 0x14,
+			HashAlgorithm::Sha3_256 => 0x16,
+			HashAlgorithm::Blake3 => 0x1e,
+		}
+	}
+
+	/// Length in bytes of the full digest.
+	pub fn digest_len(&self) -> usize {
+		match self {
+			HashAlgorithm::Sha2_512 | HashAlgorithm::Sha3_512 => 64,
+			_ => 32,
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum MultihashError {
+	/// The header is cut short, or the digest doesn't have the length the header declares.
+	Malformed,
+	/// The hash code is not one of [`HashAlgorithm`].
+	UnsupportedAlgorithm,
+}
+
+/// Split `bytes` into the hash algorithm its header names and the digest.
+pub fn parse(bytes: &[u8]) -> Result<(HashAlgorithm, &[u8]), MultihashError> {
+	let (code, rest) = read_varint(bytes).ok_or(MultihashError::Malformed)?;
+	let (len, digest) = read_varint(rest).ok_or(MultihashError::Malformed)?;
+	let algorithm = HashAlgorithm::from_code(code).ok_or(MultihashError::UnsupportedAlgorithm)?;
+	if len == 0 || len > algorithm.digest_len() as u64 || len != digest.len() as u64 {
+		return Err(MultihashError::Malformed)
+	}
+	Ok((algorithm, digest))
+}
+
+/// Read an unsigned LEB128 varint, minimally encoded and at most 9 bytes long as multiformats
+/// requires. A redundant trailing zero byte would let the same header be written several ways.
+fn read_varint(bytes: &[u8]) -> Option<(u64, &[u8])> {
+	let mut value = 0u64;
+	for (i, &byte) in bytes.iter().enumerate().take(9) {
+		value |= u64::from(byte & 0x7f) << (7 * i);
+		if byte & 0x80 == 0 {
+			if byte == 0 && i > 0 {
+				return None
+			}
+			return Some((value, &bytes[i + 1..]))
+		}
+	}
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_works() {
+		let mut sha2 = vec![0x12, 32];
+		sha2.extend_from_slice(&[7; 32]);
+		assert_eq!(parse(&sha2), Ok((HashAlgorithm::Sha2_256, &[7; 32][..])));
+
+		// truncated digests are allowed
+		assert_eq!(parse(&[0x1e, 2, 1, 2]), Ok((HashAlgorithm::Blake3, &[1, 2][..])));
+		assert_eq!(parse(&[0x14, 1, 9]), Ok((HashAlgorithm::Sha3_512, &[9][..])));
+	}
+
+	#[test]
+	fn parse_failed() {
+		assert_eq!(parse(&[]), Err(MultihashError::Malformed));
+		assert_eq!(parse(&[0x12]), Err(MultihashError::Malformed));
+		assert_eq!(parse(&[0x92]), Err(MultihashError::Malformed));
+		// varints must be minimal, so no redundant continuation bytes
+		assert_eq!(parse(&[0x96, 0x00, 1, 9]), Err(MultihashError::Malformed));
+		assert_eq!(parse(&[0x12, 0x81, 0x00, 9]), Err(MultihashError::Malformed));
+		assert_eq!(parse(&[0x92, 0x80, 0x00, 1, 9]), Err(MultihashError::Malformed));
+		// identity (0x00) and blake2b-256 (0xb220) are not supported
+		assert_eq!(parse(&[0x00, 1, 9]), Err(MultihashError::UnsupportedAlgorithm));
+		assert_eq!(parse(&[0xa0, 0xe4, 0x02, 1, 9]), Err(MultihashError::UnsupportedAlgorithm));
+		// the digest must have the declared length, and not be empty or longer than the hash
+		assert_eq!(parse(&[0x12, 2, 9]), Err(MultihashError::Malformed));
+		assert_eq!(parse(&[0x12, 1, 9, 9]), Err(MultihashError::Malformed));
+		assert_eq!(parse(&[0x12, 0]), Err(MultihashError::Malformed));
+		let mut long = vec![0x12, 33];
+		long.extend_from_slice(&[7; 33]);
+		assert_eq!(parse(&long), Err(MultihashError::Malformed));
+	}
+}
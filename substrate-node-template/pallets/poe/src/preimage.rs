@@ -0,0 +1,29 @@
+//! The preimage store large claim descriptors are noted in, e.g. `pallet-preimage`.
+//!
+//! Only a descriptor's hash is kept with a claim. The descriptor itself is noted in the store
+//! by whoever wants it available, paying the store's own deposit, and the claim keeps it
+//! requested for as long as it is linked so it can't be unnoted from under the claim.
+
+/// A store of preimages by their hash. The part of `pallet-preimage`'s `PreimageProvider`
+/// this pallet needs, with the same names, so a runtime with the preimage pallet can forward
+/// to it.
+pub trait PreimageProvider<Hash> {
+	/// Whether the preimage of `hash` is stored.
+	fn have_preimage(hash: &Hash) -> bool;
+	/// Keep the preimage of `hash` stored, and its noter's deposit refunded, until it is
+	/// unrequested.
+	fn request_preimage(hash: &Hash);
+	/// Undo a `request_preimage`.
+	fn unrequest_preimage(hash: &Hash);
+}
+
+/// No preimage store: nothing is ever noted, so no descriptor can be linked.
+impl<Hash> PreimageProvider<Hash> for () {
+	fn have_preimage(_hash: &Hash) -> bool {
+		false
+	}
+
+	fn request_preimage(_hash: &Hash) {}
+
+	fn unrequest_preimage(_hash: &Hash) {}
+}
@@ -1,16 +1,41 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{commitment::{self, CommitmentScheme}, mock::*, multihash::HashAlgorithm, Error};
+use frame_support::{assert_noop, assert_ok, traits::{Hooks, StorageVersion}};
+use sp_core::H256;
+use sp_runtime::{testing::TestSignature, traits::{BlakeTwo256, Hash}};
 use super::*;
 
+/// `digest` as a (truncated) SHA2-256 multihash, so it fits the mock's `LengthLimit`.
+fn multihash(digest: &[u8]) -> Vec<u8> {
+    let mut proof = vec![0x12, digest.len() as u8];
+    proof.extend_from_slice(digest);
+    proof
+}
+
 #[test]
 fn create_claim_works() {
     new_test_ext().execute_with(|| {
-        let proof = vec![1, 2];
+        let proof = multihash(&[1, 2]);
         assert_ok!(PoeModule::create_claim(Origin::signed(1),proof.clone()));
         assert_eq!(
             Proofs::<Test>::get(&proof),
             Some((1, <frame_system::Pallet<Test>>::block_number())),
         );
+        assert_eq!(ClaimAlgorithms::<Test>::get(&proof), Some(HashAlgorithm::Sha2_256));
+    });
+}
+
+#[test]
+fn create_claim_failed_when_not_multihash() {
+    new_test_ext().execute_with(|| {
+        // identity hashes would let anyone anchor arbitrary plain data
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), vec![0x00, 2, 1, 2]),
+            Error::<Test>::UnsupportedHashAlgorithm,
+        );
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(1), vec![0x12, 3, 1, 2]),
+            Error::<Test>::InvalidMultihash,
+        );
     });
 }
 
@@ -28,7 +53,7 @@ fn create_claim_failed_when_proof_too_long() {
 #[test]
 fn create_claim_failed_when_claim_already_exist() {
     new_test_ext().execute_with(|| {
-        let proof = vec![1, 2];
+        let proof = multihash(&[1, 2]);
         let _ = PoeModule::create_claim(Origin::signed(1), proof.clone());
         assert_noop!(
             PoeModule::create_claim(Origin::signed(1), proof.clone()),
@@ -40,17 +65,18 @@ fn create_claim_failed_when_claim_already_exist() {
 #[test]
 fn revoke_claim_works() {
     new_test_ext().execute_with(|| {
-        let proof = vec![1,2];
+        let proof = multihash(&[1, 2]);
         let _ = PoeModule::create_claim(Origin::signed(1), proof.clone());
         assert_ok!(PoeModule::revoke_claim(Origin::signed(1), proof.clone()));
         assert_eq!(Proofs::<Test>::get(&proof), None);
+        assert_eq!(ClaimAlgorithms::<Test>::get(&proof), None);
     });
 }
 
 #[test]
 fn revoke_claim_failed_when_no_such_proof() {
     new_test_ext().execute_with(|| {
-        let proof = vec![1, 2];
+        let proof = multihash(&[1, 2]);
         assert_noop!(
             PoeModule::revoke_claim(Origin::signed(1), proof),
             Error::<Test>::NoSuchProof,
@@ -61,7 +87,7 @@ fn revoke_claim_failed_when_no_such_proof() {
 #[test]
 fn revoke_claim_failed_when_not_proof_owner() {
     new_test_ext().execute_with(|| {
-        let proof = vec![1, 2];
+        let proof = multihash(&[1, 2]);
         let _ = PoeModule::create_claim(Origin::signed(1), proof.clone());
         assert_noop!(
             PoeModule::revoke_claim(Origin::signed(2), proof),
@@ -73,7 +99,7 @@ fn revoke_claim_failed_when_not_proof_owner() {
 #[test]
 fn transfer_claim_works() {
     new_test_ext().execute_with(|| {
-        let proof = vec![1, 2];
+        let proof = multihash(&[1, 2]);
         let _ = PoeModule::create_claim(Origin::signed(1), proof.clone());
         assert_ok!(PoeModule::transfer_claim(Origin::signed(1), proof.clone(), 2));
         assert_eq!(
@@ -86,7 +112,7 @@ fn transfer_claim_works() {
 #[test]
 fn transfer_claim_failed_when_no_such_proof() {
     new_test_ext().execute_with(|| {
-        let proof = vec![1, 2];
+        let proof = multihash(&[1, 2]);
         assert_noop!(
             PoeModule::transfer_claim(Origin::signed(1), proof, 2),
             Error::<Test>::NoSuchProof,
@@ -97,11 +123,1003 @@ fn transfer_claim_failed_when_no_such_proof() {
 #[test]
 fn transfer_claim_failed_when_not_proof_owner() {
     new_test_ext().execute_with(|| {
-        let proof = vec![1, 2];
+        let proof = multihash(&[1, 2]);
         let _ = PoeModule::create_claim(Origin::signed(1), proof.clone());
         assert_noop!(
             PoeModule::transfer_claim(Origin::signed(2), proof, 3), 
             Error::<Test>::NotProofOwner,
         );
     });
-}
\ No newline at end of file
+}
+
+#[test]
+fn create_batch_root_works() {
+    new_test_ext().execute_with(|| {
+        let root = H256::repeat_byte(1);
+        assert_ok!(PoeModule::create_batch_root(Origin::signed(1), root, 4));
+        assert_eq!(
+            BatchRoots::<Test>::get(&root),
+            Some((1, <frame_system::Pallet<Test>>::block_number(), 4)),
+        );
+    });
+}
+
+#[test]
+fn create_batch_root_failed() {
+    new_test_ext().execute_with(|| {
+        let root = H256::repeat_byte(1);
+        assert_noop!(
+            PoeModule::create_batch_root(Origin::signed(1), root, 0),
+            Error::<Test>::EmptyBatch,
+        );
+        let _ = PoeModule::create_batch_root(Origin::signed(1), root, 4);
+        assert_noop!(
+            PoeModule::create_batch_root(Origin::signed(2), root, 4),
+            Error::<Test>::BatchRootAlreadyAnchored,
+        );
+    });
+}
+
+#[test]
+fn verify_leaf_works() {
+    new_test_ext().execute_with(|| {
+        // root = N(N(L(a), L(b)), N(L(c), L(d)))
+        let leaves: Vec<H256> = (1..=4u8).map(H256::repeat_byte).collect();
+        let l = |leaf: H256| BlakeTwo256::hash_of(&(MERKLE_LEAF_PREFIX, leaf));
+        let n = |left: H256, right: H256| BlakeTwo256::hash_of(&(MERKLE_NODE_PREFIX, left, right));
+        let (a, b, c, d) = (l(leaves[0]), l(leaves[1]), l(leaves[2]), l(leaves[3]));
+        let ab = n(a, b);
+        let cd = n(c, d);
+        let root = n(ab, cd);
+
+        // Nothing verifies before the root is anchored.
+        let proof_c = vec![(d, Side::Right), (ab, Side::Left)];
+        assert!(!PoeModule::verify_leaf(root, leaves[2], proof_c.clone()));
+
+        let _ = PoeModule::create_batch_root(Origin::signed(1), root, 4);
+        assert!(PoeModule::verify_leaf(root, leaves[2], proof_c.clone()));
+        assert!(PoeModule::verify_leaf(root, leaves[1], vec![(a, Side::Left), (cd, Side::Right)]));
+
+        // Wrong leaf, wrong side, or an over-long proof are all rejected.
+        assert!(!PoeModule::verify_leaf(root, leaves[3], proof_c.clone()));
+        assert!(!PoeModule::verify_leaf(root, leaves[2], vec![(d, Side::Left), (ab, Side::Left)]));
+        let mut too_long = proof_c;
+        too_long.push((root, Side::Right));
+        assert!(!PoeModule::verify_leaf(root, leaves[2], too_long));
+    });
+}
+
+#[test]
+fn verify_leaf_failed() {
+    new_test_ext().execute_with(|| {
+        let leaves: Vec<H256> = (1..=4u8).map(H256::repeat_byte).collect();
+        let l = |leaf: H256| BlakeTwo256::hash_of(&(MERKLE_LEAF_PREFIX, leaf));
+        let n = |left: H256, right: H256| BlakeTwo256::hash_of(&(MERKLE_NODE_PREFIX, left, right));
+        let ab = n(l(leaves[0]), l(leaves[1]));
+        let cd = n(l(leaves[2]), l(leaves[3]));
+        let root = n(ab, cd);
+        let _ = PoeModule::create_batch_root(Origin::signed(1), root, 4);
+
+        // An inner node doesn't pass for a leaf, even one level up where its proof would fit.
+        assert!(!PoeModule::verify_leaf(root, ab, vec![(cd, Side::Right)]));
+        assert!(!PoeModule::verify_leaf(root, cd, vec![(ab, Side::Left)]));
+
+        // Nor does a tree hashed without the prefixes.
+        let plain = BlakeTwo256::hash_of(&(
+            BlakeTwo256::hash_of(&(leaves[0], leaves[1])),
+            BlakeTwo256::hash_of(&(leaves[2], leaves[3])),
+        ));
+        let _ = PoeModule::create_batch_root(Origin::signed(1), plain, 4);
+        let plain_cd = BlakeTwo256::hash_of(&(leaves[2], leaves[3]));
+        assert!(!PoeModule::verify_leaf(plain, leaves[0], vec![(leaves[1], Side::Right), (plain_cd, Side::Right)]));
+    });
+}
+
+#[test]
+fn create_claim_with_tags_works() {
+    new_test_ext().execute_with(|| {
+        let proof = multihash(&[1, 2]);
+        let tags = vec![b"invoice".to_vec(), b"2021".to_vec()];
+        assert_ok!(PoeModule::create_claim_with_tags(Origin::signed(1), proof.clone(), tags));
+        assert_eq!(PoeModule::claims_by_tag(b"invoice".to_vec()), vec![proof.clone()]);
+        assert_eq!(PoeModule::claims_by_tag(b"2021".to_vec()), vec![proof.clone()]);
+
+        // Revoking the claim drops it from the tag index.
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), proof.clone()));
+        assert!(PoeModule::claims_by_tag(b"invoice".to_vec()).is_empty());
+        assert!(!Tags::<Test>::contains_key(&proof));
+    });
+}
+
+#[test]
+fn create_claim_with_tags_failed() {
+    new_test_ext().execute_with(|| {
+        let proof = multihash(&[1, 2]);
+        assert_noop!(
+            PoeModule::create_claim_with_tags(
+                Origin::signed(1), proof.clone(), vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()],
+            ),
+            Error::<Test>::TooManyTags,
+        );
+        assert_noop!(
+            PoeModule::create_claim_with_tags(Origin::signed(1), proof, vec![b"too-long-tag".to_vec()]),
+            Error::<Test>::TagTooLong,
+        );
+    });
+}
+
+#[test]
+fn set_tags_works() {
+    new_test_ext().execute_with(|| {
+        let proof = multihash(&[1, 2]);
+        let _ = PoeModule::create_claim_with_tags(Origin::signed(1), proof.clone(), vec![b"draft".to_vec()]);
+        assert_ok!(PoeModule::set_tags(Origin::signed(1), proof.clone(), vec![b"contract".to_vec()]));
+        assert!(PoeModule::claims_by_tag(b"draft".to_vec()).is_empty());
+        assert_eq!(PoeModule::claims_by_tag(b"contract".to_vec()), vec![proof]);
+    });
+}
+
+#[test]
+fn set_tags_failed() {
+    new_test_ext().execute_with(|| {
+        let proof = multihash(&[1, 2]);
+        assert_noop!(
+            PoeModule::set_tags(Origin::signed(1), proof.clone(), vec![b"contract".to_vec()]),
+            Error::<Test>::NoSuchProof,
+        );
+        let _ = PoeModule::create_claim(Origin::signed(1), proof.clone());
+        assert_noop!(
+            PoeModule::set_tags(Origin::signed(2), proof, vec![b"contract".to_vec()]),
+            Error::<Test>::NotProofOwner,
+        );
+    });
+}
+
+#[test]
+fn reference_claim_works() {
+    new_test_ext().execute_with(|| {
+        let proof = multihash(&[1, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+        assert_ok!(PoeModule::reference_claim(Origin::signed(2), proof.clone()));
+        assert_ok!(PoeModule::reference_claim(Origin::signed(2), proof.clone()));
+
+        // 80% of each fee goes to the owner, the rest is burned
+        assert_eq!(ReferenceCount::<Test>::get(&proof), 2);
+        assert_eq!(Balances::free_balance(1), 1_160);
+        assert_eq!(Balances::free_balance(2), 800);
+        assert_eq!(Balances::total_issuance(), 2_060);
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), proof.clone()));
+        assert_eq!(ReferenceCount::<Test>::get(&proof), 0);
+    });
+}
+
+#[test]
+fn reference_claim_failed() {
+    new_test_ext().execute_with(|| {
+        let proof = multihash(&[1, 2]);
+        assert_noop!(
+            PoeModule::reference_claim(Origin::signed(2), proof.clone()),
+            Error::<Test>::NoSuchProof,
+        );
+
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+        assert_noop!(
+            PoeModule::reference_claim(Origin::signed(1), proof.clone()),
+            Error::<Test>::CannotReferenceOwnClaim,
+        );
+        // account 3 can't pay the fee and stay alive
+        assert_noop!(
+            PoeModule::reference_claim(Origin::signed(3), proof.clone()),
+            pallet_balances::Error::<Test>::KeepAlive,
+        );
+    });
+}
+
+#[test]
+fn claim_weight_scales_with_size() {
+    use frame_support::weights::{DispatchClass, GetDispatchInfo, Pays};
+
+    let short = crate::Call::<Test>::create_claim { proof: vec![1] }.get_dispatch_info();
+    let long = crate::Call::<Test>::create_claim { proof: vec![1, 2, 3] }.get_dispatch_info();
+    assert_eq!(long.weight - short.weight, 2 * WEIGHT_PER_BYTE);
+
+    let tagged = crate::Call::<Test>::create_claim_with_tags { proof: vec![1], tags: vec![vec![1, 2]] }
+        .get_dispatch_info();
+    assert!(tagged.weight > short.weight + 2 * WEIGHT_PER_BYTE);
+
+    let batch = crate::Call::<Test>::create_batch_root { root_hash: H256::zero(), leaf_count: 1_000 }
+        .get_dispatch_info();
+    assert_eq!((batch.class, batch.pays_fee), (DispatchClass::Normal, Pays::Yes));
+}
+
+#[test]
+fn create_claim_with_meta_works() {
+    new_test_ext().execute_with(|| {
+        let proof = multihash(&[1, 2]);
+        assert_ok!(PoeModule::register_content_type(Origin::root(), 1, b"pdf".to_vec()));
+        assert_ok!(PoeModule::create_claim_with_meta(Origin::signed(1), proof.clone(), 1));
+        assert_eq!(ClaimTypes::<Test>::get(&proof), Some(1));
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), proof.clone()));
+        assert_eq!(ClaimTypes::<Test>::get(&proof), None);
+    });
+}
+
+#[test]
+fn create_claim_with_meta_failed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            PoeModule::create_claim_with_meta(Origin::signed(1), multihash(&[1, 2]), 1),
+            Error::<Test>::UnknownContentType,
+        );
+        assert_noop!(
+            PoeModule::register_content_type(Origin::signed(1), 1, b"pdf".to_vec()),
+            sp_runtime::DispatchError::BadOrigin,
+        );
+        assert_noop!(
+            PoeModule::register_content_type(Origin::root(), 1, b"a-long-name".to_vec()),
+            Error::<Test>::ContentTypeNameTooLong,
+        );
+        assert_ok!(PoeModule::register_content_type(Origin::root(), 1, b"pdf".to_vec()));
+        assert_noop!(
+            PoeModule::register_content_type(Origin::root(), 1, b"image".to_vec()),
+            Error::<Test>::ContentTypeAlreadyRegistered,
+        );
+    });
+}
+
+#[test]
+fn claim_retention_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let proof = multihash(&[1, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+        assert_eq!(PaidUntil::<Test>::get(&proof), Some(11));
+
+        // only whole eras are charged, and anyone can pay
+        assert_ok!(PoeModule::top_up_claim(Origin::signed(2), proof.clone(), 25));
+        assert_eq!(PaidUntil::<Test>::get(&proof), Some(31));
+        assert_eq!(Balances::free_balance(2), 980);
+
+        // a lapsed claim can be re-activated during its grace period
+        System::set_block_number(35);
+        assert!(PoeModule::is_lapsed(&proof));
+        assert_ok!(PoeModule::top_up_claim(Origin::signed(1), proof.clone(), 10));
+        assert!(!PoeModule::is_lapsed(&proof));
+
+        System::set_block_number(46);
+        assert_ok!(PoeModule::purge_claim(Origin::signed(3), proof.clone()));
+        assert_eq!(Proofs::<Test>::get(&proof), None);
+        assert_eq!(PaidUntil::<Test>::get(&proof), None);
+        assert_eq!(PoeModule::claim_stats(), ClaimStats { total: 1, active: 0, revoked: 1 });
+    });
+}
+
+#[test]
+fn claim_retention_failed() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let proof = multihash(&[1, 2]);
+        assert_noop!(PoeModule::top_up_claim(Origin::signed(1), proof.clone(), 10), Error::<Test>::NoSuchProof);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+        assert_noop!(PoeModule::top_up_claim(Origin::signed(1), proof.clone(), 9), Error::<Test>::TopUpTooSmall);
+        assert_noop!(PoeModule::purge_claim(Origin::signed(2), proof.clone()), Error::<Test>::ClaimStillRetained);
+
+        System::set_block_number(16);
+        assert_noop!(PoeModule::top_up_claim(Origin::signed(1), proof.clone(), 10), Error::<Test>::RetentionExpired);
+
+        // claims from before retention rent are kept for free
+        Proofs::<Test>::insert(vec![3], (1, 1));
+        assert_noop!(PoeModule::top_up_claim(Origin::signed(1), vec![3], 10), Error::<Test>::RentFree);
+        assert_noop!(PoeModule::purge_claim(Origin::signed(2), vec![3]), Error::<Test>::NoSuchProof);
+    });
+}
+
+#[test]
+fn create_claim_for_kitty_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        set_kitty_owner(7, 1);
+        let bound = multihash(&[1, 2]);
+        let following = multihash(&[3, 4]);
+        assert_ok!(PoeModule::create_claim_for_kitty(Origin::signed(1), 7, bound.clone(), false));
+        assert_ok!(PoeModule::create_claim_for_kitty(Origin::signed(1), 7, following.clone(), true));
+        assert_eq!(KittyLinks::<Test>::get(&following), Some((7, true)));
+        assert_eq!(ClaimsByKitty::<Test>::get(7).into_inner(), vec![bound.clone(), following.clone()]);
+
+        // once the kitty is sold only the following claim goes with it
+        set_kitty_owner(7, 2);
+        System::set_block_number(2);
+        assert_ok!(PoeModule::sync_kitty_claims(Origin::signed(3), 7));
+        assert_eq!(Proofs::<Test>::get(&bound), Some((1, 1)));
+        assert_eq!(Proofs::<Test>::get(&following), Some((2, 2)));
+
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(2), following.clone()));
+        assert_eq!(KittyLinks::<Test>::get(&following), None);
+        assert_eq!(ClaimsByKitty::<Test>::get(7).into_inner(), vec![bound]);
+    });
+}
+
+#[test]
+fn create_claim_for_kitty_failed() {
+    new_test_ext().execute_with(|| {
+        set_kitty_owner(7, 1);
+        let proof = multihash(&[1, 2]);
+        assert_noop!(
+            PoeModule::create_claim_for_kitty(Origin::signed(2), 7, proof.clone(), true),
+            Error::<Test>::NotKittyOwner,
+        );
+        assert_noop!(
+            PoeModule::create_claim_for_kitty(Origin::signed(1), 8, proof.clone(), true),
+            Error::<Test>::NotKittyOwner,
+        );
+        assert_noop!(PoeModule::sync_kitty_claims(Origin::signed(1), 8), Error::<Test>::NotKittyOwner);
+
+        assert_ok!(PoeModule::create_claim_for_kitty(Origin::signed(1), 7, proof.clone(), true));
+        assert_ok!(PoeModule::create_claim_for_kitty(Origin::signed(1), 7, multihash(&[3]), false));
+        assert_noop!(
+            PoeModule::create_claim_for_kitty(Origin::signed(1), 7, multihash(&[4]), false),
+            Error::<Test>::TooManyKittyClaims,
+        );
+
+        // a following claim moves only with its kitty, and its old owner can't revoke it
+        assert_noop!(PoeModule::transfer_claim(Origin::signed(1), proof.clone(), 2), Error::<Test>::ClaimFollowsKitty);
+        set_kitty_owner(7, 2);
+        assert_noop!(PoeModule::revoke_claim(Origin::signed(1), proof), Error::<Test>::NotKittyOwner);
+    });
+}
+
+#[test]
+fn create_commitment_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let sealed_bid = commitment::commit(500, [9; 32]).to_vec();
+        assert_ok!(PoeModule::create_commitment(
+            Origin::signed(1),
+            CommitmentScheme::Ristretto255Pedersen,
+            sealed_bid.clone(),
+        ));
+        assert_eq!(
+            Commitments::<Test>::get(&sealed_bid),
+            Some((1, 1, CommitmentScheme::Ristretto255Pedersen)),
+        );
+
+        // the bid stays hidden until its opening is revealed
+        assert!(PoeModule::verify_opening(sealed_bid.clone(), 500, [9; 32]));
+        assert!(!PoeModule::verify_opening(sealed_bid, 501, [9; 32]));
+    });
+}
+
+#[test]
+fn create_commitment_failed() {
+    new_test_ext().execute_with(|| {
+        let scheme = CommitmentScheme::Ristretto255Pedersen;
+        let sealed_bid = commitment::commit(500, [9; 32]).to_vec();
+        assert_noop!(
+            PoeModule::create_commitment(Origin::signed(1), scheme, sealed_bid[..31].to_vec()),
+            Error::<Test>::InvalidCommitment,
+        );
+        assert_noop!(
+            PoeModule::create_commitment(Origin::signed(1), scheme, vec![0xff; 32]),
+            Error::<Test>::InvalidCommitment,
+        );
+        // unanchored commitments open to nothing
+        assert!(!PoeModule::verify_opening(sealed_bid.clone(), 500, [9; 32]));
+
+        assert_ok!(PoeModule::create_commitment(Origin::signed(1), scheme, sealed_bid.clone()));
+        assert_noop!(
+            PoeModule::create_commitment(Origin::signed(2), scheme, sealed_bid),
+            Error::<Test>::CommitmentAlreadyAnchored,
+        );
+    });
+}
+
+#[test]
+fn tombstone_claim_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        Timestamp::set_timestamp(42_000);
+        let proof = multihash(&[1, 2]);
+        assert_ok!(PoeModule::create_claim_with_tags(Origin::signed(1), proof.clone(), vec![b"cv".to_vec()]));
+        System::set_block_number(2);
+        assert_ok!(PoeModule::tombstone_claim(Origin::signed(1), proof.clone()));
+        System::assert_last_event(mock::Event::PoeModule(crate::Event::ClaimTombstoned(proof.clone())));
+
+        // only the proof and its timestamp survive
+        assert_eq!(Tombstones::<Test>::get(&proof), Some(1));
+        assert_eq!(PoeModule::claim_time(&proof), Some(42_000));
+        assert_eq!(Proofs::<Test>::get(&proof), None);
+        assert_eq!(PaidUntil::<Test>::get(&proof), None);
+        assert!(PoeModule::claims_by_tag(b"cv".to_vec()).is_empty());
+
+        // governance may tombstone anyone's claim
+        let other = multihash(&[3, 4]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), other.clone()));
+        assert_ok!(PoeModule::tombstone_claim(Origin::root(), other.clone()));
+        assert_eq!(Tombstones::<Test>::get(&other), Some(2));
+    });
+}
+
+#[test]
+fn tombstone_claim_failed() {
+    new_test_ext().execute_with(|| {
+        let proof = multihash(&[1, 2]);
+        assert_noop!(PoeModule::tombstone_claim(Origin::signed(1), proof.clone()), Error::<Test>::NoSuchProof);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+        assert_noop!(PoeModule::tombstone_claim(Origin::signed(2), proof.clone()), Error::<Test>::NotProofOwner);
+
+        // a tombstoned proof can never be claimed again, by anyone
+        assert_ok!(PoeModule::tombstone_claim(Origin::signed(1), proof.clone()));
+        assert_noop!(PoeModule::create_claim(Origin::signed(1), proof.clone()), Error::<Test>::ClaimTombstoned);
+        assert_noop!(
+            PoeModule::create_claim_with_tags(Origin::signed(2), proof.clone(), vec![]),
+            Error::<Test>::ClaimTombstoned,
+        );
+        assert_noop!(PoeModule::tombstone_claim(Origin::signed(1), proof), Error::<Test>::NoSuchProof);
+    });
+}
+
+#[test]
+fn claim_time_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        Timestamp::set_timestamp(42_000);
+        let proof = multihash(&[1, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+        assert_eq!(PoeModule::claim_time(&proof), Some(42_000));
+        System::assert_last_event(mock::Event::PoeModule(crate::Event::ClaimCreated(1, proof)));
+
+        // with `TimestampInEvent` the time is announced right after the claim
+        TimestampInEvent::set(&true);
+        Timestamp::set_timestamp(48_000);
+        let tagged = multihash(&[3, 4]);
+        assert_ok!(PoeModule::create_claim_with_tags(Origin::signed(1), tagged.clone(), vec![b"cv".to_vec()]));
+        assert_eq!(PoeModule::claim_time(&tagged), Some(48_000));
+        System::assert_has_event(mock::Event::PoeModule(crate::Event::ClaimTimestamped(tagged, 48_000)));
+    });
+}
+
+#[test]
+fn claim_time_failed() {
+    new_test_ext().execute_with(|| {
+        Timestamp::set_timestamp(42_000);
+        let proof = multihash(&[1, 2]);
+        assert_eq!(PoeModule::claim_time(&proof), None);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+
+        // a claim can't be re-timestamped by claiming it again, and its time goes with it
+        Timestamp::set_timestamp(48_000);
+        assert_noop!(PoeModule::create_claim(Origin::signed(2), proof.clone()), Error::<Test>::ProofAlreadyClaimed);
+        assert_eq!(PoeModule::claim_time(&proof), Some(42_000));
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), proof.clone()));
+        assert_eq!(PoeModule::claim_time(&proof), None);
+    });
+}
+
+#[test]
+fn claim_stats_works() {
+    new_test_ext().execute_with(|| {
+        for digest in 1..=3 {
+            assert_ok!(PoeModule::create_claim(Origin::signed(1), multihash(&[digest])));
+        }
+        assert_eq!(PoeModule::claims_this_block(), 3);
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), multihash(&[1])));
+        assert_ok!(PoeModule::tombstone_claim(Origin::root(), multihash(&[2])));
+        assert_eq!(PoeModule::claim_stats(), ClaimStats { total: 3, active: 1, revoked: 2 });
+
+        // the cap starts over every block
+        System::set_block_number(2);
+        PoeModule::on_initialize(2);
+        assert_eq!(PoeModule::claims_this_block(), 0);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), multihash(&[4])));
+        assert_eq!(PoeModule::claim_stats(), ClaimStats { total: 4, active: 2, revoked: 2 });
+    });
+}
+
+#[test]
+fn claim_stats_failed() {
+    new_test_ext().execute_with(|| {
+        for digest in 1..=3 {
+            assert_ok!(PoeModule::create_claim(Origin::signed(1), multihash(&[digest])));
+        }
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(2), multihash(&[4])),
+            Error::<Test>::TooManyClaimsThisBlock,
+        );
+        assert_noop!(
+            PoeModule::create_claim_with_tags(Origin::signed(2), multihash(&[4]), vec![]),
+            Error::<Test>::TooManyClaimsThisBlock,
+        );
+        // revoking a claim doesn't free up room in the block
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), multihash(&[1])));
+        assert_noop!(
+            PoeModule::create_claim(Origin::signed(2), multihash(&[4])),
+            Error::<Test>::TooManyClaimsThisBlock,
+        );
+        assert_eq!(PoeModule::claim_stats().total, 3);
+
+        // batch roots and commitments take room in the block too
+        PoeModule::on_initialize(2);
+        assert_ok!(PoeModule::create_batch_root(Origin::signed(1), H256::repeat_byte(1), 4));
+        let scheme = CommitmentScheme::Ristretto255Pedersen;
+        assert_ok!(PoeModule::create_commitment(Origin::signed(1), scheme, commitment::commit(500, [9; 32]).to_vec()));
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), multihash(&[4])));
+        assert_noop!(
+            PoeModule::create_batch_root(Origin::signed(1), H256::repeat_byte(2), 4),
+            Error::<Test>::TooManyClaimsThisBlock,
+        );
+        assert_noop!(
+            PoeModule::create_commitment(Origin::signed(1), scheme, commitment::commit(501, [9; 32]).to_vec()),
+            Error::<Test>::TooManyClaimsThisBlock,
+        );
+    });
+}
+
+#[test]
+fn storage_migration_works() {
+    new_test_ext().execute_with(|| {
+        Proofs::<Test>::insert(multihash(&[1]), (1, 1));
+        Proofs::<Test>::insert(multihash(&[2]), (2, 1));
+        Tombstones::<Test>::insert(multihash(&[3]), 1);
+        StorageVersion::new(0).put::<PoeModule>();
+
+        // the claims are gone through in `on_idle`, not in the upgrade itself
+        PoeModule::on_runtime_upgrade();
+        assert_eq!(PoeModule::claim_stats(), ClaimStats::default());
+        assert_eq!(PoeModule::migration_progress(), Some(ClaimsMigration::Claims { count: true, last: None }));
+        PoeModule::on_idle(1, 1_000_000);
+        assert_eq!(PoeModule::migration_progress(), None);
+        assert_eq!(PoeModule::claim_stats(), ClaimStats { total: 3, active: 2, revoked: 1 });
+        // claims from before the block index are filed under the block in `Proofs`
+        let mut claims = PoeModule::claims_in_range(1, 2, None).0;
+        claims.sort_by_key(|claim| claim.owner);
+        assert_eq!(
+            claims,
+            vec![
+                ClaimInfo { proof: multihash(&[1]), owner: 1, created_at: 1 },
+                ClaimInfo { proof: multihash(&[2]), owner: 2, created_at: 1 },
+            ],
+        );
+        assert_eq!(StorageVersion::get::<PoeModule>(), StorageVersion::new(2));
+
+        // running it again changes nothing
+        System::set_block_number(1);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), multihash(&[4])));
+        PoeModule::on_runtime_upgrade();
+        assert_eq!(PoeModule::migration_progress(), None);
+        assert_eq!(PoeModule::claim_stats(), ClaimStats { total: 4, active: 3, revoked: 1 });
+        assert_eq!(PoeModule::claims_in_range(1, 1, None).0.len(), 3);
+    });
+}
+
+#[test]
+fn genesis_claims_works() {
+    let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+    frame_support::traits::GenesisBuild::<Test>::assimilate_storage(
+        &crate::GenesisConfig::<Test> { claims: vec![(1, multihash(&[1])), (2, multihash(&[2]))] },
+        &mut t,
+    )
+    .unwrap();
+    sp_io::TestExternalities::new(t).execute_with(|| {
+        assert_eq!(Proofs::<Test>::get(multihash(&[1])), Some((1, 0)));
+        assert_eq!(ClaimAlgorithms::<Test>::get(multihash(&[2])), Some(HashAlgorithm::Sha2_256));
+        assert_eq!(PoeModule::claim_stats(), ClaimStats { total: 2, active: 2, revoked: 0 });
+        assert_eq!(PoeModule::claims_in_range(0, 0, None).0.len(), 2);
+        // and kept for free
+        assert_eq!(PaidUntil::<Test>::get(multihash(&[1])), None);
+    });
+}
+
+#[test]
+fn storage_migration_counts_claims_removed_meanwhile() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        for digest in 1..=4 {
+            Proofs::<Test>::insert(multihash(&[digest]), (1, 1));
+        }
+        Tombstones::<Test>::insert(multihash(&[5]), 1);
+        StorageVersion::new(0).put::<PoeModule>();
+        PoeModule::on_runtime_upgrade();
+
+        // claims made, revoked and tombstoned before the migration gets to them are counted once
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), multihash(&[6])));
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), multihash(&[1])));
+        assert_ok!(PoeModule::tombstone_claim(Origin::signed(1), multihash(&[2])));
+        assert_ok!(PoeModule::tombstone_claim(Origin::signed(2), multihash(&[6])));
+        PoeModule::on_idle(1, 1_000_000);
+        assert_eq!(PoeModule::claim_stats(), ClaimStats { total: 6, active: 2, revoked: 4 });
+
+        // and so are those removed after
+        assert_ok!(PoeModule::tombstone_claim(Origin::signed(1), multihash(&[3])));
+        assert_eq!(PoeModule::claim_stats(), ClaimStats { total: 6, active: 1, revoked: 5 });
+    });
+}
+
+#[test]
+fn license_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let proof = multihash(&[1, 2]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+        assert_ok!(PoeModule::set_license(Origin::signed(1), proof.clone(), b"CC-BY-4.0".to_vec(), Some(100)));
+        assert_eq!(Licenses::<Test>::get(&proof).map(|(_, fee)| fee), Some(Some(100)));
+
+        // the fee goes to the claim owner
+        assert_ok!(PoeModule::acquire_license(Origin::signed(2), proof.clone(), 100));
+        System::assert_last_event(mock::Event::PoeModule(crate::Event::LicenseAcquired(2, proof.clone(), 100)));
+        assert!(PoeModule::is_licensee(&proof, &2));
+        assert_eq!(Balances::free_balance(1), 1_100);
+        assert_eq!(Balances::free_balance(2), 900);
+
+        // free licenses cost nothing, and licensees keep theirs when the terms change
+        assert_ok!(PoeModule::set_license(Origin::signed(1), proof.clone(), b"MIT".to_vec(), None));
+        assert_ok!(PoeModule::acquire_license(Origin::signed(3), proof.clone(), 0));
+        assert_eq!(Balances::free_balance(3), 100);
+        assert_ok!(PoeModule::remove_license(Origin::signed(1), proof.clone()));
+        assert!(PoeModule::is_licensee(&proof, &2));
+        assert!(PoeModule::is_licensee(&proof, &3));
+
+        // licenses end with the claim
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), proof.clone()));
+        assert!(!PoeModule::is_licensee(&proof, &2));
+        assert_eq!(LicenseeCount::<Test>::get(&proof), 0);
+    });
+}
+
+#[test]
+fn license_failed() {
+    new_test_ext().execute_with(|| {
+        let proof = multihash(&[1, 2]);
+        assert_noop!(
+            PoeModule::set_license(Origin::signed(1), proof.clone(), b"MIT".to_vec(), None),
+            Error::<Test>::NoSuchProof,
+        );
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+        assert_noop!(
+            PoeModule::set_license(Origin::signed(2), proof.clone(), b"MIT".to_vec(), None),
+            Error::<Test>::NotProofOwner,
+        );
+        assert_noop!(
+            PoeModule::set_license(Origin::signed(1), proof.clone(), b"LicenseRef-Too-Long".to_vec(), None),
+            Error::<Test>::LicenseIdTooLong,
+        );
+        assert_noop!(PoeModule::acquire_license(Origin::signed(2), proof.clone(), 0), Error::<Test>::NoLicense);
+        assert_noop!(PoeModule::remove_license(Origin::signed(1), proof.clone()), Error::<Test>::NoLicense);
+
+        assert_ok!(PoeModule::set_license(Origin::signed(1), proof.clone(), b"CC-BY-4.0".to_vec(), Some(500)));
+        assert_noop!(
+            PoeModule::acquire_license(Origin::signed(1), proof.clone(), 500),
+            Error::<Test>::CannotLicenseOwnClaim,
+        );
+        // the fee can't be more than the licensee agreed to
+        assert_noop!(
+            PoeModule::acquire_license(Origin::signed(2), proof.clone(), 499),
+            Error::<Test>::LicenseFeeTooHigh,
+        );
+        // account 3 can't afford the fee
+        assert!(PoeModule::acquire_license(Origin::signed(3), proof.clone(), 500).is_err());
+        assert!(!PoeModule::is_licensee(&proof, &3));
+        assert_ok!(PoeModule::acquire_license(Origin::signed(2), proof.clone(), 500));
+        assert_noop!(
+            PoeModule::acquire_license(Origin::signed(2), proof.clone(), 500),
+            Error::<Test>::AlreadyLicensed,
+        );
+
+        // a claim has at most `MaxLicensees` licensees
+        assert_ok!(PoeModule::set_license(Origin::signed(1), proof.clone(), b"MIT".to_vec(), None));
+        assert_ok!(PoeModule::acquire_license(Origin::signed(3), proof.clone(), 0));
+        assert_noop!(
+            PoeModule::acquire_license(Origin::signed(4), proof.clone(), 0),
+            Error::<Test>::TooManyLicensees,
+        );
+    });
+}
+
+#[test]
+fn claims_in_range_works() {
+    new_test_ext().execute_with(|| {
+        let (a, b, c) = (multihash(&[1, 2]), multihash(&[3, 4]), multihash(&[5, 6]));
+        System::set_block_number(1);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), a.clone()));
+        System::set_block_number(2);
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), b.clone()));
+        System::set_block_number(3);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), c.clone()));
+        // claims are found by the block they were created in, and reported with their owner now
+        System::set_block_number(4);
+        assert_ok!(PoeModule::transfer_claim(Origin::signed(1), a.clone(), 2));
+
+        assert_eq!(
+            PoeModule::claims_in_range(1, 2, None).0,
+            vec![
+                ClaimInfo { proof: a.clone(), owner: 2, created_at: 1 },
+                ClaimInfo { proof: b.clone(), owner: 2, created_at: 2 },
+            ],
+        );
+        assert_eq!(
+            PoeModule::claims_in_range(1, 4, Some(1)).0,
+            vec![ClaimInfo { proof: c.clone(), owner: 1, created_at: 3 }],
+        );
+        assert_eq!(PoeModule::claims_in_range(3, 3, None).0.len(), 1);
+
+        // long ranges are read `MAX_RANGE_BLOCKS` blocks at a time
+        assert_eq!(PoeModule::claims_in_range(1, 4, None).1, None);
+        let (claims, next) = PoeModule::claims_in_range(0, 1_000, None);
+        assert_eq!(claims.len(), 3);
+        assert_eq!(next, Some(MAX_RANGE_BLOCKS as u64));
+        assert_eq!(PoeModule::claims_in_range(MAX_RANGE_BLOCKS as u64, 1_000, None), (vec![], Some(200)));
+    });
+}
+
+#[test]
+fn claims_in_range_failed() {
+    new_test_ext().execute_with(|| {
+        let proof = multihash(&[1, 2]);
+        System::set_block_number(2);
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+
+        assert!(PoeModule::claims_in_range(3, 10, None).0.is_empty());
+        assert!(PoeModule::claims_in_range(2, 1, None).0.is_empty());
+        assert!(PoeModule::claims_in_range(1, 2, Some(2)).0.is_empty());
+
+        // revoked claims drop out, and come back under the block they are claimed again in
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), proof.clone()));
+        assert!(PoeModule::claims_in_range(1, 2, None).0.is_empty());
+        System::set_block_number(5);
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), proof.clone()));
+        assert!(PoeModule::claims_in_range(1, 4, None).0.is_empty());
+        assert_eq!(PoeModule::claims_in_range(5, 5, None).0, vec![ClaimInfo { proof, owner: 2, created_at: 5 }]);
+    });
+}
+
+#[test]
+fn countersigned_claim_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let proof = multihash(&[1, 2]);
+        let signature = TestSignature(2, PoeModule::countersign_payload(&1, &proof));
+        assert_ok!(PoeModule::create_countersigned_claim(Origin::signed(1), proof.clone(), 2, signature));
+        System::assert_has_event(mock::Event::PoeModule(crate::Event::ClaimCreated(1, proof.clone())));
+        System::assert_last_event(mock::Event::PoeModule(crate::Event::ClaimCountersigned(1, 2, proof.clone())));
+        assert_eq!(Proofs::<Test>::get(&proof), Some((1, 1)));
+        assert_eq!(PoeModule::countersigner(&proof), Some(2));
+
+        // the countersigner stays with the claim until it is revoked
+        assert_ok!(PoeModule::transfer_claim(Origin::signed(1), proof.clone(), 3));
+        assert_eq!(PoeModule::countersigner(&proof), Some(2));
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(3), proof.clone()));
+        assert_eq!(PoeModule::countersigner(&proof), None);
+    });
+}
+
+#[test]
+fn countersigned_claim_failed() {
+    new_test_ext().execute_with(|| {
+        let proof = multihash(&[1, 2]);
+        assert_noop!(
+            PoeModule::create_countersigned_claim(
+                Origin::signed(1), proof.clone(), 1, TestSignature(1, PoeModule::countersign_payload(&1, &proof)),
+            ),
+            Error::<Test>::CannotCountersignOwnClaim,
+        );
+        // signed by someone else, for another proof, or for another claimant
+        for signature in [
+            TestSignature(3, PoeModule::countersign_payload(&1, &proof)),
+            TestSignature(2, PoeModule::countersign_payload(&1, &multihash(&[1, 3]))),
+            TestSignature(2, PoeModule::countersign_payload(&3, &proof)),
+        ] {
+            assert_noop!(
+                PoeModule::create_countersigned_claim(Origin::signed(1), proof.clone(), 2, signature),
+                Error::<Test>::InvalidCountersignature,
+            );
+        }
+
+        assert_ok!(PoeModule::create_claim(Origin::signed(3), proof.clone()));
+        assert_noop!(
+            PoeModule::create_countersigned_claim(
+                Origin::signed(1), proof.clone(), 2, TestSignature(2, PoeModule::countersign_payload(&1, &proof)),
+            ),
+            Error::<Test>::ProofAlreadyClaimed,
+        );
+        assert_eq!(PoeModule::countersigner(&proof), None);
+    });
+}
+
+#[test]
+fn link_preimage_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let proof = multihash(&[1, 2]);
+        let descriptor = note_preimage(b"a descriptor too large to keep with the claim");
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+
+        assert_ok!(PoeModule::link_preimage(Origin::signed(1), proof.clone(), descriptor));
+        System::assert_last_event(mock::Event::PoeModule(crate::Event::PreimageLinked(1, proof.clone(), descriptor)));
+        assert_eq!(PoeModule::descriptor_preimage(&proof), Some(descriptor));
+        assert_eq!(preimage_requests(&descriptor), 1);
+        assert_eq!(Balances::reserved_balance(1), 50);
+
+        // a new descriptor replaces the old, which is no longer kept for the claim
+        let revised = note_preimage(b"a revised descriptor");
+        assert_ok!(PoeModule::link_preimage(Origin::signed(1), proof.clone(), revised));
+        assert_eq!(PoeModule::descriptor_preimage(&proof), Some(revised));
+        assert_eq!(preimage_requests(&descriptor), 0);
+        assert_eq!(preimage_requests(&revised), 1);
+        assert_eq!(Balances::reserved_balance(1), 50);
+
+        // the deposit is returned with the claim
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), proof.clone()));
+        assert_eq!(PoeModule::descriptor_preimage(&proof), None);
+        assert_eq!(preimage_requests(&revised), 0);
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn transfer_claim_unlinks_preimage() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let proof = multihash(&[1, 2]);
+        let descriptor = note_preimage(b"a descriptor");
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+        assert_ok!(PoeModule::link_preimage(Origin::signed(1), proof.clone(), descriptor));
+
+        // the new owner hasn't vouched for the descriptor, and the old one gets the deposit back
+        assert_ok!(PoeModule::transfer_claim(Origin::signed(1), proof.clone(), 2));
+        assert_eq!(PoeModule::descriptor_preimage(&proof), None);
+        assert_eq!(preimage_requests(&descriptor), 0);
+        assert_eq!(Balances::reserved_balance(1), 0);
+
+        // so the new owner links one and pays for it
+        assert_ok!(PoeModule::link_preimage(Origin::signed(2), proof.clone(), descriptor));
+        assert_eq!(Balances::reserved_balance(2), 50);
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(2), proof.clone()));
+        assert_eq!(Balances::reserved_balance(2), 0);
+    });
+}
+
+#[test]
+fn link_preimage_failed() {
+    new_test_ext().execute_with(|| {
+        let proof = multihash(&[1, 2]);
+        let descriptor = note_preimage(b"a descriptor");
+        assert_noop!(
+            PoeModule::link_preimage(Origin::signed(1), proof.clone(), descriptor),
+            Error::<Test>::NoSuchProof
+        );
+
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+        assert_noop!(
+            PoeModule::link_preimage(Origin::signed(2), proof.clone(), descriptor),
+            Error::<Test>::NotProofOwner
+        );
+        // the descriptor must be noted before it is linked
+        assert_noop!(
+            PoeModule::link_preimage(Origin::signed(1), proof.clone(), BlakeTwo256::hash(b"never noted")),
+            Error::<Test>::PreimageNotFound
+        );
+        // account 4 can't afford the deposit
+        let other = multihash(&[3, 4]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(4), other.clone()));
+        assert_noop!(
+            PoeModule::link_preimage(Origin::signed(4), other, descriptor),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+        assert_eq!(preimage_requests(&descriptor), 0);
+    });
+}
+
+#[test]
+fn link_equivalent_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        // the same document under SHA2-256, BLAKE3 and SHA3-256
+        let sha2 = multihash(&[1, 2]);
+        let blake3 = vec![0x1e, 2, 3, 4];
+        let sha3 = vec![0x16, 2, 5, 6];
+        for proof in [&sha2, &blake3, &sha3] {
+            assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+        }
+
+        assert_ok!(PoeModule::link_equivalent(Origin::signed(1), sha2.clone(), blake3.clone()));
+        System::assert_last_event(mock::Event::PoeModule(crate::Event::EquivalentClaimsLinked(
+            1, sha2.clone(), blake3.clone(), 0,
+        )));
+        assert_eq!(PoeModule::equivalence_group(&blake3), Some(0));
+        assert_eq!(PoeModule::find_equivalent(sha2.clone()), vec![blake3.clone()]);
+        assert_eq!(PoeModule::find_equivalent(blake3.clone()), vec![sha2.clone()]);
+
+        // links are transitive, so everything linked to the BLAKE3 claim joins the SHA3 one
+        assert_ok!(PoeModule::link_equivalent(Origin::signed(1), sha3.clone(), blake3.clone()));
+        assert_eq!(PoeModule::equivalence_group(&sha2), Some(1));
+        assert_eq!(PoeModule::find_equivalent(sha2.clone()), vec![sha3.clone(), blake3.clone()]);
+        assert_eq!(EquivalentClaims::<Test>::get(0).len(), 0);
+
+        // removed claims drop out, and a claim left on its own is unlinked
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), blake3.clone()));
+        assert_eq!(PoeModule::find_equivalent(sha2.clone()), vec![sha3.clone()]);
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), sha3.clone()));
+        assert_eq!(PoeModule::equivalence_group(&sha2), None);
+        assert!(PoeModule::find_equivalent(sha2).is_empty());
+    });
+}
+
+#[test]
+fn reclaimed_claims_start_a_new_equivalence_group() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (sha2, blake3, sha3) = (multihash(&[1, 2]), vec![0x1e, 2, 3, 4], vec![0x16, 2, 5, 6]);
+        for proof in [&sha2, &blake3, &sha3] {
+            assert_ok!(PoeModule::create_claim(Origin::signed(1), proof.clone()));
+        }
+        assert_ok!(PoeModule::link_equivalent(Origin::signed(1), sha2.clone(), blake3.clone()));
+        assert_ok!(PoeModule::link_equivalent(Origin::signed(1), sha2.clone(), sha3.clone()));
+
+        // the group outlives the claim it was started from
+        assert_ok!(PoeModule::revoke_claim(Origin::signed(1), sha2.clone()));
+        assert_eq!(PoeModule::find_equivalent(blake3.clone()), vec![sha3.clone()]);
+
+        // so someone else claiming it again mustn't be let into it
+        System::set_block_number(2);
+        PoeModule::on_initialize(2);
+        let other = multihash(&[7]);
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), sha2.clone()));
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), other.clone()));
+        assert_ok!(PoeModule::link_equivalent(Origin::signed(2), sha2.clone(), other.clone()));
+        assert_eq!(PoeModule::equivalence_group(&sha2), Some(1));
+        assert_eq!(PoeModule::find_equivalent(sha2.clone()), vec![other.clone()]);
+        assert_eq!(PoeModule::find_equivalent(blake3.clone()), vec![sha3.clone()]);
+
+        // nor does a transferred claim take its links along
+        assert_ok!(PoeModule::transfer_claim(Origin::signed(1), blake3.clone(), 2));
+        assert_eq!(PoeModule::equivalence_group(&blake3), None);
+        assert_eq!(PoeModule::equivalence_group(&sha3), None);
+        assert!(PoeModule::find_equivalent(sha3).is_empty());
+    });
+}
+
+#[test]
+fn link_equivalent_failed() {
+    new_test_ext().execute_with(|| {
+        let sha2 = multihash(&[1, 2]);
+        let blake3 = vec![0x1e, 2, 3, 4];
+        let others = vec![0x16, 2, 5, 6];
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), sha2.clone()));
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), blake3.clone()));
+        assert_ok!(PoeModule::create_claim(Origin::signed(2), others.clone()));
+
+        assert_noop!(
+            PoeModule::link_equivalent(Origin::signed(1), sha2.clone(), sha2.clone()),
+            Error::<Test>::CannotLinkToItself
+        );
+        assert_noop!(
+            PoeModule::link_equivalent(Origin::signed(1), sha2.clone(), multihash(&[9])),
+            Error::<Test>::NoSuchProof
+        );
+        // both claims must be the caller's
+        assert_noop!(
+            PoeModule::link_equivalent(Origin::signed(1), sha2.clone(), others.clone()),
+            Error::<Test>::NotProofOwner
+        );
+
+        assert_ok!(PoeModule::link_equivalent(Origin::signed(1), sha2.clone(), blake3.clone()));
+        assert_noop!(
+            PoeModule::link_equivalent(Origin::signed(1), blake3.clone(), sha2.clone()),
+            Error::<Test>::AlreadyEquivalent
+        );
+
+        // merging two groups may not go over `MaxEquivalentClaims`
+        System::set_block_number(2);
+        PoeModule::on_initialize(2);
+        let (more, most) = (multihash(&[5]), multihash(&[6]));
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), more.clone()));
+        assert_ok!(PoeModule::create_claim(Origin::signed(1), most.clone()));
+        assert_ok!(PoeModule::link_equivalent(Origin::signed(1), more.clone(), most.clone()));
+        assert_noop!(
+            PoeModule::link_equivalent(Origin::signed(1), sha2.clone(), more.clone()),
+            Error::<Test>::TooManyEquivalentClaims
+        );
+        assert_eq!(PoeModule::find_equivalent(more), vec![most]);
+    });
+}
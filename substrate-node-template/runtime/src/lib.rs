@@ -6,6 +6,10 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+use frame_support::{
+	traits::{Currency, OnUnbalanced},
+	PalletId,
+};
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
@@ -14,7 +18,10 @@ use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
-	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify},
+	traits::{
+		AccountIdConversion, AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount,
+		NumberFor, Verify,
+	},
 	transaction_validity::{TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature,
 };
@@ -34,6 +41,7 @@ pub use frame_support::{
 	StorageValue,
 };
 pub use pallet_balances::Call as BalancesCall;
+pub use pallet_sudo::Call as SudoCall;
 pub use pallet_timestamp::Call as TimestampCall;
 use pallet_transaction_payment::CurrencyAdapter;
 #[cfg(any(feature = "std", test))]
@@ -106,6 +114,12 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	transaction_version: 1,
 };
 
+/// The number of decimals balances of the native token are displayed with.
+pub const TOKEN_DECIMALS: u8 = 12;
+
+/// The ticker of the native token.
+pub const TOKEN_SYMBOL: &str = "UNIT";
+
 /// This determines the average expected block time that we are targeting.
 /// Blocks will be produced at a minimum duration defined by `SLOT_DURATION`.
 /// `SLOT_DURATION` is picked up by `pallet_timestamp` which is in turn picked
@@ -281,10 +295,74 @@ impl pallet_template::Config for Runtime {
 	type Event = Event;
 }
 
+parameter_types! {
+	pub const ReferenceFee: Balance = 1_000;
+	pub const ReferenceOwnerShare: Permill = Permill::from_percent(80);
+	pub const RetentionFee: Balance = 100;
+	pub const DescriptorDeposit: Balance = 1_000;
+	pub const RentEra: BlockNumber = 30 * DAYS;
+	pub const RetentionGracePeriod: BlockNumber = 7 * DAYS;
+	pub const TimestampClaimEvents: bool = true;
+}
+
+parameter_types! {
+	/// The account the treasury's share of reference fees and all retention fees are paid
+	/// into. It is the account `pallet-treasury` uses, so a treasury added later inherits them.
+	pub const TreasuryPalletId: PalletId = PalletId(*b"py/trsry");
+}
+
+/// Pays what the PoE pallet sends to the treasury into the `TreasuryPalletId` account.
+pub struct ToTreasury;
+
+impl OnUnbalanced<pallet_balances::NegativeImbalance<Runtime>> for ToTreasury {
+	fn on_nonzero_unbalanced(amount: pallet_balances::NegativeImbalance<Runtime>) {
+		Balances::resolve_creating(&TreasuryPalletId::get().into_account(), amount);
+	}
+}
+
+/// Kitties live on the kitties chain, not in this runtime, so no claim can be bound to one here.
+/// The kitties runtime hosts this pallet too, with the kitties pallet as its `Kitties`.
+pub struct NoKitties;
+
+impl frame_support::traits::tokens::nonfungible::Inspect<AccountId> for NoKitties {
+	type InstanceId = u32;
+
+	fn owner(_kitty_id: &u32) -> Option<AccountId> {
+		None
+	}
+}
+
 /// Configure the pallet-poe in pallets/poe.
 impl pallet_poe::Config for Runtime {
 	type Event = Event;
-	type LengthLimit = ConstU32<6>;
+	// A 512-bit digest behind its two-byte multihash header.
+	type LengthLimit = ConstU32<66>;
+	type MaxTags = ConstU32<8>;
+	type MaxTagLength = ConstU32<32>;
+	type Currency = Balances;
+	type ReferenceFee = ReferenceFee;
+	type OwnerShare = ReferenceOwnerShare;
+	type Treasury = ToTreasury;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxContentTypeNameLength = ConstU32<32>;
+	type RetentionFee = RetentionFee;
+	type RentEra = RentEra;
+	type GracePeriod = RetentionGracePeriod;
+	type KittyId = u32;
+	type Kitties = NoKitties;
+	type MaxClaimsPerKitty = ConstU32<16>;
+	type TimeProvider = Timestamp;
+	type TimestampInEvent = TimestampClaimEvents;
+	type MaxClaimsPerBlock = ConstU32<1_000>;
+	type MaxLicenseIdLength = ConstU32<64>;
+	type MaxLicensees = ConstU32<1_000>;
+	type CounterSignature = Signature;
+	type CounterSigner = <Signature as Verify>::Signer;
+	// The preimage pallet isn't available on this Substrate version yet, so no descriptor can
+	// be linked until it is added.
+	type Preimages = ();
+	type DescriptorDeposit = DescriptorDeposit;
+	type MaxEquivalentClaims = ConstU32<16>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -451,6 +529,54 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl system_info_runtime_api::SystemInfoApi<Block> for Runtime {
+		fn system_info() -> system_info_runtime_api::SystemInfo {
+			system_info_runtime_api::SystemInfo {
+				spec_name: VERSION.spec_name.as_bytes().to_vec(),
+				spec_version: VERSION.spec_version,
+				impl_version: VERSION.impl_version,
+				transaction_version: VERSION.transaction_version,
+				ss58_prefix: SS58Prefix::get().into(),
+				token_decimals: TOKEN_DECIMALS,
+				token_symbol: TOKEN_SYMBOL.as_bytes().to_vec(),
+			}
+		}
+	}
+
+	impl pallet_poe_runtime_api::PoeApi<Block, Hash, AccountId, BlockNumber> for Runtime {
+		fn verify_leaf(
+			root: Hash,
+			leaf: Hash,
+			proof: Vec<(Hash, pallet_poe_runtime_api::Side)>,
+		) -> bool {
+			PoeModule::verify_leaf(root, leaf, proof)
+		}
+
+		fn claims_by_tag(tag: Vec<u8>) -> Vec<Vec<u8>> {
+			PoeModule::claims_by_tag(tag)
+		}
+
+		fn verify_opening(commitment: Vec<u8>, value: u128, blinding: [u8; 32]) -> bool {
+			PoeModule::verify_opening(commitment, value, blinding)
+		}
+
+		fn claim_stats() -> pallet_poe_runtime_api::ClaimStats {
+			PoeModule::claim_stats()
+		}
+
+		fn claims_in_range(
+			from_block: BlockNumber,
+			to_block: BlockNumber,
+			owner: Option<AccountId>,
+		) -> (Vec<pallet_poe_runtime_api::ClaimInfo<AccountId, BlockNumber>>, Option<BlockNumber>) {
+			PoeModule::claims_in_range(from_block, to_block, owner)
+		}
+
+		fn find_equivalent(proof: Vec<u8>) -> Vec<Vec<u8>> {
+			PoeModule::find_equivalent(proof)
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
 		fn query_info(
 			uxt: <Block as BlockT>::Extrinsic,
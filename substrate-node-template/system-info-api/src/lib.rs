@@ -0,0 +1,28 @@
+//! Runtime API describing the chain to wallets, so they can configure themselves against it.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// The runtime's versions, address format and native token.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct SystemInfo {
+	pub spec_name: Vec<u8>,
+	pub spec_version: u32,
+	pub impl_version: u32,
+	pub transaction_version: u32,
+	/// The SS58 prefix addresses on this chain are encoded with.
+	pub ss58_prefix: u16,
+	pub token_decimals: u8,
+	pub token_symbol: Vec<u8>,
+}
+
+sp_api::decl_runtime_apis! {
+	pub trait SystemInfoApi {
+		/// The runtime's versions, address format and native token.
+		fn system_info() -> SystemInfo;
+	}
+}